@@ -0,0 +1,9 @@
+#include <stdio.h>
+#include <stdlib.h>
+int main(int argc, char **argv) {
+    volatile int *p = (int *)0x10;
+    static char marker[] = "elfeat core marker";
+    puts(marker);
+    *p = 42;
+    return 0;
+}
@@ -0,0 +1,21 @@
+#include "dwarf.h"
+
+struct point {
+    int x;
+    int y;
+};
+
+int counter = 3;
+
+static int add(struct point p)
+{
+    return p.x + p.y;
+}
+
+int main(void)
+{
+    struct point p = {1, 2};
+
+    counter += square(add(p));
+    return counter;
+}
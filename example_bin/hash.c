@@ -0,0 +1,21 @@
+/* Shared object with both .hash and .gnu.hash.
+ * gcc -shared -fPIC -O1 -s -Wl,--hash-style=both -o hash_so hash.c
+ */
+
+int counter;
+const char greeting[] = "hello";
+
+int add(int a, int b) { return a + b; }
+int sub(int a, int b) { return a - b; }
+int mul(int a, int b) { return a * b; }
+int div_floor(int a, int b) { return a / b; }
+int square(int x) { return x * x; }
+int cube(int x) { return x * x * x; }
+int negate(int x) { return -x; }
+int increment(void) { return ++counter; }
+int decrement(void) { return --counter; }
+int reset(void) { return counter = 0; }
+const char *hello(void) { return greeting; }
+
+static int helper(int x) { return x + counter; }
+int use_helper(int x) { return helper(x); }
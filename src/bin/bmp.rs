@@ -0,0 +1,44 @@
+use elfeat::{file, types};
+
+use file::Sequential;
+use types::bmp::*;
+
+use std::{fs::File, io::Read};
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "./example_bin/image.bmp".to_string());
+    let mut f = File::open(path).unwrap();
+    let mut v = vec![];
+    f.read_to_end(&mut v).unwrap();
+
+    let s = Sequential::from_vec(v);
+
+    let bmp = Bmp::parse(s.to_seeakble()).unwrap();
+
+    println!("{}", bmp.file_header());
+    println!("{}", bmp.info_header());
+    if let Some([r, g, b, a]) = bmp.masks() {
+        println!("Masks: R {:08x} G {:08x} B {:08x} A {:08x}", r, g, b, a);
+    }
+    if !bmp.palette().is_empty() {
+        println!("Palette: {} colors", bmp.palette().len());
+        for (i, [r, g, b, _]) in bmp.palette().iter().enumerate() {
+            println!("{:>5}: #{:02x}{:02x}{:02x}", i, r, g, b);
+        }
+    }
+
+    match bmp.decode() {
+        Ok(image) => {
+            let opaque = image.pixels.chunks_exact(4).all(|px| px[3] == 0xff);
+            println!(
+                "Decoded {}x{} RGBA, {}",
+                image.width,
+                image.height,
+                if opaque { "opaque" } else { "with transparency" }
+            );
+        }
+        Err(e) => println!("Decode failed: {}", e),
+    }
+}
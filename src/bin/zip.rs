@@ -6,14 +6,32 @@ use types::zip::*;
 use std::{fs::File, io::Read};
 
 fn main() {
-    let mut f = File::open("./example_bin/archives.zip").unwrap();
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "./example_bin/archives.zip".to_string());
+    let mut f = File::open(path).unwrap();
     let mut v = vec![];
     f.read_to_end(&mut v).unwrap();
 
     let s = Sequential::from_vec(v);
 
-    let mut main_seeker = s.to_seeakble();
-    let header = main_seeker.interpret_next::<Header>().to_tuple_unwrap().1;
+    let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
 
-    println!("{}", header);
+    println!("{}", zip.eocd());
+    if let Some(zip64) = zip.zip64_eocd() {
+        println!("{}", zip64);
+    }
+
+    println!("    Offset     Length       Size   CRC-32  Modified             Name");
+    for entry in zip.entries() {
+        println!(
+            "{:>10} {:>10} {:>10} {:08x}  {}  {}",
+            entry.local_header_offset(),
+            entry.uncompressed_size(),
+            entry.compressed_size(),
+            entry.crc32(),
+            entry.last_modified(),
+            entry.name()
+        );
+    }
 }
@@ -0,0 +1,7 @@
+//! Self-contained compression algorithms.
+//! They are implemented in-crate to keep dependencies minimal.
+
+pub mod crc32;
+pub mod deflate;
+pub mod inflate;
+pub mod zlib;
@@ -0,0 +1,77 @@
+/// CRC-32 (ISO-HDLC) that is used by zip and gzip.
+/// Polynomial is 0xedb88320 (reversed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+const TABLE: [u32; 256] = make_table();
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Self { state: 0xffffffff }
+    }
+
+    /// Feed data.
+    pub fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.state = update_raw(self.state, b);
+        }
+    }
+
+    /// CRC of fed data.
+    pub fn value(&self) -> u32 {
+        self.state ^ 0xffffffff
+    }
+}
+
+/// Update raw CRC-32 state with a byte, without pre and post inversion.
+/// ZipCrypto key schedule uses this form.
+pub fn update_raw(state: u32, byte: u8) -> u32 {
+    TABLE[((state ^ byte as u32) & 0xff) as usize] ^ (state >> 8)
+}
+
+/// CRC-32 of data.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut c = Crc32::new();
+    c.update(data);
+    c.value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+
+        let mut c = Crc32::new();
+        c.update(b"12345");
+        c.update(b"6789");
+        assert_eq!(c.value(), 0xcbf43926);
+    }
+}
@@ -0,0 +1,238 @@
+//! DEFLATE (RFC 1951) compression.
+//! Matches are found with hash chains and encoded with fixed Huffman codes.
+//! If that does not pay, data is emitted as stored blocks.
+
+use super::inflate::{DIST_BASE, DIST_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+const WINDOW_SIZE: usize = 0x8000;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+
+/// How many candidates are examined for each position.
+const MAX_CHAIN: usize = 128;
+
+/// Max length of stored block.
+const MAX_STORED: usize = 0xffff;
+
+enum Token {
+    Literal(u8),
+    Match { len: u16, dist: u16 },
+}
+
+/// LSB first bit writer.
+struct BitWriter {
+    out: Vec<u8>,
+    bit_buf: u64,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            out: vec![],
+            bit_buf: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn bits(&mut self, value: u32, n: u32) {
+        self.bit_buf |= (value as u64) << self.bit_count;
+        self.bit_count += n;
+        while self.bit_count >= 8 {
+            self.out.push(self.bit_buf as u8);
+            self.bit_buf >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// Huffman codes are packed from MSB.
+    fn code(&mut self, code: u32, n: u32) {
+        self.bits(code.reverse_bits() >> (32 - n), n);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.out.push(self.bit_buf as u8);
+        }
+        self.out
+    }
+}
+
+fn hash(data: &[u8]) -> usize {
+    let v = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+    (v.wrapping_mul(0x9e3779b1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Find matches greedily.
+fn tokenize(data: &[u8]) -> Vec<Token> {
+    // Positions are stored with offset 1, so that 0 means none.
+    let mut head = vec![0usize; 1 << HASH_BITS];
+    let mut prev = vec![0usize; data.len()];
+    let mut tokens = vec![];
+
+    let insert = |head: &mut [usize], prev: &mut [usize], i: usize| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(&data[i..]);
+            prev[i] = head[h];
+            head[h] = i + 1;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best_len = 0;
+        let mut best_dist = 0;
+
+        if i + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(&data[i..])];
+            let mut chain = 0;
+            while candidate != 0 && chain < MAX_CHAIN {
+                let j = candidate - 1;
+                if i - j > WINDOW_SIZE {
+                    break;
+                }
+                let len = data[j..]
+                    .iter()
+                    .zip(&data[i..i + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    best_dist = i - j;
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[j];
+                chain += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            tokens.push(Token::Match {
+                len: best_len as u16,
+                dist: best_dist as u16,
+            });
+            for k in i..i + best_len {
+                insert(&mut head, &mut prev, k);
+            }
+            i += best_len;
+        } else {
+            tokens.push(Token::Literal(data[i]));
+            insert(&mut head, &mut prev, i);
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// Index of the last base that is not greater than `value`.
+fn base_index(bases: &[u16], value: u16) -> usize {
+    bases.iter().rposition(|&b| b <= value).unwrap()
+}
+
+fn literal_length_code(w: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => w.code(0x30 + symbol, 8),
+        144..=255 => w.code(0x190 + symbol - 144, 9),
+        256..=279 => w.code(symbol - 256, 7),
+        _ => w.code(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn fixed_block(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    // BFINAL = 1, BTYPE = 1
+    w.bits(1, 1);
+    w.bits(1, 2);
+
+    for token in tokenize(data) {
+        match token {
+            Token::Literal(b) => literal_length_code(&mut w, b as u32),
+            Token::Match { len, dist } => {
+                let i = base_index(&LENGTH_BASE, len);
+                literal_length_code(&mut w, 257 + i as u32);
+                w.bits((len - LENGTH_BASE[i]) as u32, LENGTH_EXTRA[i] as u32);
+
+                let i = base_index(&DIST_BASE, dist);
+                w.code(i as u32, 5);
+                w.bits((dist - DIST_BASE[i]) as u32, DIST_EXTRA[i] as u32);
+            }
+        }
+    }
+    literal_length_code(&mut w, 256);
+
+    w.finish()
+}
+
+fn stored_blocks(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    let mut chunks = data.chunks(MAX_STORED).peekable();
+
+    // Empty data still needs a final block.
+    if chunks.peek().is_none() {
+        return vec![0x01, 0x00, 0x00, 0xff, 0xff];
+    }
+    while let Some(chunk) = chunks.next() {
+        let len = chunk.len() as u16;
+        out.push(chunks.peek().is_none() as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out
+}
+
+/// Compress data into raw DEFLATE stream.
+pub fn deflate(data: &[u8]) -> Vec<u8> {
+    let fixed = fixed_block(data);
+    let stored_size = data.len() + 5 * data.len().div_ceil(MAX_STORED).max(1);
+    if fixed.len() <= stored_size {
+        fixed
+    } else {
+        stored_blocks(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress::inflate::inflate;
+
+    #[test]
+    fn round_trip() {
+        let text: Vec<u8> = (0..200)
+            .flat_map(|i| format!("line {}: the quick brown fox jumps over the lazy dog\n", i).into_bytes())
+            .collect();
+        let compressed = deflate(&text);
+        assert!(compressed.len() < text.len() / 4);
+        assert_eq!(inflate(&compressed).unwrap(), text);
+
+        // Long runs use overlapped matches.
+        let run = vec![b'a'; 1000];
+        assert_eq!(inflate(&deflate(&run)).unwrap(), run);
+
+        assert_eq!(inflate(&deflate(b"")).unwrap(), b"");
+        assert_eq!(inflate(&deflate(b"ab")).unwrap(), b"ab");
+    }
+
+    #[test]
+    fn incompressible() {
+        // xorshift noise is not compressible, so it is stored.
+        let mut x = 0x12345678u32;
+        let noise: Vec<u8> = (0..0x12000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                x as u8
+            })
+            .collect();
+        let compressed = deflate(&noise);
+        assert_eq!(compressed.len(), noise.len() + 10);
+        assert_eq!(inflate(&compressed).unwrap(), noise);
+    }
+}
@@ -0,0 +1,380 @@
+//! DEFLATE (RFC 1951) decompression.
+//! Huffman codes are decoded in canonical order, bit by bit.
+
+/// Error of DEFLATE decompression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InflateError {
+    /// Input ends before the final block.
+    UnexpectedEof,
+
+    /// Block type 3 is reserved.
+    InvalidBlockType,
+
+    /// LEN and NLEN of stored block do not match.
+    StoredLengthMismatch,
+
+    /// Code lengths do not make a valid Huffman code.
+    InvalidCodeLengths,
+
+    /// The bits are not any code, or the symbol is out of range.
+    InvalidCode,
+
+    /// Distance is farther than output.
+    InvalidDistance,
+
+    /// Output exceeds the limit.
+    OutputLimit,
+}
+
+impl core::fmt::Display for InflateError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            InflateError::UnexpectedEof => "unexpected end of compressed data",
+            InflateError::InvalidBlockType => "invalid block type",
+            InflateError::StoredLengthMismatch => "stored block length mismatch",
+            InflateError::InvalidCodeLengths => "invalid code lengths",
+            InflateError::InvalidCode => "invalid code",
+            InflateError::InvalidDistance => "distance too far back",
+            InflateError::OutputLimit => "output exceeds the limit",
+        };
+        write!(fmt, "{}", s)
+    }
+}
+
+impl std::error::Error for InflateError {}
+
+const MAX_BITS: usize = 15;
+
+pub(super) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+pub(super) const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+pub(super) const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+pub(super) const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Order of code length code lengths in dynamic block header.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Canonical Huffman code.
+struct Huffman {
+    /// Number of codes of each length.
+    counts: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Build code from lengths of each symbol. Length 0 means the symbol is unused.
+    /// Incomplete codes are allowed as zlib does, only over-subscribed ones are rejected.
+    fn new(lengths: &[u8]) -> Result<Self, InflateError> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &l in lengths {
+            counts[l as usize] += 1;
+        }
+
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(InflateError::InvalidCodeLengths);
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; offsets[MAX_BITS + 1] as usize];
+        for (symbol, &l) in lengths.iter().enumerate() {
+            if l != 0 {
+                symbols[offsets[l as usize] as usize] = symbol as u16;
+                offsets[l as usize] += 1;
+            }
+        }
+
+        counts[0] = 0;
+        Ok(Self { counts, symbols })
+    }
+}
+
+/// Bit reader and output window.
+struct Decoder<'a> {
+    input: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_count: u32,
+    out: Vec<u8>,
+    limit: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Check that `len` more bytes can be output.
+    fn reserve(&self, len: usize) -> Result<(), InflateError> {
+        match self.out.len().checked_add(len) {
+            Some(x) if x <= self.limit => Ok(()),
+            _ => Err(InflateError::OutputLimit),
+        }
+    }
+
+    fn bits(&mut self, n: u32) -> Result<u32, InflateError> {
+        while self.bit_count < n {
+            let b = *self.input.get(self.pos).ok_or(InflateError::UnexpectedEof)?;
+            self.pos += 1;
+            self.bit_buf |= (b as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let ret = self.bit_buf & ((1u64 << n) - 1) as u32;
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+        Ok(ret)
+    }
+
+    fn decode(&mut self, h: &Huffman) -> Result<u16, InflateError> {
+        // Huffman codes are packed from MSB, unlike other fields.
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS {
+            code |= self.bits(1)? as i32;
+            let count = h.counts[len] as i32;
+            if code - first < count {
+                return Ok(h.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(InflateError::InvalidCode)
+    }
+
+    fn stored(&mut self) -> Result<(), InflateError> {
+        // Discard remaining bits in current byte.
+        self.bit_buf = 0;
+        self.bit_count = 0;
+
+        let header = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or(InflateError::UnexpectedEof)?;
+        let len = u16::from_le_bytes([header[0], header[1]]);
+        let nlen = u16::from_le_bytes([header[2], header[3]]);
+        if len != !nlen {
+            return Err(InflateError::StoredLengthMismatch);
+        }
+        self.pos += 4;
+
+        let data = self
+            .input
+            .get(self.pos..self.pos + len as usize)
+            .ok_or(InflateError::UnexpectedEof)?;
+        self.reserve(data.len())?;
+        self.out.extend_from_slice(data);
+        self.pos += len as usize;
+        Ok(())
+    }
+
+    fn codes(&mut self, lit: &Huffman, dist: &Huffman) -> Result<(), InflateError> {
+        loop {
+            let symbol = self.decode(lit)? as usize;
+            match symbol {
+                0..=255 => {
+                    self.reserve(1)?;
+                    self.out.push(symbol as u8);
+                }
+                256 => return Ok(()),
+                257..=285 => {
+                    let i = symbol - 257;
+                    let len = LENGTH_BASE[i] as usize + self.bits(LENGTH_EXTRA[i] as u32)? as usize;
+
+                    let i = self.decode(dist)? as usize;
+                    if i >= DIST_BASE.len() {
+                        return Err(InflateError::InvalidCode);
+                    }
+                    let d = DIST_BASE[i] as usize + self.bits(DIST_EXTRA[i] as u32)? as usize;
+                    if d > self.out.len() {
+                        return Err(InflateError::InvalidDistance);
+                    }
+                    self.reserve(len)?;
+
+                    // Source may overlap with destination, so copy byte by byte.
+                    let start = self.out.len() - d;
+                    for k in 0..len {
+                        let b = self.out[start + k];
+                        self.out.push(b);
+                    }
+                }
+                _ => return Err(InflateError::InvalidCode),
+            }
+        }
+    }
+
+    fn fixed(&mut self) -> Result<(), InflateError> {
+        let mut lengths = [0u8; 288];
+        lengths[..144].fill(8);
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        lengths[280..].fill(8);
+        let lit = Huffman::new(&lengths)?;
+        let dist = Huffman::new(&[5; 30])?;
+        self.codes(&lit, &dist)
+    }
+
+    fn dynamic(&mut self) -> Result<(), InflateError> {
+        let nlen = self.bits(5)? as usize + 257;
+        let ndist = self.bits(5)? as usize + 1;
+        let ncode = self.bits(4)? as usize + 4;
+        if nlen > 286 || ndist > 30 {
+            return Err(InflateError::InvalidCodeLengths);
+        }
+
+        let mut lengths = [0u8; 19];
+        for &i in &CODE_LENGTH_ORDER[..ncode] {
+            lengths[i] = self.bits(3)? as u8;
+        }
+        let code_length = Huffman::new(&lengths)?;
+
+        let mut lengths = vec![0u8; nlen + ndist];
+        let mut i = 0;
+        while i < lengths.len() {
+            let symbol = self.decode(&code_length)?;
+            let (value, repeat) = match symbol {
+                0..=15 => (symbol as u8, 1),
+                16 => {
+                    let prev = *lengths[..i]
+                        .last()
+                        .ok_or(InflateError::InvalidCodeLengths)?;
+                    (prev, 3 + self.bits(2)? as usize)
+                }
+                17 => (0, 3 + self.bits(3)? as usize),
+                _ => (0, 11 + self.bits(7)? as usize),
+            };
+            let fill = lengths
+                .get_mut(i..i + repeat)
+                .ok_or(InflateError::InvalidCodeLengths)?;
+            fill.fill(value);
+            i += repeat;
+        }
+
+        // End of block code is required.
+        if lengths[256] == 0 {
+            return Err(InflateError::InvalidCodeLengths);
+        }
+
+        let lit = Huffman::new(&lengths[..nlen])?;
+        let dist = Huffman::new(&lengths[nlen..])?;
+        self.codes(&lit, &dist)
+    }
+}
+
+/// Decompress raw DEFLATE stream.
+/// This returns decompressed data and number of consumed input bytes.
+/// Trailing bytes after the final block are not consumed.
+pub fn inflate_consume(input: &[u8]) -> Result<(Vec<u8>, usize), InflateError> {
+    inflate_consume_limited(input, usize::MAX)
+}
+
+/// Same as `inflate_consume`, but fails with `OutputLimit` if output exceeds `limit` bytes.
+/// Use this for untrusted input, which may expand over 1000 times.
+pub fn inflate_consume_limited(input: &[u8], limit: usize) -> Result<(Vec<u8>, usize), InflateError> {
+    let mut d = Decoder {
+        input,
+        pos: 0,
+        bit_buf: 0,
+        bit_count: 0,
+        out: vec![],
+        limit,
+    };
+
+    loop {
+        let last = d.bits(1)? == 1;
+        match d.bits(2)? {
+            0 => d.stored()?,
+            1 => d.fixed()?,
+            2 => d.dynamic()?,
+            _ => return Err(InflateError::InvalidBlockType),
+        }
+        if last {
+            break;
+        }
+    }
+
+    Ok((d.out, d.pos))
+}
+
+/// Decompress raw DEFLATE stream.
+pub fn inflate(input: &[u8]) -> Result<Vec<u8>, InflateError> {
+    inflate_consume(input).map(|(out, _)| out)
+}
+
+/// Decompress raw DEFLATE stream up to `limit` bytes.
+pub fn inflate_limited(input: &[u8], limit: usize) -> Result<Vec<u8>, InflateError> {
+    inflate_consume_limited(input, limit).map(|(out, _)| out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_block() {
+        let input = [0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o', 0xee];
+        assert_eq!(inflate_consume(&input).unwrap(), (b"hello".to_vec(), 10));
+
+        let mut broken = input;
+        broken[3] = 0;
+        assert_eq!(inflate(&broken), Err(InflateError::StoredLengthMismatch));
+    }
+
+    #[test]
+    fn fixed_block() {
+        let input = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01];
+        assert_eq!(inflate(&input).unwrap(), b"hello hello hello hello");
+        assert_eq!(inflate(&input[..5]), Err(InflateError::UnexpectedEof));
+    }
+
+    #[test]
+    fn dynamic_block() {
+        let input = [
+            0x9d, 0xd2, 0x5d, 0x16, 0x42, 0x50, 0x18, 0x85, 0xe1, 0x7b, 0xa3, 0xf8, 0x86, 0x60,
+            0x4b, 0x3f, 0x9a, 0x8d, 0x38, 0x4a, 0x0e, 0x27, 0x0a, 0x65, 0xf4, 0x96, 0x66, 0xe0,
+            0xbd, 0xde, 0xeb, 0xbd, 0xda, 0x8f, 0xaf, 0x3b, 0x67, 0xf1, 0xd5, 0x3e, 0x0f, 0x67,
+            0xfd, 0x58, 0x17, 0x8d, 0xdd, 0x86, 0x30, 0x77, 0x56, 0x85, 0xaf, 0x3d, 0xc7, 0xf6,
+            0xf5, 0xb6, 0x30, 0xb9, 0xe1, 0x3f, 0xfb, 0x7c, 0xf9, 0x59, 0x19, 0xee, 0x91, 0xdf,
+            0x1a, 0x81, 0x26, 0x01, 0xcd, 0x01, 0x34, 0x29, 0x68, 0x8e, 0xa0, 0x39, 0x81, 0xe6,
+            0x0c, 0x9a, 0x0b, 0x68, 0x32, 0xf2, 0x29, 0x82, 0x40, 0x24, 0x88, 0x50, 0x10, 0xb1,
+            0x20, 0x82, 0x41, 0x44, 0x83, 0x08, 0x07, 0x11, 0x0f, 0x22, 0x20, 0xb4, 0x53, 0xc4,
+            0x0a,
+        ];
+        let expected: Vec<u8> = (0..20)
+            .flat_map(|i| format!("line {}: the quick brown fox jumps over the lazy dog\n", i).into_bytes())
+            .collect();
+        assert_eq!(inflate(&input).unwrap(), expected);
+    }
+
+    #[test]
+    fn invalid_stream() {
+        // BFINAL = 1, BTYPE = 3
+        assert_eq!(inflate(&[0x07]), Err(InflateError::InvalidBlockType));
+        // Fixed block starting with distance code before any output.
+        assert_eq!(inflate(&[0x03, 0x02]), Err(InflateError::InvalidDistance));
+    }
+
+    #[test]
+    fn output_limit() {
+        let input = [0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01];
+        assert_eq!(inflate_limited(&input, 23).unwrap().len(), 23);
+        assert_eq!(inflate_limited(&input, 22), Err(InflateError::OutputLimit));
+        assert_eq!(inflate_limited(&input, 3), Err(InflateError::OutputLimit));
+    }
+}
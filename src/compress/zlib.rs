@@ -0,0 +1,130 @@
+//! zlib (RFC 1950) stream, which wraps DEFLATE with a header and Adler-32 checksum.
+
+use super::inflate::{inflate_consume_limited, InflateError};
+
+/// Error of zlib decompression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZlibError {
+    /// Input ends before the header or checksum.
+    UnexpectedEof,
+
+    /// Compression method is not DEFLATE, or header check bits are wrong.
+    InvalidHeader,
+
+    /// The stream needs a preset dictionary.
+    PresetDictionary,
+
+    /// Adler-32 of output does not match.
+    ChecksumMismatch,
+
+    Inflate(InflateError),
+}
+
+impl core::fmt::Display for ZlibError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ZlibError::UnexpectedEof => write!(fmt, "unexpected end of zlib stream"),
+            ZlibError::InvalidHeader => write!(fmt, "invalid zlib header"),
+            ZlibError::PresetDictionary => write!(fmt, "preset dictionary is not supported"),
+            ZlibError::ChecksumMismatch => write!(fmt, "Adler-32 mismatch"),
+            ZlibError::Inflate(e) => write!(fmt, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ZlibError {}
+
+impl From<InflateError> for ZlibError {
+    fn from(e: InflateError) -> Self {
+        ZlibError::Inflate(e)
+    }
+}
+
+/// Adler-32 of data.
+pub fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    // Sums do not overflow u32 in 5552 bytes.
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &x in chunk {
+            a += x as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// Decompress zlib stream up to `limit` bytes.
+/// Use this for untrusted input, which may expand over 1000 times.
+pub fn decompress_limited(input: &[u8], limit: usize) -> Result<Vec<u8>, ZlibError> {
+    let [cmf, flg] = *input.first_chunk::<2>().ok_or(ZlibError::UnexpectedEof)?;
+    if cmf & 0x0f != 8 || cmf >> 4 > 7 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) {
+        return Err(ZlibError::InvalidHeader);
+    }
+    if flg & 0x20 != 0 {
+        return Err(ZlibError::PresetDictionary);
+    }
+
+    let (out, read) = inflate_consume_limited(&input[2..], limit)?;
+    let checksum = input
+        .get(2 + read..)
+        .and_then(|x| x.first_chunk::<4>())
+        .ok_or(ZlibError::UnexpectedEof)?;
+    if u32::from_be_bytes(*checksum) != adler32(&out) {
+        return Err(ZlibError::ChecksumMismatch);
+    }
+    Ok(out)
+}
+
+/// Decompress zlib stream.
+pub fn decompress(input: &[u8]) -> Result<Vec<u8>, ZlibError> {
+    decompress_limited(input, usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress::deflate::deflate;
+
+    #[test]
+    fn check_value() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+        let long = vec![0xff; 100000];
+        let naive = long.iter().fold((1u64, 0u64), |(a, b), x| {
+            let a = (a + *x as u64) % 65521;
+            (a, (b + a) % 65521)
+        });
+        assert_eq!(adler32(&long), ((naive.1 << 16) | naive.0) as u32);
+    }
+
+    #[test]
+    fn stream() {
+        let input = [
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00, 0x06, 0x2c, 0x02, 0x15,
+        ];
+        assert_eq!(decompress(&input).unwrap(), b"hello");
+
+        let mut broken = input;
+        broken[12] ^= 1;
+        assert_eq!(decompress(&broken), Err(ZlibError::ChecksumMismatch));
+        assert_eq!(decompress(&input[..11]), Err(ZlibError::UnexpectedEof));
+        assert_eq!(decompress(&[0x78, 0x9d]), Err(ZlibError::InvalidHeader));
+        assert_eq!(decompress(&[0x78, 0xbb]), Err(ZlibError::PresetDictionary));
+        assert_eq!(
+            decompress_limited(&input, 4),
+            Err(ZlibError::Inflate(InflateError::OutputLimit))
+        );
+
+        let data: Vec<u8> = (0..10000u32).map(|x| (x % 251) as u8).collect();
+        let stream = [
+            &[0x78, 0x9c][..],
+            &deflate(&data),
+            &adler32(&data).to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(decompress(&stream).unwrap(), data);
+    }
+}
@@ -14,7 +14,12 @@ impl Sequential {
 
     /// Create Seekable instance at `pos`
     pub fn to_seeakble_at(&self, pos: usize) -> Seekable<'_> {
-        Seekable { inner: self, pos }
+        Seekable {
+            inner: self,
+            start: 0,
+            end: self.vector.len(),
+            pos,
+        }
     }
 
     /// Create Seekable instance at 0
@@ -25,9 +30,12 @@ impl Sequential {
 
 /// Seekable binary wrapper.
 /// This provide binary interpret methods.
+/// It may be limited to a range of the binary. Then offsets are relative to the start of the range.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Seekable<'a> {
     inner: &'a Sequential,
+    start: usize,
+    end: usize,
     pos: usize,
 }
 
@@ -84,11 +92,13 @@ impl<T> InterpretObject<T> {}
 
 impl Seekable<'_> {
     /// Interpret object at absolute offset at `apos`.
+    /// If `apos` is out of binary, this results NotEnoughSlice.
     pub fn interpret_abs_pos<InterpretType>(&self, apos: usize) -> InterpretResult<InterpretType>
     where
         InterpretType: FromU8Array,
     {
-        InterpretResult::new(apos, InterpretType::from_slice(&self.inner.vector[apos..]))
+        let slice = self.bytes().get(apos..).unwrap_or(&[]);
+        InterpretResult::new(apos, InterpretType::from_slice(slice))
     }
 
     /// Interpret object at self.pos. and proceed self.pos by interpreted object size.
@@ -113,4 +123,51 @@ impl Seekable<'_> {
         self.pos = at;
         return *self;
     }
+
+    /// Current offset.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Size of binary or range.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Return true if binary is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> Seekable<'a> {
+    fn bytes(&self) -> &'a [u8] {
+        &self.inner.vector[self.start..self.end]
+    }
+
+    /// Raw bytes at [`apos`, `apos` + `len`).
+    /// If the range is out of binary, this returns None.
+    pub fn bytes_abs_pos(&self, apos: usize, len: usize) -> Option<&'a [u8]> {
+        self.bytes().get(apos..apos.checked_add(len)?)
+    }
+
+    /// Seekable limited to [`apos`, `apos` + `len`) of self. Offsets of the result are relative to `apos`.
+    /// If the range is out of self, this returns None.
+    pub fn range(&self, apos: usize, len: usize) -> Option<Self> {
+        let end = apos.checked_add(len)?;
+        if end > self.len() {
+            return None;
+        }
+        Some(Self {
+            inner: self.inner,
+            start: self.start + apos,
+            end: self.start + end,
+            pos: 0,
+        })
+    }
+
+    /// Offset of the start of self in the whole binary.
+    pub fn base(&self) -> usize {
+        self.start
+    }
 }
@@ -1,6 +1,7 @@
 #![feature(split_array)]
 #![feature(maybe_uninit_uninit_array)]
 
+pub mod compress;
 pub mod file;
 
 #[macro_use]
@@ -1,78 +1,429 @@
-#![feature(split_array)]
-#![feature(maybe_uninit_uninit_array)]
-pub mod file;
-use file::Sequential;
+use elfeat::{file, types};
 
-use std::collections::HashMap;
+use file::{Seekable, Sequential};
+use types::ar::Archive;
+use types::detect::{detect, Detection, Kind};
+use types::elf::{lint, Elf};
+use types::model::Severity;
+use types::{bmp, dwarf, macho, pe, scan, zip};
 
-#[macro_use]
-pub mod types;
+const USAGE: &str = "usage: elfeat [<command>] <file>
 
-use types::elf::*;
-use types::Array;
+commands:
+    header  print headers of the detected format (default)
+    detect  list formats that match the file
+    scan    find formats embedded at any offset of the file
+    lint    check consistency of ELF structures
+    symbols print symbol table of ELF file or each ELF member of ar archive
+    core    print process, signal, threads and mapped files of ELF core file
+    lines   print address to source line table of DWARF in ELF file
+    debuginfo print functions, variables and types of DWARF in ELF file
+    frames  print call frame information and unwind rows of ELF file";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+fn fail(message: impl core::fmt::Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
 
 fn main() {
-    use std::fs::File;
-    use std::io::Read;
-    let mut f = File::open("./bin/static").unwrap();
-    let mut v = vec![];
+    let args: Vec<String> = std::env::args().collect();
+    let (command, path) = match args.as_slice() {
+        [_, path] => ("header", path.as_str()),
+        [_, command, path] => (command.as_str(), path.as_str()),
+        _ => usage(),
+    };
+
+    let s = match std::fs::read(path) {
+        Ok(v) => Sequential::from_vec(v),
+        Err(e) => fail(format!("{}: {}", path, e)),
+    };
+
+    match command {
+        "header" => header(&s),
+        "detect" => print_detections(&detect(&s)),
+        "scan" => scan(&s),
+        "lint" => lint(&s),
+        "symbols" => symbols(&s),
+        "core" => core_dump(&s),
+        "lines" => lines(&s),
+        "debuginfo" => debuginfo(&s),
+        "frames" => frames(&s),
+        _ => usage(),
+    }
+}
+
+/// The most confident format of the file.
+fn best(s: &Sequential) -> Detection {
+    match detect(s).first() {
+        Some(x) => *x,
+        None => fail("unknown format"),
+    }
+}
 
-    f.read_to_end(&mut v).unwrap();
+fn print_detections(detections: &[Detection]) {
+    if detections.is_empty() {
+        fail("unknown format");
+    }
+    for d in detections {
+        println!("{:<12} {}", d.format.name, d.confidence);
+    }
+}
 
-    let s = Sequential::from_vec(v);
+fn scan(s: &Sequential) {
+    let found = scan::scan(s);
 
-    let mut main_seeker = s.to_seeakble();
-    let header: elf_header::Header = main_seeker.interpret_abs_pos(0).to_tuple_unwrap().1;
+    println!("    Offset     Length  Confidence  Format");
+    for p in &found.payloads {
+        println!(
+            "{:>10} {:>10}  {:<10}  {}{}",
+            p.offset,
+            p.len,
+            p.confidence.to_string(),
+            "  ".repeat(p.depth),
+            p.format.name
+        );
+    }
+    if found.is_polyglot() {
+        let names: Vec<_> = found.whole.iter().map(|x| x.name).collect();
+        println!("polyglot: the whole file is valid as {}", names.join(", "));
+    }
+}
 
-    println!("{}", header);
+fn header(s: &Sequential) {
+    let seeker = s.to_seeakble();
 
-    if header.e_shstrndx.unwrap().is_SHN_UNDEF() {
-        panic!();
+    match best(s).format.kind {
+        Kind::Elf => header_elf(seeker),
+        Kind::Zip => header_zip(seeker),
+        Kind::Bmp => header_bmp(seeker),
+        Kind::Pe => header_pe(seeker),
+        Kind::Ar => header_ar(seeker),
+        Kind::MachO => header_macho(seeker),
+        Kind::FatMachO => header_fat(seeker),
     }
+}
+
+fn header_elf(seeker: Seekable) {
+    let elf = Elf::parse(seeker).unwrap_or_else(|_| fail("ELF header is truncated"));
 
-    let section_header_offset = header.e_shoff.unwrap().inner();
-    let section_strtab_index = header.e_shstrndx.unwrap().inner() as usize;
+    println!("{}", elf.header());
 
-    main_seeker.seek(section_header_offset);
+    for (i, sh) in elf.section_headers().iter().enumerate() {
+        let name = elf.section_name(sh).unwrap_or_default();
+        println!("[{}] {}\n{}", i, name, sh);
+    }
+    for (i, ph) in elf.program_headers().iter().enumerate() {
+        println!("[{}] {}", i, ph);
+    }
+}
 
-    let mut section_headers = vec![];
-    for _i in 0.. {
-        let (_at, read) = main_seeker
-            .interpret_next::<section_header::Header>()
-            .to_tuple();
+fn header_zip(seeker: Seekable) {
+    let zip = zip::ZipArchive::parse(seeker).unwrap_or_else(|e| fail(e));
 
-        if read.is_err() {
-            break;
+    println!("{}", zip.eocd());
+    if let Some(zip64) = zip.zip64_eocd() {
+        println!("{}", zip64);
+    }
+    for entry in zip.entries() {
+        println!(
+            "{:>10} {:>10} {:08x}  {}  {}",
+            entry.uncompressed_size(),
+            entry.compressed_size(),
+            entry.crc32(),
+            entry.last_modified(),
+            entry.name()
+        );
+    }
+}
+
+fn header_bmp(seeker: Seekable) {
+    let bmp = bmp::Bmp::parse(seeker).unwrap_or_else(|e| fail(e));
+
+    println!("{}", bmp.file_header());
+    println!("{}", bmp.info_header());
+}
+
+fn header_pe(seeker: Seekable) {
+    let pe = pe::Pe::parse(seeker).unwrap_or_else(|e| fail(e));
+
+    println!("{}", pe.dos_header());
+    println!("{}", pe.file_header());
+    if let Some(oh) = pe.optional_header() {
+        println!("{}", oh);
+    }
+    for (i, sh) in pe.section_headers().iter().enumerate() {
+        println!("[{}] {}", i, sh);
+    }
+    match pe.imports() {
+        Ok(imports) => {
+            for lib in imports {
+                let names: Vec<_> = lib.symbols.iter().map(|x| x.to_string()).collect();
+                println!("import {}: {}", lib.name, names.join(", "));
+            }
         }
+        Err(e) => println!("imports: {}", e),
+    }
+    match pe.exports() {
+        Ok(Some(exports)) => {
+            for sym in exports.symbols {
+                let name = sym.name.unwrap_or_default();
+                println!("export #{} {} {:?}", sym.ordinal, name, sym.target);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => println!("exports: {}", e),
+    }
+}
 
-        section_headers.push(read.unwrap().1);
+fn header_ar(seeker: Seekable) {
+    let archive = Archive::parse(seeker).unwrap_or_else(|e| fail(e));
+
+    for m in archive.members() {
+        println!("{:>10} {:>10} {}", m.offset, m.size, m.name);
     }
+    println!("{} symbols in index", archive.symbols().len());
+}
 
-    let strtab = section_headers[section_strtab_index];
-    println!("{}", strtab);
+fn header_macho(seeker: Seekable) {
+    let m = macho::MachO::parse(seeker).unwrap_or_else(|e| fail(e));
 
-    let mut sections = HashMap::new();
+    println!("{}", m.header());
+    for (i, lc) in m.load_commands().iter().enumerate() {
+        println!("[{}] {}", i, lc.command);
+    }
+    match m.sections() {
+        Ok(sections) => {
+            for (i, section) in sections.iter().enumerate() {
+                println!("[{}] {}", i + 1, section);
+            }
+        }
+        Err(e) => println!("sections: {}", e),
+    }
+}
 
-    // TODO: range limited seeker
+fn header_fat(seeker: Seekable) {
+    let fat = macho::FatBinary::parse(seeker).unwrap_or_else(|e| fail(e));
 
-    for sh in section_headers {
-        let (_, name) = main_seeker
-            .interpret_abs_pos::<crate::types::primitive::NullTermString>(
-                sh.get_sh_name_unwrap().inner() as usize + strtab.get_sh_offset_unwrap().inner(),
-            )
-            .to_tuple_unwrap();
+    println!("{}", fat.header());
+    for arch in fat.archs() {
+        println!("{}", arch);
+        match fat.macho(arch) {
+            Ok(m) => println!("{}", m.header()),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+fn lint(s: &Sequential) {
+    let findings = lint::lint(s.to_seeakble());
+
+    for f in &findings {
+        println!("{}", f);
+    }
+
+    if findings
+        .iter()
+        .any(|f| f.diagnostic.severity == Severity::Error)
+    {
+        std::process::exit(1);
+    }
+}
+
+fn symbols(s: &Sequential) {
+    let detection = best(s);
+
+    match detection.format.kind {
+        Kind::Elf => {
+            print_symbols(s.to_seeakble());
+        }
+        Kind::Ar => {
+            let archive = Archive::parse(s.to_seeakble()).unwrap_or_else(|e| fail(e));
+            for member in archive.members() {
+                println!("\n{}:", member.name);
+                let printed = archive
+                    .member_seeker(member)
+                    .map(print_symbols)
+                    .unwrap_or(false);
+                if !printed {
+                    println!("not an ELF file");
+                }
+            }
+        }
+        _ => fail(format!("symbols of {} are not supported", detection.format.name)),
+    }
+}
+
+/// Print symbols of ELF file in `seeker`. If it is not ELF, this returns false.
+fn print_symbols(seeker: Seekable) -> bool {
+    let elf = match Elf::parse(seeker) {
+        Ok(elf) if elf.header().get_e_ident_unwrap().is_elf() => elf,
+        _ => return false,
+    };
+    let symtab = match elf.symbol_table() {
+        Some(x) => x,
+        None => return true,
+    };
+
+    for sym in elf.symbols(symtab) {
+        let e = &sym.entry;
+        let info = e.get_st_info_unwrap();
+        let shndx = e.get_st_shndx_unwrap();
+        println!(
+            "{:016x} {:>6} {:<12} {:<10} {:>10} {}",
+            e.get_st_value_unwrap().inner(),
+            e.get_st_size_unwrap().inner(),
+            info.typ().name().unwrap_or("?"),
+            info.bind().name().unwrap_or("?"),
+            shndx.name().map(str::to_string).unwrap_or_else(|| shndx.inner().to_string()),
+            sym.name
+        );
+    }
+
+    true
+}
+
+fn core_dump(s: &Sequential) {
+    let elf = match Elf::parse(s.to_seeakble()) {
+        Ok(elf) if elf.header().get_e_type_unwrap().is_ET_CORE() => elf,
+        _ => fail("not an ELF core file"),
+    };
+
+    if let Some(info) = elf.process_info() {
+        println!("process {} ({}): {}", info.pid, info.fname, info.psargs);
+    }
+    if let Some(sig) = elf.siginfo() {
+        print!("signal {} code {}", sig.signo, sig.code);
+        match sig.fault_address() {
+            Some(addr) => println!(" at {:#x}", addr),
+            None => println!(),
+        }
+    }
+    for t in elf.threads() {
+        println!("\nthread {} signal {}", t.pid, t.cursig);
+        for (name, value) in t.registers.named() {
+            println!("{:>8} {:016x}", name, value);
+        }
+    }
+    println!();
+    for f in elf.mapped_files() {
+        println!("{:016x}-{:016x} {:>10x} {}", f.start, f.end, f.offset, f.name);
+    }
+}
+
+fn lines(s: &Sequential) {
+    let elf = Elf::parse(s.to_seeakble()).unwrap_or_else(|_| fail("ELF header is truncated"));
+    let table = dwarf::Dwarf::from_elf(&elf)
+        .line_table()
+        .unwrap_or_else(|e| fail(e));
 
-        sections.insert(name, sh);
+    for entry in table.entries() {
+        println!(
+            "{:016x}-{:016x} {}:{}:{}",
+            entry.start,
+            entry.end,
+            table.files()[entry.file],
+            entry.line,
+            entry.column
+        );
     }
+}
 
-    let interp = sections[".interp"];
-    println!("{}", interp);
-    main_seeker.seek(interp.get_sh_offset_unwrap().inner());
+fn debuginfo(s: &Sequential) {
+    let elf = Elf::parse(s.to_seeakble()).unwrap_or_else(|_| fail("ELF header is truncated"));
+    let dwarf = dwarf::Dwarf::from_elf(&elf);
 
-    let (_pos, interp) = main_seeker
-        // .interpret_next::<Array<u8, 0x20>>()
-        .interpret_next::<types::primitive::NullTermString>()
-        .to_tuple_unwrap();
+    println!("functions:");
+    for f in dwarf.functions().unwrap_or_else(|e| fail(e)) {
+        let ranges: Vec<_> = f
+            .ranges
+            .iter()
+            .map(|x| format!("{:x}-{:x}", x.start, x.end))
+            .collect();
+        let kind = if f.inlined { " (inlined)" } else { "" };
+        println!("    {}{} {}", f.name, kind, ranges.join(" "));
+    }
 
-    println!("{}: {}", interp.len(), interp);
+    println!("variables:");
+    for v in dwarf.variables().unwrap_or_else(|e| fail(e)) {
+        print!("    {} {}", v.type_name, v.name);
+        if let Some(scope) = v.scope {
+            print!(" in {}", scope);
+        }
+        if let Some(address) = v.address {
+            print!(" at {:#x}", address);
+        }
+        println!();
+    }
+
+    println!("types:");
+    for t in dwarf.types().unwrap_or_else(|e| fail(e)) {
+        let tag = t.tag.name().unwrap_or("?");
+        match t.byte_size {
+            Some(size) => println!("    {} {} ({} bytes)", tag, t.name, size),
+            None => println!("    {} {}", tag, t.name),
+        }
+    }
+}
+
+fn frames(s: &Sequential) {
+    let elf = Elf::parse(s.to_seeakble()).unwrap_or_else(|_| fail("ELF header is truncated"));
+
+    for (name, kind) in [
+        (".eh_frame", dwarf::FrameKind::EhFrame),
+        (".debug_frame", dwarf::FrameKind::DebugFrame),
+    ] {
+        let Some(frame) = dwarf::CallFrame::from_elf(&elf, kind) else {
+            continue;
+        };
+        println!("{}:", name);
+        for entry in frame.entries().unwrap_or_else(|e| fail(e)) {
+            let fde = match entry {
+                dwarf::FrameEntry::Cie(cie) => {
+                    println!(
+                        "{:08x} CIE \"{}\" cf={} df={} ra={}",
+                        cie.offset,
+                        cie.augmentation,
+                        cie.code_alignment_factor,
+                        cie.data_alignment_factor,
+                        cie.return_address_register
+                    );
+                    continue;
+                }
+                dwarf::FrameEntry::Fde(fde) => fde,
+            };
+            let range = fde.range();
+            println!(
+                "{:08x} FDE cie={:08x} pc={:x}..{:x}",
+                fde.offset, fde.cie.offset, range.start, range.end
+            );
+            for row in fde.rows().unwrap_or_else(|e| fail(e)) {
+                let cfa = match row.cfa {
+                    dwarf::CfaRule::RegisterOffset(r, offset) => format!("r{}{:+}", r, offset),
+                    dwarf::CfaRule::Expression(_) => "exp".to_string(),
+                };
+                let registers: Vec<_> = row
+                    .registers
+                    .iter()
+                    .map(|(r, rule)| {
+                        let rule = match rule {
+                            dwarf::RegisterRule::Undefined => "u".to_string(),
+                            dwarf::RegisterRule::SameValue => "s".to_string(),
+                            dwarf::RegisterRule::Offset(x) => format!("c{:+}", x),
+                            dwarf::RegisterRule::ValOffset(x) => format!("v{:+}", x),
+                            dwarf::RegisterRule::Register(x) => format!("r{}", x),
+                            dwarf::RegisterRule::Expression(_) => "exp".to_string(),
+                            dwarf::RegisterRule::ValExpression(_) => "vexp".to_string(),
+                        };
+                        format!("r{}={}", r, rule)
+                    })
+                    .collect();
+                println!("    {:016x} cfa={} {}", row.start, cfa, registers.join(" "));
+            }
+        }
+    }
 }
@@ -1,5 +1,12 @@
+pub mod ar;
+pub mod bmp;
+pub mod detect;
+pub mod dwarf;
 pub mod elf;
+pub mod macho;
+pub mod pe;
 pub mod primitive;
+pub mod scan;
 pub mod zip;
 
 #[macro_use]
@@ -7,7 +14,7 @@ pub mod model;
 
 /// Array struct that is able to intepreted to &[u8]
 /// The reason why we don't use [T; N] as FromU8Array is, to implement Display trait by user.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Array<T, const N: usize>([T; N]);
 
 impl<T, const N: usize> From<Array<T, N>> for [T; N] {
@@ -39,6 +46,32 @@ impl<T, const N: usize> Array<T, N> {
     }
 }
 
+/// Undecoded bytes. This takes all of the given slice.
+/// Tagged types hold this when the tag is unknown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawBytes(Vec<u8>);
+
+impl From<RawBytes> for Vec<u8> {
+    fn from(x: RawBytes) -> Self {
+        x.0
+    }
+}
+impl From<Vec<u8>> for RawBytes {
+    fn from(x: Vec<u8>) -> Self {
+        Self(x)
+    }
+}
+
+impl RawBytes {
+    pub fn inner(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Error of writing a body whose length, held here, does not fit in its length field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthOverflow(pub usize);
+
 /// Error type of represent from u8.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FromU8Error<T> {
@@ -0,0 +1,157 @@
+use super::Array;
+use crate::{define_composed_type, define_model_type};
+
+mod reader;
+pub use reader::{ArError, Archive, IndexSymbol, Member};
+
+/// Size of global header in bytes.
+pub const MAGIC_SIZE: usize = 8;
+
+/// Size of member header in bytes.
+pub const MEMBER_HEADER_SIZE: usize = 60;
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Magic(u64),
+    pub
+    [
+        (ARMAG: 0x0a3e686372613c21),   // "!<arch>\n"
+        (THINMAG: 0x0a3e6e6968743c21), // "!<thin>\n"
+    ],
+    display = true, bitflags = false,
+);
+
+/// Text of header field without padding spaces.
+fn field_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).trim_end_matches(' ').to_string()
+}
+
+/// Number in header field. Empty fields are 0.
+fn field_number(bytes: &[u8], radix: u32) -> Option<u64> {
+    let text = field_text(bytes);
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(&text, radix).ok()
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Name(Array<u8, 16>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for Name {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.text())
+    }
+}
+impl Name {
+    /// Name without padding spaces. This may be a reference to long name.
+    pub fn text(&self) -> String {
+        field_text(self.0.inner())
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Date(Array<u8, 12>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for Date {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", field_text(self.0.inner()))
+    }
+}
+impl Date {
+    /// Modification time in seconds since the epoch.
+    pub fn value(&self) -> Option<u64> {
+        field_number(self.0.inner(), 10)
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Id(Array<u8, 6>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for Id {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", field_text(self.0.inner()))
+    }
+}
+impl Id {
+    pub fn value(&self) -> Option<u64> {
+        field_number(self.0.inner(), 10)
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Mode(Array<u8, 8>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for Mode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", field_text(self.0.inner()))
+    }
+}
+impl Mode {
+    /// File mode. This is written in octal.
+    pub fn value(&self) -> Option<u64> {
+        field_number(self.0.inner(), 8)
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Size(Array<u8, 10>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for Size {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", field_text(self.0.inner()))
+    }
+}
+impl Size {
+    /// Size of member data in bytes. BSD long names are included.
+    pub fn value(&self) -> Option<u64> {
+        field_number(self.0.inner(), 10)
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Terminator(u16),
+    pub
+    [
+        (ARFMAG: 0x0a60), // "`\n"
+    ],
+    display = true, bitflags = false,
+);
+
+// All fields are ASCII text padded with spaces.
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct MemberHeader {
+        /// Member name, "/" terminated in GNU format
+        ar_name: Option<Name>,
+        /// Modification time in decimal
+        ar_date: Option<Date>,
+        /// Owner id in decimal
+        ar_uid: Option<Id>,
+        /// Group id in decimal
+        ar_gid: Option<Id>,
+        /// File mode in octal
+        ar_mode: Option<Mode>,
+        /// Size of member data in decimal
+        ar_size: Option<Size>,
+        /// Always "`\n"
+        ar_fmag: Option<Terminator>,
+    },
+    display = true,
+);
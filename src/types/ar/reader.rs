@@ -0,0 +1,352 @@
+use super::{Magic, MemberHeader, MAGIC_SIZE, MEMBER_HEADER_SIZE};
+use crate::file::Seekable;
+
+/// Error of ar archive parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArError {
+    /// File does not start with "!<arch>\n".
+    BadMagic,
+
+    /// Thin archive refers members by path. They are not in the file.
+    ThinArchive,
+
+    /// The structure at the offset is out of file.
+    Truncated(usize),
+
+    /// Member header at the offset has bad terminator or size.
+    BadMemberHeader(usize),
+
+    /// Long name of member at the offset is not in the long name table.
+    BadName(usize),
+
+    /// Symbol index at the offset is broken.
+    BadSymbolIndex(usize),
+}
+
+impl core::fmt::Display for ArError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ArError::BadMagic => write!(fmt, "bad ar magic"),
+            ArError::ThinArchive => write!(fmt, "thin archive is not supported"),
+            ArError::Truncated(at) => write!(fmt, "truncated structure at {:#x}", at),
+            ArError::BadMemberHeader(at) => write!(fmt, "bad member header at {:#x}", at),
+            ArError::BadName(at) => write!(fmt, "bad long name of member at {:#x}", at),
+            ArError::BadSymbolIndex(at) => write!(fmt, "bad symbol index at {:#x}", at),
+        }
+    }
+}
+
+impl std::error::Error for ArError {}
+
+/// Regular member of archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    /// File offset of the member header. Symbol index refers members by this.
+    pub offset: usize,
+    pub header: MemberHeader,
+    /// Name resolved from GNU long name table or BSD "#1/" name.
+    pub name: String,
+    /// File offset of the data. BSD long name is skipped.
+    pub data_offset: usize,
+    /// Size of the data. BSD long name is excluded.
+    pub size: usize,
+}
+
+/// Entry of symbol index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSymbol {
+    pub name: String,
+    /// File offset of the header of the member that defines the symbol.
+    pub member: usize,
+}
+
+/// ar archive in GNU or BSD format.
+/// Special members (symbol index and long name table) are interpreted and not listed in members.
+#[derive(Debug, Clone)]
+pub struct Archive<'a> {
+    seeker: Seekable<'a>,
+    members: Vec<Member>,
+    symbols: Vec<IndexSymbol>,
+}
+
+/// Big endian number of `size` bytes at `at` of `data`.
+fn be_at(data: &[u8], at: usize, size: usize) -> Option<usize> {
+    let bytes = data.get(at..at.checked_add(size)?)?;
+    Some(bytes.iter().fold(0usize, |acc, x| (acc << 8) | *x as usize))
+}
+
+/// Little endian number of `size` bytes at `at` of `data`.
+fn le_at(data: &[u8], at: usize, size: usize) -> Option<usize> {
+    let bytes = data.get(at..at.checked_add(size)?)?;
+    Some(bytes.iter().rev().fold(0usize, |acc, x| (acc << 8) | *x as usize))
+}
+
+/// Null terminated string at `at` of `data`.
+fn string_at(data: &[u8], at: usize) -> Option<String> {
+    let rest = data.get(at..)?;
+    let len = rest.iter().position(|x| *x == 0)?;
+    Some(String::from_utf8_lossy(&rest[..len]).into_owned())
+}
+
+/// GNU symbol index: count, member offsets and null terminated names. Numbers are big endian.
+/// `size` is 4 for "/" and 8 for "/SYM64/".
+fn parse_gnu_index(data: &[u8], size: usize) -> Option<Vec<IndexSymbol>> {
+    let count = be_at(data, 0, size)?;
+    let mut name_at = count.checked_add(1)?.checked_mul(size)?;
+    let mut ret = vec![];
+
+    for i in 0..count {
+        let member = be_at(data, (i + 1) * size, size)?;
+        let name = string_at(data, name_at)?;
+        name_at += name.len() + 1;
+        ret.push(IndexSymbol { name, member });
+    }
+
+    Some(ret)
+}
+
+/// BSD symbol index: size of ranlib array, ranlib entries (name, member offset), size of strings and strings.
+/// Numbers are little endian. `size` is 4 for "__.SYMDEF" and 8 for "__.SYMDEF_64".
+fn parse_bsd_index(data: &[u8], size: usize) -> Option<Vec<IndexSymbol>> {
+    let ranlib_size = le_at(data, 0, size)?;
+    let strings_at = size.checked_add(ranlib_size)?.checked_add(size)?;
+    let strings = data.get(strings_at..strings_at.checked_add(le_at(data, size + ranlib_size, size)?)?)?;
+    let mut ret = vec![];
+
+    for i in 0..ranlib_size / (size * 2) {
+        let at = size + i * size * 2;
+        let name = string_at(strings, le_at(data, at, size)?)?;
+        let member = le_at(data, at + size, size)?;
+        ret.push(IndexSymbol { name, member });
+    }
+
+    Some(ret)
+}
+
+impl<'a> Archive<'a> {
+    /// Interpret ar archive that starts at offset 0 of `seeker`.
+    pub fn parse(seeker: Seekable<'a>) -> Result<Self, ArError> {
+        let (_, magic) = seeker
+            .interpret_abs_pos::<Magic>(0)
+            .to_tuple()
+            .1
+            .map_err(|_| ArError::Truncated(0))?;
+        if magic.is_THINMAG() {
+            return Err(ArError::ThinArchive);
+        }
+        if !magic.is_ARMAG() {
+            return Err(ArError::BadMagic);
+        }
+
+        let mut ret = Self {
+            seeker,
+            members: vec![],
+            symbols: vec![],
+        };
+        let mut long_names: &[u8] = &[];
+        let mut at = MAGIC_SIZE;
+
+        while at < seeker.len() {
+            let (_, header) = seeker
+                .interpret_abs_pos::<MemberHeader>(at)
+                .to_tuple()
+                .1
+                .map_err(|_| ArError::Truncated(at))?;
+            if !header.get_ar_fmag_unwrap().is_ARFMAG() {
+                return Err(ArError::BadMemberHeader(at));
+            }
+            let size = header
+                .get_ar_size_unwrap()
+                .value()
+                .and_then(|x| usize::try_from(x).ok())
+                .ok_or(ArError::BadMemberHeader(at))?;
+            let data_offset = at + MEMBER_HEADER_SIZE;
+            let data = seeker
+                .bytes_abs_pos(data_offset, size)
+                .ok_or(ArError::Truncated(at))?;
+            let next = data_offset + size + (size & 1);
+            let raw_name = header.get_ar_name_unwrap().text();
+
+            match raw_name.as_str() {
+                "/" | "/SYM64/" => {
+                    let width = if raw_name == "/" { 4 } else { 8 };
+                    ret.symbols = parse_gnu_index(data, width).ok_or(ArError::BadSymbolIndex(at))?;
+                }
+                "//" => long_names = data,
+                _ => {
+                    let mut member = Member {
+                        offset: at,
+                        header,
+                        name: String::new(),
+                        data_offset,
+                        size,
+                    };
+                    if let Some(len) = raw_name.strip_prefix("#1/") {
+                        // BSD name follows the header and it is a part of data.
+                        let len = len
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|x| *x <= size)
+                            .ok_or(ArError::BadName(at))?;
+                        let name = &data[..len];
+                        let end = name.iter().position(|x| *x == 0).unwrap_or(len);
+                        member.name = String::from_utf8_lossy(&name[..end]).into_owned();
+                        member.data_offset += len;
+                        member.size -= len;
+                    } else if let Some(offset) = raw_name.strip_prefix('/') {
+                        // GNU long name is terminated by "/\n".
+                        let name = offset
+                            .parse::<usize>()
+                            .ok()
+                            .and_then(|x| long_names.get(x..))
+                            .ok_or(ArError::BadName(at))?;
+                        let end = name.iter().position(|x| *x == b'\n').unwrap_or(name.len());
+                        let name = String::from_utf8_lossy(&name[..end]);
+                        member.name = name.strip_suffix('/').unwrap_or(&name).to_string();
+                    } else {
+                        member.name = raw_name.strip_suffix('/').unwrap_or(&raw_name).to_string();
+                    }
+
+                    if member.name.starts_with("__.SYMDEF") {
+                        let data = &data[member.data_offset - data_offset..];
+                        let width = if member.name.starts_with("__.SYMDEF_64") { 8 } else { 4 };
+                        ret.symbols = parse_bsd_index(data, width).ok_or(ArError::BadSymbolIndex(at))?;
+                    } else {
+                        ret.members.push(member);
+                    }
+                }
+            }
+
+            at = next;
+        }
+
+        Ok(ret)
+    }
+
+    pub fn seeker(&self) -> Seekable<'a> {
+        self.seeker
+    }
+
+    pub fn members(&self) -> &[Member] {
+        &self.members
+    }
+
+    /// Symbol index. If the archive has no index, this is empty.
+    pub fn symbols(&self) -> &[IndexSymbol] {
+        &self.symbols
+    }
+
+    /// Member whose header is at `offset`. Use this to resolve symbol index entries.
+    pub fn member_at(&self, offset: usize) -> Option<&Member> {
+        self.members.iter().find(|x| x.offset == offset)
+    }
+
+    /// Find member by name.
+    pub fn member_by_name(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|x| x.name == name)
+    }
+
+    /// Seekable limited to the data of `member`.
+    pub fn member_seeker(&self, member: &Member) -> Option<Seekable<'a>> {
+        self.seeker.range(member.data_offset, member.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::elf::{symbol, Elf};
+
+    const GNU: &[u8] = include_bytes!("../../../example_bin/libsample.a");
+    const GNU64: &[u8] = include_bytes!("../../../example_bin/libsample64.a");
+    const BSD: &[u8] = include_bytes!("../../../example_bin/libsample_bsd.a");
+
+    const LONG_NAME: &str = "a_rather_long_member_name.o";
+
+    fn index(archive: &Archive) -> Vec<(String, String)> {
+        archive
+            .symbols()
+            .iter()
+            .map(|x| (x.name.clone(), archive.member_at(x.member).unwrap().name.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn members_and_index() {
+        let expected: Vec<_> = [
+            ("add", "add.o"),
+            ("counter", "add.o"),
+            ("twice", LONG_NAME),
+            ("greeting", LONG_NAME),
+        ]
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+
+        for bin in [GNU, GNU64, BSD] {
+            let s = Sequential::from_vec(bin.to_vec());
+            let archive = Archive::parse(s.to_seeakble()).unwrap();
+
+            let names: Vec<_> = archive.members().iter().map(|x| x.name.as_str()).collect();
+            assert_eq!(names, vec!["add.o", LONG_NAME]);
+            assert_eq!(index(&archive), expected);
+
+            let m = archive.member_by_name("add.o").unwrap();
+            assert_eq!(m.header.get_ar_mode_unwrap().value(), Some(0o644));
+            let data = archive.member_seeker(m).unwrap();
+            assert_eq!(data.base(), m.data_offset);
+            assert_eq!(data.bytes_abs_pos(0, 4), Some(&b"\x7fELF"[..]));
+        }
+    }
+
+    #[test]
+    fn member_elf_symbols() {
+        let s = Sequential::from_vec(GNU.to_vec());
+        let archive = Archive::parse(s.to_seeakble()).unwrap();
+        let m = archive.member_by_name(LONG_NAME).unwrap();
+        let elf = Elf::parse(archive.member_seeker(m).unwrap()).unwrap();
+
+        let symbols = elf.symbols(elf.symbol_table().unwrap());
+        let twice = symbols.iter().find(|x| x.name == "twice").unwrap();
+        assert_eq!(twice.entry.get_st_info_unwrap().typ(), symbol::Type::STT_FUNC);
+        assert_eq!(twice.entry.get_st_size_unwrap().inner(), 16);
+        let add = symbols.iter().find(|x| x.name == "add").unwrap();
+        assert!(add.entry.get_st_shndx_unwrap().is_SHN_UNDEF());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let s = Sequential::from_vec(b"!<thin>\n".to_vec());
+        assert_eq!(Archive::parse(s.to_seeakble()).unwrap_err(), ArError::ThinArchive);
+
+        let s = Sequential::from_vec(b"\x7fELF\x02\x01\x01\x00".to_vec());
+        assert_eq!(Archive::parse(s.to_seeakble()).unwrap_err(), ArError::BadMagic);
+
+        let s = Sequential::from_vec(b"!<arch>\n".to_vec());
+        assert!(Archive::parse(s.to_seeakble()).unwrap().members().is_empty());
+
+        // Member data runs out of file.
+        let s = Sequential::from_vec(GNU[..GNU.len() - 1].to_vec());
+        assert!(matches!(
+            Archive::parse(s.to_seeakble()),
+            Err(ArError::Truncated(_))
+        ));
+
+        // Long name refers out of table.
+        let mut v = GNU.to_vec();
+        let at = v.windows(4).position(|x| x == b"/0  ").unwrap();
+        v[at..at + 4].copy_from_slice(b"/999");
+        let s = Sequential::from_vec(v);
+        assert_eq!(Archive::parse(s.to_seeakble()).unwrap_err(), ArError::BadName(at));
+
+        // Broken terminator.
+        let mut v = GNU.to_vec();
+        v[MAGIC_SIZE + 58] = b'x';
+        let s = Sequential::from_vec(v);
+        assert_eq!(
+            Archive::parse(s.to_seeakble()).unwrap_err(),
+            ArError::BadMemberHeader(MAGIC_SIZE)
+        );
+    }
+}
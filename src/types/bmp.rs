@@ -0,0 +1,431 @@
+use crate::{define_composed_type, define_model_type, define_tagged_type};
+
+mod decoder;
+pub use decoder::{Bmp, BmpError, Image};
+
+/// Size of BITMAPFILEHEADER in bytes.
+pub const FILE_HEADER_SIZE: usize = 14;
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Signature(u16),
+    pub
+    [
+        (BM: 0x4d42),
+        // OS/2 variants.
+        (BA: 0x4142),
+        (CI: 0x4943),
+        (CP: 0x5043),
+        (IC: 0x4349),
+        (PT: 0x5450),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FileSize(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Reserved16(u16),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct PixelOffset(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FileHeader {
+        /// "BM"
+        bf_type: Option<Signature>,
+        /// Size of file in bytes
+        bf_size: Option<FileSize>,
+        bf_reserved1: Option<Reserved16>,
+        bf_reserved2: Option<Reserved16>,
+        /// Offset of pixel array from the beginning of file
+        bf_off_bits: Option<PixelOffset>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct InfoHeaderSize(u32),
+    pub
+    [
+        (BITMAPCOREHEADER: 12),
+        (BITMAPINFOHEADER: 40),
+        (BITMAPV2INFOHEADER: 52),
+        (BITMAPV3INFOHEADER: 56),
+        (BITMAPV4HEADER: 108),
+        (BITMAPV5HEADER: 124),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CoreDimension(u16),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Width(i32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+    predicates = [
+        (Severity::Error, "not positive", |x: &i32| *x > 0),
+    ],
+);
+
+// Negative height means top-down pixel array.
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Height(i32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+    predicates = [
+        (Severity::Error, "zero", |x: &i32| *x != 0),
+    ],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Planes(u16),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+    valid = [1..=1],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct BitCount(u16),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+    predicates = [
+        (Severity::Error, "not a valid bit count", |x: &u16| matches!(*x, 0 | 1 | 2 | 4 | 8 | 16 | 24 | 32)),
+    ],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Compression(u32),
+    pub
+    [
+        (BI_RGB: 0),
+        (BI_RLE8: 1),
+        (BI_RLE4: 2),
+        (BI_BITFIELDS: 3),
+        (BI_JPEG: 4),
+        (BI_PNG: 5),
+        (BI_ALPHABITFIELDS: 6),
+        (BI_CMYK: 11),
+        (BI_CMYKRLE8: 12),
+        (BI_CMYKRLE4: 13),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ImageSize(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Resolution(i32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ColorCount(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Mask(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ColorSpace(u32),
+    pub
+    [
+        (LCS_CALIBRATED_RGB: 0),
+        (LCS_SRGB: 0x73524742),
+        (LCS_WINDOWS_COLOR_SPACE: 0x57696e20),
+        (PROFILE_LINKED: 0x4c494e4b),
+        (PROFILE_EMBEDDED: 0x4d424544),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Fixed2Dot30(i32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Gamma(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Intent(u32),
+    pub
+    [
+        (LCS_GM_BUSINESS: 1),
+        (LCS_GM_GRAPHICS: 2),
+        (LCS_GM_IMAGES: 4),
+        (LCS_GM_ABS_COLORIMETRIC: 8),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ProfileOffset(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Reserved32(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+// OS/2 1.x header. Dimensions are unsigned and the palette holds RGBTRIPLE.
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CoreHeader {
+        bc_width: Option<CoreDimension>,
+        bc_height: Option<CoreDimension>,
+        bc_planes: Option<Planes>,
+        bc_bit_count: Option<BitCount>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct InfoHeaderV1 {
+        bi_width: Option<Width>,
+        /// Negative height means top-down pixel array
+        bi_height: Option<Height>,
+        bi_planes: Option<Planes>,
+        bi_bit_count: Option<BitCount>,
+        bi_compression: Option<Compression>,
+        /// Size of pixel array. This may be 0 for BI_RGB
+        bi_size_image: Option<ImageSize>,
+        /// Pixels per meter
+        bi_x_pels_per_meter: Option<Resolution>,
+        bi_y_pels_per_meter: Option<Resolution>,
+        /// Number of palette entries. 0 means 2^bit_count
+        bi_clr_used: Option<ColorCount>,
+        bi_clr_important: Option<ColorCount>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CieXyz {
+        ciexyz_x: Option<Fixed2Dot30>,
+        ciexyz_y: Option<Fixed2Dot30>,
+        ciexyz_z: Option<Fixed2Dot30>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CieXyzTriple {
+        ciexyz_red: Option<CieXyz>,
+        ciexyz_green: Option<CieXyz>,
+        ciexyz_blue: Option<CieXyz>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct InfoHeaderV4 {
+        /// Fields of BITMAPINFOHEADER
+        info: Option<InfoHeaderV1>,
+        bv4_red_mask: Option<Mask>,
+        bv4_green_mask: Option<Mask>,
+        bv4_blue_mask: Option<Mask>,
+        bv4_alpha_mask: Option<Mask>,
+        bv4_cs_type: Option<ColorSpace>,
+        bv4_endpoints: Option<CieXyzTriple>,
+        /// Gamma in 16.16 fixed point
+        bv4_gamma_red: Option<Gamma>,
+        bv4_gamma_green: Option<Gamma>,
+        bv4_gamma_blue: Option<Gamma>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct InfoHeaderV5 {
+        /// Fields of BITMAPV4HEADER
+        v4: Option<InfoHeaderV4>,
+        bv5_intent: Option<Intent>,
+        /// Offset of ICC profile from the beginning of info header
+        bv5_profile_data: Option<ProfileOffset>,
+        bv5_profile_size: Option<ImageSize>,
+        bv5_reserved: Option<Reserved32>,
+    },
+    display = true,
+);
+
+define_tagged_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub enum InfoHeader(InfoHeaderSize) {
+        (BITMAPCOREHEADER: Core(CoreHeader)),
+        (BITMAPINFOHEADER: V1(InfoHeaderV1)),
+        (BITMAPV4HEADER: V4(InfoHeaderV4)),
+        (BITMAPV5HEADER: V5(InfoHeaderV5)),
+    },
+    display = true,
+);
+
+impl InfoHeader {
+    /// Fields of BITMAPINFOHEADER that V4 and V5 headers extend.
+    pub fn info(&self) -> Option<&InfoHeaderV1> {
+        match self {
+            InfoHeader::V1(x) => Some(x),
+            InfoHeader::V4(x) => Some(x.get_info_unwrap()),
+            InfoHeader::V5(x) => Some(x.get_v4_unwrap().get_info_unwrap()),
+            _ => None,
+        }
+    }
+
+    /// V4 fields that V5 header extends.
+    pub fn v4(&self) -> Option<&InfoHeaderV4> {
+        match self {
+            InfoHeader::V4(x) => Some(x),
+            InfoHeader::V5(x) => Some(x.get_v4_unwrap()),
+            _ => None,
+        }
+    }
+
+    pub fn width(&self) -> Option<i32> {
+        match self {
+            InfoHeader::Core(x) => Some(x.get_bc_width_unwrap().inner() as i32),
+            _ => self.info().map(|x| x.get_bi_width_unwrap().inner()),
+        }
+    }
+
+    /// Height. Negative height means top-down pixel array.
+    pub fn height(&self) -> Option<i32> {
+        match self {
+            InfoHeader::Core(x) => Some(x.get_bc_height_unwrap().inner() as i32),
+            _ => self.info().map(|x| x.get_bi_height_unwrap().inner()),
+        }
+    }
+
+    pub fn bit_count(&self) -> Option<u16> {
+        match self {
+            InfoHeader::Core(x) => Some(x.get_bc_bit_count_unwrap().inner()),
+            _ => self.info().map(|x| x.get_bi_bit_count_unwrap().inner()),
+        }
+    }
+
+    pub fn compression(&self) -> Option<Compression> {
+        match self {
+            InfoHeader::Core(_) => Some(Compression::BI_RGB),
+            _ => self.info().map(|x| *x.get_bi_compression_unwrap()),
+        }
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Channel(u8),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct RgbQuad {
+        rgb_blue: Option<Channel>,
+        rgb_green: Option<Channel>,
+        rgb_red: Option<Channel>,
+        rgb_reserved: Option<Channel>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct RgbTriple {
+        rgbt_blue: Option<Channel>,
+        rgbt_green: Option<Channel>,
+        rgbt_red: Option<Channel>,
+    },
+    display = true,
+);
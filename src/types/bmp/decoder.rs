@@ -0,0 +1,600 @@
+use super::{Compression, FileHeader, InfoHeader, RgbQuad, RgbTriple, FILE_HEADER_SIZE};
+use crate::file::Seekable;
+
+/// Decoded image is limited to this number of pixels, since RLE can declare huge dimensions with a few bytes.
+const MAX_PIXELS: usize = 1 << 26;
+
+/// Bytes of an uncompressed row. Each row is padded to 4 bytes.
+fn stride(width: usize, bit_count: u16) -> usize {
+    (width * bit_count as usize).div_ceil(32) * 4
+}
+
+/// Error of BMP decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BmpError {
+    /// File does not start with "BM".
+    BadSignature,
+
+    /// The structure at the offset is out of file.
+    Truncated(usize),
+
+    /// Info header of the size is not supported.
+    UnsupportedHeader(u32),
+
+    UnsupportedCompression(Compression),
+
+    UnsupportedBitCount(u16),
+
+    /// Dimensions are zero, negative or too large.
+    InvalidDimensions,
+}
+
+impl core::fmt::Display for BmpError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BmpError::BadSignature => write!(fmt, "bad signature"),
+            BmpError::Truncated(at) => write!(fmt, "truncated structure at {:#x}", at),
+            BmpError::UnsupportedHeader(size) => write!(fmt, "unsupported info header of size {}", size),
+            BmpError::UnsupportedCompression(c) => write!(fmt, "unsupported compression {}", c),
+            BmpError::UnsupportedBitCount(b) => write!(fmt, "unsupported bit count {}", b),
+            BmpError::InvalidDimensions => write!(fmt, "invalid dimensions"),
+        }
+    }
+}
+
+impl std::error::Error for BmpError {}
+
+/// Decoded image. Pixels are RGBA and rows are top-down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    /// RGBA of pixel at column `x` and row `y` from top.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let at = (y * self.width + x) * 4;
+        self.pixels[at..at + 4].try_into().unwrap()
+    }
+
+    fn set(&mut self, x: usize, y: usize, rgba: [u8; 4]) {
+        let at = (y * self.width + x) * 4;
+        self.pixels[at..at + 4].copy_from_slice(&rgba);
+    }
+}
+
+/// Channel value scaled to 8 bits by bit mask. If mask is 0, this returns None.
+fn channel(pixel: u32, mask: u32) -> Option<u8> {
+    if mask == 0 {
+        return None;
+    }
+    let shift = mask.trailing_zeros();
+    let max = (mask >> shift) as u64;
+    let value = ((pixel & mask) >> shift) as u64;
+    Some(((value * 255 + max / 2) / max) as u8)
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().unwrap()))
+}
+
+/// BMP file.
+#[derive(Debug, Clone)]
+pub struct Bmp<'a> {
+    seeker: Seekable<'a>,
+    file_header: FileHeader,
+    info_header: InfoHeader,
+    masks: Option<[u32; 4]>,
+    palette: Vec<[u8; 4]>,
+}
+
+impl<'a> Bmp<'a> {
+    /// Read headers, bit masks and palette.
+    pub fn parse(seeker: Seekable<'a>) -> Result<Self, BmpError> {
+        let (_, file_header) = seeker
+            .interpret_abs_pos::<FileHeader>(0)
+            .to_tuple()
+            .1
+            .map_err(|_| BmpError::Truncated(0))?;
+        if !file_header.get_bf_type_unwrap().is_BM() {
+            return Err(BmpError::BadSignature);
+        }
+
+        let (_, info_header) = seeker
+            .interpret_abs_pos::<InfoHeader>(FILE_HEADER_SIZE)
+            .to_tuple()
+            .1
+            .map_err(|_| BmpError::Truncated(FILE_HEADER_SIZE))?;
+        if let InfoHeader::Raw(size, _) = info_header {
+            return Err(BmpError::UnsupportedHeader(size.inner()));
+        }
+        let header_size = info_header.tag().inner() as usize;
+
+        let mut ret = Self {
+            seeker,
+            file_header,
+            info_header,
+            masks: None,
+            palette: vec![],
+        };
+        let mut at = FILE_HEADER_SIZE + header_size;
+        at += ret.read_masks(at)?;
+        ret.read_palette(at)?;
+
+        Ok(ret)
+    }
+
+    /// Read bit masks. They follow BITMAPINFOHEADER, or are in V4 and V5 headers.
+    /// This returns the size of masks after the header.
+    fn read_masks(&mut self, at: usize) -> Result<usize, BmpError> {
+        if let Some(v4) = self.info_header.v4() {
+            self.masks = Some([
+                v4.get_bv4_red_mask_unwrap().inner(),
+                v4.get_bv4_green_mask_unwrap().inner(),
+                v4.get_bv4_blue_mask_unwrap().inner(),
+                v4.get_bv4_alpha_mask_unwrap().inner(),
+            ]);
+            return Ok(0);
+        }
+
+        let count = match self.info_header.compression() {
+            Some(Compression::BI_BITFIELDS) => 3,
+            Some(Compression::BI_ALPHABITFIELDS) => 4,
+            _ => return Ok(0),
+        };
+        let bytes = self
+            .seeker
+            .bytes_abs_pos(at, count * 4)
+            .ok_or(BmpError::Truncated(at))?;
+        let mut masks = [0; 4];
+        for (i, mask) in masks.iter_mut().take(count).enumerate() {
+            *mask = u32_at(bytes, i * 4).unwrap();
+        }
+        self.masks = Some(masks);
+
+        Ok(count * 4)
+    }
+
+    fn read_palette(&mut self, mut at: usize) -> Result<(), BmpError> {
+        let bit_count = self.info_header.bit_count().unwrap_or(0);
+        if bit_count == 0 || bit_count > 8 {
+            return Ok(());
+        }
+
+        let used = self
+            .info_header
+            .info()
+            .map_or(0, |x| x.get_bi_clr_used_unwrap().inner() as usize);
+        let count = match used {
+            0 => 1 << bit_count,
+            n => n.min(256),
+        };
+
+        for _ in 0..count {
+            let rgba = match self.info_header {
+                InfoHeader::Core(_) => {
+                    let (read, t) = self
+                        .seeker
+                        .interpret_abs_pos::<RgbTriple>(at)
+                        .to_tuple()
+                        .1
+                        .map_err(|_| BmpError::Truncated(at))?;
+                    at += read;
+                    [t.get_rgbt_red_unwrap().inner(), t.get_rgbt_green_unwrap().inner(), t.get_rgbt_blue_unwrap().inner(), 0xff]
+                }
+                _ => {
+                    let (read, q) = self
+                        .seeker
+                        .interpret_abs_pos::<RgbQuad>(at)
+                        .to_tuple()
+                        .1
+                        .map_err(|_| BmpError::Truncated(at))?;
+                    at += read;
+                    [q.get_rgb_red_unwrap().inner(), q.get_rgb_green_unwrap().inner(), q.get_rgb_blue_unwrap().inner(), 0xff]
+                }
+            };
+            self.palette.push(rgba);
+        }
+
+        Ok(())
+    }
+
+    pub fn file_header(&self) -> &FileHeader {
+        &self.file_header
+    }
+
+    pub fn info_header(&self) -> &InfoHeader {
+        &self.info_header
+    }
+
+    /// Red, green, blue and alpha bit masks, if the image has them.
+    pub fn masks(&self) -> Option<[u32; 4]> {
+        self.masks
+    }
+
+    /// Palette in RGBA.
+    pub fn palette(&self) -> &[[u8; 4]] {
+        &self.palette
+    }
+
+    /// Decode pixel array into RGBA image.
+    pub fn decode(&self) -> Result<Image, BmpError> {
+        let width = self.info_header.width().unwrap_or(0);
+        let height = self.info_header.height().unwrap_or(0);
+        let bit_count = self.info_header.bit_count().unwrap_or(0);
+        let compression = self.info_header.compression().unwrap_or(Compression::BI_RGB);
+
+        let top_down = height < 0;
+        let (width, height) = (width.max(0) as usize, height.unsigned_abs() as usize);
+        match width.checked_mul(height) {
+            Some(n) if n != 0 && n <= MAX_PIXELS => {}
+            _ => return Err(BmpError::InvalidDimensions),
+        }
+
+        let offset = self.file_header.get_bf_off_bits_unwrap().inner() as usize;
+        let data = self
+            .seeker
+            .bytes_abs_pos(offset, self.seeker.len().saturating_sub(offset))
+            .ok_or(BmpError::Truncated(offset))?;
+
+        let masks = match (compression, bit_count) {
+            (Compression::BI_RGB, 1 | 2 | 4 | 8 | 16 | 24 | 32) => match bit_count {
+                16 => Some([0x7c00, 0x03e0, 0x001f, 0]),
+                _ => Some([0xff0000, 0x00ff00, 0x0000ff, 0]),
+            },
+            (Compression::BI_BITFIELDS | Compression::BI_ALPHABITFIELDS, 16 | 32) => {
+                Some(self.masks.unwrap_or_default())
+            }
+            (Compression::BI_RLE8, 8) | (Compression::BI_RLE4, 4) if !top_down => None,
+            (Compression::BI_RGB | Compression::BI_BITFIELDS, b) => {
+                return Err(BmpError::UnsupportedBitCount(b))
+            }
+            (c, _) => return Err(BmpError::UnsupportedCompression(c)),
+        };
+
+        // The pixel array is checked before the image is allocated, since a few bytes of header
+        // can declare MAX_PIXELS. Each 2 bytes of RLE draw at most 255 pixels.
+        let enough = match masks {
+            Some(_) => stride(width, bit_count)
+                .checked_mul(height)
+                .is_some_and(|n| n <= data.len()),
+            None => width * height <= data.len() / 2 * 255,
+        };
+        if !enough {
+            return Err(BmpError::Truncated(offset));
+        }
+
+        let mut image = Image::new(width, height);
+        match masks {
+            Some(masks) => self.decode_rows(&mut image, data, bit_count, top_down, masks),
+            None => self.decode_rle(&mut image, data, bit_count),
+        }
+
+        Ok(image)
+    }
+
+    fn color(&self, index: usize) -> [u8; 4] {
+        self.palette.get(index).copied().unwrap_or([0, 0, 0, 0xff])
+    }
+
+    /// Decode uncompressed rows. `data` has at least `height` rows.
+    fn decode_rows(&self, image: &mut Image, data: &[u8], bit_count: u16, top_down: bool, masks: [u32; 4]) {
+        let bits = bit_count as usize;
+        let stride = stride(image.width, bit_count);
+
+        for (i, row) in data.chunks_exact(stride).take(image.height).enumerate() {
+            let y = if top_down { i } else { image.height - 1 - i };
+            for x in 0..image.width {
+                let rgba = match bit_count {
+                    1 | 2 | 4 | 8 => {
+                        let bit = x * bits;
+                        let byte = row[bit / 8];
+                        let index = (byte >> (8 - bits - bit % 8)) & ((1 << bits) - 1) as u8;
+                        self.color(index as usize)
+                    }
+                    24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 0xff],
+                    _ => {
+                        let size = bits / 8;
+                        let mut px = [0; 4];
+                        px[..size].copy_from_slice(&row[x * size..x * size + size]);
+                        let px = u32::from_le_bytes(px);
+                        [
+                            channel(px, masks[0]).unwrap_or(0),
+                            channel(px, masks[1]).unwrap_or(0),
+                            channel(px, masks[2]).unwrap_or(0),
+                            channel(px, masks[3]).unwrap_or(0xff),
+                        ]
+                    }
+                };
+                image.set(x, y, rgba);
+            }
+        }
+    }
+
+    /// Decode RLE8 or RLE4 pixel array. It is always bottom-up.
+    /// Pixels skipped by delta or end of line are left transparent.
+    /// Broken or truncated streams end decoding at that point.
+    fn decode_rle(&self, image: &mut Image, data: &[u8], bit_count: u16) {
+        let (width, height) = (image.width, image.height);
+        let mut set = |x: usize, y: usize, index: u8| {
+            if x < width && y < height {
+                image.set(x, height - 1 - y, self.color(index as usize));
+            }
+        };
+        let nibble = |byte: u8, k: usize| match bit_count {
+            4 if k & 1 == 0 => byte >> 4,
+            4 => byte & 0xf,
+            _ => byte,
+        };
+
+        let (mut x, mut y) = (0, 0);
+        let mut i = 0;
+        while i + 2 <= data.len() {
+            let (n, c) = (data[i], data[i + 1]);
+            i += 2;
+            if n > 0 {
+                for k in 0..n as usize {
+                    set(x, y, nibble(c, k));
+                    x += 1;
+                }
+                continue;
+            }
+            match c {
+                0 => {
+                    x = 0;
+                    y += 1;
+                }
+                1 => return,
+                2 => {
+                    if i + 2 > data.len() {
+                        return;
+                    }
+                    x += data[i] as usize;
+                    y += data[i + 1] as usize;
+                    i += 2;
+                }
+                count => {
+                    let count = count as usize;
+                    let size = match bit_count {
+                        4 => count.div_ceil(2),
+                        _ => count,
+                    };
+                    let Some(run) = data.get(i..i + size) else {
+                        return;
+                    };
+                    for k in 0..count {
+                        let byte = run[if bit_count == 4 { k / 2 } else { k }];
+                        set(x, y, nibble(byte, k));
+                        x += 1;
+                    }
+                    // Absolute runs are padded to 2 bytes.
+                    i += size + size % 2;
+                }
+            }
+            if y >= height {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    /// BITMAPINFOHEADER without the size field.
+    fn info(width: i32, height: i32, bit_count: u16, compression: u32, clr_used: u32) -> Vec<u8> {
+        let mut v = vec![];
+        v.extend_from_slice(&width.to_le_bytes());
+        v.extend_from_slice(&height.to_le_bytes());
+        v.extend_from_slice(&1u16.to_le_bytes());
+        v.extend_from_slice(&bit_count.to_le_bytes());
+        v.extend_from_slice(&compression.to_le_bytes());
+        v.extend_from_slice(&[0; 12]);
+        v.extend_from_slice(&clr_used.to_le_bytes());
+        v.extend_from_slice(&0u32.to_le_bytes());
+        v
+    }
+
+    /// File with header, info header of `header` (without size), `extra` bytes (masks and palette) and pixels.
+    fn file(header: &[u8], extra: &[u8], pixels: &[u8]) -> Vec<u8> {
+        let offset = FILE_HEADER_SIZE + 4 + header.len() + extra.len();
+        let mut v = b"BM".to_vec();
+        v.extend_from_slice(&((offset + pixels.len()) as u32).to_le_bytes());
+        v.extend_from_slice(&[0; 4]);
+        v.extend_from_slice(&(offset as u32).to_le_bytes());
+        v.extend_from_slice(&((header.len() + 4) as u32).to_le_bytes());
+        v.extend_from_slice(header);
+        v.extend_from_slice(extra);
+        v.extend_from_slice(pixels);
+        v
+    }
+
+    fn decode(v: Vec<u8>) -> Result<Image, BmpError> {
+        let s = Sequential::from_vec(v);
+        Bmp::parse(s.to_seeakble())?.decode()
+    }
+
+    const BLACK: [u8; 4] = [0, 0, 0, 0xff];
+    const WHITE: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+    const RED: [u8; 4] = [0xff, 0, 0, 0xff];
+    const BLUE: [u8; 4] = [0, 0, 0xff, 0xff];
+    /// Palette of black, white, red and blue.
+    const PALETTE: [u8; 16] = [0, 0, 0, 0, 0xff, 0xff, 0xff, 0, 0, 0, 0xff, 0, 0xff, 0, 0, 0];
+
+    #[test]
+    fn rgb24_bottom_up() {
+        // 3x2, rows are padded from 9 to 12 bytes. Bottom row first.
+        let pixels = [
+            0, 0, 0xff, 0xff, 0, 0, 0xff, 0xff, 0xff, 0, 0, 0, //
+            0x10, 0x20, 0x30, 0, 0, 0, 0, 0xff, 0, 0, 0, 0,
+        ];
+        let image = decode(file(&info(3, 2, 24, 0, 0), &[], &pixels)).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.pixel(0, 0), [0x30, 0x20, 0x10, 0xff]);
+        assert_eq!(image.pixel(2, 0), [0, 0xff, 0, 0xff]);
+        assert_eq!(image.pixel(0, 1), RED);
+        assert_eq!(image.pixel(1, 1), BLUE);
+        assert_eq!(image.pixel(2, 1), WHITE);
+    }
+
+    #[test]
+    fn palette_top_down() {
+        let pixels = [0b1010_0000, 0, 0, 0, 0b0101_0000, 0, 0, 0];
+        let image = decode(file(&info(4, -2, 1, 0, 2), &PALETTE[..8], &pixels)).unwrap();
+        assert_eq!(image.pixel(0, 0), WHITE);
+        assert_eq!(image.pixel(1, 0), BLACK);
+        assert_eq!(image.pixel(1, 1), WHITE);
+
+        let pixels = [0x23, 0x10, 0, 0];
+        let s = Sequential::from_vec(file(&info(3, -1, 4, 0, 4), &PALETTE, &pixels));
+        let bmp = Bmp::parse(s.to_seeakble()).unwrap();
+        assert_eq!(bmp.palette().len(), 4);
+        let image = bmp.decode().unwrap();
+        assert_eq!([image.pixel(0, 0), image.pixel(1, 0), image.pixel(2, 0)], [RED, BLUE, WHITE]);
+
+        let pixels = [3, 2, 1, 0];
+        let image = decode(file(&info(4, -1, 8, 0, 4), &PALETTE, &pixels)).unwrap();
+        assert_eq!(image.pixel(0, 0), BLUE);
+        assert_eq!(image.pixel(3, 0), BLACK);
+
+        // Index out of palette is black.
+        let pixels = [9, 0, 0, 0];
+        let image = decode(file(&info(1, 1, 8, 0, 4), &PALETTE, &pixels)).unwrap();
+        assert_eq!(image.pixel(0, 0), BLACK);
+    }
+
+    #[test]
+    fn rgb16() {
+        // 555 by default.
+        let pixels = [0x00, 0x7c, 0x1f, 0x00];
+        let image = decode(file(&info(2, 1, 16, 0, 0), &[], &pixels)).unwrap();
+        assert_eq!([image.pixel(0, 0), image.pixel(1, 0)], [RED, BLUE]);
+
+        // 565 with bit fields.
+        let masks = [0x00, 0xf8, 0, 0, 0xe0, 0x07, 0, 0, 0x1f, 0, 0, 0];
+        let pixels = [0xe0, 0x07, 0x00, 0x80];
+        let image = decode(file(&info(2, 1, 16, 3, 0), &masks, &pixels)).unwrap();
+        assert_eq!(image.pixel(0, 0), [0, 0xff, 0, 0xff]);
+        assert_eq!(image.pixel(1, 0), [0x84, 0, 0, 0xff]);
+    }
+
+    #[test]
+    fn v4_alpha() {
+        let mut header = info(1, 1, 32, 3, 0);
+        for mask in [0x00ff0000u32, 0x0000ff00, 0x000000ff, 0xff000000] {
+            header.extend_from_slice(&mask.to_le_bytes());
+        }
+        // sRGB color space, endpoints and gamma.
+        header.extend_from_slice(b"BGRs");
+        header.extend_from_slice(&[0; 48]);
+        assert_eq!(header.len() + 4, 108);
+
+        let s = Sequential::from_vec(file(&header, &[], &[0x30, 0x20, 0x10, 0x80]));
+        let bmp = Bmp::parse(s.to_seeakble()).unwrap();
+        assert!(matches!(bmp.info_header(), InfoHeader::V4(_)));
+        assert_eq!(bmp.masks(), Some([0x00ff0000, 0x0000ff00, 0x000000ff, 0xff000000]));
+        assert_eq!(bmp.decode().unwrap().pixel(0, 0), [0x10, 0x20, 0x30, 0x80]);
+    }
+
+    #[test]
+    fn rle8() {
+        #[rustfmt::skip]
+        let pixels = [
+            3, 2, // run of 3 red
+            0, 0, // end of line
+            0, 3, 1, 3, 2, 0, // absolute run, padded
+            0, 0,
+            0, 2, 1, 0, // delta to the second pixel
+            1, 1, // white
+            0, 1, // end of bitmap
+        ];
+        let image = decode(file(&info(4, 3, 8, 1, 4), &PALETTE, &pixels)).unwrap();
+        assert_eq!(image.pixel(0, 2), RED);
+        assert_eq!(image.pixel(2, 2), RED);
+        assert_eq!(image.pixel(3, 2), [0; 4]);
+        assert_eq!([image.pixel(0, 1), image.pixel(1, 1), image.pixel(2, 1)], [WHITE, BLUE, RED]);
+        assert_eq!(image.pixel(3, 1), [0; 4]);
+        assert_eq!(image.pixel(0, 0), [0; 4]);
+        assert_eq!(image.pixel(1, 0), WHITE);
+    }
+
+    #[test]
+    fn rle4() {
+        #[rustfmt::skip]
+        let pixels = [
+            5, 0x23, // red, blue, red, blue, red
+            0, 0,
+            0, 3, 0x12, 0x30, // absolute run of 3 pixels in 2 bytes
+            0, 1,
+        ];
+        let image = decode(file(&info(5, 2, 4, 2, 4), &PALETTE, &pixels)).unwrap();
+        let row: Vec<_> = (0..5).map(|x| image.pixel(x, 1)).collect();
+        assert_eq!(row, vec![RED, BLUE, RED, BLUE, RED]);
+        let row: Vec<_> = (0..4).map(|x| image.pixel(x, 0)).collect();
+        assert_eq!(row, vec![WHITE, RED, BLUE, [0; 4]]);
+
+        // Truncated stream stops decoding.
+        let image = decode(file(&info(5, 1, 4, 2, 4), &PALETTE, &[0, 4, 0x12])).unwrap();
+        assert_eq!(image.pixel(0, 0), [0; 4]);
+    }
+
+    #[test]
+    fn core_header() {
+        let mut header = vec![];
+        header.extend_from_slice(&2u16.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&8u16.to_le_bytes());
+        // 256 RGBTRIPLEs, only the first two are used.
+        let mut palette = vec![0; 256 * 3];
+        palette[3..6].copy_from_slice(&[0xff, 0, 0]);
+        let s = Sequential::from_vec(file(&header, &palette, &[1, 0, 0, 0]));
+        let bmp = Bmp::parse(s.to_seeakble()).unwrap();
+        assert!(matches!(bmp.info_header(), InfoHeader::Core(_)));
+        assert_eq!(bmp.palette().len(), 256);
+        let image = bmp.decode().unwrap();
+        assert_eq!([image.pixel(0, 0), image.pixel(1, 0)], [BLUE, BLACK]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut v = file(&info(1, 1, 24, 0, 0), &[], &[0; 4]);
+        v[0] = b'X';
+        assert_eq!(decode(v).unwrap_err(), BmpError::BadSignature);
+
+        let v = file(&info(1, 1, 24, 4, 0), &[], &[0; 4]);
+        assert_eq!(decode(v).unwrap_err(), BmpError::UnsupportedCompression(Compression::BI_JPEG));
+
+        let v = file(&info(1, 1, 24, 0, 0), &[], &[0; 2]);
+        assert!(matches!(decode(v).unwrap_err(), BmpError::Truncated(_)));
+
+        let v = file(&info(0, 1, 24, 0, 0), &[], &[]);
+        assert_eq!(decode(v).unwrap_err(), BmpError::InvalidDimensions);
+
+        let v = file(&info(1 << 20, 1 << 20, 24, 0, 0), &[], &[0, 1]);
+        assert_eq!(decode(v).unwrap_err(), BmpError::InvalidDimensions);
+
+        // Pixel arrays too short for the declared size.
+        let v = file(&info(1 << 13, 1 << 13, 24, 0, 0), &[], &[0; 4]);
+        assert!(matches!(decode(v).unwrap_err(), BmpError::Truncated(_)));
+        let v = file(&info(1 << 13, 1 << 13, 8, 1, 4), &PALETTE, &[0, 1]);
+        assert!(matches!(decode(v).unwrap_err(), BmpError::Truncated(_)));
+
+        let v = file(&[0; 60], &[], &[]);
+        assert_eq!(decode(v).unwrap_err(), BmpError::UnsupportedHeader(64));
+
+        assert!(matches!(decode(b"BM".to_vec()).unwrap_err(), BmpError::Truncated(0)));
+    }
+}
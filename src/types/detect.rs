@@ -0,0 +1,443 @@
+use super::{ar, bmp, elf, macho, pe, zip};
+use crate::file::{Seekable, Sequential};
+use macho::Layout;
+
+/// How surely the data is in the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// Magic matches, but the header cannot be read.
+    Low,
+
+    /// Header is read, but the structures after it are broken or not supported.
+    Medium,
+
+    /// The format is parsed as a whole.
+    High,
+}
+
+impl core::fmt::Display for Confidence {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Confidence::Low => write!(fmt, "low"),
+            Confidence::Medium => write!(fmt, "medium"),
+            Confidence::High => write!(fmt, "high"),
+        }
+    }
+}
+
+/// Formats known to the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Elf,
+    Zip,
+    Bmp,
+    Pe,
+    Ar,
+    MachO,
+    FatMachO,
+}
+
+/// Entry of format registry.
+#[derive(Debug, Clone, Copy)]
+pub struct Format {
+    pub kind: Kind,
+    pub name: &'static str,
+    /// Byte sequences one of which starts the format. These filter data before probe.
+    /// If this is empty, probe is always called.
+    pub magics: &'static [&'static [u8]],
+    /// Check data that starts at offset 0 of the seeker. If the data is not in the format, this returns None.
+    pub probe: fn(Seekable) -> Option<Confidence>,
+    /// Number of bytes the format spans from offset 0 of the seeker. Call this after probe accepts the data.
+    pub extent: fn(Seekable) -> Option<usize>,
+}
+
+impl Format {
+    /// Return true if `bytes` starts with one of magics.
+    pub fn matches_magic(&self, bytes: &[u8]) -> bool {
+        self.magics.is_empty() || self.magics.iter().any(|m| bytes.starts_with(m))
+    }
+}
+
+/// Format that matches data.
+#[derive(Debug, Clone, Copy)]
+pub struct Detection {
+    pub format: &'static Format,
+    pub confidence: Confidence,
+}
+
+/// Registered formats. Magics are checked against the model constants in tests.
+pub static FORMATS: &[Format] = &[
+    Format {
+        kind: Kind::Elf,
+        name: "ELF",
+        magics: &[&elf::elf_header::Magic::ELFMAG],
+        probe: probe_elf,
+        extent: extent_elf,
+    },
+    Format {
+        kind: Kind::Zip,
+        name: "zip",
+        magics: &[b"PK\x03\x04"],
+        probe: probe_zip,
+        extent: extent_zip,
+    },
+    Format {
+        kind: Kind::Bmp,
+        name: "BMP",
+        magics: &[b"BM"],
+        probe: probe_bmp,
+        extent: extent_bmp,
+    },
+    Format {
+        kind: Kind::Pe,
+        name: "PE",
+        magics: &[b"MZ"],
+        probe: probe_pe,
+        extent: extent_pe,
+    },
+    Format {
+        kind: Kind::Ar,
+        name: "ar",
+        magics: &[b"!<arch>\n", b"!<thin>\n"],
+        probe: probe_ar,
+        extent: extent_ar,
+    },
+    Format {
+        kind: Kind::MachO,
+        name: "Mach-O",
+        magics: &[
+            b"\xce\xfa\xed\xfe",
+            b"\xfe\xed\xfa\xce",
+            b"\xcf\xfa\xed\xfe",
+            b"\xfe\xed\xfa\xcf",
+        ],
+        probe: probe_macho,
+        extent: extent_macho,
+    },
+    Format {
+        kind: Kind::FatMachO,
+        name: "fat Mach-O",
+        magics: &[b"\xca\xfe\xba\xbe", b"\xca\xfe\xba\xbf"],
+        probe: probe_fat,
+        extent: extent_fat,
+    },
+];
+
+fn probe_elf(seeker: Seekable) -> Option<Confidence> {
+    let elf = match elf::Elf::parse(seeker) {
+        Ok(x) => x,
+        Err(_) => return Some(Confidence::Low),
+    };
+    if !elf.header().get_e_ident_unwrap().is_elf() {
+        return None;
+    }
+    let broken = elf::lint::lint_elf(&elf)
+        .iter()
+        .any(|f| f.diagnostic.severity == super::model::Severity::Error);
+    match broken {
+        true => Some(Confidence::Medium),
+        false => Some(Confidence::High),
+    }
+}
+
+fn probe_zip(seeker: Seekable) -> Option<Confidence> {
+    if zip::ZipArchive::parse(seeker).is_ok() {
+        return Some(Confidence::High);
+    }
+    // Local file header without central directory. The zip is truncated or embedded.
+    match seeker.interpret_abs_pos::<zip::Header>(0).to_tuple().1 {
+        Ok((_, h)) if h.get_magic_unwrap().is_VALID() => Some(Confidence::Medium),
+        _ => Some(Confidence::Low),
+    }
+}
+
+fn probe_bmp(seeker: Seekable) -> Option<Confidence> {
+    // "BM" is too short to be a magic by itself.
+    bmp::Bmp::parse(seeker).ok().map(|_| Confidence::High)
+}
+
+fn probe_pe(seeker: Seekable) -> Option<Confidence> {
+    match pe::Pe::parse(seeker) {
+        Ok(_) => Some(Confidence::High),
+        Err(pe::PeError::BadDosSignature) => None,
+        Err(pe::PeError::UnsupportedOptionalHeader(_)) => Some(Confidence::Medium),
+        // MS-DOS executable or truncated PE.
+        Err(_) => Some(Confidence::Low),
+    }
+}
+
+fn probe_ar(seeker: Seekable) -> Option<Confidence> {
+    match ar::Archive::parse(seeker) {
+        Ok(_) => Some(Confidence::High),
+        Err(ar::ArError::BadMagic) => None,
+        Err(ar::ArError::ThinArchive) => Some(Confidence::Medium),
+        Err(_) => Some(Confidence::Low),
+    }
+}
+
+fn probe_macho(seeker: Seekable) -> Option<Confidence> {
+    match macho::MachO::parse(seeker) {
+        Ok(_) => Some(Confidence::High),
+        Err(macho::MachOError::BadMagic) => None,
+        Err(_) => Some(Confidence::Low),
+    }
+}
+
+fn probe_fat(seeker: Seekable) -> Option<Confidence> {
+    let fat = match macho::FatBinary::parse(seeker) {
+        Ok(x) => x,
+        // Java class file.
+        Err(macho::MachOError::TooManyArchitectures(_)) => return None,
+        Err(_) => return Some(Confidence::Low),
+    };
+    match fat.archs().iter().all(|x| fat.macho(x).is_ok()) {
+        true => Some(Confidence::High),
+        false => Some(Confidence::Medium),
+    }
+}
+
+/// End of `size` bytes at `offset`. They come from untrusted headers, so overflow is None.
+fn end(offset: u64, size: u64) -> Option<usize> {
+    usize::try_from(offset.checked_add(size)?).ok()
+}
+
+/// The largest of `ends`, or None if any of them overflows.
+fn max_end(ends: impl IntoIterator<Item = Option<usize>>) -> Option<usize> {
+    ends.into_iter().try_fold(0, |max, x| Some(max.max(x?)))
+}
+
+fn extent_elf(seeker: Seekable) -> Option<usize> {
+    let elf = elf::Elf::parse(seeker).ok()?;
+    let h = elf.header();
+    let shsize = elf.section_count().checked_mul(h.get_e_shentsize_unwrap().inner() as usize)?;
+    let phsize = elf.segment_count().checked_mul(h.get_e_phentsize_unwrap().inner() as usize)?;
+    let tables = [(h.get_e_shoff_unwrap().inner(), shsize), (h.get_e_phoff_unwrap().inner(), phsize)];
+    let sections = elf
+        .section_headers()
+        .iter()
+        .filter(|sh| !sh.get_sh_type_unwrap().is_SHT_NOBITS())
+        .map(|sh| (sh.get_sh_offset_unwrap().inner(), sh.get_sh_size_unwrap().inner() as usize));
+    let segments = elf
+        .program_headers()
+        .iter()
+        .map(|ph| (ph.get_p_offset_unwrap().inner(), ph.get_p_filesz_unwrap().inner() as usize));
+
+    let ends = tables
+        .into_iter()
+        .chain(sections)
+        .chain(segments)
+        .map(|(offset, size)| end(offset as u64, size as u64));
+    max_end(ends).map(|x| x.max(h.get_e_ehsize_unwrap().inner() as usize))
+}
+
+fn extent_zip(seeker: Seekable) -> Option<usize> {
+    if let Ok(zip) = zip::ZipArchive::parse(seeker) {
+        return Some(zip.end_offset());
+    }
+    // Follow local file headers. Sizes are unknown if data descriptor follows the data.
+    let mut at = 0;
+    while let Ok((read, h)) = seeker.interpret_abs_pos::<zip::Header>(at).to_tuple().1 {
+        if !h.get_magic_unwrap().is_VALID() {
+            break;
+        }
+        let lengths = *h.get_file_name_length_unwrap().inner_ref() as usize
+            + *h.get_extra_field_length_unwrap().inner_ref() as usize;
+        at = at.checked_add(read + lengths)?;
+        if h.get_general_purpose_bf_unwrap().get(zip::BitFlags::DATA_DESCRIPTOR) {
+            break;
+        }
+        at = at.checked_add(*h.get_compressed_size_unwrap().inner_ref() as usize)?;
+    }
+    Some(at.min(seeker.len()))
+}
+
+fn extent_bmp(seeker: Seekable) -> Option<usize> {
+    let bmp = bmp::Bmp::parse(seeker).ok()?;
+    let size = bmp.file_header().get_bf_size_unwrap().inner() as usize;
+    // Some writers leave bfSize 0.
+    match size {
+        0 => Some(seeker.len()),
+        _ => Some(size),
+    }
+}
+
+fn extent_pe(seeker: Seekable) -> Option<usize> {
+    let pe = pe::Pe::parse(seeker).ok()?;
+    let headers = pe
+        .optional_header()
+        .and_then(|x| x.size_of_headers())
+        .unwrap_or(0) as usize;
+    let sections = pe.section_headers().iter().map(|sh| {
+        end(
+            sh.get_pointer_to_raw_data_unwrap().inner() as u64,
+            sh.get_size_of_raw_data_unwrap().inner() as u64,
+        )
+    });
+    // Certificate table is appended after sections. Its address is a file offset.
+    let certificates = pe
+        .data_directory(pe::DirectoryEntry::IMAGE_DIRECTORY_ENTRY_SECURITY)
+        .map(|x| end(x.get_virtual_address_unwrap().inner() as u64, x.get_size_unwrap().inner() as u64));
+
+    max_end(sections.chain(certificates).chain([Some(headers)]))
+}
+
+fn extent_ar(seeker: Seekable) -> Option<usize> {
+    let archive = ar::Archive::parse(seeker).ok()?;
+    let members = archive.members().iter().map(|m| {
+        let end = end(m.data_offset as u64, m.size as u64)?;
+        end.checked_add(end & 1)
+    });
+
+    max_end(members.chain([Some(ar::MAGIC_SIZE)])).map(|x| x.min(seeker.len()))
+}
+
+fn extent_macho(seeker: Seekable) -> Option<usize> {
+    let m = macho::MachO::parse(seeker).ok()?;
+    let header = match m.is_64() {
+        true => macho::HEADER_64_SIZE,
+        false => macho::HEADER_SIZE,
+    };
+    let commands = end(header as u64, m.header().get_sizeofcmds_unwrap().inner() as u64);
+    let segments = m
+        .segments()
+        .into_iter()
+        .map(|x| end(x.get_fileoff_unwrap().inner(), x.get_filesize_unwrap().inner()));
+    let strings = m
+        .symtab()
+        .map(|x| end(x.get_stroff_unwrap().inner() as u64, x.get_strsize_unwrap().inner() as u64));
+
+    max_end(segments.chain(strings).chain([commands]))
+}
+
+fn extent_fat(seeker: Seekable) -> Option<usize> {
+    let fat = macho::FatBinary::parse(seeker).ok()?;
+    let archs = fat
+        .archs()
+        .iter()
+        .map(|x| end(x.get_offset_unwrap().inner(), x.get_size_unwrap().inner()));
+    let arch_size = match fat.header().get_magic_unwrap().is_FAT_MAGIC_64() {
+        true => macho::FatArch64::size(),
+        false => macho::FatArch::size(),
+    };
+    let header = macho::FatHeader::size() + fat.archs().len() * arch_size;
+
+    max_end(archs.chain([Some(header)]))
+}
+
+/// Formats of data that starts at offset 0 of `seeker`. The most confident one comes first.
+pub fn detect_seekable(seeker: Seekable) -> Vec<Detection> {
+    let head = seeker.bytes_abs_pos(0, seeker.len().min(16)).unwrap_or(&[]);
+    let mut ret: Vec<_> = FORMATS
+        .iter()
+        .filter(|f| f.matches_magic(head))
+        .filter_map(|format| {
+            (format.probe)(seeker).map(|confidence| Detection { format, confidence })
+        })
+        .collect();
+    ret.sort_by_key(|x| core::cmp::Reverse(x.confidence));
+
+    ret
+}
+
+/// Formats of the whole binary. The most confident one comes first.
+pub fn detect(s: &Sequential) -> Vec<Detection> {
+    detect_seekable(s.to_seeakble())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(bin: &[u8]) -> Vec<(Kind, Confidence)> {
+        let s = Sequential::from_vec(bin.to_vec());
+        detect(&s)
+            .iter()
+            .map(|x| (x.format.kind, x.confidence))
+            .collect()
+    }
+
+    fn magic_of(kind: Kind) -> &'static [&'static [u8]] {
+        FORMATS.iter().find(|x| x.kind == kind).unwrap().magics
+    }
+
+    #[test]
+    fn magics_match_model() {
+        let le = |x: u32| x.to_le_bytes().to_vec();
+        let out: &[u8] = include_bytes!("../../example_bin/out");
+        let s = Sequential::from_vec(out.to_vec());
+        let elf = elf::Elf::parse(s.to_seeakble()).unwrap();
+        assert!(elf.header().get_e_ident_unwrap().is_elf());
+        assert_eq!(magic_of(Kind::Elf), [&out[..4]]);
+        assert_eq!(magic_of(Kind::Zip), [&le(zip::Magic::VALID.inner())[..]]);
+        assert_eq!(magic_of(Kind::Bmp)[0], &bmp::Signature::BM.inner().to_le_bytes());
+        assert_eq!(magic_of(Kind::Pe)[0], &pe::DosMagic::IMAGE_DOS_SIGNATURE.inner().to_le_bytes());
+        assert_eq!(magic_of(Kind::Ar)[0], &ar::Magic::ARMAG.inner().to_le_bytes());
+        assert_eq!(magic_of(Kind::Ar)[1], &ar::Magic::THINMAG.inner().to_le_bytes());
+        let macho = [
+            macho::Magic::MH_MAGIC,
+            macho::Magic::MH_CIGAM,
+            macho::Magic::MH_MAGIC_64,
+            macho::Magic::MH_CIGAM_64,
+        ];
+        for (m, bytes) in macho.iter().zip(magic_of(Kind::MachO)) {
+            assert_eq!(*bytes, &m.inner().to_le_bytes());
+        }
+        assert_eq!(magic_of(Kind::FatMachO)[0], &macho::FatMagic::FAT_MAGIC.inner().to_be_bytes());
+        assert_eq!(magic_of(Kind::FatMachO)[1], &macho::FatMagic::FAT_MAGIC_64.inner().to_be_bytes());
+    }
+
+    #[test]
+    fn detect_examples() {
+        use Confidence::*;
+        let cases: &[(&[u8], Kind)] = &[
+            (include_bytes!("../../example_bin/out"), Kind::Elf),
+            (include_bytes!("../../example_bin/archives.zip"), Kind::Zip),
+            (include_bytes!("../../example_bin/image.bmp"), Kind::Bmp),
+            (include_bytes!("../../example_bin/sample.dll"), Kind::Pe),
+            (include_bytes!("../../example_bin/libsample.a"), Kind::Ar),
+            (include_bytes!("../../example_bin/macho_ppc"), Kind::MachO),
+            (include_bytes!("../../example_bin/macho_fat"), Kind::FatMachO),
+        ];
+        for (bin, kind) in cases {
+            assert_eq!(kinds(bin), vec![(*kind, High)], "{:?}", kind);
+
+            let s = Sequential::from_vec(bin.to_vec());
+            let format = FORMATS.iter().find(|x| x.kind == *kind).unwrap();
+            assert_eq!((format.extent)(s.to_seeakble()), Some(bin.len()), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn extent_overflow() {
+        // Extended section count of 2^62 in the initial section header.
+        let mut v = include_bytes!("../../example_bin/out").to_vec();
+        let shoff = u64::from_le_bytes(v[0x28..0x30].try_into().unwrap()) as usize;
+        v[0x3c..0x3e].fill(0);
+        v[shoff + 32..shoff + 40].copy_from_slice(&(1u64 << 62).to_le_bytes());
+        let s = Sequential::from_vec(v);
+        assert_eq!(extent_elf(s.to_seeakble()), None);
+        detect(&s);
+
+        // File offset of __LINKEDIT segment near u64::MAX.
+        let mut v = include_bytes!("../../example_bin/macho_x86_64").to_vec();
+        v[336 + 40..336 + 48].copy_from_slice(&(u64::MAX - 10).to_le_bytes());
+        let s = Sequential::from_vec(v);
+        assert_eq!(extent_macho(s.to_seeakble()), None);
+        detect(&s);
+    }
+
+    #[test]
+    fn weak_matches() {
+        use Confidence::*;
+        assert_eq!(kinds(b""), vec![]);
+        assert_eq!(kinds(b"\x7fELF\x02\x01"), vec![(Kind::Elf, Low)]);
+        assert_eq!(kinds(b"MZ\x90\x00"), vec![(Kind::Pe, Low)]);
+        assert_eq!(kinds(b"BM text that is not a bitmap"), vec![]);
+        // Java class file shares the fat magic.
+        assert_eq!(kinds(b"\xca\xfe\xba\xbe\x00\x00\x00\x34"), vec![]);
+
+        // Local file header without central directory.
+        let zip = include_bytes!("../../example_bin/archives.zip");
+        assert_eq!(kinds(&zip[..100]), vec![(Kind::Zip, Medium)]);
+    }
+}
@@ -0,0 +1,1072 @@
+use super::elf::Elf;
+use super::primitive::{Sleb128, Uleb128};
+use super::FromU8Array;
+use crate::define_model_type;
+
+mod frame;
+mod info;
+mod line;
+pub use frame::{
+    evaluate, CallFrame, CfaRule, Cie, EhFrameHdr, Fde, FrameEntry, FrameKind, RegisterRule,
+    UnwindRow,
+};
+pub use info::{
+    Abbreviation, Attribute, AttributeSpec, Die, Function, TypeEntry, Unit, UnitHeader, Variable,
+};
+pub use line::{
+    FileEntry, LineEntry, LineProgram, LineProgramHeader, LineRow, LineTable, SourceLocation,
+};
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Form(u16),
+    pub
+    [
+        (DW_FORM_addr: 0x01),
+        (DW_FORM_block2: 0x03),
+        (DW_FORM_block4: 0x04),
+        (DW_FORM_data2: 0x05),
+        (DW_FORM_data4: 0x06),
+        (DW_FORM_data8: 0x07),
+        (DW_FORM_string: 0x08),
+        (DW_FORM_block: 0x09),
+        (DW_FORM_block1: 0x0a),
+        (DW_FORM_data1: 0x0b),
+        (DW_FORM_flag: 0x0c),
+        (DW_FORM_sdata: 0x0d),
+        (DW_FORM_strp: 0x0e),
+        (DW_FORM_udata: 0x0f),
+        (DW_FORM_ref_addr: 0x10),
+        (DW_FORM_ref1: 0x11),
+        (DW_FORM_ref2: 0x12),
+        (DW_FORM_ref4: 0x13),
+        (DW_FORM_ref8: 0x14),
+        (DW_FORM_ref_udata: 0x15),
+        (DW_FORM_indirect: 0x16),
+        (DW_FORM_sec_offset: 0x17),    // DWARF 4
+        (DW_FORM_exprloc: 0x18),       // DWARF 4
+        (DW_FORM_flag_present: 0x19),  // DWARF 4
+        (DW_FORM_strx: 0x1a),          // DWARF 5
+        (DW_FORM_addrx: 0x1b),         // DWARF 5
+        (DW_FORM_ref_sup4: 0x1c),      // DWARF 5
+        (DW_FORM_strp_sup: 0x1d),      // DWARF 5
+        (DW_FORM_data16: 0x1e),        // DWARF 5
+        (DW_FORM_line_strp: 0x1f),     // DWARF 5
+        (DW_FORM_ref_sig8: 0x20),      // DWARF 4
+        (DW_FORM_implicit_const: 0x21), // DWARF 5
+        (DW_FORM_loclistx: 0x22),      // DWARF 5
+        (DW_FORM_rnglistx: 0x23),      // DWARF 5
+        (DW_FORM_ref_sup8: 0x24),      // DWARF 5
+        (DW_FORM_strx1: 0x25),         // DWARF 5
+        (DW_FORM_strx2: 0x26),         // DWARF 5
+        (DW_FORM_strx3: 0x27),         // DWARF 5
+        (DW_FORM_strx4: 0x28),         // DWARF 5
+        (DW_FORM_addrx1: 0x29),        // DWARF 5
+        (DW_FORM_addrx2: 0x2a),        // DWARF 5
+        (DW_FORM_addrx3: 0x2b),        // DWARF 5
+        (DW_FORM_addrx4: 0x2c),        // DWARF 5
+        (DW_FORM_GNU_addr_index: 0x1f01), // Split DWARF before DWARF 5
+        (DW_FORM_GNU_str_index: 0x1f02),  // Split DWARF before DWARF 5
+        (DW_FORM_GNU_ref_alt: 0x1f20),    // dwz
+        (DW_FORM_GNU_strp_alt: 0x1f21),   // dwz
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Tag(u16),
+    pub
+    [
+        (DW_TAG_array_type: 0x01),
+        (DW_TAG_class_type: 0x02),
+        (DW_TAG_entry_point: 0x03),
+        (DW_TAG_enumeration_type: 0x04),
+        (DW_TAG_formal_parameter: 0x05),
+        (DW_TAG_imported_declaration: 0x08),
+        (DW_TAG_label: 0x0a),
+        (DW_TAG_lexical_block: 0x0b),
+        (DW_TAG_member: 0x0d),
+        (DW_TAG_pointer_type: 0x0f),
+        (DW_TAG_reference_type: 0x10),
+        (DW_TAG_compile_unit: 0x11),
+        (DW_TAG_string_type: 0x12),
+        (DW_TAG_structure_type: 0x13),
+        (DW_TAG_subroutine_type: 0x15),
+        (DW_TAG_typedef: 0x16),
+        (DW_TAG_union_type: 0x17),
+        (DW_TAG_unspecified_parameters: 0x18),
+        (DW_TAG_variant: 0x19),
+        (DW_TAG_common_block: 0x1a),
+        (DW_TAG_common_inclusion: 0x1b),
+        (DW_TAG_inheritance: 0x1c),
+        (DW_TAG_inlined_subroutine: 0x1d),
+        (DW_TAG_module: 0x1e),
+        (DW_TAG_ptr_to_member_type: 0x1f),
+        (DW_TAG_set_type: 0x20),
+        (DW_TAG_subrange_type: 0x21),
+        (DW_TAG_with_stmt: 0x22),
+        (DW_TAG_access_declaration: 0x23),
+        (DW_TAG_base_type: 0x24),
+        (DW_TAG_catch_block: 0x25),
+        (DW_TAG_const_type: 0x26),
+        (DW_TAG_constant: 0x27),
+        (DW_TAG_enumerator: 0x28),
+        (DW_TAG_file_type: 0x29),
+        (DW_TAG_friend: 0x2a),
+        (DW_TAG_namelist: 0x2b),
+        (DW_TAG_namelist_item: 0x2c),
+        (DW_TAG_packed_type: 0x2d),
+        (DW_TAG_subprogram: 0x2e),
+        (DW_TAG_template_type_parameter: 0x2f),
+        (DW_TAG_template_value_parameter: 0x30),
+        (DW_TAG_thrown_type: 0x31),
+        (DW_TAG_try_block: 0x32),
+        (DW_TAG_variant_part: 0x33),
+        (DW_TAG_variable: 0x34),
+        (DW_TAG_volatile_type: 0x35),
+        (DW_TAG_dwarf_procedure: 0x36),      // DWARF 3
+        (DW_TAG_restrict_type: 0x37),        // DWARF 3
+        (DW_TAG_interface_type: 0x38),       // DWARF 3
+        (DW_TAG_namespace: 0x39),            // DWARF 3
+        (DW_TAG_imported_module: 0x3a),      // DWARF 3
+        (DW_TAG_unspecified_type: 0x3b),     // DWARF 3
+        (DW_TAG_partial_unit: 0x3c),         // DWARF 3
+        (DW_TAG_imported_unit: 0x3d),        // DWARF 3
+        (DW_TAG_condition: 0x3f),            // DWARF 3
+        (DW_TAG_shared_type: 0x40),          // DWARF 3
+        (DW_TAG_type_unit: 0x41),            // DWARF 4
+        (DW_TAG_rvalue_reference_type: 0x42), // DWARF 4
+        (DW_TAG_template_alias: 0x43),       // DWARF 4
+        (DW_TAG_coarray_type: 0x44),         // DWARF 5
+        (DW_TAG_generic_subrange: 0x45),     // DWARF 5
+        (DW_TAG_dynamic_type: 0x46),         // DWARF 5
+        (DW_TAG_atomic_type: 0x47),          // DWARF 5
+        (DW_TAG_call_site: 0x48),            // DWARF 5
+        (DW_TAG_call_site_parameter: 0x49),  // DWARF 5
+        (DW_TAG_skeleton_unit: 0x4a),        // DWARF 5
+        (DW_TAG_immutable_type: 0x4b),       // DWARF 5
+        (DW_TAG_lo_user: 0x4080),
+        (DW_TAG_GNU_template_template_param: 0x4106),
+        (DW_TAG_GNU_template_parameter_pack: 0x4107),
+        (DW_TAG_GNU_formal_parameter_pack: 0x4108),
+        (DW_TAG_GNU_call_site: 0x4109),
+        (DW_TAG_GNU_call_site_parameter: 0x410a),
+        (DW_TAG_hi_user: 0xffff),
+    ],
+    display = true, bitflags = false,
+    ranges = [
+        ("User-defined": DW_TAG_lo_user..=DW_TAG_hi_user),
+    ],
+    aliases = [DW_TAG_lo_user, DW_TAG_hi_user],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct At(u16),
+    pub
+    [
+        (DW_AT_sibling: 0x01),
+        (DW_AT_location: 0x02),
+        (DW_AT_name: 0x03),
+        (DW_AT_ordering: 0x09),
+        (DW_AT_byte_size: 0x0b),
+        (DW_AT_bit_offset: 0x0c),            // Removed in DWARF 4
+        (DW_AT_bit_size: 0x0d),
+        (DW_AT_stmt_list: 0x10),
+        (DW_AT_low_pc: 0x11),
+        (DW_AT_high_pc: 0x12),
+        (DW_AT_language: 0x13),
+        (DW_AT_discr: 0x15),
+        (DW_AT_discr_value: 0x16),
+        (DW_AT_visibility: 0x17),
+        (DW_AT_import: 0x18),
+        (DW_AT_string_length: 0x19),
+        (DW_AT_common_reference: 0x1a),
+        (DW_AT_comp_dir: 0x1b),
+        (DW_AT_const_value: 0x1c),
+        (DW_AT_containing_type: 0x1d),
+        (DW_AT_default_value: 0x1e),
+        (DW_AT_inline: 0x20),
+        (DW_AT_is_optional: 0x21),
+        (DW_AT_lower_bound: 0x22),
+        (DW_AT_producer: 0x25),
+        (DW_AT_prototyped: 0x27),
+        (DW_AT_return_addr: 0x2a),
+        (DW_AT_start_scope: 0x2c),
+        (DW_AT_bit_stride: 0x2e),
+        (DW_AT_upper_bound: 0x2f),
+        (DW_AT_abstract_origin: 0x31),
+        (DW_AT_accessibility: 0x32),
+        (DW_AT_address_class: 0x33),
+        (DW_AT_artificial: 0x34),
+        (DW_AT_base_types: 0x35),
+        (DW_AT_calling_convention: 0x36),
+        (DW_AT_count: 0x37),
+        (DW_AT_data_member_location: 0x38),
+        (DW_AT_decl_column: 0x39),
+        (DW_AT_decl_file: 0x3a),
+        (DW_AT_decl_line: 0x3b),
+        (DW_AT_declaration: 0x3c),
+        (DW_AT_discr_list: 0x3d),
+        (DW_AT_encoding: 0x3e),
+        (DW_AT_external: 0x3f),
+        (DW_AT_frame_base: 0x40),
+        (DW_AT_friend: 0x41),
+        (DW_AT_identifier_case: 0x42),
+        (DW_AT_macro_info: 0x43),            // Removed in DWARF 5
+        (DW_AT_namelist_item: 0x44),
+        (DW_AT_priority: 0x45),
+        (DW_AT_segment: 0x46),
+        (DW_AT_specification: 0x47),
+        (DW_AT_static_link: 0x48),
+        (DW_AT_type: 0x49),
+        (DW_AT_use_location: 0x4a),
+        (DW_AT_variable_parameter: 0x4b),
+        (DW_AT_virtuality: 0x4c),
+        (DW_AT_vtable_elem_location: 0x4d),
+        (DW_AT_allocated: 0x4e),             // DWARF 3
+        (DW_AT_associated: 0x4f),            // DWARF 3
+        (DW_AT_data_location: 0x50),         // DWARF 3
+        (DW_AT_byte_stride: 0x51),           // DWARF 3
+        (DW_AT_entry_pc: 0x52),              // DWARF 3
+        (DW_AT_use_UTF8: 0x53),              // DWARF 3
+        (DW_AT_extension: 0x54),             // DWARF 3
+        (DW_AT_ranges: 0x55),                // DWARF 3
+        (DW_AT_trampoline: 0x56),            // DWARF 3
+        (DW_AT_call_column: 0x57),           // DWARF 3
+        (DW_AT_call_file: 0x58),             // DWARF 3
+        (DW_AT_call_line: 0x59),             // DWARF 3
+        (DW_AT_description: 0x5a),           // DWARF 3
+        (DW_AT_binary_scale: 0x5b),          // DWARF 3
+        (DW_AT_decimal_scale: 0x5c),         // DWARF 3
+        (DW_AT_small: 0x5d),                 // DWARF 3
+        (DW_AT_decimal_sign: 0x5e),          // DWARF 3
+        (DW_AT_digit_count: 0x5f),           // DWARF 3
+        (DW_AT_picture_string: 0x60),        // DWARF 3
+        (DW_AT_mutable: 0x61),               // DWARF 3
+        (DW_AT_threads_scaled: 0x62),        // DWARF 3
+        (DW_AT_explicit: 0x63),              // DWARF 3
+        (DW_AT_object_pointer: 0x64),        // DWARF 3
+        (DW_AT_endianity: 0x65),             // DWARF 3
+        (DW_AT_elemental: 0x66),             // DWARF 3
+        (DW_AT_pure: 0x67),                  // DWARF 3
+        (DW_AT_recursive: 0x68),             // DWARF 3
+        (DW_AT_signature: 0x69),             // DWARF 4
+        (DW_AT_main_subprogram: 0x6a),       // DWARF 4
+        (DW_AT_data_bit_offset: 0x6b),       // DWARF 4
+        (DW_AT_const_expr: 0x6c),            // DWARF 4
+        (DW_AT_enum_class: 0x6d),            // DWARF 4
+        (DW_AT_linkage_name: 0x6e),          // DWARF 4
+        (DW_AT_string_length_bit_size: 0x6f), // DWARF 5
+        (DW_AT_string_length_byte_size: 0x70), // DWARF 5
+        (DW_AT_rank: 0x71),                  // DWARF 5
+        (DW_AT_str_offsets_base: 0x72),      // DWARF 5
+        (DW_AT_addr_base: 0x73),             // DWARF 5
+        (DW_AT_rnglists_base: 0x74),         // DWARF 5
+        (DW_AT_dwo_name: 0x76),              // DWARF 5
+        (DW_AT_reference: 0x77),             // DWARF 5
+        (DW_AT_rvalue_reference: 0x78),      // DWARF 5
+        (DW_AT_macros: 0x79),                // DWARF 5
+        (DW_AT_call_all_calls: 0x7a),        // DWARF 5
+        (DW_AT_call_all_source_calls: 0x7b), // DWARF 5
+        (DW_AT_call_all_tail_calls: 0x7c),   // DWARF 5
+        (DW_AT_call_return_pc: 0x7d),        // DWARF 5
+        (DW_AT_call_value: 0x7e),            // DWARF 5
+        (DW_AT_call_origin: 0x7f),           // DWARF 5
+        (DW_AT_call_parameter: 0x80),        // DWARF 5
+        (DW_AT_call_pc: 0x81),               // DWARF 5
+        (DW_AT_call_tail_call: 0x82),        // DWARF 5
+        (DW_AT_call_target: 0x83),           // DWARF 5
+        (DW_AT_call_target_clobbered: 0x84), // DWARF 5
+        (DW_AT_call_data_location: 0x85),    // DWARF 5
+        (DW_AT_call_data_value: 0x86),       // DWARF 5
+        (DW_AT_noreturn: 0x87),              // DWARF 5
+        (DW_AT_alignment: 0x88),             // DWARF 5
+        (DW_AT_export_symbols: 0x89),        // DWARF 5
+        (DW_AT_deleted: 0x8a),               // DWARF 5
+        (DW_AT_defaulted: 0x8b),             // DWARF 5
+        (DW_AT_loclists_base: 0x8c),         // DWARF 5
+        (DW_AT_lo_user: 0x2000),
+        (DW_AT_MIPS_linkage_name: 0x2007),
+        (DW_AT_GNU_vector: 0x2107),
+        (DW_AT_GNU_template_name: 0x2110),
+        (DW_AT_GNU_call_site_value: 0x2111),
+        (DW_AT_GNU_call_site_data_value: 0x2112),
+        (DW_AT_GNU_call_site_target: 0x2113),
+        (DW_AT_GNU_call_site_target_clobbered: 0x2114),
+        (DW_AT_GNU_tail_call: 0x2115),
+        (DW_AT_GNU_all_tail_call_sites: 0x2116),
+        (DW_AT_GNU_all_call_sites: 0x2117),
+        (DW_AT_GNU_all_source_call_sites: 0x2118),
+        (DW_AT_GNU_macros: 0x2119),
+        (DW_AT_GNU_deleted: 0x211a),
+        (DW_AT_GNU_dwo_name: 0x2130),
+        (DW_AT_GNU_dwo_id: 0x2131),
+        (DW_AT_GNU_ranges_base: 0x2132),
+        (DW_AT_GNU_addr_base: 0x2133),
+        (DW_AT_GNU_pubnames: 0x2134),
+        (DW_AT_GNU_pubtypes: 0x2135),
+        (DW_AT_GNU_discriminator: 0x2136),
+        (DW_AT_GNU_locviews: 0x2137),
+        (DW_AT_GNU_entry_view: 0x2138),
+        (DW_AT_hi_user: 0x3fff),
+    ],
+    display = true, bitflags = false,
+    ranges = [
+        ("User-defined": DW_AT_lo_user..=DW_AT_hi_user),
+    ],
+    aliases = [DW_AT_lo_user, DW_AT_hi_user],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct UnitType(u8),
+    pub
+    [
+        (DW_UT_compile: 0x01),
+        (DW_UT_type: 0x02),
+        (DW_UT_partial: 0x03),
+        (DW_UT_skeleton: 0x04),
+        (DW_UT_split_compile: 0x05),
+        (DW_UT_split_type: 0x06),
+        (DW_UT_lo_user: 0x80),
+        (DW_UT_hi_user: 0xff),
+    ],
+    display = true, bitflags = false,
+    ranges = [
+        ("User-defined": DW_UT_lo_user..=DW_UT_hi_user),
+    ],
+    aliases = [DW_UT_lo_user, DW_UT_hi_user],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct RangeListEntry(u8),
+    pub
+    [
+        (DW_RLE_end_of_list: 0x00),
+        (DW_RLE_base_addressx: 0x01),
+        (DW_RLE_startx_endx: 0x02),
+        (DW_RLE_startx_length: 0x03),
+        (DW_RLE_offset_pair: 0x04),
+        (DW_RLE_base_address: 0x05),
+        (DW_RLE_start_end: 0x06),
+        (DW_RLE_start_length: 0x07),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct LineContentType(u16),
+    pub
+    [
+        (DW_LNCT_path: 0x1),
+        (DW_LNCT_directory_index: 0x2),
+        (DW_LNCT_timestamp: 0x3),
+        (DW_LNCT_size: 0x4),
+        (DW_LNCT_MD5: 0x5),
+        (DW_LNCT_lo_user: 0x2000),
+        (DW_LNCT_hi_user: 0x3fff),
+    ],
+    display = true, bitflags = false,
+    ranges = [
+        ("User-defined": DW_LNCT_lo_user..=DW_LNCT_hi_user),
+    ],
+    aliases = [DW_LNCT_lo_user, DW_LNCT_hi_user],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct LineStandardOpcode(u8),
+    pub
+    [
+        (DW_LNS_copy: 0x01),
+        (DW_LNS_advance_pc: 0x02),
+        (DW_LNS_advance_line: 0x03),
+        (DW_LNS_set_file: 0x04),
+        (DW_LNS_set_column: 0x05),
+        (DW_LNS_negate_stmt: 0x06),
+        (DW_LNS_set_basic_block: 0x07),
+        (DW_LNS_const_add_pc: 0x08),
+        (DW_LNS_fixed_advance_pc: 0x09),
+        (DW_LNS_set_prologue_end: 0x0a),   // DWARF 3
+        (DW_LNS_set_epilogue_begin: 0x0b), // DWARF 3
+        (DW_LNS_set_isa: 0x0c),            // DWARF 3
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct LineExtendedOpcode(u8),
+    pub
+    [
+        (DW_LNE_end_sequence: 0x01),
+        (DW_LNE_set_address: 0x02),
+        (DW_LNE_define_file: 0x03),       // Removed in DWARF 5
+        (DW_LNE_set_discriminator: 0x04), // DWARF 4
+        (DW_LNE_lo_user: 0x80),
+        (DW_LNE_hi_user: 0xff),
+    ],
+    display = true, bitflags = false,
+    ranges = [
+        ("User-defined": DW_LNE_lo_user..=DW_LNE_hi_user),
+    ],
+    aliases = [DW_LNE_lo_user, DW_LNE_hi_user],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Operation(u8),
+    pub
+    [
+        (DW_OP_addr: 0x03),
+        (DW_OP_deref: 0x06),
+        (DW_OP_const1u: 0x08),
+        (DW_OP_const1s: 0x09),
+        (DW_OP_const2u: 0x0a),
+        (DW_OP_const2s: 0x0b),
+        (DW_OP_const4u: 0x0c),
+        (DW_OP_const4s: 0x0d),
+        (DW_OP_const8u: 0x0e),
+        (DW_OP_const8s: 0x0f),
+        (DW_OP_constu: 0x10),
+        (DW_OP_consts: 0x11),
+        (DW_OP_dup: 0x12),
+        (DW_OP_drop: 0x13),
+        (DW_OP_over: 0x14),
+        (DW_OP_pick: 0x15),
+        (DW_OP_swap: 0x16),
+        (DW_OP_rot: 0x17),
+        (DW_OP_xderef: 0x18),
+        (DW_OP_abs: 0x19),
+        (DW_OP_and: 0x1a),
+        (DW_OP_div: 0x1b),
+        (DW_OP_minus: 0x1c),
+        (DW_OP_mod: 0x1d),
+        (DW_OP_mul: 0x1e),
+        (DW_OP_neg: 0x1f),
+        (DW_OP_not: 0x20),
+        (DW_OP_or: 0x21),
+        (DW_OP_plus: 0x22),
+        (DW_OP_plus_uconst: 0x23),
+        (DW_OP_shl: 0x24),
+        (DW_OP_shr: 0x25),
+        (DW_OP_shra: 0x26),
+        (DW_OP_xor: 0x27),
+        (DW_OP_bra: 0x28),
+        (DW_OP_eq: 0x29),
+        (DW_OP_ge: 0x2a),
+        (DW_OP_gt: 0x2b),
+        (DW_OP_le: 0x2c),
+        (DW_OP_lt: 0x2d),
+        (DW_OP_ne: 0x2e),
+        (DW_OP_skip: 0x2f),
+        (DW_OP_lit0: 0x30),
+        (DW_OP_lit31: 0x4f),
+        (DW_OP_reg0: 0x50),
+        (DW_OP_reg31: 0x6f),
+        (DW_OP_breg0: 0x70),
+        (DW_OP_breg31: 0x8f),
+        (DW_OP_regx: 0x90),
+        (DW_OP_fbreg: 0x91),
+        (DW_OP_bregx: 0x92),
+        (DW_OP_piece: 0x93),
+        (DW_OP_deref_size: 0x94),
+        (DW_OP_xderef_size: 0x95),
+        (DW_OP_nop: 0x96),
+        (DW_OP_push_object_address: 0x97), // DWARF 3
+        (DW_OP_call2: 0x98),               // DWARF 3
+        (DW_OP_call4: 0x99),               // DWARF 3
+        (DW_OP_call_ref: 0x9a),            // DWARF 3
+        (DW_OP_form_tls_address: 0x9b),    // DWARF 3
+        (DW_OP_call_frame_cfa: 0x9c),      // DWARF 3
+        (DW_OP_bit_piece: 0x9d),           // DWARF 3
+        (DW_OP_implicit_value: 0x9e),      // DWARF 4
+        (DW_OP_stack_value: 0x9f),         // DWARF 4
+        (DW_OP_implicit_pointer: 0xa0),    // DWARF 5
+        (DW_OP_addrx: 0xa1),               // DWARF 5
+        (DW_OP_constx: 0xa2),              // DWARF 5
+        (DW_OP_entry_value: 0xa3),         // DWARF 5
+        (DW_OP_const_type: 0xa4),          // DWARF 5
+        (DW_OP_regval_type: 0xa5),         // DWARF 5
+        (DW_OP_deref_type: 0xa6),          // DWARF 5
+        (DW_OP_xderef_type: 0xa7),         // DWARF 5
+        (DW_OP_convert: 0xa8),             // DWARF 5
+        (DW_OP_reinterpret: 0xa9),         // DWARF 5
+        (DW_OP_lo_user: 0xe0),
+        (DW_OP_GNU_push_tls_address: 0xe0),
+        (DW_OP_GNU_uninit: 0xf0),
+        (DW_OP_GNU_encoded_addr: 0xf1),
+        (DW_OP_GNU_implicit_pointer: 0xf2),
+        (DW_OP_GNU_entry_value: 0xf3),
+        (DW_OP_GNU_const_type: 0xf4),
+        (DW_OP_GNU_regval_type: 0xf5),
+        (DW_OP_GNU_deref_type: 0xf6),
+        (DW_OP_GNU_convert: 0xf7),
+        (DW_OP_GNU_reinterpret: 0xf9),
+        (DW_OP_GNU_parameter_ref: 0xfa),
+        (DW_OP_GNU_addr_index: 0xfb),
+        (DW_OP_GNU_const_index: 0xfc),
+        (DW_OP_GNU_variable_value: 0xfd),
+        (DW_OP_hi_user: 0xff),
+    ],
+    display = true, bitflags = false,
+    ranges = [
+        ("Literal": DW_OP_lit0..=DW_OP_lit31),
+        ("Register": DW_OP_reg0..=DW_OP_reg31),
+        ("Register based address": DW_OP_breg0..=DW_OP_breg31),
+        ("User-defined": DW_OP_lo_user..=DW_OP_hi_user),
+    ],
+    aliases = [DW_OP_lo_user, DW_OP_hi_user],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CallFrameInstruction(u8),
+    pub
+    [
+        // Primary opcodes in the high 2 bits. The low 6 bits are the operand.
+        (DW_CFA_advance_loc: 0x40),
+        (DW_CFA_offset: 0x80),
+        (DW_CFA_restore: 0xc0),
+        (DW_CFA_nop: 0x00),
+        (DW_CFA_set_loc: 0x01),
+        (DW_CFA_advance_loc1: 0x02),
+        (DW_CFA_advance_loc2: 0x03),
+        (DW_CFA_advance_loc4: 0x04),
+        (DW_CFA_offset_extended: 0x05),
+        (DW_CFA_restore_extended: 0x06),
+        (DW_CFA_undefined: 0x07),
+        (DW_CFA_same_value: 0x08),
+        (DW_CFA_register: 0x09),
+        (DW_CFA_remember_state: 0x0a),
+        (DW_CFA_restore_state: 0x0b),
+        (DW_CFA_def_cfa: 0x0c),
+        (DW_CFA_def_cfa_register: 0x0d),
+        (DW_CFA_def_cfa_offset: 0x0e),
+        (DW_CFA_def_cfa_expression: 0x0f),   // DWARF 3
+        (DW_CFA_expression: 0x10),           // DWARF 3
+        (DW_CFA_offset_extended_sf: 0x11),   // DWARF 3
+        (DW_CFA_def_cfa_sf: 0x12),           // DWARF 3
+        (DW_CFA_def_cfa_offset_sf: 0x13),    // DWARF 3
+        (DW_CFA_val_offset: 0x14),           // DWARF 3
+        (DW_CFA_val_offset_sf: 0x15),        // DWARF 3
+        (DW_CFA_val_expression: 0x16),       // DWARF 3
+        (DW_CFA_lo_user: 0x1c),
+        (DW_CFA_GNU_window_save: 0x2d),      // DW_CFA_AARCH64_negate_ra_state on AArch64
+        (DW_CFA_GNU_args_size: 0x2e),
+        (DW_CFA_GNU_negative_offset_extended: 0x2f),
+        (DW_CFA_hi_user: 0x3f),
+    ],
+    display = true, bitflags = false,
+    ranges = [
+        ("User-defined": DW_CFA_lo_user..=DW_CFA_hi_user),
+    ],
+    aliases = [DW_CFA_lo_user, DW_CFA_hi_user],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct PointerEncoding(u8),
+    pub
+    [
+        // Format of the value in the low 4 bits
+        (DW_EH_PE_absptr: 0x00),
+        (DW_EH_PE_uleb128: 0x01),
+        (DW_EH_PE_udata2: 0x02),
+        (DW_EH_PE_udata4: 0x03),
+        (DW_EH_PE_udata8: 0x04),
+        (DW_EH_PE_signed: 0x08),
+        (DW_EH_PE_sleb128: 0x09),
+        (DW_EH_PE_sdata2: 0x0a),
+        (DW_EH_PE_sdata4: 0x0b),
+        (DW_EH_PE_sdata8: 0x0c),
+        // What the value is relative to in bits 4 to 6
+        (DW_EH_PE_pcrel: 0x10),
+        (DW_EH_PE_textrel: 0x20),
+        (DW_EH_PE_datarel: 0x30),
+        (DW_EH_PE_funcrel: 0x40),
+        (DW_EH_PE_aligned: 0x50),
+        // The value is the address of the pointer
+        (DW_EH_PE_indirect: 0x80),
+        (DW_EH_PE_omit: 0xff),
+    ],
+    display = true, bitflags = false,
+);
+
+impl PointerEncoding {
+    /// Format of the value, like DW_EH_PE_sdata4.
+    pub fn format(&self) -> PointerEncoding {
+        PointerEncoding::new(self.inner() & 0x0f)
+    }
+
+    /// Base of the value, like DW_EH_PE_pcrel.
+    pub fn application(&self) -> PointerEncoding {
+        PointerEncoding::new(self.inner() & 0x70)
+    }
+
+    pub fn is_indirect_pointer(&self) -> bool {
+        !self.is_DW_EH_PE_omit() && self.inner() & 0x80 != 0
+    }
+}
+
+/// Error of DWARF parsing. Offsets are relative to the section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DwarfError {
+    /// The structure at the offset runs out of section.
+    Truncated(usize),
+
+    /// LEB128 number at the offset overflows 64 bits or the field it is read into.
+    BadLeb128(usize),
+
+    /// Version of unit at the offset is not supported.
+    UnsupportedVersion(usize, u16),
+
+    /// The form can not be read in this context.
+    UnsupportedForm(Form),
+
+    /// Offset of string is out of string section.
+    BadStringOffset(u64),
+
+    /// Line program at the offset has a header that can not be executed, like zero line_range.
+    BadLineProgram(usize),
+
+    /// DIE at the offset refers an abbreviation code that is not in the table.
+    BadAbbreviation(usize, u64),
+
+    /// Reference to DIE points out of unit or section.
+    BadReference(u64),
+
+    /// The value is not of the class expected for the attribute, like a string for an address.
+    UnexpectedValue(At),
+
+    /// Call frame entry at the offset is broken, like an FDE that refers to no CIE.
+    BadFrameEntry(usize),
+
+    /// Call frame instruction at the offset is unknown or not allowed in the state.
+    BadFrameInstruction(usize),
+
+    /// The pointer encoding can not be decoded in this context.
+    UnsupportedPointerEncoding(PointerEncoding),
+}
+
+impl core::fmt::Display for DwarfError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DwarfError::Truncated(at) => write!(fmt, "truncated structure at {:#x}", at),
+            DwarfError::BadLeb128(at) => write!(fmt, "LEB128 overflow at {:#x}", at),
+            DwarfError::UnsupportedVersion(at, v) => {
+                write!(fmt, "unsupported version {} of unit at {:#x}", v, at)
+            }
+            DwarfError::UnsupportedForm(form) => write!(fmt, "unsupported form {}", form),
+            DwarfError::BadStringOffset(x) => {
+                write!(fmt, "string offset {:#x} is out of section", x)
+            }
+            DwarfError::BadLineProgram(at) => write!(fmt, "bad line program header at {:#x}", at),
+            DwarfError::BadAbbreviation(at, code) => {
+                write!(fmt, "unknown abbreviation code {} of DIE at {:#x}", code, at)
+            }
+            DwarfError::BadReference(x) => write!(fmt, "bad DIE reference {:#x}", x),
+            DwarfError::UnexpectedValue(at) => write!(fmt, "unexpected value class of {}", at),
+            DwarfError::BadFrameEntry(at) => write!(fmt, "bad call frame entry at {:#x}", at),
+            DwarfError::BadFrameInstruction(at) => {
+                write!(fmt, "bad call frame instruction at {:#x}", at)
+            }
+            DwarfError::UnsupportedPointerEncoding(enc) => {
+                write!(fmt, "unsupported pointer encoding {:#x}", enc.inner())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DwarfError {}
+
+/// Size of offsets to other sections and of unit lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dwarf32,
+    /// Unit length starts with 0xffffffff.
+    Dwarf64,
+}
+
+impl Format {
+    pub fn offset_size(&self) -> usize {
+        match self {
+            Format::Dwarf32 => 4,
+            Format::Dwarf64 => 8,
+        }
+    }
+}
+
+/// Parameters of a unit that decide how attribute values are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoding {
+    pub format: Format,
+    pub version: u16,
+    pub address_size: u8,
+}
+
+/// Attribute value read by form. Offsets and indices are not resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value<'a> {
+    Address(u64),
+    /// Index into .debug_addr
+    Addrx(u64),
+    Block(&'a [u8]),
+    /// DWARF expression
+    Exprloc(&'a [u8]),
+    /// Unsigned constant of data1, data2, data4, data8 and udata.
+    Udata(u64),
+    Sdata(i64),
+    /// Constant of data16, like MD5 digest.
+    Data16(&'a [u8; 16]),
+    Flag(bool),
+    /// Constant in the abbreviation.
+    ImplicitConst(i64),
+    /// Null terminated string in place, without the null.
+    String(&'a [u8]),
+    /// Offset in .debug_str
+    Strp(u64),
+    /// Offset in .debug_line_str
+    LineStrp(u64),
+    /// Index into .debug_str_offsets
+    Strx(u64),
+    /// Offset in .debug_str of supplementary object file
+    StrpSup(u64),
+    /// Offset from the start of the unit
+    Ref(u64),
+    /// Offset in .debug_info
+    RefAddr(u64),
+    /// Type signature of type unit
+    RefSig8(u64),
+    /// Offset in .debug_info of supplementary object file
+    RefSup(u64),
+    /// Offset in other section, like .debug_line or .debug_ranges
+    SecOffset(u64),
+    /// Index into .debug_loclists
+    Loclistx(u64),
+    /// Index into .debug_rnglists
+    Rnglistx(u64),
+}
+
+impl<'a> Value<'a> {
+    /// Value as unsigned constant.
+    pub fn udata(&self) -> Option<u64> {
+        match *self {
+            Value::Udata(x) => Some(x),
+            Value::Sdata(x) | Value::ImplicitConst(x) if x >= 0 => Some(x as u64),
+            _ => None,
+        }
+    }
+}
+
+/// Cursor over section data. Numbers are little endian.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8], DwarfError> {
+        let ret = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or(DwarfError::Truncated(self.pos))?;
+        self.pos += len;
+        Ok(ret)
+    }
+
+    /// All bytes left.
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let ret = self.data.get(self.pos..).unwrap_or(&[]);
+        self.pos += ret.len();
+        ret
+    }
+
+    /// Little endian number of `size` bytes.
+    pub(crate) fn uint(&mut self, size: usize) -> Result<u64, DwarfError> {
+        let bytes = self.bytes(size)?;
+        Ok(bytes.iter().rev().fold(0, |acc, x| (acc << 8) | *x as u64))
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, DwarfError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, DwarfError> {
+        Ok(self.uint(2)? as u16)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, DwarfError> {
+        Ok(self.uint(4)? as u32)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, DwarfError> {
+        self.uint(8)
+    }
+
+    pub(crate) fn uleb(&mut self) -> Result<u64, DwarfError> {
+        let rest = self.data.get(self.pos..).unwrap_or(&[]);
+        match Uleb128::from_slice(rest) {
+            Ok((read, x)) => {
+                self.pos += read;
+                Ok(x.0)
+            }
+            Err(super::FromU8Error::NotEnoughSlice(_)) => Err(DwarfError::Truncated(self.pos)),
+            Err(_) => Err(DwarfError::BadLeb128(self.pos)),
+        }
+    }
+
+    /// ULEB128 of a 16 bit field, like a tag, an attribute or a form.
+    pub(crate) fn uleb_u16(&mut self) -> Result<u16, DwarfError> {
+        let at = self.pos;
+        u16::try_from(self.uleb()?).map_err(|_| DwarfError::BadLeb128(at))
+    }
+
+    pub(crate) fn sleb(&mut self) -> Result<i64, DwarfError> {
+        let rest = self.data.get(self.pos..).unwrap_or(&[]);
+        match Sleb128::from_slice(rest) {
+            Ok((read, x)) => {
+                self.pos += read;
+                Ok(x.0)
+            }
+            Err(super::FromU8Error::NotEnoughSlice(_)) => Err(DwarfError::Truncated(self.pos)),
+            Err(_) => Err(DwarfError::BadLeb128(self.pos)),
+        }
+    }
+
+    /// Null terminated string without the null.
+    pub(crate) fn cstr(&mut self) -> Result<&'a [u8], DwarfError> {
+        let rest = self.data.get(self.pos..).unwrap_or(&[]);
+        let len = rest
+            .iter()
+            .position(|x| *x == 0)
+            .ok_or(DwarfError::Truncated(self.pos))?;
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+
+    pub(crate) fn offset(&mut self, format: Format) -> Result<u64, DwarfError> {
+        self.uint(format.offset_size())
+    }
+
+    pub(crate) fn address(&mut self, size: u8) -> Result<u64, DwarfError> {
+        self.uint(size as usize)
+    }
+
+    /// Unit length and format. The returned reader ends at the end of the unit.
+    pub(crate) fn unit(&mut self) -> Result<(Format, Reader<'a>), DwarfError> {
+        let start = self.pos;
+        let (format, length) = match self.u32()? {
+            0xffff_ffff => (Format::Dwarf64, self.u64()?),
+            x => (Format::Dwarf32, x as u64),
+        };
+        let end = usize::try_from(length)
+            .ok()
+            .and_then(|x| x.checked_add(self.pos))
+            .filter(|x| *x <= self.data.len())
+            .ok_or(DwarfError::Truncated(start))?;
+        let unit = Reader {
+            data: &self.data[..end],
+            pos: self.pos,
+        };
+        self.pos = end;
+        Ok((format, unit))
+    }
+
+    /// Read a value of `form`. `implicit_const` is the value in the abbreviation for DW_FORM_implicit_const.
+    pub(crate) fn value(
+        &mut self,
+        form: Form,
+        encoding: &Encoding,
+        implicit_const: Option<i64>,
+    ) -> Result<Value<'a>, DwarfError> {
+        let format = encoding.format;
+        Ok(match form {
+            Form::DW_FORM_addr => Value::Address(self.address(encoding.address_size)?),
+            Form::DW_FORM_addrx | Form::DW_FORM_GNU_addr_index => Value::Addrx(self.uleb()?),
+            Form::DW_FORM_addrx1 => Value::Addrx(self.uint(1)?),
+            Form::DW_FORM_addrx2 => Value::Addrx(self.uint(2)?),
+            Form::DW_FORM_addrx3 => Value::Addrx(self.uint(3)?),
+            Form::DW_FORM_addrx4 => Value::Addrx(self.uint(4)?),
+            Form::DW_FORM_block1 => {
+                let len = self.u8()? as usize;
+                Value::Block(self.bytes(len)?)
+            }
+            Form::DW_FORM_block2 => {
+                let len = self.u16()? as usize;
+                Value::Block(self.bytes(len)?)
+            }
+            Form::DW_FORM_block4 => {
+                let len = self.u32()? as usize;
+                Value::Block(self.bytes(len)?)
+            }
+            Form::DW_FORM_block => {
+                let at = self.pos;
+                let len = usize::try_from(self.uleb()?).map_err(|_| DwarfError::Truncated(at))?;
+                Value::Block(self.bytes(len)?)
+            }
+            Form::DW_FORM_exprloc => {
+                let at = self.pos;
+                let len = usize::try_from(self.uleb()?).map_err(|_| DwarfError::Truncated(at))?;
+                Value::Exprloc(self.bytes(len)?)
+            }
+            Form::DW_FORM_data1 => Value::Udata(self.uint(1)?),
+            Form::DW_FORM_data2 => Value::Udata(self.uint(2)?),
+            Form::DW_FORM_data4 => Value::Udata(self.uint(4)?),
+            Form::DW_FORM_data8 => Value::Udata(self.uint(8)?),
+            Form::DW_FORM_udata => Value::Udata(self.uleb()?),
+            Form::DW_FORM_sdata => Value::Sdata(self.sleb()?),
+            Form::DW_FORM_data16 => Value::Data16(self.bytes(16)?.try_into().unwrap()),
+            Form::DW_FORM_flag => Value::Flag(self.u8()? != 0),
+            Form::DW_FORM_flag_present => Value::Flag(true),
+            Form::DW_FORM_implicit_const => {
+                Value::ImplicitConst(implicit_const.ok_or(DwarfError::UnsupportedForm(form))?)
+            }
+            Form::DW_FORM_string => Value::String(self.cstr()?),
+            Form::DW_FORM_strp => Value::Strp(self.offset(format)?),
+            Form::DW_FORM_line_strp => Value::LineStrp(self.offset(format)?),
+            Form::DW_FORM_strp_sup | Form::DW_FORM_GNU_strp_alt => {
+                Value::StrpSup(self.offset(format)?)
+            }
+            Form::DW_FORM_strx | Form::DW_FORM_GNU_str_index => Value::Strx(self.uleb()?),
+            Form::DW_FORM_strx1 => Value::Strx(self.uint(1)?),
+            Form::DW_FORM_strx2 => Value::Strx(self.uint(2)?),
+            Form::DW_FORM_strx3 => Value::Strx(self.uint(3)?),
+            Form::DW_FORM_strx4 => Value::Strx(self.uint(4)?),
+            Form::DW_FORM_ref1 => Value::Ref(self.uint(1)?),
+            Form::DW_FORM_ref2 => Value::Ref(self.uint(2)?),
+            Form::DW_FORM_ref4 => Value::Ref(self.uint(4)?),
+            Form::DW_FORM_ref8 => Value::Ref(self.uint(8)?),
+            Form::DW_FORM_ref_udata => Value::Ref(self.uleb()?),
+            // DWARF 2 uses the address size for DW_FORM_ref_addr.
+            Form::DW_FORM_ref_addr if encoding.version == 2 => {
+                Value::RefAddr(self.address(encoding.address_size)?)
+            }
+            Form::DW_FORM_ref_addr => Value::RefAddr(self.offset(format)?),
+            Form::DW_FORM_ref_sig8 => Value::RefSig8(self.u64()?),
+            Form::DW_FORM_ref_sup4 => Value::RefSup(self.uint(4)?),
+            Form::DW_FORM_ref_sup8 => Value::RefSup(self.uint(8)?),
+            Form::DW_FORM_GNU_ref_alt => Value::RefSup(self.offset(format)?),
+            Form::DW_FORM_sec_offset => Value::SecOffset(self.offset(format)?),
+            Form::DW_FORM_loclistx => Value::Loclistx(self.uleb()?),
+            Form::DW_FORM_rnglistx => Value::Rnglistx(self.uleb()?),
+            Form::DW_FORM_indirect => {
+                let form = Form::new(self.uleb_u16()?);
+                if form.is_DW_FORM_indirect() {
+                    return Err(DwarfError::UnsupportedForm(form));
+                }
+                self.value(form, encoding, implicit_const)?
+            }
+            _ => return Err(DwarfError::UnsupportedForm(form)),
+        })
+    }
+}
+
+/// Null terminated string at `offset` of string section `data`.
+fn string_in(data: &[u8], offset: u64) -> Result<String, DwarfError> {
+    let mut r = Reader::new(data, 0);
+    r.pos = usize::try_from(offset).map_err(|_| DwarfError::BadStringOffset(offset))?;
+    let s = r.cstr().map_err(|_| DwarfError::BadStringOffset(offset))?;
+    Ok(String::from_utf8_lossy(s).into_owned())
+}
+
+/// Contents of DWARF sections. Missing sections are empty.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dwarf<'a> {
+    /// Size of target address. Line programs before DWARF 5 need this.
+    pub address_size: u8,
+    pub debug_info: &'a [u8],
+    pub debug_abbrev: &'a [u8],
+    pub debug_line: &'a [u8],
+    pub debug_str: &'a [u8],
+    pub debug_line_str: &'a [u8],
+    pub debug_str_offsets: &'a [u8],
+    pub debug_addr: &'a [u8],
+    /// Range lists before DWARF 5
+    pub debug_ranges: &'a [u8],
+    pub debug_rnglists: &'a [u8],
+}
+
+impl<'a> Dwarf<'a> {
+    /// Sections of ELF file found by name.
+    /// Compressed sections, including legacy .zdebug ones, are decompressed. Sections that
+    /// cannot be decompressed are regarded as empty.
+    pub fn from_elf(elf: &'a Elf) -> Self {
+        let section = |name: &str| {
+            let legacy = name.replacen(".debug", ".zdebug", 1);
+            elf.section_by_name(name)
+                .or_else(|| elf.section_by_name(&legacy))
+                .and_then(|sh| elf.section_contents(sh).ok())
+                .unwrap_or(&[])
+        };
+        Self {
+            // FIXME: support 32bit
+            address_size: 8,
+            debug_info: section(".debug_info"),
+            debug_abbrev: section(".debug_abbrev"),
+            debug_line: section(".debug_line"),
+            debug_str: section(".debug_str"),
+            debug_line_str: section(".debug_line_str"),
+            debug_str_offsets: section(".debug_str_offsets"),
+            debug_addr: section(".debug_addr"),
+            debug_ranges: section(".debug_ranges"),
+            debug_rnglists: section(".debug_rnglists"),
+        }
+    }
+
+    /// String at `offset` in .debug_str.
+    pub fn str_at(&self, offset: u64) -> Result<String, DwarfError> {
+        string_in(self.debug_str, offset)
+    }
+
+    /// String at `offset` in .debug_line_str.
+    pub fn line_str_at(&self, offset: u64) -> Result<String, DwarfError> {
+        string_in(self.debug_line_str, offset)
+    }
+
+    /// Resolve string value that does not need unit information.
+    pub fn string(&self, value: &Value) -> Result<String, DwarfError> {
+        match value {
+            Value::String(s) => Ok(String::from_utf8_lossy(s).into_owned()),
+            Value::Strp(x) => self.str_at(*x),
+            Value::LineStrp(x) => self.line_str_at(*x),
+            Value::Strx(_) => Err(DwarfError::UnsupportedForm(Form::DW_FORM_strx)),
+            _ => Err(DwarfError::UnsupportedForm(Form::DW_FORM_string)),
+        }
+    }
+
+    /// All line programs in .debug_line.
+    pub fn line_programs(&self) -> Result<Vec<LineProgram>, DwarfError> {
+        let mut ret = vec![];
+        let mut offset = 0;
+        while offset < self.debug_line.len() {
+            let (program, next) = LineProgram::parse(self, offset)?;
+            ret.push(program);
+            offset = next;
+        }
+        Ok(ret)
+    }
+
+    /// Address to source position table of all line programs.
+    pub fn line_table(&self) -> Result<LineTable, DwarfError> {
+        Ok(LineTable::new(&self.line_programs()?))
+    }
+}
@@ -0,0 +1,1074 @@
+use super::{CallFrameInstruction, DwarfError, Format, Operation, PointerEncoding, Reader};
+use crate::types::elf::Elf;
+use core::ops::Range;
+
+/// Section that holds call frame information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// .eh_frame, loaded for exception handling. Pointers may be encoded.
+    EhFrame,
+    /// .debug_frame
+    DebugFrame,
+}
+
+/// Read a pointer encoded in `encoding`.
+/// `section_address` is the address of the start of reader data, the base of DW_EH_PE_pcrel.
+/// Indirect pointers are not dereferenced. None if the pointer is omitted.
+fn read_pointer(
+    r: &mut Reader,
+    encoding: PointerEncoding,
+    address_size: u8,
+    section_address: u64,
+    data_base: Option<u64>,
+) -> Result<Option<u64>, DwarfError> {
+    if encoding.is_DW_EH_PE_omit() {
+        return Ok(None);
+    }
+    if encoding.application().is_DW_EH_PE_aligned() {
+        let size = address_size.max(1) as usize;
+        r.bytes((size - r.pos() % size) % size)?;
+    }
+
+    let at = r.pos() as u64;
+    let value = match encoding.format() {
+        PointerEncoding::DW_EH_PE_absptr => r.address(address_size)?,
+        PointerEncoding::DW_EH_PE_uleb128 => r.uleb()?,
+        PointerEncoding::DW_EH_PE_udata2 => r.u16()? as u64,
+        PointerEncoding::DW_EH_PE_udata4 => r.u32()? as u64,
+        PointerEncoding::DW_EH_PE_udata8 => r.u64()?,
+        PointerEncoding::DW_EH_PE_sleb128 => r.sleb()? as u64,
+        PointerEncoding::DW_EH_PE_sdata2 => r.u16()? as i16 as u64,
+        PointerEncoding::DW_EH_PE_sdata4 => r.u32()? as i32 as u64,
+        PointerEncoding::DW_EH_PE_sdata8 => r.u64()?,
+        _ => return Err(DwarfError::UnsupportedPointerEncoding(encoding)),
+    };
+    let base = match encoding.application() {
+        PointerEncoding::DW_EH_PE_absptr | PointerEncoding::DW_EH_PE_aligned => 0,
+        PointerEncoding::DW_EH_PE_pcrel => section_address.wrapping_add(at),
+        PointerEncoding::DW_EH_PE_datarel => {
+            data_base.ok_or(DwarfError::UnsupportedPointerEncoding(encoding))?
+        }
+        // Text and function relative pointers are not used on supported targets.
+        _ => return Err(DwarfError::UnsupportedPointerEncoding(encoding)),
+    };
+    Ok(Some(base.wrapping_add(value)))
+}
+
+/// Common information entry, shared by FDEs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cie<'a> {
+    /// Offset in the section
+    pub offset: usize,
+    pub format: Format,
+    pub version: u8,
+    pub augmentation: String,
+    pub address_size: u8,
+    pub segment_selector_size: u8,
+    pub code_alignment_factor: u64,
+    pub data_alignment_factor: i64,
+    pub return_address_register: u16,
+    /// Encoding of addresses in FDEs. DW_EH_PE_absptr without 'R' augmentation.
+    pub fde_encoding: PointerEncoding,
+    /// Encoding of LSDA pointers in FDEs. DW_EH_PE_omit without 'L' augmentation.
+    pub lsda_encoding: PointerEncoding,
+    /// Personality routine. Address of the pointer if it is encoded indirectly.
+    pub personality: Option<u64>,
+    /// Frames of signal handlers, whose return address is not after a call.
+    pub signal_frame: bool,
+    /// FDEs have augmentation data, with 'z' augmentation.
+    pub has_augmentation_data: bool,
+    pub initial_instructions: &'a [u8],
+    /// Address of the section, to decode DW_CFA_set_loc.
+    section_address: u64,
+    instructions_offset: usize,
+}
+
+/// Frame description entry, which describes how to unwind a range of code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fde<'a> {
+    /// Offset in the section
+    pub offset: usize,
+    pub cie: Cie<'a>,
+    pub initial_location: u64,
+    pub address_range: u64,
+    /// Language specific data area, used by personality routine.
+    pub lsda: Option<u64>,
+    pub instructions: &'a [u8],
+    instructions_offset: usize,
+}
+
+/// Entry of call frame section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameEntry<'a> {
+    Cie(Cie<'a>),
+    Fde(Fde<'a>),
+}
+
+/// Rule to compute the canonical frame address, the stack pointer at the call site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CfaRule<'a> {
+    /// Register plus offset
+    RegisterOffset(u16, i64),
+    /// DWARF expression that computes the CFA
+    Expression(&'a [u8]),
+}
+
+/// Rule to recover the value of a register in the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterRule<'a> {
+    /// The value is lost.
+    Undefined,
+    /// The register is not modified.
+    SameValue,
+    /// Saved at CFA plus offset
+    Offset(i64),
+    /// The value is CFA plus offset.
+    ValOffset(i64),
+    /// Saved in another register
+    Register(u16),
+    /// Saved at the address that the expression computes. The CFA is pushed first.
+    Expression(&'a [u8]),
+    /// The value is what the expression computes. The CFA is pushed first.
+    ValExpression(&'a [u8]),
+}
+
+/// Rules to unwind a frame, for a range of addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnwindRow<'a> {
+    pub start: u64,
+    pub end: u64,
+    pub cfa: CfaRule<'a>,
+    /// Rules by register number, sorted. Other registers follow the convention of architecture,
+    /// usually the same value for callee-saved ones.
+    pub registers: Vec<(u16, RegisterRule<'a>)>,
+    /// Size of arguments pushed on stack, from DW_CFA_GNU_args_size.
+    pub args_size: u64,
+}
+
+impl<'a> UnwindRow<'a> {
+    pub fn register(&self, register: u16) -> Option<RegisterRule<'a>> {
+        self.registers
+            .binary_search_by_key(&register, |x| x.0)
+            .ok()
+            .map(|i| self.registers[i].1)
+    }
+
+    fn set(&mut self, register: u16, rule: Option<RegisterRule<'a>>) {
+        match (
+            self.registers.binary_search_by_key(&register, |x| x.0),
+            rule,
+        ) {
+            (Ok(i), Some(rule)) => self.registers[i].1 = rule,
+            (Ok(i), None) => {
+                self.registers.remove(i);
+            }
+            (Err(i), Some(rule)) => self.registers.insert(i, (register, rule)),
+            (Err(_), None) => (),
+        }
+    }
+}
+
+/// Call frame section, .eh_frame or .debug_frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallFrame<'a> {
+    pub kind: FrameKind,
+    pub data: &'a [u8],
+    /// Virtual address of the section. Base of DW_EH_PE_pcrel pointers.
+    pub address: u64,
+    /// Size of target address, if CIE does not specify.
+    pub address_size: u8,
+}
+
+impl<'a> CallFrame<'a> {
+    /// Section of ELF file found by name. Compressed .debug_frame is decompressed.
+    pub fn from_elf(elf: &'a Elf, kind: FrameKind) -> Option<Self> {
+        let sh = match kind {
+            FrameKind::EhFrame => elf.section_by_name(".eh_frame")?,
+            FrameKind::DebugFrame => elf
+                .section_by_name(".debug_frame")
+                .or_else(|| elf.section_by_name(".zdebug_frame"))?,
+        };
+        Some(Self {
+            kind,
+            data: elf.section_contents(sh).ok()?,
+            address: sh.get_sh_addr_unwrap().inner() as u64,
+            // FIXME: support 32bit
+            address_size: 8,
+        })
+    }
+
+    /// Entry at `offset` and the offset of the next one. None for the zero terminator.
+    pub fn entry_at(&self, offset: usize) -> Result<(Option<FrameEntry<'a>>, usize), DwarfError> {
+        let mut r = Reader::new(self.data, offset);
+        let (format, mut entry) = r.unit()?;
+        let next = r.pos();
+        if entry.is_empty() {
+            return Ok((None, next));
+        }
+
+        let id_at = entry.pos();
+        let id = entry.offset(format)?;
+        let is_cie = match (self.kind, format) {
+            (FrameKind::EhFrame, _) => id == 0,
+            (FrameKind::DebugFrame, Format::Dwarf32) => id == 0xffff_ffff,
+            (FrameKind::DebugFrame, Format::Dwarf64) => id == u64::MAX,
+        };
+        if is_cie {
+            return Ok((
+                Some(FrameEntry::Cie(self.parse_cie(offset, format, entry)?)),
+                next,
+            ));
+        }
+
+        // .eh_frame points the CIE relative to the pointer itself.
+        let cie_offset = match self.kind {
+            FrameKind::EhFrame => id_at.checked_sub(id as usize),
+            FrameKind::DebugFrame => usize::try_from(id).ok(),
+        }
+        .filter(|x| *x != offset)
+        .ok_or(DwarfError::BadFrameEntry(offset))?;
+        let cie = self
+            .cie_at(cie_offset)
+            .map_err(|_| DwarfError::BadFrameEntry(offset))?;
+        Ok((
+            Some(FrameEntry::Fde(self.parse_fde(offset, cie, entry)?)),
+            next,
+        ))
+    }
+
+    /// CIE at `offset`.
+    pub fn cie_at(&self, offset: usize) -> Result<Cie<'a>, DwarfError> {
+        match self.entry_at(offset)?.0 {
+            Some(FrameEntry::Cie(cie)) => Ok(cie),
+            _ => Err(DwarfError::BadFrameEntry(offset)),
+        }
+    }
+
+    /// All entries up to the end of section or zero terminator.
+    pub fn entries(&self) -> Result<Vec<FrameEntry<'a>>, DwarfError> {
+        let mut ret = vec![];
+        let mut offset = 0;
+        while offset < self.data.len() {
+            let (entry, next) = self.entry_at(offset)?;
+            match entry {
+                Some(entry) => ret.push(entry),
+                None => break,
+            }
+            offset = next;
+        }
+        Ok(ret)
+    }
+
+    /// FDE that covers `address`. The search table of .eh_frame_hdr is used if given.
+    pub fn find_fde(
+        &self,
+        address: u64,
+        hdr: Option<&EhFrameHdr>,
+    ) -> Result<Option<Fde<'a>>, DwarfError> {
+        if let Some(hdr) = hdr.filter(|x| !x.table.is_empty()) {
+            let Some(fde_address) = hdr.lookup(address) else {
+                return Ok(None);
+            };
+            let offset = fde_address
+                .checked_sub(self.address)
+                .and_then(|x| usize::try_from(x).ok())
+                .ok_or(DwarfError::BadFrameEntry(self.data.len()))?;
+            return match self.entry_at(offset)?.0 {
+                Some(FrameEntry::Fde(fde)) => Ok(Some(fde).filter(|x| x.contains(address))),
+                _ => Err(DwarfError::BadFrameEntry(offset)),
+            };
+        }
+
+        Ok(self.entries()?.into_iter().find_map(|x| match x {
+            FrameEntry::Fde(fde) if fde.contains(address) => Some(fde),
+            _ => None,
+        }))
+    }
+
+    fn parse_cie(
+        &self,
+        offset: usize,
+        format: Format,
+        mut entry: Reader<'a>,
+    ) -> Result<Cie<'a>, DwarfError> {
+        let version = entry.u8()?;
+        if !matches!(version, 1 | 3 | 4) {
+            return Err(DwarfError::UnsupportedVersion(offset, version as u16));
+        }
+        let augmentation = entry.cstr()?;
+        let (address_size, segment_selector_size) = if version >= 4 {
+            (entry.u8()?, entry.u8()?)
+        } else {
+            (self.address_size, 0)
+        };
+        // Old GCC put the address of exception table.
+        if augmentation.starts_with(b"eh") {
+            entry.address(address_size)?;
+        }
+        let code_alignment_factor = entry.uleb()?;
+        let data_alignment_factor = entry.sleb()?;
+        let return_address_register = if version == 1 {
+            entry.u8()? as u64
+        } else {
+            entry.uleb()?
+        };
+
+        let mut cie = Cie {
+            offset,
+            format,
+            version,
+            augmentation: String::from_utf8_lossy(augmentation).into_owned(),
+            address_size,
+            segment_selector_size,
+            code_alignment_factor,
+            data_alignment_factor,
+            return_address_register: u16::try_from(return_address_register)
+                .map_err(|_| DwarfError::BadFrameEntry(offset))?,
+            fde_encoding: PointerEncoding::DW_EH_PE_absptr,
+            lsda_encoding: PointerEncoding::DW_EH_PE_omit,
+            personality: None,
+            signal_frame: false,
+            has_augmentation_data: augmentation.first() == Some(&b'z'),
+            initial_instructions: &[],
+            section_address: self.address,
+            instructions_offset: 0,
+        };
+
+        if cie.has_augmentation_data {
+            let length = entry.uleb()? as usize;
+            let end = entry
+                .pos()
+                .checked_add(length)
+                .ok_or(DwarfError::BadFrameEntry(offset))?;
+            for c in &augmentation[1..] {
+                match c {
+                    b'L' => cie.lsda_encoding = PointerEncoding::new(entry.u8()?),
+                    b'P' => {
+                        let encoding = PointerEncoding::new(entry.u8()?);
+                        cie.personality =
+                            read_pointer(&mut entry, encoding, address_size, self.address, None)?;
+                    }
+                    b'R' => cie.fde_encoding = PointerEncoding::new(entry.u8()?),
+                    b'S' => cie.signal_frame = true,
+                    // Branch target identification and memory tagging of AArch64 have no data.
+                    b'B' | b'G' => (),
+                    // The length lets us skip the rest.
+                    _ => break,
+                }
+            }
+            let rest = end
+                .checked_sub(entry.pos())
+                .ok_or(DwarfError::BadFrameEntry(offset))?;
+            entry.bytes(rest)?;
+        } else if !augmentation.is_empty() && augmentation != b"eh" {
+            // Unknown augmentation may change the layout of entries.
+            return Err(DwarfError::BadFrameEntry(offset));
+        }
+
+        cie.instructions_offset = entry.pos();
+        cie.initial_instructions = entry.rest();
+        Ok(cie)
+    }
+
+    fn parse_fde(
+        &self,
+        offset: usize,
+        cie: Cie<'a>,
+        mut entry: Reader<'a>,
+    ) -> Result<Fde<'a>, DwarfError> {
+        entry.bytes(cie.segment_selector_size as usize)?;
+        let initial_location = read_pointer(
+            &mut entry,
+            cie.fde_encoding,
+            cie.address_size,
+            self.address,
+            None,
+        )?
+        .ok_or(DwarfError::BadFrameEntry(offset))?;
+        // The range is the size, which is not relative to anything.
+        let address_range = read_pointer(
+            &mut entry,
+            cie.fde_encoding.format(),
+            cie.address_size,
+            self.address,
+            None,
+        )?
+        .ok_or(DwarfError::BadFrameEntry(offset))?;
+
+        let mut lsda = None;
+        if cie.has_augmentation_data {
+            let length = entry.uleb()? as usize;
+            let end = entry
+                .pos()
+                .checked_add(length)
+                .ok_or(DwarfError::BadFrameEntry(offset))?;
+            if length != 0 {
+                lsda = read_pointer(
+                    &mut entry,
+                    cie.lsda_encoding,
+                    cie.address_size,
+                    self.address,
+                    None,
+                )?;
+            }
+            let rest = end
+                .checked_sub(entry.pos())
+                .ok_or(DwarfError::BadFrameEntry(offset))?;
+            entry.bytes(rest)?;
+        }
+
+        let instructions_offset = entry.pos();
+        Ok(Fde {
+            offset,
+            cie,
+            initial_location,
+            address_range,
+            lsda,
+            instructions: entry.rest(),
+            instructions_offset,
+        })
+    }
+}
+
+impl<'a> Fde<'a> {
+    pub fn range(&self) -> Range<u64> {
+        self.initial_location..self.initial_location.wrapping_add(self.address_range)
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        self.range().contains(&address)
+    }
+
+    /// Execute instructions of CIE and FDE into rows. Rows cover the range of FDE without gaps.
+    pub fn rows(&self) -> Result<Vec<UnwindRow<'a>>, DwarfError> {
+        let cie = &self.cie;
+        let mut rows = vec![];
+        let mut row = UnwindRow {
+            start: self.initial_location,
+            end: self.initial_location,
+            cfa: CfaRule::RegisterOffset(0, 0),
+            registers: vec![],
+            args_size: 0,
+        };
+
+        // Locations in error are relative to the instructions. Make them relative to the section.
+        let shift = |base: usize| {
+            move |e| match e {
+                DwarfError::Truncated(x) => DwarfError::Truncated(base + x),
+                DwarfError::BadLeb128(x) => DwarfError::BadLeb128(base + x),
+                DwarfError::BadFrameInstruction(x) => DwarfError::BadFrameInstruction(base + x),
+                e => e,
+            }
+        };
+        execute(cie, cie.initial_instructions, 0, &mut row, None, &mut rows)
+            .map_err(shift(cie.instructions_offset))?;
+        let initial = row.clone();
+        execute(
+            cie,
+            self.instructions,
+            self.instructions_offset,
+            &mut row,
+            Some(&initial),
+            &mut rows,
+        )
+        .map_err(shift(self.instructions_offset))?;
+
+        row.end = self.range().end;
+        if row.start < row.end {
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
+    /// Row that covers `address`.
+    pub fn unwind_row(&self, address: u64) -> Result<Option<UnwindRow<'a>>, DwarfError> {
+        Ok(self
+            .rows()?
+            .into_iter()
+            .find(|x| x.start <= address && address < x.end))
+    }
+}
+
+/// Execute call frame instructions on `row`. Finished rows are pushed to `rows`.
+/// `initial` is the row after CIE instructions, which DW_CFA_restore goes back to. None while executing CIE.
+/// `offset` is the section offset of instructions.
+fn execute<'a>(
+    cie: &Cie<'a>,
+    instructions: &'a [u8],
+    offset: usize,
+    row: &mut UnwindRow<'a>,
+    initial: Option<&UnwindRow<'a>>,
+    rows: &mut Vec<UnwindRow<'a>>,
+) -> Result<(), DwarfError> {
+    let mut r = Reader::new(instructions, 0);
+    let mut stack: Vec<(CfaRule, Vec<(u16, RegisterRule)>)> = vec![];
+
+    while !r.is_empty() {
+        let at = r.pos();
+        let bad = DwarfError::BadFrameInstruction(at);
+        let register = |r: &mut Reader| -> Result<u16, DwarfError> {
+            u16::try_from(r.uleb()?).map_err(|_| DwarfError::BadFrameInstruction(at))
+        };
+        let factored = |x: i64| x.wrapping_mul(cie.data_alignment_factor);
+
+        let byte = r.u8()?;
+        let mut advance_to = None;
+        match byte & 0xc0 {
+            0x40 => {
+                advance_to = Some(
+                    row.start
+                        .wrapping_add((byte & 0x3f) as u64 * cie.code_alignment_factor),
+                )
+            }
+            0x80 => row.set(
+                (byte & 0x3f) as u16,
+                Some(RegisterRule::Offset(factored(r.uleb()? as i64))),
+            ),
+            0xc0 => {
+                let register = (byte & 0x3f) as u16;
+                row.set(register, initial.ok_or(bad)?.register(register));
+            }
+            _ => match CallFrameInstruction::new(byte) {
+                CallFrameInstruction::DW_CFA_nop => (),
+                CallFrameInstruction::DW_CFA_set_loc => {
+                    let section_address = cie.section_address.wrapping_add(offset as u64);
+                    advance_to = read_pointer(
+                        &mut r,
+                        cie.fde_encoding,
+                        cie.address_size,
+                        section_address,
+                        None,
+                    )?;
+                }
+                CallFrameInstruction::DW_CFA_advance_loc1 => {
+                    let delta = r.u8()? as u64 * cie.code_alignment_factor;
+                    advance_to = Some(row.start.wrapping_add(delta));
+                }
+                CallFrameInstruction::DW_CFA_advance_loc2 => {
+                    let delta = r.u16()? as u64 * cie.code_alignment_factor;
+                    advance_to = Some(row.start.wrapping_add(delta));
+                }
+                CallFrameInstruction::DW_CFA_advance_loc4 => {
+                    let delta = r.u32()? as u64 * cie.code_alignment_factor;
+                    advance_to = Some(row.start.wrapping_add(delta));
+                }
+                CallFrameInstruction::DW_CFA_offset_extended => {
+                    let register = register(&mut r)?;
+                    let offset = factored(r.uleb()? as i64);
+                    row.set(register, Some(RegisterRule::Offset(offset)));
+                }
+                CallFrameInstruction::DW_CFA_restore_extended => {
+                    let register = register(&mut r)?;
+                    row.set(register, initial.ok_or(bad)?.register(register));
+                }
+                CallFrameInstruction::DW_CFA_undefined => {
+                    row.set(register(&mut r)?, Some(RegisterRule::Undefined))
+                }
+                CallFrameInstruction::DW_CFA_same_value => {
+                    row.set(register(&mut r)?, Some(RegisterRule::SameValue))
+                }
+                CallFrameInstruction::DW_CFA_register => {
+                    let register_ = register(&mut r)?;
+                    let source = register(&mut r)?;
+                    row.set(register_, Some(RegisterRule::Register(source)));
+                }
+                // The CFA is saved along with registers as GCC and LLVM do.
+                CallFrameInstruction::DW_CFA_remember_state => {
+                    stack.push((row.cfa, row.registers.clone()))
+                }
+                CallFrameInstruction::DW_CFA_restore_state => {
+                    (row.cfa, row.registers) = stack.pop().ok_or(bad)?;
+                }
+                CallFrameInstruction::DW_CFA_def_cfa => {
+                    let register = register(&mut r)?;
+                    row.cfa = CfaRule::RegisterOffset(register, r.uleb()? as i64);
+                }
+                CallFrameInstruction::DW_CFA_def_cfa_sf => {
+                    let register = register(&mut r)?;
+                    row.cfa = CfaRule::RegisterOffset(register, factored(r.sleb()?));
+                }
+                CallFrameInstruction::DW_CFA_def_cfa_register => {
+                    let register = register(&mut r)?;
+                    match &mut row.cfa {
+                        CfaRule::RegisterOffset(x, _) => *x = register,
+                        CfaRule::Expression(_) => return Err(bad),
+                    }
+                }
+                CallFrameInstruction::DW_CFA_def_cfa_offset => {
+                    let offset = r.uleb()? as i64;
+                    match &mut row.cfa {
+                        CfaRule::RegisterOffset(_, x) => *x = offset,
+                        CfaRule::Expression(_) => return Err(bad),
+                    }
+                }
+                CallFrameInstruction::DW_CFA_def_cfa_offset_sf => {
+                    let offset = factored(r.sleb()?);
+                    match &mut row.cfa {
+                        CfaRule::RegisterOffset(_, x) => *x = offset,
+                        CfaRule::Expression(_) => return Err(bad),
+                    }
+                }
+                CallFrameInstruction::DW_CFA_def_cfa_expression => {
+                    let length = r.uleb()? as usize;
+                    row.cfa = CfaRule::Expression(r.bytes(length)?);
+                }
+                CallFrameInstruction::DW_CFA_expression => {
+                    let register = register(&mut r)?;
+                    let length = r.uleb()? as usize;
+                    row.set(register, Some(RegisterRule::Expression(r.bytes(length)?)));
+                }
+                CallFrameInstruction::DW_CFA_val_expression => {
+                    let register = register(&mut r)?;
+                    let length = r.uleb()? as usize;
+                    row.set(
+                        register,
+                        Some(RegisterRule::ValExpression(r.bytes(length)?)),
+                    );
+                }
+                CallFrameInstruction::DW_CFA_offset_extended_sf => {
+                    let register = register(&mut r)?;
+                    let offset = factored(r.sleb()?);
+                    row.set(register, Some(RegisterRule::Offset(offset)));
+                }
+                CallFrameInstruction::DW_CFA_val_offset => {
+                    let register = register(&mut r)?;
+                    let offset = factored(r.uleb()? as i64);
+                    row.set(register, Some(RegisterRule::ValOffset(offset)));
+                }
+                CallFrameInstruction::DW_CFA_val_offset_sf => {
+                    let register = register(&mut r)?;
+                    let offset = factored(r.sleb()?);
+                    row.set(register, Some(RegisterRule::ValOffset(offset)));
+                }
+                CallFrameInstruction::DW_CFA_GNU_args_size => row.args_size = r.uleb()?,
+                // Return address signing of AArch64 does not change where registers are saved.
+                CallFrameInstruction::DW_CFA_GNU_window_save => (),
+                CallFrameInstruction::DW_CFA_GNU_negative_offset_extended => {
+                    let register = register(&mut r)?;
+                    let offset = factored(r.uleb()? as i64);
+                    row.set(register, Some(RegisterRule::Offset(offset.wrapping_neg())));
+                }
+                _ => return Err(bad),
+            },
+        }
+
+        if let Some(to) = advance_to {
+            // CIE can not advance, and rows go forward only.
+            if initial.is_none() || to < row.start {
+                return Err(bad);
+            }
+            if row.start < to {
+                row.end = to;
+                rows.push(row.clone());
+                row.start = to;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Header of .eh_frame with the table to binary search FDEs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EhFrameHdr {
+    pub version: u8,
+    /// Address of .eh_frame
+    pub eh_frame_ptr: u64,
+    /// Initial location and address of FDEs, sorted by initial location.
+    pub table: Vec<(u64, u64)>,
+}
+
+impl EhFrameHdr {
+    /// Parse .eh_frame_hdr of `data` at `address`.
+    pub fn parse(data: &[u8], address: u64) -> Result<Self, DwarfError> {
+        let mut r = Reader::new(data, 0);
+        let version = r.u8()?;
+        if version != 1 {
+            return Err(DwarfError::UnsupportedVersion(0, version as u16));
+        }
+        let eh_frame_ptr_encoding = PointerEncoding::new(r.u8()?);
+        let fde_count_encoding = PointerEncoding::new(r.u8()?);
+        let table_encoding = PointerEncoding::new(r.u8()?);
+
+        // FIXME: support 32bit
+        let read = |r: &mut Reader, encoding| read_pointer(r, encoding, 8, address, Some(address));
+        let eh_frame_ptr = read(&mut r, eh_frame_ptr_encoding)?.unwrap_or(0);
+        let count = read(&mut r, fde_count_encoding)?.unwrap_or(0);
+
+        let mut table = vec![];
+        if !table_encoding.is_DW_EH_PE_omit() {
+            for _ in 0..count {
+                let location = read(&mut r, table_encoding)?.unwrap_or(0);
+                let fde = read(&mut r, table_encoding)?.unwrap_or(0);
+                table.push((location, fde));
+            }
+        }
+        Ok(Self {
+            version,
+            eh_frame_ptr,
+            table,
+        })
+    }
+
+    /// .eh_frame_hdr of ELF file. None if it is missing.
+    pub fn from_elf(elf: &Elf) -> Option<Result<Self, DwarfError>> {
+        let sh = elf.section_by_name(".eh_frame_hdr")?;
+        let data = elf.section_data(sh)?;
+        Some(Self::parse(data, sh.get_sh_addr_unwrap().inner() as u64))
+    }
+
+    /// Address of the last FDE that starts at or before `address`.
+    /// The FDE may not cover the address.
+    pub fn lookup(&self, address: u64) -> Option<u64> {
+        let i = self.table.partition_point(|x| x.0 <= address);
+        Some(self.table.get(i.checked_sub(1)?)?.1)
+    }
+}
+
+/// Evaluate DWARF expression of call frame information to the value on top of stack.
+/// `register` gives values of registers by DWARF number, and `memory` reads little endian value
+/// of the size at the address. `push` is pushed first, like the CFA for register rules.
+/// None if the expression uses unsupported operations or values that are not available.
+pub fn evaluate(
+    expression: &[u8],
+    address_size: u8,
+    register: impl Fn(u16) -> Option<u64>,
+    memory: impl Fn(u64, u8) -> Option<u64>,
+    push: Option<u64>,
+) -> Option<u64> {
+    let mut r = Reader::new(expression, 0);
+    let mut stack: Vec<u64> = push.into_iter().collect();
+
+    while !r.is_empty() {
+        let op = Operation::new(r.u8().ok()?);
+        let code = op.inner();
+        match op {
+            _ if (Operation::DW_OP_lit0.inner()..=Operation::DW_OP_lit31.inner())
+                .contains(&code) =>
+            {
+                stack.push((code - Operation::DW_OP_lit0.inner()) as u64)
+            }
+            _ if (Operation::DW_OP_breg0.inner()..=Operation::DW_OP_breg31.inner())
+                .contains(&code) =>
+            {
+                let value = register((code - Operation::DW_OP_breg0.inner()) as u16)?;
+                stack.push(value.wrapping_add(r.sleb().ok()? as u64));
+            }
+            Operation::DW_OP_bregx => {
+                let value = register(u16::try_from(r.uleb().ok()?).ok()?)?;
+                stack.push(value.wrapping_add(r.sleb().ok()? as u64));
+            }
+            Operation::DW_OP_addr => stack.push(r.address(address_size).ok()?),
+            Operation::DW_OP_deref => {
+                let address = stack.pop()?;
+                stack.push(memory(address, address_size)?);
+            }
+            Operation::DW_OP_deref_size => {
+                let size = r.u8().ok()?;
+                let address = stack.pop()?;
+                stack.push(memory(address, size)?);
+            }
+            Operation::DW_OP_const1u => stack.push(r.u8().ok()? as u64),
+            Operation::DW_OP_const1s => stack.push(r.u8().ok()? as i8 as u64),
+            Operation::DW_OP_const2u => stack.push(r.u16().ok()? as u64),
+            Operation::DW_OP_const2s => stack.push(r.u16().ok()? as i16 as u64),
+            Operation::DW_OP_const4u => stack.push(r.u32().ok()? as u64),
+            Operation::DW_OP_const4s => stack.push(r.u32().ok()? as i32 as u64),
+            Operation::DW_OP_const8u | Operation::DW_OP_const8s => stack.push(r.u64().ok()?),
+            Operation::DW_OP_constu => stack.push(r.uleb().ok()?),
+            Operation::DW_OP_consts => stack.push(r.sleb().ok()? as u64),
+            Operation::DW_OP_dup => stack.push(*stack.last()?),
+            Operation::DW_OP_drop => {
+                stack.pop()?;
+            }
+            Operation::DW_OP_over => stack.push(*stack.get(stack.len().checked_sub(2)?)?),
+            Operation::DW_OP_pick => {
+                let index = r.u8().ok()? as usize;
+                stack.push(*stack.get(stack.len().checked_sub(index + 1)?)?);
+            }
+            Operation::DW_OP_swap => {
+                let len = stack.len();
+                stack.swap(len.checked_sub(1)?, len.checked_sub(2)?);
+            }
+            Operation::DW_OP_rot => {
+                let len = stack.len();
+                stack[len.checked_sub(3)?..].rotate_right(1);
+            }
+            Operation::DW_OP_abs => {
+                let x = stack.pop()? as i64;
+                stack.push(x.wrapping_abs() as u64);
+            }
+            Operation::DW_OP_neg => {
+                let x = stack.pop()? as i64;
+                stack.push(x.wrapping_neg() as u64);
+            }
+            Operation::DW_OP_not => {
+                let x = stack.pop()?;
+                stack.push(!x);
+            }
+            Operation::DW_OP_plus_uconst => {
+                let x = stack.pop()?;
+                stack.push(x.wrapping_add(r.uleb().ok()?));
+            }
+            Operation::DW_OP_skip => {
+                let offset = r.u16().ok()? as i16;
+                r = Reader::new(expression, r.pos().checked_add_signed(offset as isize)?);
+            }
+            Operation::DW_OP_bra => {
+                let offset = r.u16().ok()? as i16;
+                if stack.pop()? != 0 {
+                    r = Reader::new(expression, r.pos().checked_add_signed(offset as isize)?);
+                }
+            }
+            Operation::DW_OP_nop => (),
+            _ => {
+                let b = stack.pop()?;
+                let a = stack.pop()?;
+                let (sa, sb) = (a as i64, b as i64);
+                stack.push(match op {
+                    Operation::DW_OP_and => a & b,
+                    Operation::DW_OP_or => a | b,
+                    Operation::DW_OP_xor => a ^ b,
+                    Operation::DW_OP_plus => a.wrapping_add(b),
+                    Operation::DW_OP_minus => a.wrapping_sub(b),
+                    Operation::DW_OP_mul => a.wrapping_mul(b),
+                    Operation::DW_OP_div => sa.checked_div(sb)? as u64,
+                    Operation::DW_OP_mod => a.checked_rem(b)?,
+                    Operation::DW_OP_shl => a.checked_shl(b as u32).unwrap_or(0),
+                    Operation::DW_OP_shr => a.checked_shr(b as u32).unwrap_or(0),
+                    Operation::DW_OP_shra => sa.wrapping_shr(b.min(63) as u32) as u64,
+                    Operation::DW_OP_eq => (sa == sb) as u64,
+                    Operation::DW_OP_ne => (sa != sb) as u64,
+                    Operation::DW_OP_ge => (sa >= sb) as u64,
+                    Operation::DW_OP_gt => (sa > sb) as u64,
+                    Operation::DW_OP_le => (sa <= sb) as u64,
+                    Operation::DW_OP_lt => (sa < sb) as u64,
+                    _ => return None,
+                });
+            }
+        }
+    }
+    stack.pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const OUT: &[u8] = include_bytes!("../../../example_bin/out");
+    const DEBUG_FRAME: &[u8] = include_bytes!("../../../example_bin/debug_frame");
+
+    fn fdes<'a>(frame: &CallFrame<'a>) -> Vec<Fde<'a>> {
+        frame
+            .entries()
+            .unwrap()
+            .into_iter()
+            .filter_map(|x| match x {
+                FrameEntry::Fde(x) => Some(x),
+                FrameEntry::Cie(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn eh_frame() {
+        let s = Sequential::from_vec(OUT.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let frame = CallFrame::from_elf(&elf, FrameKind::EhFrame).unwrap();
+
+        let cie = frame.cie_at(0).unwrap();
+        assert_eq!(cie.augmentation, "zR");
+        assert_eq!(
+            (
+                cie.version,
+                cie.code_alignment_factor,
+                cie.data_alignment_factor,
+                cie.return_address_register
+            ),
+            (1, 1, -8, 16)
+        );
+        assert_eq!(cie.fde_encoding.inner(), 0x1b);
+        assert!(cie.fde_encoding.format().is_DW_EH_PE_sdata4());
+        assert!(cie.fde_encoding.application().is_DW_EH_PE_pcrel());
+
+        let fdes = fdes(&frame);
+        let ranges: Vec<_> = fdes
+            .iter()
+            .map(|x| (x.offset, x.initial_location, x.address_range))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0x18, 0x1040, 0x26),
+                (0x30, 0x1020, 0x20),
+                (0x58, 0x1139, 0x1a)
+            ]
+        );
+
+        let main = &fdes[2];
+        let rows: Vec<_> = main
+            .rows()
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.start, x.end, x.cfa, x.registers))
+            .collect();
+        let ra = (16, RegisterRule::Offset(-8));
+        let rbp = (6, RegisterRule::Offset(-16));
+        assert_eq!(
+            rows,
+            vec![
+                (0x1139, 0x113a, CfaRule::RegisterOffset(7, 8), vec![ra]),
+                (
+                    0x113a,
+                    0x113d,
+                    CfaRule::RegisterOffset(7, 16),
+                    vec![rbp, ra]
+                ),
+                (
+                    0x113d,
+                    0x1152,
+                    CfaRule::RegisterOffset(6, 16),
+                    vec![rbp, ra]
+                ),
+                (0x1152, 0x1153, CfaRule::RegisterOffset(7, 8), vec![rbp, ra]),
+            ]
+        );
+
+        let start = fdes[0].unwind_row(0x1050).unwrap().unwrap();
+        assert_eq!(start.register(16), Some(RegisterRule::Undefined));
+        assert_eq!(start.register(6), None);
+
+        // PLT computes the CFA from the position in the entry.
+        let plt = fdes[1].unwind_row(0x1034).unwrap().unwrap();
+        let CfaRule::Expression(expr) = plt.cfa else {
+            panic!("{:?}", plt.cfa);
+        };
+        let registers = |r| match r {
+            7 => Some(0x7ff0),
+            16 => Some(0x103c),
+            _ => None,
+        };
+        assert_eq!(
+            evaluate(expr, 8, registers, |_, _| None, None),
+            Some(0x8000)
+        );
+    }
+
+    #[test]
+    fn eh_frame_hdr() {
+        let s = Sequential::from_vec(OUT.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let frame = CallFrame::from_elf(&elf, FrameKind::EhFrame).unwrap();
+        let hdr = EhFrameHdr::from_elf(&elf).unwrap().unwrap();
+
+        assert_eq!(hdr.eh_frame_ptr, frame.address);
+        assert_eq!(
+            hdr.table,
+            vec![(0x1020, 0x2068), (0x1040, 0x2050), (0x1139, 0x2090)]
+        );
+        assert_eq!(hdr.lookup(0x1000), None);
+        assert_eq!(hdr.lookup(0x1140), Some(0x2090));
+
+        let fde = frame.find_fde(0x1140, Some(&hdr)).unwrap().unwrap();
+        assert_eq!(fde.offset, 0x58);
+        assert_eq!(frame.find_fde(0x1140, None).unwrap(), Some(fde));
+        // Between FDEs
+        assert_eq!(frame.find_fde(0x1100, Some(&hdr)).unwrap(), None);
+        assert_eq!(frame.find_fde(0x1100, None).unwrap(), None);
+    }
+
+    #[test]
+    fn debug_frame() {
+        let s = Sequential::from_vec(DEBUG_FRAME.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let frame = CallFrame::from_elf(&elf, FrameKind::DebugFrame).unwrap();
+
+        let cie = frame.cie_at(0).unwrap();
+        assert_eq!(
+            (cie.augmentation.as_str(), cie.has_augmentation_data),
+            ("", false)
+        );
+        assert!(cie.fde_encoding.is_DW_EH_PE_absptr());
+
+        let fdes = fdes(&frame);
+        let ranges: Vec<_> = fdes
+            .iter()
+            .map(|x| (x.initial_location, x.address_range))
+            .collect();
+        assert_eq!(ranges, vec![(0x1129, 0xf), (0x1138, 0x12), (0x114a, 0x3f)]);
+        let row = fdes[1].unwind_row(0x1140).unwrap().unwrap();
+        assert_eq!((row.start, row.end), (0x113c, 0x1149));
+        assert_eq!(row.cfa, CfaRule::RegisterOffset(6, 16));
+    }
+
+    #[test]
+    fn instructions() {
+        #[rustfmt::skip]
+        let data = [
+            // CIE
+            0x14, 0, 0, 0, 0, 0, 0, 0, 1, b'z', b'R', b'S', 0, 4, 0x78, 16, 1, 0x03,
+            0x0c, 7, 8, // def_cfa rsp+8
+            0x90, 1, // offset r16 cfa-8
+            0,
+            // FDE
+            0x22, 0, 0, 0, 0x1c, 0, 0, 0,
+            0x00, 0x10, 0, 0, 0x20, 0, 0, 0, 0,
+            0x41, // advance_loc 4
+            0x0a, // remember_state
+            0x0e, 32, // def_cfa_offset 32
+            0x83, 2, // offset rbx cfa-16
+            0x10, 6, 2, 0x76, 0x10, // expression rbp: breg6 16
+            0x41, // advance_loc 4
+            0x0b, // restore_state
+            0xc3, // restore rbx
+            0x02, 4, // advance_loc1 16
+            0x2e, 8, // GNU_args_size 8
+            0x07, 16, // undefined r16
+            0,
+        ];
+        let frame = CallFrame {
+            kind: FrameKind::EhFrame,
+            data: &data,
+            address: 0,
+            address_size: 8,
+        };
+        let cie = frame.cie_at(0).unwrap();
+        assert!(cie.signal_frame);
+        assert_eq!(cie.code_alignment_factor, 4);
+
+        let fde = match frame.entry_at(0x18).unwrap().0 {
+            Some(FrameEntry::Fde(x)) => x,
+            x => panic!("{:?}", x),
+        };
+        assert_eq!(fde.range(), 0x1000..0x1020);
+        let rows = fde.rows().unwrap();
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|x| (x.start, x.cfa, x.registers.clone(), x.args_size))
+            .collect();
+        let ra = (16, RegisterRule::Offset(-8));
+        assert_eq!(
+            summary,
+            vec![
+                (0x1000, CfaRule::RegisterOffset(7, 8), vec![ra], 0),
+                (
+                    0x1004,
+                    CfaRule::RegisterOffset(7, 32),
+                    vec![
+                        (3, RegisterRule::Offset(-16)),
+                        (6, RegisterRule::Expression(&[0x76, 0x10])),
+                        ra
+                    ],
+                    0
+                ),
+                (0x1008, CfaRule::RegisterOffset(7, 8), vec![ra], 0),
+                (
+                    0x1018,
+                    CfaRule::RegisterOffset(7, 8),
+                    vec![(16, RegisterRule::Undefined)],
+                    8
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,959 @@
+use super::{
+    At, Dwarf, DwarfError, Encoding, Form, Format, RangeListEntry, Reader, Tag, UnitType, Value,
+};
+use core::ops::Range;
+
+/// Attribute of an abbreviation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeSpec {
+    pub name: At,
+    pub form: Form,
+    /// Value of DW_FORM_implicit_const, which is stored in the abbreviation.
+    pub implicit_const: Option<i64>,
+}
+
+/// Entry of .debug_abbrev that describes the layout of DIEs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Abbreviation {
+    pub code: u64,
+    pub tag: Tag,
+    pub has_children: bool,
+    pub attributes: Vec<AttributeSpec>,
+}
+
+impl Abbreviation {
+    /// Abbreviation table at `offset` of .debug_abbrev, sorted by code.
+    pub fn parse_table(data: &[u8], offset: usize) -> Result<Vec<Abbreviation>, DwarfError> {
+        let mut r = Reader::new(data, offset);
+        let mut table = vec![];
+        loop {
+            let code = r.uleb()?;
+            if code == 0 {
+                break;
+            }
+            let tag = Tag::new(r.uleb_u16()?);
+            let has_children = r.u8()? != 0;
+            let mut attributes = vec![];
+            loop {
+                let name = r.uleb_u16()?;
+                let form = Form::new(r.uleb_u16()?);
+                if name == 0 && form.inner() == 0 {
+                    break;
+                }
+                let implicit_const = if form.is_DW_FORM_implicit_const() {
+                    Some(r.sleb()?)
+                } else {
+                    None
+                };
+                attributes.push(AttributeSpec {
+                    name: At::new(name),
+                    form,
+                    implicit_const,
+                });
+            }
+            table.push(Abbreviation {
+                code,
+                tag,
+                has_children,
+                attributes,
+            });
+        }
+        // Codes are usually sequential, but producers are free to number them.
+        table.sort_by_key(|x| x.code);
+        Ok(table)
+    }
+}
+
+/// Header of a unit in .debug_info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitHeader {
+    /// Offset of the unit in .debug_info
+    pub offset: usize,
+    /// Offset of the first DIE in .debug_info
+    pub entries_offset: usize,
+    /// End of the unit in .debug_info
+    pub end: usize,
+    pub format: Format,
+    pub version: u16,
+    /// DW_UT_compile for units before DWARF 5
+    pub unit_type: UnitType,
+    pub address_size: u8,
+    pub abbrev_offset: u64,
+    /// Signature of type units
+    pub type_signature: Option<u64>,
+    /// Offset of the type DIE from the unit of type units
+    pub type_offset: Option<u64>,
+    /// ID of skeleton and split units
+    pub dwo_id: Option<u64>,
+}
+
+impl UnitHeader {
+    pub fn encoding(&self) -> Encoding {
+        Encoding {
+            format: self.format,
+            version: self.version,
+            address_size: self.address_size,
+        }
+    }
+}
+
+/// Attribute of a DIE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attribute<'a> {
+    pub name: At,
+    pub form: Form,
+    pub value: Value<'a>,
+}
+
+/// Debugging information entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Die<'a> {
+    /// Offset in .debug_info
+    pub offset: usize,
+    /// 0 for the unit DIE
+    pub depth: usize,
+    /// Index of the parent DIE in the unit
+    pub parent: Option<usize>,
+    pub tag: Tag,
+    pub has_children: bool,
+    pub attributes: Vec<Attribute<'a>>,
+}
+
+impl<'a> Die<'a> {
+    /// Value of the first attribute named `name`.
+    pub fn attr(&self, name: At) -> Option<&Value<'a>> {
+        self.attributes
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| &x.value)
+    }
+
+    /// Value of boolean attribute like DW_AT_external. False if it is missing.
+    pub fn flag(&self, name: At) -> bool {
+        match self.attr(name) {
+            Some(Value::Flag(x)) => *x,
+            Some(x) => x.udata().is_some_and(|x| x != 0),
+            None => false,
+        }
+    }
+}
+
+/// Unit with its DIE tree. DIEs are in the order of .debug_info, and children follow their parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unit<'a> {
+    pub header: UnitHeader,
+    pub dies: Vec<Die<'a>>,
+    /// DW_AT_str_offsets_base of the unit DIE
+    pub str_offsets_base: Option<u64>,
+    /// DW_AT_addr_base of the unit DIE
+    pub addr_base: Option<u64>,
+    /// DW_AT_rnglists_base of the unit DIE
+    pub rnglists_base: Option<u64>,
+    /// Base of range lists, DW_AT_low_pc of the unit DIE.
+    pub base_address: u64,
+}
+
+impl<'a> Unit<'a> {
+    /// Parse the unit at `offset` of .debug_info. Also returns the offset of the next unit.
+    pub fn parse(dwarf: &Dwarf<'a>, offset: usize) -> Result<(Self, usize), DwarfError> {
+        let mut r = Reader::new(dwarf.debug_info, offset);
+        let (format, mut unit) = r.unit()?;
+        let next = r.pos();
+
+        let version = unit.u16()?;
+        if !(2..=5).contains(&version) {
+            return Err(DwarfError::UnsupportedVersion(offset, version));
+        }
+        let (mut unit_type, mut type_signature, mut type_offset, mut dwo_id) =
+            (UnitType::DW_UT_compile, None, None, None);
+        let (address_size, abbrev_offset) = if version >= 5 {
+            unit_type = UnitType::new(unit.u8()?);
+            let address_size = unit.u8()?;
+            let abbrev_offset = unit.offset(format)?;
+            match unit_type {
+                UnitType::DW_UT_skeleton | UnitType::DW_UT_split_compile => {
+                    dwo_id = Some(unit.u64()?)
+                }
+                UnitType::DW_UT_type | UnitType::DW_UT_split_type => {
+                    type_signature = Some(unit.u64()?);
+                    type_offset = Some(unit.offset(format)?);
+                }
+                _ => (),
+            }
+            (address_size, abbrev_offset)
+        } else {
+            let abbrev_offset = unit.offset(format)?;
+            (unit.u8()?, abbrev_offset)
+        };
+
+        let header = UnitHeader {
+            offset,
+            entries_offset: unit.pos(),
+            end: next,
+            format,
+            version,
+            unit_type,
+            address_size,
+            abbrev_offset,
+            type_signature,
+            type_offset,
+            dwo_id,
+        };
+        let encoding = header.encoding();
+        let abbreviations = Abbreviation::parse_table(dwarf.debug_abbrev, abbrev_offset as usize)?;
+
+        let mut dies = vec![];
+        let mut parents: Vec<usize> = vec![];
+        while !unit.is_empty() {
+            let die_offset = unit.pos();
+            let code = unit.uleb()?;
+            if code == 0 {
+                // Null entry ends siblings. Padding after the tree is also null entries.
+                parents.pop();
+                continue;
+            }
+            let abbreviation = abbreviations
+                .binary_search_by_key(&code, |x| x.code)
+                .map(|i| &abbreviations[i])
+                .map_err(|_| DwarfError::BadAbbreviation(die_offset, code))?;
+            let attributes = abbreviation
+                .attributes
+                .iter()
+                .map(|spec| {
+                    Ok(Attribute {
+                        name: spec.name,
+                        form: spec.form,
+                        value: unit.value(spec.form, &encoding, spec.implicit_const)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, DwarfError>>()?;
+
+            dies.push(Die {
+                offset: die_offset,
+                depth: parents.len(),
+                parent: parents.last().copied(),
+                tag: abbreviation.tag,
+                has_children: abbreviation.has_children,
+                attributes,
+            });
+            if abbreviation.has_children {
+                parents.push(dies.len() - 1);
+            }
+        }
+
+        let mut ret = Self {
+            header,
+            dies,
+            str_offsets_base: None,
+            addr_base: None,
+            rnglists_base: None,
+            base_address: 0,
+        };
+        if let Some(root) = ret.dies.first() {
+            let section_offset = |name| match root.attr(name) {
+                Some(Value::SecOffset(x)) => Some(*x),
+                Some(x) => x.udata(),
+                None => None,
+            };
+            ret.str_offsets_base = section_offset(At::DW_AT_str_offsets_base);
+            ret.addr_base =
+                section_offset(At::DW_AT_addr_base).or(section_offset(At::DW_AT_GNU_addr_base));
+            ret.rnglists_base = section_offset(At::DW_AT_rnglists_base);
+            // Base address may be an index, which needs addr_base.
+            if let Some(low_pc) = root.attr(At::DW_AT_low_pc).copied() {
+                ret.base_address = dwarf.address_of(&ret, &low_pc)?;
+            }
+        }
+        Ok((ret, next))
+    }
+
+    /// The unit DIE, like DW_TAG_compile_unit.
+    pub fn root(&self) -> Option<&Die<'a>> {
+        self.dies.first()
+    }
+
+    /// Indices of direct children of the DIE at `index`.
+    pub fn children(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.dies
+            .iter()
+            .enumerate()
+            .skip(index + 1)
+            .take_while(move |(_, x)| x.depth > self.dies[index].depth)
+            .filter(move |(_, x)| x.parent == Some(index))
+            .map(|(i, _)| i)
+    }
+
+    /// Index of the DIE at `offset` of .debug_info.
+    pub fn die_at(&self, offset: usize) -> Option<usize> {
+        self.dies.binary_search_by_key(&offset, |x| x.offset).ok()
+    }
+
+    /// Index of the DIE that reference `value` points.
+    /// References to other units are not resolved.
+    pub fn reference(&self, value: &Value) -> Option<usize> {
+        let offset = match *value {
+            Value::Ref(x) => (self.header.offset as u64).checked_add(x)?,
+            Value::RefAddr(x) => x,
+            _ => return None,
+        };
+        self.die_at(usize::try_from(offset).ok()?)
+    }
+
+    /// Name of the DIE at `index`. Instances of inlined or out of line defined functions
+    /// take the name of their abstract origin or specification.
+    pub fn name(&self, dwarf: &Dwarf, index: usize) -> Result<Option<String>, DwarfError> {
+        let mut index = index;
+        // Chains are short. The limit only guards against cycles of broken input.
+        for _ in 0..8 {
+            let die = &self.dies[index];
+            if let Some(name) = die.attr(At::DW_AT_name) {
+                return dwarf.string_of(self, name).map(Some);
+            }
+            match die
+                .attr(At::DW_AT_abstract_origin)
+                .or(die.attr(At::DW_AT_specification))
+                .and_then(|x| self.reference(x))
+            {
+                Some(x) => index = x,
+                None => break,
+            }
+        }
+        Ok(None)
+    }
+
+    /// C like name of the type that DW_AT_type of the DIE at `index` refers, like "struct point *".
+    /// "void" if the DIE has no type.
+    pub fn type_name(&self, dwarf: &Dwarf, index: usize) -> Result<String, DwarfError> {
+        let mut prefix = String::new();
+        let mut suffix = String::new();
+        let mut index = index;
+        for _ in 0..16 {
+            let Some(typ) = self.dies[index]
+                .attr(At::DW_AT_type)
+                .and_then(|x| self.reference(x))
+            else {
+                return Ok(format!("{}void{}", prefix, suffix));
+            };
+            let die = &self.dies[typ];
+            let name = || -> Result<String, DwarfError> {
+                Ok(self
+                    .name(dwarf, typ)?
+                    .unwrap_or_else(|| "<anonymous>".into()))
+            };
+            let keyword = match die.tag {
+                Tag::DW_TAG_structure_type => Some("struct "),
+                Tag::DW_TAG_union_type => Some("union "),
+                Tag::DW_TAG_enumeration_type => Some("enum "),
+                Tag::DW_TAG_class_type => Some("class "),
+                Tag::DW_TAG_base_type | Tag::DW_TAG_typedef | Tag::DW_TAG_unspecified_type => {
+                    Some("")
+                }
+                Tag::DW_TAG_pointer_type => {
+                    suffix.insert_str(0, " *");
+                    None
+                }
+                Tag::DW_TAG_reference_type => {
+                    suffix.insert_str(0, " &");
+                    None
+                }
+                Tag::DW_TAG_array_type => {
+                    suffix.push_str("[]");
+                    None
+                }
+                Tag::DW_TAG_const_type => {
+                    prefix.push_str("const ");
+                    None
+                }
+                Tag::DW_TAG_volatile_type => {
+                    prefix.push_str("volatile ");
+                    None
+                }
+                Tag::DW_TAG_subroutine_type => {
+                    return Ok(format!("{}fn(){}", prefix, suffix));
+                }
+                _ => Some(""),
+            };
+            if let Some(keyword) = keyword {
+                return Ok(format!("{}{}{}{}", prefix, keyword, name()?, suffix));
+            }
+            index = typ;
+        }
+        Ok(format!("{}...{}", prefix, suffix))
+    }
+}
+
+/// Function, or inlined instance of function, found in DIE tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    /// Offset of the DIE in .debug_info
+    pub offset: usize,
+    pub name: String,
+    /// Address ranges of code. Empty if the function has no code, like ones only inlined.
+    pub ranges: Vec<Range<u64>>,
+    /// True for DW_TAG_inlined_subroutine
+    pub inlined: bool,
+    pub external: bool,
+}
+
+/// Variable or formal parameter found in DIE tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    /// Offset of the DIE in .debug_info
+    pub offset: usize,
+    pub name: String,
+    pub type_name: String,
+    pub parameter: bool,
+    pub external: bool,
+    /// Static address if the location is a single DW_OP_addr.
+    pub address: Option<u64>,
+    /// Name of the enclosing function. None for global variables.
+    pub scope: Option<String>,
+}
+
+/// Named type found in DIE tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeEntry {
+    /// Offset of the DIE in .debug_info
+    pub offset: usize,
+    pub tag: Tag,
+    pub name: String,
+    pub byte_size: Option<u64>,
+}
+
+const DW_OP_ADDR: u8 = 0x03;
+
+impl<'a> Dwarf<'a> {
+    /// All units in .debug_info.
+    pub fn units(&self) -> Result<Vec<Unit<'a>>, DwarfError> {
+        let mut ret = vec![];
+        let mut offset = 0;
+        while offset < self.debug_info.len() {
+            let (unit, next) = Unit::parse(self, offset)?;
+            ret.push(unit);
+            offset = next;
+        }
+        Ok(ret)
+    }
+
+    /// Resolve string value including indices into .debug_str_offsets.
+    pub fn string_of(&self, unit: &Unit, value: &Value) -> Result<String, DwarfError> {
+        match *value {
+            Value::Strx(index) => {
+                let size = unit.header.format.offset_size() as u64;
+                // The base skips the header of the contribution if the attribute is missing.
+                let base = unit.str_offsets_base.unwrap_or(2 * size);
+                let at = index
+                    .checked_mul(size)
+                    .and_then(|x| x.checked_add(base))
+                    .and_then(|x| usize::try_from(x).ok())
+                    .ok_or(DwarfError::BadStringOffset(index))?;
+                let offset = Reader::new(self.debug_str_offsets, at)
+                    .uint(size as usize)
+                    .map_err(|_| DwarfError::BadStringOffset(index))?;
+                self.str_at(offset)
+            }
+            _ => self.string(value),
+        }
+    }
+
+    /// Resolve address value including indices into .debug_addr.
+    pub fn address_of(&self, unit: &Unit, value: &Value) -> Result<u64, DwarfError> {
+        match *value {
+            Value::Address(x) => Ok(x),
+            Value::Addrx(index) => self.indexed_address(unit, index),
+            _ => Err(DwarfError::UnsupportedForm(Form::DW_FORM_addr)),
+        }
+    }
+
+    fn indexed_address(&self, unit: &Unit, index: u64) -> Result<u64, DwarfError> {
+        let size = unit.header.address_size as u64;
+        let base = unit.addr_base.unwrap_or(8);
+        let at = index
+            .checked_mul(size)
+            .and_then(|x| x.checked_add(base))
+            .and_then(|x| usize::try_from(x).ok())
+            .ok_or(DwarfError::Truncated(self.debug_addr.len()))?;
+        Reader::new(self.debug_addr, at).address(unit.header.address_size)
+    }
+
+    /// Address ranges of the DIE at `index` from DW_AT_low_pc and DW_AT_high_pc, or DW_AT_ranges.
+    /// Empty ranges are dropped.
+    pub fn ranges(&self, unit: &Unit, index: usize) -> Result<Vec<Range<u64>>, DwarfError> {
+        let die = &unit.dies[index];
+        let mut ret = vec![];
+        if let Some(ranges) = die.attr(At::DW_AT_ranges) {
+            if unit.header.version >= 5 {
+                self.rnglist(unit, ranges, &mut ret)?;
+            } else {
+                let offset = ranges
+                    .udata()
+                    .or(match ranges {
+                        Value::SecOffset(x) => Some(*x),
+                        _ => None,
+                    })
+                    .ok_or(DwarfError::UnexpectedValue(At::DW_AT_ranges))?;
+                self.range_list(unit, offset, &mut ret)?;
+            }
+        } else if let Some(low) = die.attr(At::DW_AT_low_pc) {
+            let low = self.address_of(unit, low)?;
+            let high = match die.attr(At::DW_AT_high_pc) {
+                // Constant class is the offset from low_pc since DWARF 4.
+                Some(x) if x.udata().is_some() => low.wrapping_add(x.udata().unwrap()),
+                Some(x) => self.address_of(unit, x)?,
+                None => low.wrapping_add(1),
+            };
+            ret.push(low..high);
+        }
+        ret.retain(|x| x.start < x.end);
+        Ok(ret)
+    }
+
+    /// Range list in .debug_ranges at `offset`.
+    fn range_list(
+        &self,
+        unit: &Unit,
+        offset: u64,
+        ret: &mut Vec<Range<u64>>,
+    ) -> Result<(), DwarfError> {
+        let size = unit.header.address_size;
+        // Base address selection entry starts with the largest address.
+        let largest = match size {
+            8.. => u64::MAX,
+            x => (1 << (8 * x as u32)) - 1,
+        };
+        let mut r = Reader::new(self.debug_ranges, offset as usize);
+        let mut base = unit.base_address;
+        loop {
+            let (start, end) = (r.address(size)?, r.address(size)?);
+            match (start, end) {
+                (0, 0) => return Ok(()),
+                (x, y) if x == largest => base = y,
+                (x, y) => ret.push(base.wrapping_add(x)..base.wrapping_add(y)),
+            }
+        }
+    }
+
+    /// Range list in .debug_rnglists by offset or index.
+    fn rnglist(
+        &self,
+        unit: &Unit,
+        value: &Value,
+        ret: &mut Vec<Range<u64>>,
+    ) -> Result<(), DwarfError> {
+        let offset = match *value {
+            Value::SecOffset(x) => x,
+            Value::Rnglistx(index) => {
+                let size = unit.header.format.offset_size() as u64;
+                // Offsets follow the header: length, version, address size, segment size and count.
+                let base = unit.rnglists_base.unwrap_or(size + 8);
+                let at = index
+                    .checked_mul(size)
+                    .and_then(|x| x.checked_add(base))
+                    .ok_or(DwarfError::Truncated(self.debug_rnglists.len()))?;
+                let mut r = Reader::new(self.debug_rnglists, at as usize);
+                base.wrapping_add(r.uint(size as usize)?)
+            }
+            _ => return Err(DwarfError::UnexpectedValue(At::DW_AT_ranges)),
+        };
+
+        let size = unit.header.address_size;
+        let mut r = Reader::new(self.debug_rnglists, offset as usize);
+        let mut base = unit.base_address;
+        loop {
+            match RangeListEntry::new(r.u8()?) {
+                RangeListEntry::DW_RLE_end_of_list => return Ok(()),
+                RangeListEntry::DW_RLE_base_addressx => {
+                    base = self.indexed_address(unit, r.uleb()?)?
+                }
+                RangeListEntry::DW_RLE_startx_endx => {
+                    let start = self.indexed_address(unit, r.uleb()?)?;
+                    ret.push(start..self.indexed_address(unit, r.uleb()?)?);
+                }
+                RangeListEntry::DW_RLE_startx_length => {
+                    let start = self.indexed_address(unit, r.uleb()?)?;
+                    ret.push(start..start.wrapping_add(r.uleb()?));
+                }
+                RangeListEntry::DW_RLE_offset_pair => {
+                    let start = base.wrapping_add(r.uleb()?);
+                    ret.push(start..base.wrapping_add(r.uleb()?));
+                }
+                RangeListEntry::DW_RLE_base_address => base = r.address(size)?,
+                RangeListEntry::DW_RLE_start_end => {
+                    let start = r.address(size)?;
+                    ret.push(start..r.address(size)?);
+                }
+                RangeListEntry::DW_RLE_start_length => {
+                    let start = r.address(size)?;
+                    ret.push(start..start.wrapping_add(r.uleb()?));
+                }
+                _ => return Err(DwarfError::UnexpectedValue(At::DW_AT_ranges)),
+            }
+        }
+    }
+
+    /// Functions of all units. Declarations without definition are skipped.
+    pub fn functions(&self) -> Result<Vec<Function>, DwarfError> {
+        let mut ret = vec![];
+        for unit in self.units()? {
+            for (i, die) in unit.dies.iter().enumerate() {
+                let inlined = match die.tag {
+                    Tag::DW_TAG_subprogram => false,
+                    Tag::DW_TAG_inlined_subroutine => true,
+                    _ => continue,
+                };
+                if die.flag(At::DW_AT_declaration) {
+                    continue;
+                }
+                ret.push(Function {
+                    offset: die.offset,
+                    name: unit.name(self, i)?.unwrap_or_default(),
+                    ranges: self.ranges(&unit, i)?,
+                    inlined,
+                    external: die.flag(At::DW_AT_external),
+                });
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Variables and formal parameters of all units.
+    pub fn variables(&self) -> Result<Vec<Variable>, DwarfError> {
+        let mut ret = vec![];
+        for unit in self.units()? {
+            for (i, die) in unit.dies.iter().enumerate() {
+                let parameter = match die.tag {
+                    Tag::DW_TAG_variable => false,
+                    Tag::DW_TAG_formal_parameter => true,
+                    _ => continue,
+                };
+                let address = match die.attr(At::DW_AT_location) {
+                    Some(Value::Exprloc(expr)) | Some(Value::Block(expr))
+                        if expr.len() == 1 + unit.header.address_size as usize
+                            && expr[0] == DW_OP_ADDR =>
+                    {
+                        Some(Reader::new(expr, 1).address(unit.header.address_size)?)
+                    }
+                    _ => None,
+                };
+                let mut scope = die.parent;
+                while let Some(x) = scope.filter(|x| {
+                    !matches!(
+                        unit.dies[*x].tag,
+                        Tag::DW_TAG_subprogram | Tag::DW_TAG_inlined_subroutine
+                    )
+                }) {
+                    scope = unit.dies[x].parent;
+                }
+                ret.push(Variable {
+                    offset: die.offset,
+                    name: unit.name(self, i)?.unwrap_or_default(),
+                    type_name: unit.type_name(self, i)?,
+                    parameter,
+                    external: die.flag(At::DW_AT_external),
+                    address,
+                    scope: match scope {
+                        Some(x) => unit.name(self, x)?,
+                        None => None,
+                    },
+                });
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Named types of all units.
+    pub fn types(&self) -> Result<Vec<TypeEntry>, DwarfError> {
+        let mut ret = vec![];
+        for unit in self.units()? {
+            for (i, die) in unit.dies.iter().enumerate() {
+                if !matches!(
+                    die.tag,
+                    Tag::DW_TAG_base_type
+                        | Tag::DW_TAG_structure_type
+                        | Tag::DW_TAG_union_type
+                        | Tag::DW_TAG_enumeration_type
+                        | Tag::DW_TAG_class_type
+                        | Tag::DW_TAG_typedef
+                ) {
+                    continue;
+                }
+                let Some(name) = unit.name(self, i)? else {
+                    continue;
+                };
+                ret.push(TypeEntry {
+                    offset: die.offset,
+                    tag: die.tag,
+                    name,
+                    byte_size: die.attr(At::DW_AT_byte_size).and_then(|x| x.udata()),
+                });
+            }
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::elf::Elf;
+
+    const DWARF5: &[u8] = include_bytes!("../../../example_bin/dwarf5");
+    const DWARF5_O2: &[u8] = include_bytes!("../../../example_bin/dwarf5_o2");
+    const DWARF4: &[u8] = include_bytes!("../../../example_bin/dwarf4");
+
+    fn pairs(ranges: &[Range<u64>]) -> Vec<(u64, u64)> {
+        ranges.iter().map(|x| (x.start, x.end)).collect()
+    }
+
+    fn functions(dwarf: &Dwarf) -> Vec<(String, Vec<(u64, u64)>)> {
+        dwarf
+            .functions()
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.name, pairs(&x.ranges)))
+            .collect()
+    }
+
+    #[test]
+    fn dwarf5() {
+        let s = Sequential::from_vec(DWARF5.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let dwarf = Dwarf::from_elf(&elf);
+
+        let units = dwarf.units().unwrap();
+        assert_eq!(units.len(), 1);
+        let unit = &units[0];
+        assert_eq!(
+            (
+                unit.header.version,
+                unit.header.unit_type,
+                unit.header.entries_offset
+            ),
+            (5, UnitType::DW_UT_compile, 0xc)
+        );
+        let root = unit.root().unwrap();
+        assert_eq!(root.tag, Tag::DW_TAG_compile_unit);
+        assert_eq!(unit.name(&dwarf, 0).unwrap().as_deref(), Some("dwarf.c"));
+        assert_eq!(
+            pairs(&dwarf.ranges(unit, 0).unwrap()),
+            vec![(0x1129, 0x1189)]
+        );
+        assert_eq!(unit.base_address, 0x1129);
+
+        let point = unit.die_at(0x2e).unwrap();
+        assert_eq!(unit.dies[point].tag, Tag::DW_TAG_structure_type);
+        let members: Vec<_> = unit
+            .children(point)
+            .map(|i| unit.name(&dwarf, i).unwrap().unwrap())
+            .collect();
+        assert_eq!(members, vec!["x", "y"]);
+        assert_eq!(unit.children(0).count(), 6);
+
+        assert_eq!(
+            functions(&dwarf),
+            vec![
+                ("main".into(), vec![(0x114a, 0x1189)]),
+                ("add".into(), vec![(0x1138, 0x114a)]),
+                ("square".into(), vec![(0x1129, 0x1138)]),
+            ]
+        );
+
+        let variables: Vec<_> = dwarf
+            .variables()
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.name, x.type_name, x.parameter, x.address, x.scope))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                ("counter".into(), "int".into(), false, Some(0x4010), None),
+                (
+                    "p".into(),
+                    "struct point".into(),
+                    false,
+                    None,
+                    Some("main".into())
+                ),
+                (
+                    "p".into(),
+                    "struct point".into(),
+                    true,
+                    None,
+                    Some("add".into())
+                ),
+                ("x".into(), "int".into(), true, None, Some("square".into())),
+            ]
+        );
+
+        let types: Vec<_> = dwarf
+            .types()
+            .unwrap()
+            .into_iter()
+            .map(|x| (x.tag, x.name, x.byte_size))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                (Tag::DW_TAG_structure_type, "point".into(), Some(8)),
+                (Tag::DW_TAG_base_type, "int".into(), Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn dwarf4() {
+        let s = Sequential::from_vec(DWARF4.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let dwarf = Dwarf::from_elf(&elf);
+
+        let units = dwarf.units().unwrap();
+        assert_eq!(
+            (units[0].header.version, units[0].header.entries_offset),
+            (4, 0xb)
+        );
+        assert_eq!(
+            functions(&dwarf),
+            vec![
+                ("main".into(), vec![(0x114a, 0x1189)]),
+                ("add".into(), vec![(0x1138, 0x114a)]),
+                ("square".into(), vec![(0x1129, 0x1138)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn range_list() {
+        let s = Sequential::from_vec(DWARF5_O2.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let dwarf = Dwarf::from_elf(&elf);
+
+        let units = dwarf.units().unwrap();
+        assert_eq!(
+            pairs(&dwarf.ranges(&units[0], 0).unwrap()),
+            vec![(0x1040, 0x1050)]
+        );
+        // Functions inlined everywhere have no code.
+        assert_eq!(
+            functions(&dwarf),
+            vec![
+                ("main".into(), vec![(0x1040, 0x1050)]),
+                ("add".into(), vec![]),
+                ("square".into(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn indexed_forms() {
+        #[rustfmt::skip]
+        let abbrev = [
+            // compile_unit with children
+            1, 0x11, 1,
+            0x03, 0x25, // name: strx1
+            0x72, 0x17, // str_offsets_base: sec_offset
+            0x73, 0x17, // addr_base: sec_offset
+            0x74, 0x17, // rnglists_base: sec_offset
+            0x11, 0x29, // low_pc: addrx1
+            0x12, 0x06, // high_pc: data4
+            0, 0,
+            // subprogram
+            2, 0x2e, 0,
+            0x03, 0x1a, // name: strx
+            0x11, 0x1b, // low_pc: addrx
+            0x12, 0x0b, // high_pc: data1
+            0, 0,
+            // subprogram
+            3, 0x2e, 0,
+            0x03, 0x08, // name: string
+            0x55, 0x23, // ranges: rnglistx
+            0, 0,
+            0,
+        ];
+        let str = b"\0cu.c\0f\0";
+        #[rustfmt::skip]
+        let str_offsets = [
+            12, 0, 0, 0, 5, 0, 0, 0,
+            1, 0, 0, 0,
+            6, 0, 0, 0,
+        ];
+        #[rustfmt::skip]
+        let addr = [
+            20, 0, 0, 0, 5, 0, 8, 0,
+            0x00, 0x10, 0, 0, 0, 0, 0, 0,
+            0x10, 0x10, 0, 0, 0, 0, 0, 0,
+        ];
+        #[rustfmt::skip]
+        let rnglists = [
+            19, 0, 0, 0, 5, 0, 8, 0, 1, 0, 0, 0,
+            4, 0, 0, 0,
+            0x03, 0, 4, // startx_length
+            0x04, 0x30, 0x38, // offset_pair
+            0x00,
+        ];
+        #[rustfmt::skip]
+        let info = [
+            0x24, 0, 0, 0, 5, 0, 1, 8, 0, 0, 0, 0,
+            1, 0, 8, 0, 0, 0, 8, 0, 0, 0, 12, 0, 0, 0, 0, 0x20, 0, 0, 0,
+            2, 1, 1, 8,
+            3, b'g', 0, 0,
+            0,
+        ];
+        let dwarf = Dwarf {
+            address_size: 8,
+            debug_info: &info,
+            debug_abbrev: &abbrev,
+            debug_str: str,
+            debug_str_offsets: &str_offsets,
+            debug_addr: &addr,
+            debug_rnglists: &rnglists,
+            ..Default::default()
+        };
+
+        let units = dwarf.units().unwrap();
+        assert_eq!(units.len(), 1);
+        let unit = &units[0];
+        assert_eq!(
+            (unit.str_offsets_base, unit.addr_base, unit.rnglists_base),
+            (Some(8), Some(8), Some(12))
+        );
+        assert_eq!(unit.name(&dwarf, 0).unwrap().as_deref(), Some("cu.c"));
+        assert_eq!(unit.base_address, 0x1000);
+        assert_eq!(
+            pairs(&dwarf.ranges(unit, 0).unwrap()),
+            vec![(0x1000, 0x1020)]
+        );
+        assert_eq!(
+            functions(&dwarf),
+            vec![
+                ("f".into(), vec![(0x1010, 0x1018)]),
+                ("g".into(), vec![(0x1000, 0x1004), (0x1030, 0x1038)]),
+            ]
+        );
+
+        let bad = [&info[..info.len() - 9], &[9, 0, 0, 0, 0, 0, 0, 0, 0]].concat();
+        let dwarf = Dwarf {
+            debug_info: &bad,
+            ..dwarf
+        };
+        assert_eq!(dwarf.units(), Err(DwarfError::BadAbbreviation(0x1f, 9)));
+    }
+
+    #[test]
+    fn abbreviation_overflow() {
+        // Code 1, DW_TAG_subprogram, no children, DW_AT_name with DW_FORM_string.
+        let abbrev = [1, 0x2e, 0, 0x03, 0x08, 0, 0, 0];
+        let table = Abbreviation::parse_table(&abbrev, 0).unwrap();
+        assert_eq!(table[0].tag, Tag::DW_TAG_subprogram);
+        assert_eq!(table[0].attributes[0].name, At::DW_AT_name);
+
+        // 0x10000 does not fit in 16 bits and must not wrap to 0.
+        let tag = [1, 0x80, 0x80, 0x04, 0, 0, 0, 0];
+        assert_eq!(Abbreviation::parse_table(&tag, 0), Err(DwarfError::BadLeb128(1)));
+        let name = [1, 0x2e, 0, 0x83, 0x80, 0x04, 0x08, 0, 0, 0];
+        assert_eq!(Abbreviation::parse_table(&name, 0), Err(DwarfError::BadLeb128(3)));
+        let form = [1, 0x2e, 0, 0x03, 0x88, 0x80, 0x04, 0, 0, 0];
+        assert_eq!(Abbreviation::parse_table(&form, 0), Err(DwarfError::BadLeb128(4)));
+    }
+}
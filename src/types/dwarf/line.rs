@@ -0,0 +1,661 @@
+use super::{
+    Dwarf, DwarfError, Encoding, Format, LineContentType, LineExtendedOpcode, LineStandardOpcode,
+    Reader, Value,
+};
+
+/// Position in source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u64,
+    /// 0 if unknown
+    pub column: u64,
+}
+
+impl core::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)?;
+        if self.column != 0 {
+            write!(f, ":{}", self.column)?;
+        }
+        Ok(())
+    }
+}
+
+/// Entry of file name table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    pub path: String,
+    pub directory_index: u64,
+    /// Modification time. 0 if unknown.
+    pub timestamp: u64,
+    /// Size in bytes. 0 if unknown.
+    pub size: u64,
+    pub md5: Option<[u8; 16]>,
+}
+
+/// Header of line number program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineProgramHeader {
+    /// Offset of the program in .debug_line
+    pub offset: usize,
+    pub format: Format,
+    pub version: u16,
+    /// Size of DW_LNE_set_address operand. Before DWARF 5, this is the size of ELF address.
+    pub address_size: u8,
+    pub minimum_instruction_length: u8,
+    /// Operations in a VLIW instruction. 1 for other architectures.
+    pub maximum_operations_per_instruction: u8,
+    pub default_is_stmt: bool,
+    pub line_base: i8,
+    pub line_range: u8,
+    pub opcode_base: u8,
+    /// Number of ULEB128 operands of each standard opcode from 1
+    pub standard_opcode_lengths: Vec<u8>,
+    /// Before DWARF 5, the compilation directory is not listed and index 0 refers it.
+    pub include_directories: Vec<String>,
+    /// Before DWARF 5, index 0 is not listed and the first entry is index 1.
+    pub file_names: Vec<FileEntry>,
+}
+
+impl LineProgramHeader {
+    /// Path of file `index` joined with its directory.
+    /// Before DWARF 5, paths relative to the compilation directory are returned as they are.
+    pub fn file_path(&self, index: u64) -> Option<String> {
+        let (file, dir) = if self.version >= 5 {
+            let file = self.file_names.get(usize::try_from(index).ok()?)?;
+            (
+                file,
+                self.include_directories.get(file.directory_index as usize),
+            )
+        } else {
+            let file = self
+                .file_names
+                .get(usize::try_from(index).ok()?.checked_sub(1)?)?;
+            let dir = (file.directory_index as usize)
+                .checked_sub(1)
+                .and_then(|i| self.include_directories.get(i));
+            (file, dir)
+        };
+
+        match dir {
+            Some(dir) if !file.path.starts_with('/') => Some(format!("{}/{}", dir, file.path)),
+            _ => Some(file.path.clone()),
+        }
+    }
+}
+
+/// Row of line number matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRow {
+    pub address: u64,
+    pub op_index: u64,
+    pub file: u64,
+    pub line: u64,
+    pub column: u64,
+    pub is_stmt: bool,
+    pub basic_block: bool,
+    /// The address is just after the end of a sequence. Other fields are meaningless.
+    pub end_sequence: bool,
+    pub prologue_end: bool,
+    pub epilogue_begin: bool,
+    pub isa: u64,
+    pub discriminator: u64,
+}
+
+impl LineRow {
+    /// Initial state of the state machine.
+    fn new(header: &LineProgramHeader) -> Self {
+        Self {
+            address: 0,
+            op_index: 0,
+            file: 1,
+            line: 1,
+            column: 0,
+            is_stmt: header.default_is_stmt,
+            basic_block: false,
+            end_sequence: false,
+            prologue_end: false,
+            epilogue_begin: false,
+            isa: 0,
+            discriminator: 0,
+        }
+    }
+
+    /// Advance address and op_index by `advance` operations.
+    fn advance(&mut self, header: &LineProgramHeader, advance: u64) {
+        let min = header.minimum_instruction_length as u64;
+        let max_ops = header.maximum_operations_per_instruction.max(1) as u64;
+        let ops = self.op_index.wrapping_add(advance);
+        self.address = self.address.wrapping_add(min.wrapping_mul(ops / max_ops));
+        self.op_index = ops % max_ops;
+    }
+}
+
+/// Line number program with its executed rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineProgram {
+    pub header: LineProgramHeader,
+    pub rows: Vec<LineRow>,
+}
+
+/// Read entries of DWARF 5 directory or file name table by the entry format.
+fn read_entries(
+    r: &mut Reader,
+    dwarf: &Dwarf,
+    encoding: &Encoding,
+) -> Result<Vec<FileEntry>, DwarfError> {
+    let count = r.u8()?;
+    let mut formats = vec![];
+    for _ in 0..count {
+        let content = LineContentType::new(r.uleb_u16()?);
+        let form = super::Form::new(r.uleb_u16()?);
+        formats.push((content, form));
+    }
+
+    let count = r.uleb()?;
+    let mut ret = vec![];
+    for _ in 0..count {
+        let mut entry = FileEntry {
+            path: String::new(),
+            directory_index: 0,
+            timestamp: 0,
+            size: 0,
+            md5: None,
+        };
+        for (content, form) in &formats {
+            let value = r.value(*form, encoding, None)?;
+            match *content {
+                LineContentType::DW_LNCT_path => entry.path = dwarf.string(&value)?,
+                LineContentType::DW_LNCT_directory_index => {
+                    entry.directory_index = value.udata().unwrap_or(0)
+                }
+                LineContentType::DW_LNCT_timestamp => entry.timestamp = value.udata().unwrap_or(0),
+                LineContentType::DW_LNCT_size => entry.size = value.udata().unwrap_or(0),
+                LineContentType::DW_LNCT_MD5 => {
+                    if let Value::Data16(x) = value {
+                        entry.md5 = Some(*x);
+                    }
+                }
+                // Vendor contents are skipped.
+                _ => {}
+            }
+        }
+        ret.push(entry);
+    }
+    Ok(ret)
+}
+
+/// File entry of DWARF 4 or older: path, then directory, time and size in ULEB128.
+fn read_file_entry(r: &mut Reader, path: &[u8]) -> Result<FileEntry, DwarfError> {
+    Ok(FileEntry {
+        path: String::from_utf8_lossy(path).into_owned(),
+        directory_index: r.uleb()?,
+        timestamp: r.uleb()?,
+        size: r.uleb()?,
+        md5: None,
+    })
+}
+
+impl LineProgram {
+    /// Parse the header at `offset` of .debug_line and run the program.
+    /// This returns the offset of the next program.
+    pub fn parse(dwarf: &Dwarf, offset: usize) -> Result<(Self, usize), DwarfError> {
+        let mut r = Reader::new(dwarf.debug_line, offset);
+        let (format, mut unit) = r.unit()?;
+        let next = r.pos();
+
+        let version = unit.u16()?;
+        if !(2..=5).contains(&version) {
+            return Err(DwarfError::UnsupportedVersion(offset, version));
+        }
+        let address_size = if version >= 5 {
+            let size = unit.u8()?;
+            let _segment_selector_size = unit.u8()?;
+            size
+        } else {
+            dwarf.address_size
+        };
+        let header_length = unit.offset(format)? as usize;
+        let program_start = unit
+            .pos()
+            .checked_add(header_length)
+            .ok_or(DwarfError::Truncated(unit.pos()))?;
+
+        let minimum_instruction_length = unit.u8()?;
+        let maximum_operations_per_instruction = if version >= 4 { unit.u8()? } else { 1 };
+        let default_is_stmt = unit.u8()? != 0;
+        let line_base = unit.u8()? as i8;
+        let line_range = unit.u8()?;
+        let opcode_base = unit.u8()?;
+        if line_range == 0 || opcode_base == 0 {
+            return Err(DwarfError::BadLineProgram(offset));
+        }
+        let standard_opcode_lengths = unit.bytes(opcode_base as usize - 1)?.to_vec();
+
+        let encoding = Encoding {
+            format,
+            version,
+            address_size,
+        };
+        let (include_directories, file_names) = if version >= 5 {
+            let dirs = read_entries(&mut unit, dwarf, &encoding)?;
+            let files = read_entries(&mut unit, dwarf, &encoding)?;
+            (dirs.into_iter().map(|x| x.path).collect(), files)
+        } else {
+            let mut dirs = vec![];
+            loop {
+                let dir = unit.cstr()?;
+                if dir.is_empty() {
+                    break;
+                }
+                dirs.push(String::from_utf8_lossy(dir).into_owned());
+            }
+            let mut files = vec![];
+            loop {
+                let path = unit.cstr()?;
+                if path.is_empty() {
+                    break;
+                }
+                files.push(read_file_entry(&mut unit, path)?);
+            }
+            (dirs, files)
+        };
+
+        let mut program = Self {
+            header: LineProgramHeader {
+                offset,
+                format,
+                version,
+                address_size,
+                minimum_instruction_length,
+                maximum_operations_per_instruction,
+                default_is_stmt,
+                line_base,
+                line_range,
+                opcode_base,
+                standard_opcode_lengths,
+                include_directories,
+                file_names,
+            },
+            rows: vec![],
+        };
+
+        let mut r = unit;
+        r.bytes(program_start.saturating_sub(r.pos()))?;
+        let (rows, defined) = Self::run(&program.header, &mut r)?;
+        program.rows = rows;
+        program.header.file_names.extend(defined);
+        Ok((program, next))
+    }
+
+    /// Execute opcodes until the end of unit. This returns rows and files defined by DW_LNE_define_file.
+    fn run(
+        header: &LineProgramHeader,
+        r: &mut Reader,
+    ) -> Result<(Vec<LineRow>, Vec<FileEntry>), DwarfError> {
+        let mut rows = vec![];
+        let mut defined = vec![];
+        let mut state = LineRow::new(header);
+
+        while !r.is_empty() {
+            let opcode = r.u8()?;
+
+            if opcode >= header.opcode_base {
+                // Special opcode advances both address and line, then appends a row.
+                let adjusted = opcode - header.opcode_base;
+                state.advance(header, (adjusted / header.line_range) as u64);
+                let delta = header.line_base as i64 + (adjusted % header.line_range) as i64;
+                state.line = state.line.wrapping_add(delta as u64);
+                rows.push(state);
+                state.basic_block = false;
+                state.prologue_end = false;
+                state.epilogue_begin = false;
+                state.discriminator = 0;
+                continue;
+            }
+
+            if opcode == 0 {
+                let len = r.uleb()? as usize;
+                let mut ext = Reader::new(r.bytes(len)?, 0);
+                if len == 0 {
+                    continue;
+                }
+                match LineExtendedOpcode::new(ext.u8()?) {
+                    LineExtendedOpcode::DW_LNE_end_sequence => {
+                        state.end_sequence = true;
+                        rows.push(state);
+                        state = LineRow::new(header);
+                    }
+                    LineExtendedOpcode::DW_LNE_set_address => {
+                        state.address = ext.uint(len - 1)?;
+                        state.op_index = 0;
+                    }
+                    LineExtendedOpcode::DW_LNE_define_file => {
+                        let path = ext.cstr()?;
+                        defined.push(read_file_entry(&mut ext, path)?);
+                    }
+                    LineExtendedOpcode::DW_LNE_set_discriminator => {
+                        state.discriminator = ext.uleb()?;
+                    }
+                    // Unknown extended opcodes are skipped by the length.
+                    _ => {}
+                }
+                continue;
+            }
+
+            match LineStandardOpcode::new(opcode) {
+                LineStandardOpcode::DW_LNS_copy => {
+                    rows.push(state);
+                    state.basic_block = false;
+                    state.prologue_end = false;
+                    state.epilogue_begin = false;
+                    state.discriminator = 0;
+                }
+                LineStandardOpcode::DW_LNS_advance_pc => {
+                    let advance = r.uleb()?;
+                    state.advance(header, advance);
+                }
+                LineStandardOpcode::DW_LNS_advance_line => {
+                    state.line = state.line.wrapping_add(r.sleb()? as u64);
+                }
+                LineStandardOpcode::DW_LNS_set_file => state.file = r.uleb()?,
+                LineStandardOpcode::DW_LNS_set_column => state.column = r.uleb()?,
+                LineStandardOpcode::DW_LNS_negate_stmt => state.is_stmt = !state.is_stmt,
+                LineStandardOpcode::DW_LNS_set_basic_block => state.basic_block = true,
+                LineStandardOpcode::DW_LNS_const_add_pc => {
+                    let adjusted = 255 - header.opcode_base;
+                    state.advance(header, (adjusted / header.line_range) as u64);
+                }
+                LineStandardOpcode::DW_LNS_fixed_advance_pc => {
+                    state.address = state.address.wrapping_add(r.u16()? as u64);
+                    state.op_index = 0;
+                }
+                LineStandardOpcode::DW_LNS_set_prologue_end => state.prologue_end = true,
+                LineStandardOpcode::DW_LNS_set_epilogue_begin => state.epilogue_begin = true,
+                LineStandardOpcode::DW_LNS_set_isa => state.isa = r.uleb()?,
+                _ => {
+                    // Opcodes unknown to this version are skipped by their operand count.
+                    for _ in 0..header.standard_opcode_lengths[opcode as usize - 1] {
+                        r.uleb()?;
+                    }
+                }
+            }
+        }
+
+        Ok((rows, defined))
+    }
+}
+
+/// Address range that maps to the same source position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineEntry {
+    pub start: u64,
+    /// Address just after the range
+    pub end: u64,
+    /// Index into `LineTable::files`
+    pub file: usize,
+    pub line: u64,
+    pub column: u64,
+}
+
+/// Address to source position table built from all line programs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineTable {
+    files: Vec<String>,
+    entries: Vec<LineEntry>,
+}
+
+impl LineTable {
+    /// Ranges between rows of each sequence, sorted by address. Empty ranges are dropped.
+    pub fn new(programs: &[LineProgram]) -> Self {
+        let mut files: Vec<String> = vec![];
+        let mut entries = vec![];
+
+        for program in programs {
+            let mut file_index = |index: u64| {
+                let path = program.header.file_path(index).unwrap_or_default();
+                match files.iter().position(|x| *x == path) {
+                    Some(i) => i,
+                    None => {
+                        files.push(path);
+                        files.len() - 1
+                    }
+                }
+            };
+            for pair in program.rows.windows(2) {
+                let (row, next) = (&pair[0], &pair[1]);
+                if row.end_sequence || next.address <= row.address {
+                    continue;
+                }
+                entries.push(LineEntry {
+                    start: row.address,
+                    end: next.address,
+                    file: file_index(row.file),
+                    line: row.line,
+                    column: row.column,
+                });
+            }
+        }
+        entries.sort_by_key(|x| x.start);
+
+        Self { files, entries }
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn entries(&self) -> &[LineEntry] {
+        &self.entries
+    }
+
+    /// Source position of the instruction at `address`.
+    pub fn lookup(&self, address: u64) -> Option<SourceLocation> {
+        let i = self
+            .entries
+            .partition_point(|x| x.start <= address)
+            .checked_sub(1)?;
+        let entry = &self.entries[i];
+        if address >= entry.end {
+            return None;
+        }
+        Some(SourceLocation {
+            file: self.files[entry.file].clone(),
+            line: entry.line,
+            column: entry.column,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::elf::Elf;
+
+    const DWARF5: &[u8] = include_bytes!("../../../example_bin/dwarf5");
+    const DWARF4: &[u8] = include_bytes!("../../../example_bin/dwarf4");
+    const OUT: &[u8] = include_bytes!("../../../example_bin/out");
+
+    fn lines(table: &LineTable) -> Vec<(u64, String, u64, u64)> {
+        table
+            .entries()
+            .iter()
+            .map(|x| (x.start, table.files()[x.file].clone(), x.line, x.column))
+            .collect()
+    }
+
+    #[test]
+    fn dwarf5() {
+        let s = Sequential::from_vec(DWARF5.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let dwarf = Dwarf::from_elf(&elf);
+
+        let programs = dwarf.line_programs().unwrap();
+        assert_eq!(programs.len(), 1);
+        let header = &programs[0].header;
+        assert_eq!(
+            (header.version, header.line_base, header.line_range),
+            (5, -5, 14)
+        );
+        assert_eq!(header.include_directories, vec!["/tmp/dw"]);
+        let paths: Vec<_> = header.file_names.iter().map(|x| x.path.as_str()).collect();
+        assert_eq!(paths, vec!["dwarf.c", "dwarf.h", "dwarf.c"]);
+        assert_eq!(header.file_path(1).as_deref(), Some("/tmp/dw/dwarf.h"));
+        assert_eq!(header.file_path(3), None);
+
+        let rows = &programs[0].rows;
+        assert_eq!(rows.len(), 15);
+        assert!(rows[14].end_sequence);
+        assert_eq!(rows[14].address, 0x1189);
+
+        let table = dwarf.line_table().unwrap();
+        let found = table.lookup(0x1131).unwrap();
+        assert_eq!(found.to_string(), "/tmp/dw/dwarf.h:3:14");
+        assert_eq!(
+            table.lookup(0x1141).unwrap().to_string(),
+            "/tmp/dw/dwarf.c:12:13"
+        );
+        assert_eq!(table.lookup(0x1188).unwrap().line, 21);
+        assert_eq!(table.lookup(0x1189), None);
+        assert_eq!(table.lookup(0x1128), None);
+    }
+
+    #[test]
+    fn dwarf4() {
+        let s = Sequential::from_vec(DWARF4.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let dwarf = Dwarf::from_elf(&elf);
+
+        let programs = dwarf.line_programs().unwrap();
+        let header = &programs[0].header;
+        assert_eq!(header.version, 4);
+        assert_eq!(header.maximum_operations_per_instruction, 1);
+        assert!(header.include_directories.is_empty());
+        // Index 0 is the compilation directory and not listed.
+        assert_eq!(header.file_path(0), None);
+        assert_eq!(header.file_path(2).as_deref(), Some("dwarf.h"));
+
+        let table = dwarf.line_table().unwrap();
+        assert_eq!(
+            &lines(&table)[..4],
+            &[
+                (0x1129, "dwarf.h".to_string(), 2, 1),
+                (0x1130, "dwarf.h".to_string(), 3, 14),
+                (0x1136, "dwarf.h".to_string(), 4, 1),
+                (0x1138, "dwarf.c".to_string(), 11, 1),
+            ]
+        );
+        assert_eq!(table.lookup(0x1141).unwrap().to_string(), "dwarf.c:12:13");
+    }
+
+    #[test]
+    fn several_programs() {
+        let s = Sequential::from_vec(OUT.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let dwarf = Dwarf::from_elf(&elf);
+
+        let programs = dwarf.line_programs().unwrap();
+        assert_eq!(programs.len(), 5);
+        assert_eq!(programs[1].header.offset, 0x5d);
+
+        let table = dwarf.line_table().unwrap();
+        let found = table.lookup(0x1044).unwrap();
+        assert_eq!(
+            (found.file.as_str(), found.line),
+            ("../sysdeps/x86_64/start.S", 62)
+        );
+        assert_eq!(table.lookup(0x1155).unwrap().line, 83);
+        // main is compiled without debug information.
+        assert_eq!(table.lookup(0x1140), None);
+    }
+
+    /// DWARF 2 program with 4 byte instructions.
+    fn dwarf2_program() -> Vec<u8> {
+        let mut header = vec![4, 1, (-3i8) as u8, 12, 10];
+        header.extend_from_slice(&[0, 1, 1, 1, 1, 0, 0, 0, 1]);
+        header.extend_from_slice(b"src\0\0a.c\0\x01\x00\x00\0");
+
+        let mut program = vec![0, 9, 2];
+        program.extend_from_slice(&0x10000u64.to_le_bytes());
+        program.extend_from_slice(&[
+            0x03, 9,    // advance_line
+            0x01, // copy
+            38,   // special: 2 instructions and 1 line
+            0x05, 7,    // set_column
+            0x08, // const_add_pc: 20 instructions
+            0x06, // negate_stmt
+            0x01, // copy
+        ]);
+        program.extend_from_slice(&[0, 8, 3]);
+        program.extend_from_slice(b"b.c\0\x01\x00\x00");
+        program.extend_from_slice(&[
+            0x04, 2, // set_file
+            0x09, 0x10, 0x00, // fixed_advance_pc
+            0x02, 1,    // advance_pc
+            0x01, // copy
+            0x02, 1, // advance_pc
+            0, 1, 1, // end_sequence
+        ]);
+
+        let mut unit = 2u16.to_le_bytes().to_vec();
+        unit.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        unit.extend(header);
+        unit.extend(program);
+        let mut ret = (unit.len() as u32).to_le_bytes().to_vec();
+        ret.extend(unit);
+        ret
+    }
+
+    #[test]
+    fn dwarf2() {
+        let data = dwarf2_program();
+        let dwarf = Dwarf {
+            address_size: 8,
+            debug_line: &data,
+            ..Default::default()
+        };
+
+        let programs = dwarf.line_programs().unwrap();
+        assert_eq!(programs[0].header.file_names.len(), 2);
+        let rows = &programs[0].rows;
+        assert!(!rows[2].is_stmt);
+        assert_eq!(rows[4].address, 0x10070);
+
+        let table = dwarf.line_table().unwrap();
+        assert_eq!(
+            lines(&table),
+            vec![
+                (0x10000, "src/a.c".to_string(), 10, 0),
+                (0x10008, "src/a.c".to_string(), 11, 0),
+                (0x10058, "src/a.c".to_string(), 11, 7),
+                (0x1006c, "src/b.c".to_string(), 11, 7),
+            ]
+        );
+        assert_eq!(table.entries()[3].end, 0x10070);
+
+        // Program runs out of unit.
+        let mut broken = data.clone();
+        broken[0] -= 4;
+        let dwarf = Dwarf {
+            debug_line: &broken[..data.len() - 4],
+            ..dwarf
+        };
+        assert!(matches!(
+            dwarf.line_programs(),
+            Err(DwarfError::Truncated(_))
+        ));
+
+        let mut broken = data.clone();
+        broken[4] = 6;
+        let dwarf = Dwarf {
+            debug_line: &broken,
+            ..dwarf
+        };
+        assert_eq!(
+            dwarf.line_programs(),
+            Err(DwarfError::UnsupportedVersion(0, 6))
+        );
+    }
+}
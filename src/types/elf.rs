@@ -11,6 +11,23 @@ mod raw_elf64 {
 
 use raw_elf64::*; // FIXME: support 32bit
 
+mod compressed;
+mod coredump;
+mod hash;
+mod reader;
+mod symbolize;
+mod unwind;
+pub use compressed::DecompressError;
+pub use coredump::{
+    AArch64Registers, AuxEntry, MappedFile, PrPsInfo, PrStatus, Registers, SigInfo, X86_64Registers,
+};
+pub use hash::{gnu_hash, sysv_hash, GnuHashTable, HashError, HashTable, SysvHashTable};
+pub use reader::{Elf, Note, Symbol};
+pub use symbolize::{Symbolized, Symbolizer};
+pub use unwind::{StackFrame, Unwinder};
+
+pub mod lint;
+
 pub mod elf_header {
     use super::*;
     define_model_type!(
@@ -32,6 +49,15 @@ pub mod elf_header {
             Ok(())
         }
     }
+    impl Magic {
+        /// First 4 bytes of identification.
+        pub const ELFMAG: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+
+        /// Return true if identification starts with "\x7fELF".
+        pub fn is_elf(&self) -> bool {
+            self.0 .0[..4] == Self::ELFMAG
+        }
+    }
     define_model_type!(
         #[derive(PartialEq, Eq)]
         pub struct Type(Half),
@@ -49,6 +75,11 @@ pub mod elf_header {
     #define ET_HIPROC	0xffff
         ],
         display = true, bitflags = false,
+        ranges = [
+            ("OS-specific": ET_LOOS..=ET_HIOS),
+            ("Processor-specific": ET_LOPROC..=ET_HIPROC),
+        ],
+        aliases = [ET_NUM, ET_LOOS, ET_HIOS, ET_LOPROC, ET_HIPROC],
     );
     define_model_type!(
         #[derive(PartialEq, Eq)]
@@ -239,6 +270,7 @@ pub mod elf_header {
     #define EM_ALPHA	0x9026
         ],
         display = true, bitflags = false,
+        aliases = [EM_NUM],
     );
 
     define_model_type!(
@@ -251,6 +283,7 @@ pub mod elf_header {
     #define EV_NUM		2
         ],
         display = true, bitflags = false,
+        aliases = [EV_NUM],
     );
 
     define_model_type!(
@@ -295,6 +328,7 @@ pub mod elf_header {
         pub struct EhSize(Half),
         [],
         display = true, bitflags = false,
+        valid = [0x40..=0x40],
     );
     define_model_type!(
         #[derive(PartialEq, Eq, Clone, Copy)]
@@ -309,6 +343,7 @@ pub mod elf_header {
     #define PN_XNUM		0xffff
         ],
         display = true, bitflags = false,
+        valid = [..0xffff],
     );
     define_model_type!(
         #[derive(PartialEq, Eq, Clone, Copy)]
@@ -342,7 +377,18 @@ pub mod elf_header {
     #define SHN_HIRESERVE	0xffff		/* End of reserved indices */
         ],
         display = true, bitflags = false,
+        ranges = [
+            ("Processor-specific": SHN_LOPROC..=SHN_HIPROC),
+            ("OS-specific": SHN_LOOS..=SHN_HIOS),
+            ("Reserved": SHN_LORESERVE..=SHN_HIRESERVE),
+        ],
+        aliases = [SHN_LOPROC, SHN_BEFORE, SHN_AFTER, SHN_HIPROC, SHN_LOOS, SHN_HIOS, SHN_HIRESERVE],
+        valid = [..0xff00],
     );
+
+    /// Size of ELF header in bytes.
+    pub const SIZE: usize = 0x40;
+
     define_composed_type!(
         pub struct Header {
             /// Magic number and other info
@@ -395,11 +441,10 @@ pub mod section_header {
     use super::*;
 
     define_model_type!(
+        /// Offset in section header string table. Any value is valid, and 0 is the empty name.
         #[derive(PartialEq, Eq, Clone, Copy)]
         pub struct Name(Word),
-        [
-            (NONE: 0),
-        ],
+        [],
         display = true, bitflags = false,
     );
 
@@ -446,6 +491,15 @@ pub mod section_header {
     #define SHT_HIUSER	  0x8fffffff	/* End of application-specific */
         ],
         display = true, bitflags = false,
+        ranges = [
+            ("OS-specific": SHT_LOOS..=SHT_HIOS),
+            ("Processor-specific": SHT_LOPROC..=SHT_HIPROC),
+            ("Application-specific": SHT_LOUSER..=SHT_HIUSER),
+        ],
+        aliases = [
+            SHT_NUM, SHT_LOOS, SHT_LOSUNW, SHT_HISUNW, SHT_HIOS,
+            SHT_LOPROC, SHT_HIPROC, SHT_LOUSER, SHT_HIUSER,
+        ],
     );
 
     define_model_type!(
@@ -515,6 +569,9 @@ pub mod section_header {
         pub struct Align(XWord),
         [],
         display = true, bitflags = false,
+        predicates = [
+            (Severity::Error, "not a power of two", |x: &XWord| *x == 0 || x.is_power_of_two()),
+        ],
     );
 
     define_model_type!(
@@ -524,6 +581,9 @@ pub mod section_header {
         display = true, bitflags = false,
     );
 
+    /// Size of section header in bytes.
+    pub const SIZE: usize = 0x40;
+
     define_composed_type!(
         #[derive(PartialEq, Eq, Clone, Copy)]
         pub struct Header {
@@ -560,3 +620,546 @@ pub mod section_header {
         display = true,
     );
 }
+
+pub mod program_header {
+    use super::*;
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Type(Word),
+        pub
+        [
+    #define PT_NULL		0		/* Program header table entry unused */
+    #define PT_LOAD		1		/* Loadable program segment */
+    #define PT_DYNAMIC	2		/* Dynamic linking information */
+    #define PT_INTERP	3		/* Program interpreter */
+    #define PT_NOTE		4		/* Auxiliary information */
+    #define PT_SHLIB	5		/* Reserved */
+    #define PT_PHDR		6		/* Entry for header table itself */
+    #define PT_TLS		7		/* Thread-local storage segment */
+    #define	PT_NUM		8		/* Number of defined types */
+    #define PT_LOOS		0x60000000	/* Start of OS-specific */
+    #define PT_GNU_EH_FRAME	0x6474e550	/* GCC .eh_frame_hdr segment */
+    #define PT_GNU_STACK	0x6474e551	/* Indicates stack executability */
+    #define PT_GNU_RELRO	0x6474e552	/* Read-only after relocation */
+    #define PT_GNU_PROPERTY	0x6474e553	/* GNU property */
+    #define PT_LOSUNW	0x6ffffffa
+    #define PT_SUNWBSS	0x6ffffffa	/* Sun Specific segment */
+    #define PT_SUNWSTACK	0x6ffffffb	/* Stack segment */
+    #define PT_HISUNW	0x6fffffff
+    #define PT_HIOS		0x6fffffff	/* End of OS-specific */
+    #define PT_LOPROC	0x70000000	/* Start of processor-specific */
+    #define PT_HIPROC	0x7fffffff	/* End of processor-specific */
+        ],
+        display = true, bitflags = false,
+        ranges = [
+            ("OS-specific": PT_LOOS..=PT_HIOS),
+            ("Processor-specific": PT_LOPROC..=PT_HIPROC),
+        ],
+        aliases = [PT_NUM, PT_LOOS, PT_LOSUNW, PT_HISUNW, PT_HIOS, PT_LOPROC, PT_HIPROC],
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Flags(Word),
+        pub
+        [
+    #define PF_X		(1 << 0)	/* Segment is executable */
+    #define PF_W		(1 << 1)	/* Segment is writable */
+    #define PF_R		(1 << 2)	/* Segment is readable */
+    #define PF_MASKOS	0x0ff00000	/* OS-specific */
+    #define PF_MASKPROC	0xf0000000u32	/* Processor-specific */
+        ],
+        display = true, bitflags = true,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct FileOffset(Off),
+        [],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct VirtualAddress(Addr),
+        [],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct PhysicalAddress(Addr),
+        [],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Size(XWord),
+        [],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Align(XWord),
+        [],
+        display = true, bitflags = false,
+        predicates = [
+            (Severity::Error, "not a power of two", |x: &XWord| *x == 0 || x.is_power_of_two()),
+        ],
+    );
+
+    /// Size of program header in bytes.
+    pub const SIZE: usize = 0x38;
+
+    define_composed_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Header {
+            /// Segment type
+            p_type: Option<Type>,
+
+            /// Segment flags
+            p_flags: Option<Flags>,
+
+            /// Segment file offset
+            p_offset: Option<FileOffset>,
+
+            /// Segment virtual address
+            p_vaddr: Option<VirtualAddress>,
+
+            /// Segment physical address
+            p_paddr: Option<PhysicalAddress>,
+
+            /// Segment size in file
+            p_filesz: Option<Size>,
+
+            /// Segment size in memory
+            p_memsz: Option<Size>,
+
+            /// Segment alignment
+            p_align: Option<Align>,
+        },
+        display = true,
+    );
+}
+
+pub mod symbol {
+    use super::*;
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Name(Word),
+        [
+            (NONE: 0),
+        ],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Bind(u8),
+        pub
+        [
+    #define STB_LOCAL	0		/* Local symbol */
+    #define STB_GLOBAL	1		/* Global symbol */
+    #define STB_WEAK	2		/* Weak symbol */
+    #define	STB_NUM		3		/* Number of defined types.  */
+    #define STB_LOOS	10		/* Start of OS-specific */
+    #define STB_GNU_UNIQUE	10		/* Unique symbol.  */
+    #define STB_HIOS	12		/* End of OS-specific */
+    #define STB_LOPROC	13		/* Start of processor-specific */
+    #define STB_HIPROC	15		/* End of processor-specific */
+        ],
+        display = true, bitflags = false,
+        ranges = [
+            ("OS-specific": STB_LOOS..=STB_HIOS),
+            ("Processor-specific": STB_LOPROC..=STB_HIPROC),
+        ],
+        aliases = [STB_NUM, STB_LOOS, STB_HIOS, STB_LOPROC, STB_HIPROC],
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Type(u8),
+        pub
+        [
+    #define STT_NOTYPE	0		/* Symbol type is unspecified */
+    #define STT_OBJECT	1		/* Symbol is a data object */
+    #define STT_FUNC	2		/* Symbol is a code object */
+    #define STT_SECTION	3		/* Symbol associated with a section */
+    #define STT_FILE	4		/* Symbol's name is file name */
+    #define STT_COMMON	5		/* Symbol is a common data object */
+    #define STT_TLS		6		/* Symbol is thread-local data object*/
+    #define	STT_NUM		7		/* Number of defined types.  */
+    #define STT_LOOS	10		/* Start of OS-specific */
+    #define STT_GNU_IFUNC	10		/* Symbol is indirect code object */
+    #define STT_HIOS	12		/* End of OS-specific */
+    #define STT_LOPROC	13		/* Start of processor-specific */
+    #define STT_HIPROC	15		/* End of processor-specific */
+        ],
+        display = true, bitflags = false,
+        ranges = [
+            ("OS-specific": STT_LOOS..=STT_HIOS),
+            ("Processor-specific": STT_LOPROC..=STT_HIPROC),
+        ],
+        aliases = [STT_NUM, STT_LOOS, STT_HIOS, STT_LOPROC, STT_HIPROC],
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Info(u8),
+        [],
+        display = true, bitflags = false,
+    );
+
+    impl Info {
+        /// Binding in the high nibble.
+        pub fn bind(&self) -> Bind {
+            Bind::new(self.0 >> 4)
+        }
+
+        /// Type in the low nibble.
+        pub fn typ(&self) -> Type {
+            Type::new(self.0 & 0xf)
+        }
+    }
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Visibility(u8),
+        pub
+        [
+    #define STV_DEFAULT	0		/* Default symbol visibility rules */
+    #define STV_INTERNAL	1		/* Processor specific hidden class */
+    #define STV_HIDDEN	2		/* Sym unavailable in other modules */
+    #define STV_PROTECTED	3		/* Not preemptible, not exported */
+        ],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Other(u8),
+        [],
+        display = true, bitflags = false,
+    );
+
+    impl Other {
+        /// Visibility in the low 2 bits.
+        pub fn visibility(&self) -> Visibility {
+            Visibility::new(self.0 & 0x3)
+        }
+    }
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct SectionIndex(Half),
+        pub
+        [
+    #define SHN_UNDEF	0		/* Undefined section */
+    #define SHN_LORESERVE	0xff00		/* Start of reserved indices */
+    #define SHN_LOPROC	0xff00		/* Start of processor-specific */
+    #define SHN_HIPROC	0xff1f		/* End of processor-specific */
+    #define SHN_LOOS	0xff20		/* Start of OS-specific */
+    #define SHN_HIOS	0xff3f		/* End of OS-specific */
+    #define SHN_ABS		0xfff1		/* Associated symbol is absolute */
+    #define SHN_COMMON	0xfff2		/* Associated symbol is common */
+    #define SHN_XINDEX	0xffff		/* Index is in extra table.  */
+    #define SHN_HIRESERVE	0xffff		/* End of reserved indices */
+        ],
+        display = true, bitflags = false,
+        ranges = [
+            ("Processor-specific": SHN_LOPROC..=SHN_HIPROC),
+            ("OS-specific": SHN_LOOS..=SHN_HIOS),
+            ("Reserved": SHN_LORESERVE..=SHN_HIRESERVE),
+        ],
+        aliases = [SHN_LORESERVE, SHN_LOPROC, SHN_HIPROC, SHN_LOOS, SHN_HIOS, SHN_HIRESERVE],
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Value(Addr),
+        [],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Size(XWord),
+        [],
+        display = true, bitflags = false,
+    );
+
+    /// Size of symbol table entry in bytes.
+    pub const SIZE: usize = 0x18;
+
+    define_composed_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Header {
+            /// Symbol name (string tbl index)
+            st_name: Option<Name>,
+
+            /// Symbol type and binding
+            st_info: Option<Info>,
+
+            /// Symbol visibility
+            st_other: Option<Other>,
+
+            /// Section index
+            st_shndx: Option<SectionIndex>,
+
+            /// Symbol value
+            st_value: Option<Value>,
+
+            /// Symbol size
+            st_size: Option<Size>,
+        },
+        display = true,
+    );
+}
+
+pub mod note {
+    use super::*;
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Size(Word),
+        [],
+        display = true, bitflags = false,
+    );
+
+    // Meaning of the type depends on the owner name. These are for "CORE" and "LINUX" in core files.
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Type(Word),
+        pub
+        [
+    #define NT_PRSTATUS	1		/* Contains copy of prstatus struct */
+    #define NT_PRFPREG	2		/* Contains copy of fpregset struct.  */
+    #define NT_FPREGSET	2		/* Contains copy of fpregset struct */
+    #define NT_PRPSINFO	3		/* Contains copy of prpsinfo struct */
+    #define NT_PRXREG	4		/* Contains copy of prxregset struct */
+    #define NT_TASKSTRUCT	4		/* Contains copy of task structure */
+    #define NT_PLATFORM	5		/* String from sysinfo(SI_PLATFORM) */
+    #define NT_AUXV		6		/* Contains copy of auxv array */
+    #define NT_SIGINFO	0x53494749	/* Contains copy of siginfo_t, size might increase */
+    #define NT_FILE		0x46494c45	/* Contains information about mapped files */
+    #define NT_PRXFPREG	0x46e62b7f	/* Contains copy of user_fxsr_struct */
+    #define NT_X86_XSTATE	0x202		/* x86 extended state using xsave */
+    #define NT_X86_SHSTK	0x204		/* x86 SHSTK state */
+    #define NT_X86_XSAVE_LAYOUT	0x205	/* XSAVE layout description */
+    #define NT_ARM_VFP	0x400		/* ARM VFP/NEON registers */
+    #define NT_ARM_TLS	0x401		/* ARM TLS register */
+    #define NT_ARM_HW_BREAK	0x402		/* ARM hardware breakpoint registers */
+    #define NT_ARM_HW_WATCH	0x403		/* ARM hardware watchpoint registers */
+    #define NT_ARM_SYSTEM_CALL	0x404	/* ARM system call number */
+    #define NT_ARM_SVE	0x405		/* ARM Scalable Vector Extension registers */
+    #define NT_ARM_PAC_MASK	0x406		/* ARM pointer authentication code masks */
+        ],
+        display = true, bitflags = false,
+        aliases = [NT_FPREGSET, NT_TASKSTRUCT],
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct AuxType(XWord),
+        pub
+        [
+    #define AT_NULL		0		/* End of vector */
+    #define AT_IGNORE	1		/* Entry should be ignored */
+    #define AT_EXECFD	2		/* File descriptor of program */
+    #define AT_PHDR		3		/* Program headers for program */
+    #define AT_PHENT	4		/* Size of program header entry */
+    #define AT_PHNUM	5		/* Number of program headers */
+    #define AT_PAGESZ	6		/* System page size */
+    #define AT_BASE		7		/* Base address of interpreter */
+    #define AT_FLAGS	8		/* Flags */
+    #define AT_ENTRY	9		/* Entry point of program */
+    #define AT_NOTELF	10		/* Program is not ELF */
+    #define AT_UID		11		/* Real uid */
+    #define AT_EUID		12		/* Effective uid */
+    #define AT_GID		13		/* Real gid */
+    #define AT_EGID		14		/* Effective gid */
+    #define AT_PLATFORM	15		/* String identifying platform.  */
+    #define AT_HWCAP	16		/* Machine-dependent hints about processor capabilities.  */
+    #define AT_CLKTCK	17		/* Frequency of times() */
+    #define AT_SECURE	23		/* Boolean, was exec setuid-like?  */
+    #define AT_BASE_PLATFORM 24		/* String identifying real platforms.*/
+    #define AT_RANDOM	25		/* Address of 16 random bytes.  */
+    #define AT_HWCAP2	26		/* More machine-dependent hints about processor capabilities.  */
+    #define AT_RSEQ_FEATURE_SIZE	27	/* rseq supported feature size.  */
+    #define AT_RSEQ_ALIGN	28		/* rseq allocation alignment.  */
+    #define AT_HWCAP3	29		/* extension of AT_HWCAP.  */
+    #define AT_HWCAP4	30		/* extension of AT_HWCAP.  */
+    #define AT_EXECFN	31		/* Filename of executable.  */
+    #define AT_SYSINFO	32		/* Entry point of vDSO on i386 */
+    #define AT_SYSINFO_EHDR	33		/* Address of vDSO ELF header */
+    #define AT_MINSIGSTKSZ	51		/* Minimal stack size for signal delivery.  */
+        ],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Signal(i32),
+        pub
+        [
+    #define SIGHUP		1	/* Hangup.  */
+    #define SIGINT		2	/* Interactive attention signal.  */
+    #define SIGQUIT		3	/* Quit.  */
+    #define SIGILL		4	/* Illegal instruction.  */
+    #define SIGTRAP		5	/* Trace/breakpoint trap.  */
+    #define SIGABRT		6	/* Abnormal termination.  */
+    #define SIGBUS		7	/* Bus error.  */
+    #define SIGFPE		8	/* Erroneous arithmetic operation.  */
+    #define SIGKILL		9	/* Killed.  */
+    #define SIGUSR1		10	/* User-defined signal 1.  */
+    #define SIGSEGV		11	/* Invalid access to storage.  */
+    #define SIGUSR2		12	/* User-defined signal 2.  */
+    #define SIGPIPE		13	/* Broken pipe.  */
+    #define SIGALRM		14	/* Alarm clock.  */
+    #define SIGTERM		15	/* Termination request.  */
+    #define SIGSTKFLT	16	/* Stack fault (obsolete).  */
+    #define SIGCHLD		17	/* Child terminated or stopped.  */
+    #define SIGCONT		18	/* Continue.  */
+    #define SIGSTOP		19	/* Stop, unblockable.  */
+    #define SIGTSTP		20	/* Keyboard stop.  */
+    #define SIGTTIN		21	/* Background read from control terminal.  */
+    #define SIGTTOU		22	/* Background write to control terminal.  */
+    #define SIGURG		23	/* Urgent data is available at a socket.  */
+    #define SIGXCPU		24	/* CPU time limit exceeded.  */
+    #define SIGXFSZ		25	/* File size limit exceeded.  */
+    #define SIGVTALRM	26	/* Virtual timer expired.  */
+    #define SIGPROF		27	/* Profiling timer expired.  */
+    #define SIGWINCH	28	/* Window size change (4.3 BSD, Sun).  */
+    #define SIGIO		29	/* I/O now possible (4.2 BSD).  */
+    #define SIGPWR		30	/* Power failure imminent.  */
+    #define SIGSYS		31	/* Bad system call.  */
+        ],
+        display = true, bitflags = false,
+    );
+
+    /// Size of note header in bytes.
+    pub const SIZE: usize = 0xc;
+
+    // Name and descriptor follow the header. Each of them is padded to the alignment of the note segment.
+    define_composed_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Header {
+            /// Length of the owner name including the terminating null
+            n_namesz: Option<Size>,
+
+            /// Length of the descriptor
+            n_descsz: Option<Size>,
+
+            /// Type of the descriptor
+            n_type: Option<Type>,
+        },
+        display = true,
+    );
+}
+
+/// Header at the start of sections with SHF_COMPRESSED.
+pub mod compression_header {
+    use super::*;
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Type(Word),
+        pub
+        [
+    #define ELFCOMPRESS_ZLIB	1	   /* ZLIB/DEFLATE algorithm.  */
+    #define ELFCOMPRESS_ZSTD	2	   /* Zstandard algorithm.  */
+    #define ELFCOMPRESS_LOOS	0x60000000 /* Start of OS-specific.  */
+    #define ELFCOMPRESS_HIOS	0x6fffffff /* End of OS-specific.  */
+    #define ELFCOMPRESS_LOPROC	0x70000000 /* Start of processor-specific.  */
+    #define ELFCOMPRESS_HIPROC	0x7fffffff /* End of processor-specific.  */
+        ],
+        display = true, bitflags = false,
+        ranges = [
+            ("OS-specific": ELFCOMPRESS_LOOS..=ELFCOMPRESS_HIOS),
+            ("Processor-specific": ELFCOMPRESS_LOPROC..=ELFCOMPRESS_HIPROC),
+        ],
+        aliases = [ELFCOMPRESS_LOOS, ELFCOMPRESS_HIOS, ELFCOMPRESS_LOPROC, ELFCOMPRESS_HIPROC],
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Reserved(Word),
+        [],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Size(XWord),
+        [],
+        display = true, bitflags = false,
+    );
+
+    define_model_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct AddrAlign(XWord),
+        [],
+        display = true, bitflags = false,
+    );
+
+    /// Size of compression header in bytes.
+    pub const SIZE: usize = 0x18;
+
+    define_composed_type!(
+        #[derive(PartialEq, Eq, Clone, Copy)]
+        pub struct Header {
+            /// Compression format
+            ch_type: Option<Type>,
+
+            ch_reserved: Option<Reserved>,
+
+            /// Uncompressed data size
+            ch_size: Option<Size>,
+
+            /// Uncompressed data alignment
+            ch_addralign: Option<AddrAlign>,
+        },
+        display = true,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_classification() {
+        let t = elf_header::Type::new(0xfe05);
+        assert_eq!(t.to_string(), "OS-specific(0xfe05)");
+        assert_eq!(t.class(), Some("OS-specific"));
+        assert_eq!(t.name(), None);
+        assert_eq!(elf_header::Type::ET_LOOS.to_string(), "OS-specific(0xfe00)");
+        assert_eq!(elf_header::Type::ET_DYN.to_string(), "ET_DYN(3)");
+        assert_eq!(elf_header::Type::new(0x10).to_string(), "Unknown(16)");
+
+        let t = section_header::Type::SHT_GNU_HASH;
+        assert_eq!(t.to_string(), "SHT_GNU_HASH(1879048182)");
+        assert_eq!(t.class(), Some("OS-specific"));
+    }
+
+    #[test]
+    fn alias_primary_name() {
+        let i = elf_header::ShStrIndex::new(0xff00);
+        assert_eq!(i.name(), Some("SHN_LORESERVE"));
+        assert_eq!(i.names(), vec!["SHN_LORESERVE", "SHN_LOPROC", "SHN_BEFORE"]);
+        assert_eq!(i.to_string(), "SHN_LORESERVE(65280)");
+        // Solaris ordering flags are not section indices.
+        assert_eq!(elf_header::ShStrIndex::new(0xff01).to_string(), "Processor-specific(0xff01)");
+        assert_eq!(elf_header::ShStrIndex::new(0xffff).to_string(), "SHN_XINDEX(65535)");
+        assert_eq!(elf_header::ShStrIndex::new(0xff10).to_string(), "Processor-specific(0xff10)");
+        assert_eq!(elf_header::ShStrIndex::new(0xff50).to_string(), "Reserved(0xff50)");
+        assert_eq!(elf_header::ShStrIndex::new(36).to_string(), "36");
+
+        assert_eq!(section_header::Type::SHT_HISUNW.to_string(), "SHT_GNU_versym(1879048191)");
+        assert_eq!(program_header::Type::PT_SUNWBSS.name(), Some("PT_SUNWBSS"));
+    }
+}
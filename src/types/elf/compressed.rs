@@ -0,0 +1,296 @@
+use super::{compression_header, section_header, Elf};
+use crate::compress::zlib::{self, ZlibError};
+use crate::types::FromU8Array;
+
+/// Magic of legacy .zdebug sections, followed by big endian uncompressed size.
+const ZLIB_MAGIC: &[u8; 4] = b"ZLIB";
+
+/// Error of reading compressed section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecompressError {
+    /// The section header is not in the table of this file.
+    UnknownSection,
+
+    /// Section contents are out of file.
+    OutOfFile,
+
+    /// Compression header is truncated.
+    TruncatedHeader,
+
+    /// Compression algorithm is not supported.
+    UnsupportedType(compression_header::Type),
+
+    /// Compressed data is broken.
+    Zlib(ZlibError),
+
+    /// Decompressed data does not match the size in the header.
+    SizeMismatch { expected: u64, actual: u64 },
+}
+
+impl From<ZlibError> for DecompressError {
+    fn from(e: ZlibError) -> Self {
+        DecompressError::Zlib(e)
+    }
+}
+
+impl core::fmt::Display for DecompressError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecompressError::UnknownSection => write!(fmt, "section is not in this file"),
+            DecompressError::OutOfFile => write!(fmt, "section contents exceed file"),
+            DecompressError::TruncatedHeader => write!(fmt, "compression header is truncated"),
+            DecompressError::UnsupportedType(t) => {
+                write!(fmt, "unsupported compression {}", t.name().unwrap_or("?"))
+            }
+            DecompressError::Zlib(e) => write!(fmt, "{}", e),
+            DecompressError::SizeMismatch { expected, actual } => {
+                write!(fmt, "size mismatch: expected {}, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecompressError {}
+
+impl<'a> Elf<'a> {
+    /// Compression header of section with SHF_COMPRESSED. None for other sections.
+    pub fn compression_header(
+        &self,
+        sh: &section_header::Header,
+    ) -> Option<Result<compression_header::Header, DecompressError>> {
+        if !sh.get_sh_flags_unwrap().get_SHF_COMPRESSED() || sh.get_sh_type_unwrap().is_SHT_NOBITS()
+        {
+            return None;
+        }
+        let header = self
+            .section_data(sh)
+            .ok_or(DecompressError::OutOfFile)
+            .and_then(|data| {
+                compression_header::Header::from_slice(data)
+                    .map_err(|_| DecompressError::TruncatedHeader)
+            })
+            .map(|(_, header)| header);
+        Some(header)
+    }
+
+    /// Whether contents of the section are compressed, with SHF_COMPRESSED or as legacy .zdebug section.
+    pub fn is_compressed(&self, sh: &section_header::Header) -> bool {
+        self.compression_header(sh).is_some() || self.legacy_compressed(sh).is_some()
+    }
+
+    /// Data after "ZLIB" magic of .zdebug section. GNU tools leave the section uncompressed
+    /// without the magic, so such sections are not compressed.
+    fn legacy_compressed(&self, sh: &section_header::Header) -> Option<&'a [u8]> {
+        if !self.section_name(sh)?.starts_with(".zdebug") {
+            return None;
+        }
+        self.section_data(sh)?.strip_prefix(ZLIB_MAGIC)
+    }
+
+    /// Decompress contents of the section. None if the section is not compressed.
+    /// Decompressed data is not kept. Use `section_contents` to read it repeatedly.
+    pub fn decompress_section(
+        &self,
+        sh: &section_header::Header,
+    ) -> Option<Result<Vec<u8>, DecompressError>> {
+        let (stream, expected) = match self.compression_header(sh) {
+            Some(Err(e)) => return Some(Err(e)),
+            Some(Ok(header)) => {
+                let ty = *header.get_ch_type_unwrap();
+                if !ty.is_ELFCOMPRESS_ZLIB() {
+                    return Some(Err(DecompressError::UnsupportedType(ty)));
+                }
+                // Compression header was read from the data, so it is in range.
+                let stream = &self.section_data(sh)?[compression_header::SIZE..];
+                (stream, header.get_ch_size_unwrap().inner())
+            }
+            None => {
+                let data = self.legacy_compressed(sh)?;
+                let Some((size, stream)) = data.split_first_chunk::<8>() else {
+                    return Some(Err(DecompressError::TruncatedHeader));
+                };
+                (stream, u64::from_be_bytes(*size))
+            }
+        };
+
+        let limit = usize::try_from(expected).unwrap_or(usize::MAX);
+        let ret = zlib::decompress_limited(stream, limit)
+            .map_err(DecompressError::from)
+            .and_then(|out| {
+                if out.len() as u64 == expected {
+                    Ok(out)
+                } else {
+                    Err(DecompressError::SizeMismatch {
+                        expected,
+                        actual: out.len() as u64,
+                    })
+                }
+            });
+        Some(ret)
+    }
+
+    /// Contents of the section, decompressed if compressed.
+    /// Decompressed data is kept in this struct, so the section is decompressed only once.
+    pub fn section_contents(&self, sh: &section_header::Header) -> Result<&[u8], DecompressError> {
+        if !self.is_compressed(sh) {
+            return self.section_data(sh).ok_or(DecompressError::OutOfFile);
+        }
+        let index = self
+            .section_headers()
+            .iter()
+            .position(|x| x == sh)
+            .ok_or(DecompressError::UnknownSection)?;
+        let cached = self.decompressed[index].get_or_init(|| {
+            // Checked to be compressed above.
+            self.decompress_section(sh)
+                .unwrap_or(Err(DecompressError::UnknownSection))
+        });
+        match cached {
+            Ok(x) => Ok(x),
+            Err(e) => Err(*e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::dwarf::Dwarf;
+
+    const PLAIN: &[u8] = include_bytes!("../../../example_bin/dwarf5");
+    const ZLIB: &[u8] = include_bytes!("../../../example_bin/dwarf5_zlib");
+    const ZLIB_GNU: &[u8] = include_bytes!("../../../example_bin/dwarf5_zlib_gnu");
+
+    fn contents(elf: &Elf, name: &str) -> Vec<u8> {
+        let sh = elf.section_by_name(name).unwrap();
+        elf.section_contents(sh).unwrap().to_vec()
+    }
+
+    #[test]
+    fn compressed_sections() {
+        let s = Sequential::from_vec(PLAIN.to_vec());
+        let plain = Elf::parse(s.to_seeakble()).unwrap();
+        let s = Sequential::from_vec(ZLIB.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        let sh = elf.section_by_name(".debug_info").unwrap();
+        assert!(elf.is_compressed(sh));
+        let header = elf.compression_header(sh).unwrap().unwrap();
+        assert!(header.get_ch_type_unwrap().is_ELFCOMPRESS_ZLIB());
+        assert_eq!(header.get_ch_addralign_unwrap().inner(), 1);
+        assert_eq!(
+            contents(&elf, ".debug_info"),
+            contents(&plain, ".debug_info")
+        );
+        assert_eq!(
+            header.get_ch_size_unwrap().inner(),
+            contents(&plain, ".debug_info").len() as u64
+        );
+        // Cached data is returned for later calls.
+        assert!(std::ptr::eq(
+            elf.section_contents(sh).unwrap(),
+            elf.section_contents(sh).unwrap()
+        ));
+        assert_eq!(
+            contents(&elf, ".debug_abbrev"),
+            contents(&plain, ".debug_abbrev")
+        );
+
+        let sh = elf.section_by_name(".debug_str").unwrap();
+        assert!(!elf.is_compressed(sh));
+        assert!(elf.compression_header(sh).is_none());
+        assert_eq!(
+            elf.section_contents(sh).unwrap(),
+            elf.section_data(sh).unwrap()
+        );
+    }
+
+    #[test]
+    fn legacy_sections() {
+        let s = Sequential::from_vec(PLAIN.to_vec());
+        let plain = Elf::parse(s.to_seeakble()).unwrap();
+        let s = Sequential::from_vec(ZLIB_GNU.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        let sh = elf.section_by_name(".zdebug_info").unwrap();
+        assert!(elf.is_compressed(sh));
+        assert!(elf.compression_header(sh).is_none());
+        assert_eq!(
+            contents(&elf, ".zdebug_info"),
+            contents(&plain, ".debug_info")
+        );
+        assert_eq!(
+            contents(&elf, ".zdebug_line"),
+            contents(&plain, ".debug_line")
+        );
+    }
+
+    #[test]
+    fn dwarf_of_compressed_sections() {
+        let s = Sequential::from_vec(PLAIN.to_vec());
+        let plain = Elf::parse(s.to_seeakble()).unwrap();
+        let expected = Dwarf::from_elf(&plain);
+
+        for file in [ZLIB, ZLIB_GNU] {
+            let s = Sequential::from_vec(file.to_vec());
+            let elf = Elf::parse(s.to_seeakble()).unwrap();
+            let dwarf = Dwarf::from_elf(&elf);
+            assert_eq!(dwarf.functions().unwrap(), expected.functions().unwrap());
+            assert_eq!(dwarf.line_table().unwrap(), expected.line_table().unwrap());
+        }
+    }
+
+    #[test]
+    fn broken_sections() {
+        let mut v = ZLIB.to_vec();
+        let s = Sequential::from_vec(v.clone());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let sh = elf.section_by_name(".debug_info").unwrap();
+        let offset = sh.get_sh_offset_unwrap().inner();
+        let size = sh.get_sh_size_unwrap().inner() as usize;
+        let expected = elf
+            .compression_header(sh)
+            .unwrap()
+            .unwrap()
+            .get_ch_size_unwrap()
+            .inner();
+
+        // Unsupported algorithm
+        v[offset] = 2;
+        let s = Sequential::from_vec(v.clone());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let sh = elf.section_by_name(".debug_info").unwrap();
+        assert_eq!(
+            elf.section_contents(sh),
+            Err(DecompressError::UnsupportedType(
+                compression_header::Type::ELFCOMPRESS_ZSTD
+            ))
+        );
+
+        // Size in header is larger than data
+        v[offset] = 1;
+        v[offset + 8..offset + 16].copy_from_slice(&(expected + 1).to_le_bytes());
+        let s = Sequential::from_vec(v.clone());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let sh = elf.section_by_name(".debug_info").unwrap();
+        assert_eq!(
+            elf.section_contents(sh),
+            Err(DecompressError::SizeMismatch {
+                expected: expected + 1,
+                actual: expected
+            })
+        );
+
+        // Broken checksum
+        v[offset + 8..offset + 16].copy_from_slice(&expected.to_le_bytes());
+        v[offset + size - 1] ^= 1;
+        let s = Sequential::from_vec(v);
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let sh = elf.section_by_name(".debug_info").unwrap();
+        assert_eq!(
+            elf.section_contents(sh),
+            Err(DecompressError::Zlib(ZlibError::ChecksumMismatch))
+        );
+    }
+}
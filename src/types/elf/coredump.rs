@@ -0,0 +1,629 @@
+use super::{elf_header, note, Elf, Note};
+use core::time::Duration;
+
+/// Offset of pr_reg in elf_prstatus. The preceding fields are the same on x86_64 and AArch64.
+const PRSTATUS_REGISTERS: usize = 112;
+
+fn u64_at(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        data.get(at..at.checked_add(8)?)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(at..at.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn i32_at(data: &[u8], at: usize) -> Option<i32> {
+    u32_at(data, at).map(|x| x as i32)
+}
+
+/// Little endian words of `N` * 8 bytes at `at` of `data`.
+fn words_at<const N: usize>(data: &[u8], at: usize) -> Option<[u64; N]> {
+    let mut ret = [0; N];
+    for (i, x) in ret.iter_mut().enumerate() {
+        *x = u64_at(data, at + i * 8)?;
+    }
+    Some(ret)
+}
+
+/// Fixed size text field padded with nulls.
+fn text_at(data: &[u8], at: usize, len: usize) -> Option<String> {
+    let bytes = data.get(at..at.checked_add(len)?)?;
+    let len = bytes.iter().position(|x| *x == 0).unwrap_or(len);
+    Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+}
+
+/// struct timeval at `at` of `data`. Negative fields are clamped to 0.
+fn timeval_at(data: &[u8], at: usize) -> Option<Duration> {
+    let sec = u64_at(data, at)? as i64;
+    let usec = u64_at(data, at + 8)? as i64;
+    Some(
+        Duration::from_secs(sec.max(0) as u64)
+            + Duration::from_micros(usec.clamp(0, 999_999) as u64),
+    )
+}
+
+/// struct user_regs_struct of x86_64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X86_64Registers {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub rbp: u64,
+    pub rbx: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rax: u64,
+    pub rcx: u64,
+    pub rdx: u64,
+    pub rsi: u64,
+    pub rdi: u64,
+    pub orig_rax: u64,
+    pub rip: u64,
+    pub cs: u64,
+    pub eflags: u64,
+    pub rsp: u64,
+    pub ss: u64,
+    pub fs_base: u64,
+    pub gs_base: u64,
+    pub ds: u64,
+    pub es: u64,
+    pub fs: u64,
+    pub gs: u64,
+}
+
+impl X86_64Registers {
+    /// Size of user_regs_struct in bytes.
+    pub const SIZE: usize = 27 * 8;
+
+    const NAMES: [&'static str; 27] = [
+        "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx", "rdx",
+        "rsi", "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base", "gs_base", "ds",
+        "es", "fs", "gs",
+    ];
+
+    fn from_words(words: [u64; 27]) -> Self {
+        let [r15, r14, r13, r12, rbp, rbx, r11, r10, r9, r8, rax, rcx, rdx, rsi, rdi, orig_rax, rip, cs, eflags, rsp, ss, fs_base, gs_base, ds, es, fs, gs] =
+            words;
+        Self {
+            r15,
+            r14,
+            r13,
+            r12,
+            rbp,
+            rbx,
+            r11,
+            r10,
+            r9,
+            r8,
+            rax,
+            rcx,
+            rdx,
+            rsi,
+            rdi,
+            orig_rax,
+            rip,
+            cs,
+            eflags,
+            rsp,
+            ss,
+            fs_base,
+            gs_base,
+            ds,
+            es,
+            fs,
+            gs,
+        }
+    }
+
+    /// Registers in the order of user_regs_struct.
+    pub fn words(&self) -> [u64; 27] {
+        [
+            self.r15,
+            self.r14,
+            self.r13,
+            self.r12,
+            self.rbp,
+            self.rbx,
+            self.r11,
+            self.r10,
+            self.r9,
+            self.r8,
+            self.rax,
+            self.rcx,
+            self.rdx,
+            self.rsi,
+            self.rdi,
+            self.orig_rax,
+            self.rip,
+            self.cs,
+            self.eflags,
+            self.rsp,
+            self.ss,
+            self.fs_base,
+            self.gs_base,
+            self.ds,
+            self.es,
+            self.fs,
+            self.gs,
+        ]
+    }
+}
+
+/// struct user_pt_regs of AArch64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AArch64Registers {
+    /// x0 to x30. x29 is the frame pointer and x30 is the link register.
+    pub x: [u64; 31],
+    pub sp: u64,
+    pub pc: u64,
+    pub pstate: u64,
+}
+
+impl AArch64Registers {
+    /// Size of user_pt_regs in bytes.
+    pub const SIZE: usize = 34 * 8;
+}
+
+/// General purpose registers of a thread. The layout is chosen by e_machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Registers {
+    X86_64(X86_64Registers),
+    AArch64(AArch64Registers),
+    /// Raw bytes after the common part of elf_prstatus for other machines.
+    Unknown(Vec<u8>),
+}
+
+impl Registers {
+    fn parse(data: &[u8], machine: &elf_header::Machine) -> Option<Self> {
+        if machine.is_EM_X86_64() {
+            return Some(Self::X86_64(X86_64Registers::from_words(words_at(
+                data, 0,
+            )?)));
+        }
+        if machine.is_EM_AARCH64() {
+            let words: [u64; 34] = words_at(data, 0)?;
+            return Some(Self::AArch64(AArch64Registers {
+                x: words[..31].try_into().unwrap(),
+                sp: words[31],
+                pc: words[32],
+                pstate: words[33],
+            }));
+        }
+        Some(Self::Unknown(data.to_vec()))
+    }
+
+    /// Program counter.
+    pub fn pc(&self) -> Option<u64> {
+        match self {
+            Self::X86_64(x) => Some(x.rip),
+            Self::AArch64(x) => Some(x.pc),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Stack pointer.
+    pub fn sp(&self) -> Option<u64> {
+        match self {
+            Self::X86_64(x) => Some(x.rsp),
+            Self::AArch64(x) => Some(x.sp),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Values indexed by DWARF register number, which call frame information refers.
+    pub fn dwarf(&self) -> Vec<Option<u64>> {
+        match self {
+            Self::X86_64(x) => [
+                x.rax, x.rdx, x.rcx, x.rbx, x.rsi, x.rdi, x.rbp, x.rsp, x.r8, x.r9, x.r10, x.r11,
+                x.r12, x.r13, x.r14, x.r15, x.rip,
+            ]
+            .map(Some)
+            .to_vec(),
+            Self::AArch64(x) => x.x.iter().copied().chain([x.sp]).map(Some).collect(),
+            Self::Unknown(_) => vec![],
+        }
+    }
+
+    /// DWARF register number of the stack pointer.
+    pub fn dwarf_sp(&self) -> Option<u16> {
+        match self {
+            Self::X86_64(_) => Some(7),
+            Self::AArch64(_) => Some(31),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Registers with their names in the order of the register set.
+    pub fn named(&self) -> Vec<(String, u64)> {
+        match self {
+            Self::X86_64(x) => X86_64Registers::NAMES
+                .iter()
+                .map(|x| x.to_string())
+                .zip(x.words())
+                .collect(),
+            Self::AArch64(x) => (0..31)
+                .map(|i| (format!("x{}", i), x.x[i]))
+                .chain([
+                    ("sp".to_string(), x.sp),
+                    ("pc".to_string(), x.pc),
+                    ("pstate".to_string(), x.pstate),
+                ])
+                .collect(),
+            Self::Unknown(_) => vec![],
+        }
+    }
+}
+
+/// NT_PRSTATUS: status of a thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrStatus {
+    /// Signal that caused the dump
+    pub signal: note::Signal,
+    pub code: i32,
+    pub errno: i32,
+    /// Current signal of the thread
+    pub cursig: note::Signal,
+    /// Pending signals
+    pub sigpend: u64,
+    /// Blocked signals
+    pub sighold: u64,
+    pub pid: i32,
+    pub ppid: i32,
+    pub pgrp: i32,
+    pub sid: i32,
+    /// User time
+    pub utime: Duration,
+    /// System time
+    pub stime: Duration,
+    /// Cumulative user time of children
+    pub cutime: Duration,
+    /// Cumulative system time of children
+    pub cstime: Duration,
+    pub registers: Registers,
+}
+
+impl PrStatus {
+    /// Interpret elf_prstatus for `machine`.
+    pub fn parse(desc: &[u8], machine: &elf_header::Machine) -> Option<Self> {
+        Some(Self {
+            signal: note::Signal::new(i32_at(desc, 0)?),
+            code: i32_at(desc, 4)?,
+            errno: i32_at(desc, 8)?,
+            cursig: note::Signal::new(u32_at(desc, 12)? as i16 as i32),
+            sigpend: u64_at(desc, 16)?,
+            sighold: u64_at(desc, 24)?,
+            pid: i32_at(desc, 32)?,
+            ppid: i32_at(desc, 36)?,
+            pgrp: i32_at(desc, 40)?,
+            sid: i32_at(desc, 44)?,
+            utime: timeval_at(desc, 48)?,
+            stime: timeval_at(desc, 64)?,
+            cutime: timeval_at(desc, 80)?,
+            cstime: timeval_at(desc, 96)?,
+            registers: Registers::parse(desc.get(PRSTATUS_REGISTERS..)?, machine)?,
+        })
+    }
+}
+
+/// NT_PRPSINFO: information about the process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrPsInfo {
+    /// Numeric process state
+    pub state: i8,
+    /// Character of the state, like 'R' for running
+    pub sname: char,
+    pub zombie: bool,
+    pub nice: i8,
+    pub flag: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: i32,
+    pub ppid: i32,
+    pub pgrp: i32,
+    pub sid: i32,
+    /// Executable name truncated to 15 bytes
+    pub fname: String,
+    /// Command line separated by spaces and truncated to 80 bytes
+    pub psargs: String,
+}
+
+impl PrPsInfo {
+    /// Interpret elf_prpsinfo of 64 bit Linux.
+    pub fn parse(desc: &[u8]) -> Option<Self> {
+        Some(Self {
+            state: *desc.first()? as i8,
+            sname: *desc.get(1)? as char,
+            zombie: *desc.get(2)? != 0,
+            nice: *desc.get(3)? as i8,
+            flag: u64_at(desc, 8)?,
+            uid: u32_at(desc, 16)?,
+            gid: u32_at(desc, 20)?,
+            pid: i32_at(desc, 24)?,
+            ppid: i32_at(desc, 28)?,
+            pgrp: i32_at(desc, 32)?,
+            sid: i32_at(desc, 36)?,
+            fname: text_at(desc, 40, 16)?,
+            psargs: text_at(desc, 56, 80)?.trim_end_matches(' ').to_string(),
+        })
+    }
+}
+
+/// NT_SIGINFO: siginfo_t of the signal that caused the dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigInfo {
+    pub signo: note::Signal,
+    pub errno: i32,
+    pub code: i32,
+    /// First word of the union. This is the faulting address for SIGSEGV, SIGBUS, SIGILL, SIGFPE and SIGTRAP.
+    pub addr: u64,
+}
+
+impl SigInfo {
+    pub fn parse(desc: &[u8]) -> Option<Self> {
+        Some(Self {
+            signo: note::Signal::new(i32_at(desc, 0)?),
+            errno: i32_at(desc, 4)?,
+            code: i32_at(desc, 8)?,
+            addr: u64_at(desc, 16)?,
+        })
+    }
+
+    /// Faulting address if the signal is raised by a fault.
+    pub fn fault_address(&self) -> Option<u64> {
+        use note::Signal;
+        match self.signo {
+            Signal::SIGSEGV
+            | Signal::SIGBUS
+            | Signal::SIGILL
+            | Signal::SIGFPE
+            | Signal::SIGTRAP => Some(self.addr),
+            _ => None,
+        }
+    }
+}
+
+/// Entry of auxiliary vector in NT_AUXV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuxEntry {
+    pub typ: note::AuxType,
+    pub value: u64,
+}
+
+/// Entry of NT_FILE: a file mapped to memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappedFile {
+    pub start: u64,
+    pub end: u64,
+    /// Offset in the file in bytes
+    pub offset: u64,
+    pub name: String,
+}
+
+impl MappedFile {
+    /// Interpret NT_FILE: count, page size, (start, end, page offset) of each entry and null terminated names.
+    pub fn parse_all(desc: &[u8]) -> Option<Vec<Self>> {
+        let count = usize::try_from(u64_at(desc, 0)?).ok()?;
+        let page_size = u64_at(desc, 8)?;
+        let mut names = desc.get(count.checked_mul(24)?.checked_add(16)?..)?.split(|x| *x == 0);
+
+        let mut ret = vec![];
+        for i in 0..count {
+            let [start, end, page] = words_at(desc, 16 + i * 24)?;
+            ret.push(Self {
+                start,
+                end,
+                offset: page.checked_mul(page_size)?,
+                name: String::from_utf8_lossy(names.next()?).into_owned(),
+            });
+        }
+        Some(ret)
+    }
+}
+
+/// Interpretation of core files (ET_CORE).
+/// Notes are looked up by type with owner "CORE". Malformed notes are skipped.
+impl<'a> Elf<'a> {
+    fn core_notes(&self, typ: note::Type) -> impl Iterator<Item = Note<'a>> {
+        self.notes()
+            .into_iter()
+            .filter(move |x| x.name == "CORE" && x.typ == typ)
+    }
+
+    /// Status of each thread. The first one is the thread that caused the dump.
+    pub fn threads(&self) -> Vec<PrStatus> {
+        let machine = self.header().get_e_machine_unwrap();
+        self.core_notes(note::Type::NT_PRSTATUS)
+            .filter_map(|x| PrStatus::parse(x.desc, machine))
+            .collect()
+    }
+
+    pub fn process_info(&self) -> Option<PrPsInfo> {
+        self.core_notes(note::Type::NT_PRPSINFO)
+            .find_map(|x| PrPsInfo::parse(x.desc))
+    }
+
+    pub fn siginfo(&self) -> Option<SigInfo> {
+        self.core_notes(note::Type::NT_SIGINFO)
+            .find_map(|x| SigInfo::parse(x.desc))
+    }
+
+    /// Auxiliary vector until AT_NULL.
+    pub fn auxv(&self) -> Vec<AuxEntry> {
+        let desc = match self.core_notes(note::Type::NT_AUXV).next() {
+            Some(x) => x.desc,
+            None => return vec![],
+        };
+        desc.chunks_exact(16)
+            .map(|x| AuxEntry {
+                typ: note::AuxType::new(u64_at(x, 0).unwrap()),
+                value: u64_at(x, 8).unwrap(),
+            })
+            .take_while(|x| !x.typ.is_AT_NULL())
+            .collect()
+    }
+
+    /// Files mapped to memory of the process.
+    pub fn mapped_files(&self) -> Vec<MappedFile> {
+        self.core_notes(note::Type::NT_FILE)
+            .find_map(|x| MappedFile::parse_all(x.desc))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const CORE: &[u8] = include_bytes!("../../../example_bin/core_x86_64");
+
+    #[test]
+    fn notes() {
+        let s = Sequential::from_vec(CORE.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        assert!(elf.header().get_e_type_unwrap().is_ET_CORE());
+
+        let types: Vec<_> = elf
+            .notes()
+            .iter()
+            .map(|x| (x.name.clone(), x.typ))
+            .collect();
+        assert_eq!(
+            &types[..6],
+            &[
+                ("CORE".to_string(), note::Type::NT_PRSTATUS),
+                ("CORE".to_string(), note::Type::NT_PRPSINFO),
+                ("CORE".to_string(), note::Type::NT_SIGINFO),
+                ("CORE".to_string(), note::Type::NT_AUXV),
+                ("CORE".to_string(), note::Type::NT_FILE),
+                ("CORE".to_string(), note::Type::NT_FPREGSET),
+            ]
+        );
+        assert_eq!(types[6], ("LINUX".to_string(), note::Type::NT_X86_XSTATE));
+
+        let threads = elf.threads();
+        assert_eq!(threads.len(), 1);
+        let t = &threads[0];
+        assert_eq!(t.signal, note::Signal::SIGSEGV);
+        assert_eq!(t.cursig, note::Signal::SIGSEGV);
+        assert_eq!((t.pid, t.ppid, t.pgrp, t.sid), (25953, 25952, 25952, 25941));
+        let regs = match &t.registers {
+            Registers::X86_64(x) => x,
+            x => panic!("{:?}", x),
+        };
+        assert_eq!(regs.rip, 0x40167f);
+        assert_eq!(regs.rax, 0x10);
+        assert_eq!(t.registers.sp(), Some(0x7ffc0bc2a430));
+        assert_eq!(t.registers.named()[16], ("rip".to_string(), 0x40167f));
+
+        let info = elf.process_info().unwrap();
+        assert_eq!(info.fname, "crash");
+        assert_eq!(info.psargs, "./crash");
+        assert_eq!(info.pid, 25953);
+
+        let sig = elf.siginfo().unwrap();
+        assert_eq!(
+            (sig.signo, sig.errno, sig.code),
+            (note::Signal::SIGSEGV, 0, 1)
+        );
+        assert_eq!(sig.fault_address(), Some(0x10));
+    }
+
+    #[test]
+    fn auxv_and_files() {
+        let s = Sequential::from_vec(CORE.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        let auxv = elf.auxv();
+        let get = |t: note::AuxType| auxv.iter().find(|x| x.typ == t).map(|x| x.value);
+        assert_eq!(get(note::AuxType::AT_PAGESZ), Some(4096));
+        assert_eq!(get(note::AuxType::AT_PHDR), Some(0x400040));
+        // The file name of executable is on the dumped stack.
+        let execfn = elf
+            .memory_at(get(note::AuxType::AT_EXECFN).unwrap())
+            .unwrap();
+        assert!(execfn.starts_with(b"./crash\0"));
+
+        let files = elf.mapped_files();
+        let ranges: Vec<_> = files.iter().map(|x| (x.start, x.end, x.offset)).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0x400000, 0x401000, 0),
+                (0x401000, 0x479000, 0x1000),
+                (0x479000, 0x4a0000, 0x79000),
+                (0x4a0000, 0x4a4000, 0xa0000),
+                (0x4a4000, 0x4a7000, 0xa4000),
+            ]
+        );
+        assert!(files.iter().all(|x| x.name == "/tmp/core/crash"));
+
+        // Count that overflows the offset of names.
+        let mut desc = ((usize::MAX / 24) as u64).to_le_bytes().to_vec();
+        desc.resize(64, 0);
+        assert!(MappedFile::parse_all(&desc).is_none());
+    }
+
+    #[test]
+    fn memory() {
+        let s = Sequential::from_vec(CORE.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        // Static data of core_x86_64.c.
+        assert_eq!(
+            elf.read_memory(0x4a40d0, 18).unwrap(),
+            b"elfeat core marker"
+        );
+        assert_eq!(elf.read_memory(0x400000, 4).unwrap(), b"\x7fELF");
+        // Text is not dumped.
+        assert_eq!(elf.memory_at(0x401000), None);
+        assert_eq!(elf.read_memory(0x400ffe, 4), None);
+        assert_eq!(elf.read_memory(0x10, 1), None);
+        assert_eq!(elf.read_memory(0x4a40d0, 0).unwrap(), b"");
+        assert_eq!(elf.read_memory(0x4a40d0, usize::MAX), None);
+    }
+
+    #[test]
+    fn aarch64_prstatus() {
+        let mut desc = vec![0; PRSTATUS_REGISTERS + AArch64Registers::SIZE + 8];
+        desc[0..4].copy_from_slice(&6i32.to_le_bytes());
+        desc[12..14].copy_from_slice(&6i16.to_le_bytes());
+        desc[32..36].copy_from_slice(&1234i32.to_le_bytes());
+        desc[48..56].copy_from_slice(&3u64.to_le_bytes());
+        desc[56..64].copy_from_slice(&500_000u64.to_le_bytes());
+        for i in 0..34 {
+            let at = PRSTATUS_REGISTERS + i * 8;
+            desc[at..at + 8].copy_from_slice(&(0x1000 + i as u64).to_le_bytes());
+        }
+
+        let t = PrStatus::parse(&desc, &elf_header::Machine::EM_AARCH64).unwrap();
+        assert_eq!(t.signal, note::Signal::SIGABRT);
+        assert_eq!(t.cursig, note::Signal::SIGABRT);
+        assert_eq!(t.pid, 1234);
+        assert_eq!(t.utime, Duration::from_millis(3500));
+        assert_eq!(t.registers.pc(), Some(0x1000 + 32));
+        assert_eq!(t.registers.sp(), Some(0x1000 + 31));
+        let named = t.registers.named();
+        assert_eq!(named.len(), 34);
+        assert_eq!(named[30], ("x30".to_string(), 0x1000 + 30));
+        assert_eq!(named[33], ("pstate".to_string(), 0x1000 + 33));
+
+        // Register set is cut off.
+        assert_eq!(
+            PrStatus::parse(&desc[..200], &elf_header::Machine::EM_AARCH64),
+            None
+        );
+        let t = PrStatus::parse(&desc[..200], &elf_header::Machine::EM_RISCV).unwrap();
+        assert_eq!(
+            t.registers,
+            Registers::Unknown(desc[PRSTATUS_REGISTERS..200].to_vec())
+        );
+    }
+}
@@ -0,0 +1,486 @@
+use super::{section_header, symbol, Elf, Symbol};
+
+/// Error of reading symbol hash table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashError {
+    /// No hash table for the dynamic symbol table.
+    Missing,
+
+    /// Table contents are out of file.
+    OutOfFile,
+
+    /// The table is shorter than its header says.
+    Truncated,
+
+    /// The table has no buckets.
+    NoBuckets,
+
+    /// Bloom filter of GNU hash table has no words, or the number is not a power of two.
+    BadBloomSize(u32),
+}
+
+impl core::fmt::Display for HashError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HashError::Missing => write!(fmt, "no hash table for dynamic symbols"),
+            HashError::OutOfFile => write!(fmt, "hash table exceeds file"),
+            HashError::Truncated => write!(fmt, "hash table is truncated"),
+            HashError::NoBuckets => write!(fmt, "hash table has no buckets"),
+            HashError::BadBloomSize(n) => {
+                write!(fmt, "bloom filter size {} is not a power of two", n)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HashError {}
+
+/// Hash function of SHT_HASH, from the System V ABI.
+pub fn sysv_hash(name: &[u8]) -> u32 {
+    name.iter().fold(0u32, |h, &c| {
+        let h = (h << 4).wrapping_add(c as u32);
+        let g = h & 0xf000_0000;
+        (h ^ (g >> 24)) & !g
+    })
+}
+
+/// Hash function of SHT_GNU_HASH, which is DJB hash.
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter()
+        .fold(5381u32, |h, &c| h.wrapping_mul(33).wrapping_add(c as u32))
+}
+
+/// Read little endian words. None if `data` is shorter than `count` words.
+fn words(data: &[u8], count: usize) -> Option<Vec<u32>> {
+    let bytes = data.get(..count.checked_mul(4)?)?;
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+            .collect(),
+    )
+}
+
+/// Hash table of SHT_HASH section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SysvHashTable {
+    pub buckets: Vec<u32>,
+    /// Next symbol index of each symbol. The length is the number of symbols.
+    pub chains: Vec<u32>,
+}
+
+impl SysvHashTable {
+    pub fn parse(data: &[u8]) -> Result<Self, HashError> {
+        let header = words(data, 2).ok_or(HashError::Truncated)?;
+        let (nbucket, nchain) = (header[0] as usize, header[1] as usize);
+        if nbucket == 0 {
+            return Err(HashError::NoBuckets);
+        }
+        let mut table =
+            words(&data[8..], nbucket.saturating_add(nchain)).ok_or(HashError::Truncated)?;
+        let chains = table.split_off(nbucket);
+        Ok(Self {
+            buckets: table,
+            chains,
+        })
+    }
+
+    /// Symbol indices in the chain of the bucket for `name`.
+    /// The walk stops at STN_UNDEF, at index out of the chain, or after visiting all symbols.
+    pub fn candidates(&self, name: &[u8]) -> Vec<u32> {
+        let mut ret = vec![];
+        let mut index = self.buckets[sysv_hash(name) as usize % self.buckets.len()];
+        while index != 0 && ret.len() <= self.chains.len() {
+            ret.push(index);
+            index = match self.chains.get(index as usize) {
+                Some(x) => *x,
+                None => break,
+            };
+        }
+        ret
+    }
+}
+
+/// Hash table of SHT_GNU_HASH section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GnuHashTable {
+    /// Index of the first hashed symbol. Symbols before it are not in the table.
+    pub symbol_offset: u32,
+    /// Shift of the hash for the second bit of bloom filter.
+    pub bloom_shift: u32,
+    // FIXME: support 32bit, whose bloom filter words are 32 bits
+    pub bloom: Vec<u64>,
+    pub buckets: Vec<u32>,
+    /// Hash of each hashed symbol with the lowest bit replaced by the end of chain mark.
+    /// This runs to the end of section, since the header does not have the length.
+    pub chains: Vec<u32>,
+}
+
+impl GnuHashTable {
+    pub fn parse(data: &[u8]) -> Result<Self, HashError> {
+        let header = words(data, 4).ok_or(HashError::Truncated)?;
+        let (nbucket, symbol_offset, bloom_size, bloom_shift) =
+            (header[0] as usize, header[1], header[2], header[3]);
+        if nbucket == 0 {
+            return Err(HashError::NoBuckets);
+        }
+        // ld.so masks the bloom filter index with the size minus one.
+        if !bloom_size.is_power_of_two() {
+            return Err(HashError::BadBloomSize(bloom_size));
+        }
+
+        let at = 16;
+        let bloom = words(&data[at..], (bloom_size as usize).saturating_mul(2))
+            .ok_or(HashError::Truncated)?
+            .chunks_exact(2)
+            .map(|x| x[0] as u64 | (x[1] as u64) << 32)
+            .collect();
+        let at = at + bloom_size as usize * 8;
+        let buckets = words(&data[at..], nbucket).ok_or(HashError::Truncated)?;
+        let rest = &data[at + nbucket * 4..];
+        let chains = words(rest, rest.len() / 4).unwrap_or_default();
+
+        Ok(Self {
+            symbol_offset,
+            bloom_shift,
+            bloom,
+            buckets,
+            chains,
+        })
+    }
+
+    /// Whether the bloom filter may contain the symbol of `hash`.
+    pub fn may_contain(&self, hash: u32) -> bool {
+        let word = self.bloom[(hash / u64::BITS) as usize & (self.bloom.len() - 1)];
+        let second = hash.checked_shr(self.bloom_shift).unwrap_or(0);
+        let mask = 1 << (hash % u64::BITS) | 1 << (second % u64::BITS);
+        word & mask == mask
+    }
+
+    /// Symbol indices in the chain of the bucket for `name` whose hash matches.
+    /// The walk stops at the end of chain mark or at index out of the chain.
+    pub fn candidates(&self, name: &[u8]) -> Vec<u32> {
+        let hash = gnu_hash(name);
+        if !self.may_contain(hash) {
+            return vec![];
+        }
+
+        let mut ret = vec![];
+        let mut index = self.buckets[hash as usize % self.buckets.len()];
+        // Bucket of no symbols is 0, and others are at or after the offset.
+        if index < self.symbol_offset {
+            return ret;
+        }
+        while let Some(x) = self.chains.get((index - self.symbol_offset) as usize) {
+            if (x ^ hash) >> 1 == 0 {
+                ret.push(index);
+            }
+            if x & 1 != 0 {
+                break;
+            }
+            index = match index.checked_add(1) {
+                Some(x) => x,
+                None => break,
+            };
+        }
+        ret
+    }
+}
+
+/// Symbol hash table of SHT_HASH or SHT_GNU_HASH section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashTable {
+    Sysv(SysvHashTable),
+    Gnu(GnuHashTable),
+}
+
+impl HashTable {
+    /// Symbol indices to compare with `name`, in the order ld.so does.
+    pub fn candidates(&self, name: &[u8]) -> Vec<u32> {
+        match self {
+            HashTable::Sysv(x) => x.candidates(name),
+            HashTable::Gnu(x) => x.candidates(name),
+        }
+    }
+}
+
+/// Whether ld.so takes the symbol as a definition, like check_match of glibc.
+fn is_definition(entry: &symbol::Header) -> bool {
+    let shndx = entry.get_st_shndx_unwrap();
+    let typ = entry.get_st_info_unwrap().typ();
+    if shndx.is_SHN_UNDEF()
+        || (entry.get_st_value_unwrap().inner() == 0 && !shndx.is_SHN_ABS() && !typ.is_STT_TLS())
+    {
+        return false;
+    }
+    matches!(
+        typ,
+        symbol::Type::STT_NOTYPE
+            | symbol::Type::STT_OBJECT
+            | symbol::Type::STT_FUNC
+            | symbol::Type::STT_COMMON
+            | symbol::Type::STT_TLS
+            | symbol::Type::STT_GNU_IFUNC
+    )
+}
+
+impl<'a> Elf<'a> {
+    /// Hash table in the section. None if the section is not SHT_HASH nor SHT_GNU_HASH.
+    pub fn hash_table(&self, sh: &section_header::Header) -> Option<Result<HashTable, HashError>> {
+        let ty = sh.get_sh_type_unwrap();
+        if !ty.is_SHT_HASH() && !ty.is_SHT_GNU_HASH() {
+            return None;
+        }
+        let Some(data) = self.section_data(sh) else {
+            return Some(Err(HashError::OutOfFile));
+        };
+        let table = if ty.is_SHT_GNU_HASH() {
+            GnuHashTable::parse(data).map(HashTable::Gnu)
+        } else {
+            SysvHashTable::parse(data).map(HashTable::Sysv)
+        };
+        Some(table)
+    }
+
+    /// Hash table section that ld.so uses for dynamic symbols, and the symbol table it links to.
+    /// SHT_GNU_HASH is preferred to SHT_HASH as ld.so does.
+    pub fn dynamic_hash_section(
+        &self,
+    ) -> Option<(&section_header::Header, &section_header::Header)> {
+        let sections = self.section_headers();
+        let find = |t: section_header::Type| {
+            sections.iter().find_map(|sh| {
+                if *sh.get_sh_type_unwrap() != t {
+                    return None;
+                }
+                let symtab = sections.get(sh.get_sh_link_unwrap().inner() as usize)?;
+                symtab
+                    .get_sh_type_unwrap()
+                    .is_SHT_DYNSYM()
+                    .then_some((sh, symtab))
+            })
+        };
+        find(section_header::Type::SHT_GNU_HASH).or_else(|| find(section_header::Type::SHT_HASH))
+    }
+
+    /// Look up `name` in symbol table `symtab` through hash table `table`.
+    /// As ld.so does, the first defined global or weak symbol of the name is taken, and local
+    /// or undefined ones are skipped. Symbol versions are not considered.
+    pub fn lookup_symbol(
+        &self,
+        table: &HashTable,
+        symtab: &section_header::Header,
+        name: &str,
+    ) -> Option<(usize, Symbol)> {
+        for index in table.candidates(name.as_bytes()) {
+            let Some(symbol) = self.symbol(symtab, index as usize) else {
+                continue;
+            };
+            if symbol.name != name || !is_definition(&symbol.entry) {
+                continue;
+            }
+            if symbol.entry.get_st_info_unwrap().bind().is_STB_LOCAL() {
+                continue;
+            }
+            return Some((index as usize, symbol));
+        }
+        None
+    }
+
+    /// Look up dynamic symbol defined in this file, with its index in the dynamic symbol table.
+    /// This walks the hash table that ld.so uses, so a symbol missing in the table is not found.
+    pub fn lookup_dynamic_symbol(&self, name: &str) -> Result<Option<(usize, Symbol)>, HashError> {
+        let (sh, symtab) = self.dynamic_hash_section().ok_or(HashError::Missing)?;
+        let table = self.hash_table(sh).ok_or(HashError::Missing)??;
+        Ok(self.lookup_symbol(&table, symtab, name))
+    }
+
+    /// Indices of symbols in `symtab` that cannot be reached through hash table `table`.
+    /// Only exported definitions are checked, since ld.so never looks up others. Linkers
+    /// leave undefined symbols out of GNU hash table even after its symbol offset.
+    pub fn unreachable_symbols(
+        &self,
+        table: &HashTable,
+        symtab: &section_header::Header,
+    ) -> Vec<usize> {
+        self.symbols(symtab)
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| {
+                is_definition(&symbol.entry)
+                    && !symbol.entry.get_st_info_unwrap().bind().is_STB_LOCAL()
+            })
+            .filter(|(i, symbol)| {
+                !table
+                    .candidates(symbol.name.as_bytes())
+                    .contains(&(*i as u32))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const LIBRARY: &[u8] = include_bytes!("../../../example_bin/hash_so");
+
+    fn tables(elf: &Elf) -> (HashTable, HashTable, section_header::Header) {
+        let table = |name| {
+            let sh = elf.section_by_name(name).unwrap();
+            elf.hash_table(sh).unwrap().unwrap()
+        };
+        let symtab = *elf.section_by_name(".dynsym").unwrap();
+        (table(".hash"), table(".gnu.hash"), symtab)
+    }
+
+    #[test]
+    fn hash_functions() {
+        assert_eq!(sysv_hash(b""), 0);
+        assert_eq!(sysv_hash(b"printf"), 0x077905a6);
+        assert_eq!(gnu_hash(b""), 0x1505);
+        assert_eq!(gnu_hash(b"printf"), 0x156b2bb8);
+        // High bits are folded into the lower ones.
+        assert_eq!(sysv_hash(b"abcdefghij") & 0xf000_0000, 0);
+    }
+
+    #[test]
+    fn parse() {
+        let s = Sequential::from_vec(LIBRARY.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let (sysv, gnu, symtab) = tables(&elf);
+        let count = elf.symbols(&symtab).len();
+
+        let HashTable::Sysv(sysv) = sysv else {
+            panic!("not SHT_HASH");
+        };
+        assert_eq!(sysv.chains.len(), count);
+        let HashTable::Gnu(gnu) = gnu else {
+            panic!("not SHT_GNU_HASH");
+        };
+        // Undefined symbols are not hashed.
+        assert_eq!(gnu.symbol_offset, 5);
+        assert_eq!(gnu.chains.len(), count - 5);
+        assert!(gnu.bloom.len().is_power_of_two());
+
+        let sh = elf.section_by_name(".dynsym").unwrap();
+        assert!(elf.hash_table(sh).is_none());
+        assert_eq!(
+            elf.dynamic_hash_section().map(|x| elf.section_name(x.0)),
+            Some(Some(".gnu.hash".to_string()))
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        let s = Sequential::from_vec(LIBRARY.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let (sysv, gnu, symtab) = tables(&elf);
+
+        for symbol in elf.symbols(&symtab).iter().skip(5) {
+            let (i, found) = elf.lookup_dynamic_symbol(&symbol.name).unwrap().unwrap();
+            assert_eq!(&found, symbol);
+            let by_sysv = elf.lookup_symbol(&sysv, &symtab, &symbol.name).unwrap();
+            assert_eq!(by_sysv.0, i);
+        }
+
+        let (_, square) = elf.lookup_dynamic_symbol("square").unwrap().unwrap();
+        assert_eq!(square.entry.get_st_value_unwrap().inner(), 0x110e);
+        // Static function
+        assert_eq!(elf.lookup_dynamic_symbol("helper"), Ok(None));
+        // Undefined symbols are not definitions.
+        assert_eq!(elf.lookup_dynamic_symbol("__cxa_finalize"), Ok(None));
+        assert_eq!(elf.lookup_symbol(&sysv, &symtab, "__cxa_finalize"), None);
+        assert_eq!(elf.lookup_symbol(&gnu, &symtab, ""), None);
+
+        assert_eq!(elf.unreachable_symbols(&sysv, &symtab), vec![]);
+        assert_eq!(elf.unreachable_symbols(&gnu, &symtab), vec![]);
+    }
+
+    #[test]
+    fn local_symbol_in_chain() {
+        let s = Sequential::from_vec(LIBRARY.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let (_, _, symtab) = tables(&elf);
+        let (square, _) = elf.lookup_dynamic_symbol("square").unwrap().unwrap();
+        let (cube, _) = elf.lookup_dynamic_symbol("cube").unwrap().unwrap();
+
+        // Make "cube" a local symbol named "square".
+        let mut v = LIBRARY.to_vec();
+        let entry = |i: usize| symtab.get_sh_offset_unwrap().inner() + i * symbol::SIZE;
+        let (name, local) = (entry(square), entry(cube));
+        v.copy_within(name..name + 4, local);
+        v[local + 4] = 0x02; // STB_LOCAL, STT_FUNC
+        let s = Sequential::from_vec(v);
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        // The local symbol comes first in the chain, and the global one still resolves.
+        let mut chains = vec![0; elf.symbols(&symtab).len()];
+        chains[cube] = square as u32;
+        let table = HashTable::Sysv(SysvHashTable {
+            buckets: vec![cube as u32],
+            chains,
+        });
+        assert_eq!(table.candidates(b"square"), vec![cube as u32, square as u32]);
+        assert_eq!(elf.lookup_symbol(&table, &symtab, "square").unwrap().0, square);
+    }
+
+    #[test]
+    fn bloom_filter() {
+        let s = Sequential::from_vec(LIBRARY.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let (_, gnu, symtab) = tables(&elf);
+        let HashTable::Gnu(mut gnu) = gnu else {
+            panic!("not SHT_GNU_HASH");
+        };
+
+        assert!(gnu.may_contain(gnu_hash(b"square")));
+        gnu.bloom.iter_mut().for_each(|x| *x = 0);
+        let table = HashTable::Gnu(gnu);
+        assert!(table.candidates(b"square").is_empty());
+        assert_eq!(elf.lookup_symbol(&table, &symtab, "square"), None);
+        assert_eq!(
+            elf.unreachable_symbols(&table, &symtab),
+            (5..19).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn broken_tables() {
+        #[rustfmt::skip]
+        let sysv = [
+            1, 0, 0, 0, 2, 0, 0, 0,
+            // bucket
+            1, 0, 0, 0,
+            // chain of the second symbol is missing
+            0, 0, 0, 0,
+        ];
+        assert_eq!(SysvHashTable::parse(&sysv), Err(HashError::Truncated));
+        assert_eq!(SysvHashTable::parse(&sysv[..4]), Err(HashError::Truncated));
+        assert_eq!(SysvHashTable::parse(&[0; 8]), Err(HashError::NoBuckets));
+        let mut empty = sysv;
+        empty[4] = 0;
+        let table = SysvHashTable::parse(&empty[..12]).unwrap();
+        assert_eq!(table.buckets, vec![1]);
+        assert!(table.chains.is_empty());
+        // Walk stops at index out of chain.
+        assert_eq!(table.candidates(b"x"), vec![1]);
+
+        #[rustfmt::skip]
+        let gnu = [
+            1, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 6, 0, 0, 0,
+        ];
+        assert_eq!(GnuHashTable::parse(&gnu), Err(HashError::BadBloomSize(3)));
+        let mut gnu = gnu.to_vec();
+        gnu[8] = 1;
+        assert_eq!(GnuHashTable::parse(&gnu), Err(HashError::Truncated));
+        gnu.extend([0xff; 8]);
+        gnu.extend([0; 4]);
+        let table = GnuHashTable::parse(&gnu).unwrap();
+        assert!(table.chains.is_empty());
+        // Empty bucket
+        assert_eq!(table.candidates(b"x"), vec![]);
+    }
+}
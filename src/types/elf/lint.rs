@@ -0,0 +1,621 @@
+//! Consistency checks across ELF structures.
+//! This is intended to triage malformed ELF files, so it never panics on broken input.
+
+use super::{compression_header, program_header, section_header, DecompressError, Elf, HashError};
+use crate::file::Seekable;
+use crate::types::model::{Diagnostic, ModelFromU8Array, Severity};
+
+/// A finding of lint. `offset` is file offset of the structure that the finding is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub offset: usize,
+    pub diagnostic: Diagnostic,
+}
+
+impl Finding {
+    pub fn new(offset: usize, diagnostic: Diagnostic) -> Self {
+        Self { offset, diagnostic }
+    }
+}
+
+impl core::fmt::Display for Finding {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{:#010x}: {}", self.offset, self.diagnostic)
+    }
+}
+
+/// Offsets of ELF header fields.
+mod offset {
+    pub const E_IDENT: usize = 0x00;
+    pub const E_ENTRY: usize = 0x18;
+    pub const E_PHOFF: usize = 0x20;
+    pub const E_SHOFF: usize = 0x28;
+    pub const E_PHENTSIZE: usize = 0x36;
+    pub const E_SHENTSIZE: usize = 0x3a;
+    pub const E_SHSTRNDX: usize = 0x3e;
+}
+
+/// Lint ELF file that starts at offset 0 of `seeker`.
+pub fn lint(seeker: Seekable) -> Vec<Finding> {
+    match Elf::parse(seeker) {
+        Ok(elf) => lint_elf(&elf),
+        Err(_) => vec![Finding::new(
+            0,
+            Diagnostic::new(Severity::Error, format!("{} bytes", seeker.len()), "ELF header is truncated"),
+        )],
+    }
+}
+
+/// Lint parsed ELF file.
+pub fn lint_elf(elf: &Elf) -> Vec<Finding> {
+    let mut ret = vec![];
+
+    check_ident(elf, &mut ret);
+    check_header(elf, &mut ret);
+    check_tables(elf, &mut ret);
+    check_sections(elf, &mut ret);
+    check_hash_tables(elf, &mut ret);
+    check_segments(elf, &mut ret);
+    check_loads(elf, &mut ret);
+    check_entry(elf, &mut ret);
+
+    ret
+}
+
+fn error(offset: usize, path: &str, value: impl Into<String>, reason: impl Into<String>) -> Finding {
+    Finding::new(offset, Diagnostic::new(Severity::Error, value, reason).nest(path))
+}
+
+fn warning(offset: usize, path: &str, value: impl Into<String>, reason: impl Into<String>) -> Finding {
+    Finding::new(offset, Diagnostic::new(Severity::Warning, value, reason).nest(path))
+}
+
+/// Return true if [`offset`, `offset` + `size`) is in file.
+fn is_in_file(elf: &Elf, offset: usize, size: usize) -> bool {
+    match offset.checked_add(size) {
+        Some(end) => end <= elf.seeker().len(),
+        None => false,
+    }
+}
+
+fn section_offset(elf: &Elf, index: usize) -> usize {
+    let h = elf.header();
+    let entsize = (h.get_e_shentsize_unwrap().inner() as usize).max(section_header::SIZE);
+    h.get_e_shoff_unwrap().inner().wrapping_add(index.wrapping_mul(entsize))
+}
+
+fn segment_offset(elf: &Elf, index: usize) -> usize {
+    let h = elf.header();
+    let entsize = (h.get_e_phentsize_unwrap().inner() as usize).max(program_header::SIZE);
+    h.get_e_phoff_unwrap().inner().wrapping_add(index.wrapping_mul(entsize))
+}
+
+fn check_ident(elf: &Elf, ret: &mut Vec<Finding>) {
+    let ident = elf.header().get_e_ident_unwrap().inner_ref().inner();
+
+    if ident[..4] != [0x7f, b'E', b'L', b'F'] {
+        ret.push(error(offset::E_IDENT, "e_ident", format!("{:x?}", &ident[..4]), "bad magic"));
+    }
+    if ident[4] != 2 {
+        ret.push(error(
+            offset::E_IDENT + 4,
+            "e_ident[EI_CLASS]",
+            ident[4].to_string(),
+            "only ELFCLASS64 is supported",
+        ));
+    }
+    if ident[5] != 1 {
+        ret.push(error(
+            offset::E_IDENT + 5,
+            "e_ident[EI_DATA]",
+            ident[5].to_string(),
+            "only ELFDATA2LSB is supported",
+        ));
+    }
+    if ident[6] != 1 {
+        ret.push(warning(
+            offset::E_IDENT + 6,
+            "e_ident[EI_VERSION]",
+            ident[6].to_string(),
+            "not EV_CURRENT",
+        ));
+    }
+}
+
+fn check_header(elf: &Elf, ret: &mut Vec<Finding>) {
+    ret.extend(elf.header().validate().into_iter().map(|d| Finding::new(0, d)));
+}
+
+fn check_tables(elf: &Elf, ret: &mut Vec<Finding>) {
+    let h = elf.header();
+    let shnum = elf.section_count();
+    let phnum = elf.segment_count();
+
+    let shentsize = h.get_e_shentsize_unwrap().inner() as usize;
+    if shnum != 0 && shentsize != section_header::SIZE {
+        ret.push(error(
+            offset::E_SHENTSIZE,
+            "e_shentsize",
+            shentsize.to_string(),
+            format!("section header size is {}", section_header::SIZE),
+        ));
+    }
+    let phentsize = h.get_e_phentsize_unwrap().inner() as usize;
+    if phnum != 0 && phentsize != program_header::SIZE {
+        ret.push(error(
+            offset::E_PHENTSIZE,
+            "e_phentsize",
+            phentsize.to_string(),
+            format!("program header size is {}", program_header::SIZE),
+        ));
+    }
+
+    let shoff = h.get_e_shoff_unwrap().inner();
+    let table = shnum.checked_mul(shentsize.max(section_header::SIZE));
+    if shnum != 0 && !table.is_some_and(|size| is_in_file(elf, shoff, size)) {
+        ret.push(error(
+            offset::E_SHOFF,
+            "e_shoff",
+            format!("{:#x}", shoff),
+            format!("section header table ({} entries) exceeds file", shnum),
+        ));
+    }
+    let phoff = h.get_e_phoff_unwrap().inner();
+    let table = phnum.checked_mul(phentsize.max(program_header::SIZE));
+    if phnum != 0 && !table.is_some_and(|size| is_in_file(elf, phoff, size)) {
+        ret.push(error(
+            offset::E_PHOFF,
+            "e_phoff",
+            format!("{:#x}", phoff),
+            format!("program header table ({} entries) exceeds file", phnum),
+        ));
+    }
+
+    if let Some(shstrndx) = elf.section_name_table_index() {
+        match elf.section_headers().get(shstrndx) {
+            None => ret.push(error(
+                offset::E_SHSTRNDX,
+                "e_shstrndx",
+                shstrndx.to_string(),
+                format!("not less than e_shnum ({})", shnum),
+            )),
+            Some(sh) if !sh.get_sh_type_unwrap().is_SHT_STRTAB() => ret.push(error(
+                offset::E_SHSTRNDX,
+                "e_shstrndx",
+                shstrndx.to_string(),
+                "section header string table is not SHT_STRTAB",
+            )),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Section types that `sh_link` of `ty` must point.
+/// None if `sh_link` has no meaning for the type.
+fn expected_link_types(ty: &section_header::Type) -> Option<&'static [section_header::Type]> {
+    use section_header::Type;
+
+    const STRTAB: &[Type] = &[Type::SHT_STRTAB];
+    const SYMBOLS: &[Type] = &[Type::SHT_SYMTAB, Type::SHT_DYNSYM];
+    const DYNSYM: &[Type] = &[Type::SHT_DYNSYM];
+    const SYMTAB: &[Type] = &[Type::SHT_SYMTAB];
+
+    if ty.is_SHT_SYMTAB()
+        || ty.is_SHT_DYNSYM()
+        || ty.is_SHT_DYNAMIC()
+        || ty.is_SHT_GNU_verdef()
+        || ty.is_SHT_GNU_verneed()
+    {
+        Some(STRTAB)
+    } else if ty.is_SHT_REL() || ty.is_SHT_RELA() || ty.is_SHT_HASH() || ty.is_SHT_GNU_HASH() {
+        Some(SYMBOLS)
+    } else if ty.is_SHT_GNU_versym() {
+        Some(DYNSYM)
+    } else if ty.is_SHT_SYMTAB_SHNDX() || ty.is_SHT_GROUP() {
+        Some(SYMTAB)
+    } else {
+        None
+    }
+}
+
+fn check_sections(elf: &Elf, ret: &mut Vec<Finding>) {
+    let sections = elf.section_headers();
+
+    for (i, sh) in sections.iter().enumerate() {
+        let at = section_offset(elf, i);
+        let path = format!("section[{}]", i);
+
+        ret.extend(sh.validate().into_iter().map(|d| Finding::new(at, d.nest(&path))));
+
+        // The initial entry holds extended numbers.
+        if i == 0 {
+            continue;
+        }
+
+        let ty = sh.get_sh_type_unwrap();
+        let offset = sh.get_sh_offset_unwrap().inner();
+        let size = sh.get_sh_size_unwrap().inner() as usize;
+        if !ty.is_SHT_NOBITS() && !is_in_file(elf, offset, size) {
+            ret.push(error(
+                at,
+                &path,
+                format!("[{:#x}, {:#x})", offset, offset as u128 + size as u128),
+                "section contents exceed file",
+            ));
+        }
+
+        let align = sh.get_sh_addralign_unwrap().inner();
+        let addr = sh.get_sh_addr_unwrap().inner() as u64;
+        if align.is_power_of_two() && !addr.is_multiple_of(align) {
+            ret.push(error(
+                at,
+                &format!("{}.sh_addr", path),
+                format!("{:#x}", addr),
+                format!("not aligned to sh_addralign ({:#x})", align),
+            ));
+        }
+
+        let link = sh.get_sh_link_unwrap().inner() as usize;
+        if link != 0 {
+            match sections.get(link) {
+                None => ret.push(error(
+                    at,
+                    &format!("{}.sh_link", path),
+                    link.to_string(),
+                    "no such section",
+                )),
+                Some(target) => {
+                    let target_ty = target.get_sh_type_unwrap();
+                    if let Some(expected) = expected_link_types(ty) {
+                        if !expected.contains(target_ty) {
+                            ret.push(error(
+                                at,
+                                &format!("{}.sh_link", path),
+                                link.to_string(),
+                                format!("{} links to {}", ty, target_ty),
+                            ));
+                        }
+                    }
+                }
+            }
+        } else if ty.is_SHT_SYMTAB() || ty.is_SHT_DYNSYM() || ty.is_SHT_HASH() || ty.is_SHT_GNU_HASH() {
+            ret.push(error(
+                at,
+                &format!("{}.sh_link", path),
+                link.to_string(),
+                format!("{} needs a linked section", ty),
+            ));
+        }
+
+        let flags = sh.get_sh_flags_unwrap();
+        let info = sh.get_sh_info_unwrap().inner() as usize;
+        if flags.get_SHF_INFO_LINK() && sections.get(info).is_none() {
+            ret.push(error(
+                at,
+                &format!("{}.sh_info", path),
+                info.to_string(),
+                "SHF_INFO_LINK is set, but no such section",
+            ));
+        }
+
+        // Contents out of file are reported above.
+        match elf.compression_header(sh) {
+            Some(Ok(ch)) => check_compression_header(&ch, size, at, &path, ret),
+            Some(Err(DecompressError::TruncatedHeader)) => ret.push(error(
+                at,
+                &path,
+                format!("{} bytes", size),
+                "compression header is truncated",
+            )),
+            _ => {}
+        }
+    }
+}
+
+/// Deflate encodes at most 258 bytes in a match of 2 bits, so zlib data expands at most 1032 times.
+const MAX_DEFLATE_RATIO: u128 = 1032;
+
+/// Check the header only. Decompressing every section here would let a small file cost a lot of
+/// work, so broken streams are left to the readers of the contents.
+fn check_compression_header(
+    ch: &compression_header::Header,
+    section_size: usize,
+    at: usize,
+    path: &str,
+    ret: &mut Vec<Finding>,
+) {
+    let ty = ch.get_ch_type_unwrap();
+    if !ty.is_ELFCOMPRESS_ZLIB() && !ty.is_ELFCOMPRESS_ZSTD() && ty.class().is_none() {
+        ret.push(error(at, &format!("{}.ch_type", path), ty.to_string(), "unknown compression type"));
+    }
+
+    let size = ch.get_ch_size_unwrap().inner();
+    let stream = section_size.saturating_sub(compression_header::SIZE);
+    if ty.is_ELFCOMPRESS_ZLIB() && size as u128 > stream as u128 * MAX_DEFLATE_RATIO {
+        ret.push(error(
+            at,
+            &format!("{}.ch_size", path),
+            format!("{:#x}", size),
+            format!("{:#x} bytes of zlib data cannot expand to this size", stream),
+        ));
+    }
+
+    let align = ch.get_ch_addralign_unwrap().inner();
+    if align != 0 && !align.is_power_of_two() {
+        ret.push(error(
+            at,
+            &format!("{}.ch_addralign", path),
+            format!("{:#x}", align),
+            "not a power of two",
+        ));
+    }
+}
+
+/// Every hashed symbol must be found through the hash table, or ld.so cannot resolve it.
+fn check_hash_tables(elf: &Elf, ret: &mut Vec<Finding>) {
+    let sections = elf.section_headers();
+
+    for (i, sh) in sections.iter().enumerate() {
+        let at = section_offset(elf, i);
+        let path = format!("section[{}]", i);
+
+        let table = match elf.hash_table(sh) {
+            None => continue,
+            Some(Ok(x)) => x,
+            // Contents out of file are reported in check_sections.
+            Some(Err(HashError::OutOfFile)) => continue,
+            Some(Err(e)) => {
+                ret.push(error(at, &path, sh.get_sh_type_unwrap().to_string(), e.to_string()));
+                continue;
+            }
+        };
+        // Missing link is reported in check_sections.
+        let Some(symtab) = sections.get(sh.get_sh_link_unwrap().inner() as usize) else {
+            continue;
+        };
+        for index in elf.unreachable_symbols(&table, symtab) {
+            let name = elf.symbol(symtab, index).map(|x| x.name).unwrap_or_default();
+            ret.push(error(
+                at,
+                &path,
+                format!("symbol[{}] {}", index, name),
+                "symbol is not reachable through hash table",
+            ));
+        }
+    }
+}
+
+fn check_segments(elf: &Elf, ret: &mut Vec<Finding>) {
+    for (i, ph) in elf.program_headers().iter().enumerate() {
+        let at = segment_offset(elf, i);
+        let path = format!("segment[{}]", i);
+
+        ret.extend(ph.validate().into_iter().map(|d| Finding::new(at, d.nest(&path))));
+
+        let offset = ph.get_p_offset_unwrap().inner();
+        let filesz = ph.get_p_filesz_unwrap().inner();
+        let memsz = ph.get_p_memsz_unwrap().inner();
+        if !is_in_file(elf, offset, filesz as usize) {
+            ret.push(error(
+                at,
+                &path,
+                format!("[{:#x}, {:#x})", offset, offset as u128 + filesz as u128),
+                "segment contents exceed file",
+            ));
+        }
+        if filesz > memsz {
+            ret.push(error(
+                at,
+                &format!("{}.p_filesz", path),
+                format!("{:#x}", filesz),
+                format!("greater than p_memsz ({:#x})", memsz),
+            ));
+        }
+
+        let align = ph.get_p_align_unwrap().inner();
+        let vaddr = ph.get_p_vaddr_unwrap().inner() as u64;
+        if ph.get_p_type_unwrap().is_PT_LOAD()
+            && align.is_power_of_two()
+            && vaddr % align != offset as u64 % align
+        {
+            ret.push(error(
+                at,
+                &format!("{}.p_vaddr", path),
+                format!("{:#x}", vaddr),
+                format!("not congruent to p_offset ({:#x}) modulo p_align ({:#x})", offset, align),
+            ));
+        }
+    }
+}
+
+/// PT_LOAD segments must be sorted by p_vaddr, and must not overlap.
+fn check_loads(elf: &Elf, ret: &mut Vec<Finding>) {
+    let mut previous: Option<(usize, u128)> = None;
+
+    for (i, ph) in elf.program_headers().iter().enumerate() {
+        if !ph.get_p_type_unwrap().is_PT_LOAD() {
+            continue;
+        }
+        let at = segment_offset(elf, i);
+        let path = format!("segment[{}].p_vaddr", i);
+        let start = ph.get_p_vaddr_unwrap().inner() as u128;
+        let end = start + ph.get_p_memsz_unwrap().inner() as u128;
+
+        if let Some((prev, prev_end)) = previous {
+            let prev_start = elf.program_headers()[prev].get_p_vaddr_unwrap().inner() as u128;
+            if start < prev_start {
+                ret.push(error(
+                    at,
+                    &path,
+                    format!("{:#x}", start),
+                    format!("PT_LOAD is not sorted, previous is segment[{}]", prev),
+                ));
+            } else if start < prev_end {
+                ret.push(error(
+                    at,
+                    &path,
+                    format!("{:#x}", start),
+                    format!("PT_LOAD overlaps segment[{}] that ends at {:#x}", prev, prev_end),
+                ));
+            }
+        }
+
+        previous = Some((i, end));
+    }
+}
+
+/// Entry point should be in an executable PT_LOAD.
+fn check_entry(elf: &Elf, ret: &mut Vec<Finding>) {
+    let ty = elf.header().get_e_type_unwrap();
+    let entry = elf.header().get_e_entry_unwrap().inner();
+    if !(ty.is_ET_EXEC() || ty.is_ET_DYN()) || entry == 0 || elf.program_headers().is_empty() {
+        return;
+    }
+
+    let is_covered = elf.program_headers().iter().any(|ph| {
+        let start = ph.get_p_vaddr_unwrap().inner();
+        let size = ph.get_p_memsz_unwrap().inner() as usize;
+        ph.get_p_type_unwrap().is_PT_LOAD()
+            && ph.get_p_flags_unwrap().get_PF_X()
+            && start <= entry
+            && entry - start < size
+    });
+    if !is_covered {
+        ret.push(warning(
+            offset::E_ENTRY,
+            "e_entry",
+            format!("{:#x}", entry),
+            "not in an executable PT_LOAD segment",
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::elf::sysv_hash;
+
+    const OUT: &[u8] = include_bytes!("../../../example_bin/out");
+
+    fn errors(v: Vec<u8>) -> Vec<Finding> {
+        let s = Sequential::from_vec(v);
+        lint(s.to_seeakble())
+            .into_iter()
+            .filter(|f| f.diagnostic.severity == Severity::Error)
+            .collect()
+    }
+
+    #[test]
+    fn lint_example_is_clean() {
+        assert_eq!(errors(OUT.to_vec()), vec![]);
+    }
+
+    #[test]
+    fn lint_truncated() {
+        let e = errors(OUT[..0x20].to_vec());
+        assert_eq!(e.len(), 1);
+        assert_eq!(e[0].diagnostic.reason, "ELF header is truncated");
+    }
+
+    #[test]
+    fn lint_broken_shstrndx() {
+        let mut v = OUT.to_vec();
+        v[offset::E_SHSTRNDX] = 37;
+
+        let e = errors(v);
+        assert_eq!(e.len(), 1);
+        assert_eq!(e[0].offset, offset::E_SHSTRNDX);
+        assert_eq!(e[0].diagnostic.path, "e_shstrndx");
+    }
+
+    #[test]
+    fn lint_broken_section_range() {
+        let mut v = OUT.to_vec();
+        // sh_size of .interp (section[1])
+        let at = 0x4700 + 0x40 + 0x20;
+        v[at..at + 8].copy_from_slice(&0x10_0000u64.to_le_bytes());
+
+        let e = errors(v);
+        assert_eq!(e.len(), 1);
+        assert_eq!(e[0].offset, 0x4740);
+        assert_eq!(e[0].diagnostic.path, "section[1]");
+    }
+
+    #[test]
+    fn lint_overlapped_load() {
+        let mut v = OUT.to_vec();
+        // p_vaddr of the second PT_LOAD (segment[3]) moves into the first.
+        let at = 0x40 + 0x38 * 3 + 0x10;
+        v[at..at + 8].copy_from_slice(&0x0u64.to_le_bytes());
+
+        let e = errors(v);
+        assert!(e.iter().any(|f| f.diagnostic.reason.starts_with("PT_LOAD overlaps")));
+    }
+
+    #[test]
+    fn lint_unreachable_symbol() {
+        let mut v = include_bytes!("../../../example_bin/hash_so").to_vec();
+        assert_eq!(errors(v.clone()), vec![]);
+
+        let s = Sequential::from_vec(v.clone());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let sh = elf.section_by_name(".hash").unwrap();
+        let symtab = elf.section_by_name(".dynsym").unwrap();
+        let offset = sh.get_sh_offset_unwrap().inner();
+        let nbucket = u32::from_le_bytes(v[offset..offset + 4].try_into().unwrap());
+        let bucket = sysv_hash(b"square") % nbucket;
+        let square = elf.symbols(symtab).iter().position(|x| x.name == "square").unwrap();
+        // Empty the bucket of square.
+        let at = offset + 8 + bucket as usize * 4;
+        v[at..at + 4].copy_from_slice(&0u32.to_le_bytes());
+
+        let e = errors(v);
+        assert!(!e.is_empty());
+        for f in &e {
+            assert_eq!(f.offset, section_offset(&elf, 2));
+            assert_eq!(f.diagnostic.reason, "symbol is not reachable through hash table");
+        }
+        assert!(e.iter().any(|f| f.diagnostic.value == format!("symbol[{}] square", square)));
+    }
+
+    #[test]
+    fn lint_broken_compressed_section() {
+        let mut v = include_bytes!("../../../example_bin/dwarf5_zlib").to_vec();
+        assert_eq!(errors(v.clone()), vec![]);
+
+        let s = Sequential::from_vec(v.clone());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+        let (i, sh) = elf
+            .section_headers()
+            .iter()
+            .enumerate()
+            .find(|(_, sh)| elf.section_name(sh).as_deref() == Some(".debug_info"))
+            .unwrap();
+        let offset = sh.get_sh_offset_unwrap().inner();
+        let path = format!("section[{}]", i);
+
+        // Broken zlib stream after the compression header is not decompressed here.
+        v[offset + 0x18] = 0;
+        assert_eq!(errors(v.clone()), vec![]);
+
+        v[offset..offset + 4].copy_from_slice(&7u32.to_le_bytes());
+        v[offset + 0x10..offset + 0x18].copy_from_slice(&3u64.to_le_bytes());
+        let e = errors(v.clone());
+        let reasons: Vec<_> = e.iter().map(|f| (f.diagnostic.path.as_str(), f.diagnostic.reason.as_str())).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (format!("{}.ch_type", path).as_str(), "unknown compression type"),
+                (format!("{}.ch_addralign", path).as_str(), "not a power of two"),
+            ]
+        );
+
+        v[offset..offset + 4].copy_from_slice(&1u32.to_le_bytes());
+        v[offset + 0x10..offset + 0x18].copy_from_slice(&1u64.to_le_bytes());
+        v[offset + 8..offset + 0x10].copy_from_slice(&u64::MAX.to_le_bytes());
+        let e = errors(v);
+        assert_eq!(e.len(), 1);
+        assert_eq!(e[0].diagnostic.path, format!("{}.ch_size", path));
+    }
+}
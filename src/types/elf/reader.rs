@@ -0,0 +1,382 @@
+use super::{elf_header, note, program_header, section_header, symbol, DecompressError};
+use crate::file::Seekable;
+use std::cell::OnceCell;
+use crate::types::{primitive::NullTermString, FromU8Array, FromU8Error};
+
+/// Symbol table entry and its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub entry: symbol::Header,
+}
+
+/// Note in PT_NOTE segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note<'a> {
+    /// Owner name without the terminating null, like "CORE" or "GNU".
+    pub name: String,
+    pub typ: note::Type,
+    pub desc: &'a [u8],
+}
+
+/// ELF file.
+/// This holds the ELF header and header tables. Other structures are interpreted on demand.
+#[derive(Debug)]
+pub struct Elf<'a> {
+    seeker: Seekable<'a>,
+    header: elf_header::Header,
+    section_headers: Vec<section_header::Header>,
+    program_headers: Vec<program_header::Header>,
+    /// Decompressed contents of compressed sections, indexed by section.
+    pub(super) decompressed: Vec<OnceCell<Result<Vec<u8>, DecompressError>>>,
+}
+
+impl<'a> Elf<'a> {
+    /// Interpret ELF file that starts at offset 0 of `seeker`.
+    /// Header tables are read as much as possible. Use `lint` to know why some of them are missing.
+    /// The error holds the partially read header on heap, since the header is large.
+    pub fn parse(seeker: Seekable<'a>) -> Result<Self, FromU8Error<Box<elf_header::Header>>> {
+        let (_, header) = seeker
+            .interpret_abs_pos::<elf_header::Header>(0)
+            .to_tuple()
+            .1
+            .map_err(|e| e.into())?;
+
+        let mut ret = Self {
+            seeker,
+            header,
+            section_headers: vec![],
+            program_headers: vec![],
+            decompressed: vec![],
+        };
+
+        ret.section_headers = ret.read_table(
+            ret.header.get_e_shoff_unwrap().inner(),
+            ret.header.get_e_shentsize_unwrap().inner() as usize,
+            section_header::SIZE,
+            ret.section_count(),
+        );
+        ret.program_headers = ret.read_table(
+            ret.header.get_e_phoff_unwrap().inner(),
+            ret.header.get_e_phentsize_unwrap().inner() as usize,
+            program_header::SIZE,
+            ret.segment_count(),
+        );
+        ret.decompressed = ret.section_headers.iter().map(|_| OnceCell::new()).collect();
+
+        Ok(ret)
+    }
+
+    /// Read table entries until error.
+    /// If `entsize` is too short for an entry, entries are read as they are packed.
+    fn read_table<T>(&self, offset: usize, entsize: usize, size: usize, count: usize) -> Vec<T>
+    where
+        T: FromU8Array,
+    {
+        let entsize = entsize.max(size);
+        let mut ret = vec![];
+
+        for i in 0..count {
+            let at = match i.checked_mul(entsize).and_then(|x| x.checked_add(offset)) {
+                Some(at) => at,
+                None => break,
+            };
+            match self.seeker.interpret_abs_pos::<T>(at).to_tuple().1 {
+                Ok((_, entry)) => ret.push(entry),
+                Err(_) => break,
+            }
+        }
+
+        ret
+    }
+
+    /// The first section header. This holds extended numbers if they overflow in ELF header.
+    fn initial_section_header(&self) -> Option<section_header::Header> {
+        let shoff = self.header.get_e_shoff_unwrap().inner();
+        if shoff == 0 {
+            return None;
+        }
+        self.seeker
+            .interpret_abs_pos::<section_header::Header>(shoff)
+            .to_tuple()
+            .1
+            .ok()
+            .map(|x| x.1)
+    }
+
+    /// Number of section headers. This follows extended numbering.
+    pub fn section_count(&self) -> usize {
+        let shnum = self.header.get_e_shnum_unwrap().inner() as usize;
+        if shnum != 0 {
+            return shnum;
+        }
+        match self.initial_section_header() {
+            Some(sh) => sh.get_sh_size_unwrap().inner() as usize,
+            None => 0,
+        }
+    }
+
+    /// Number of program headers. This follows extended numbering.
+    pub fn segment_count(&self) -> usize {
+        let phnum = self.header.get_e_phnum_unwrap();
+        if !phnum.is_PN_XNUM() {
+            return phnum.inner() as usize;
+        }
+        match self.initial_section_header() {
+            Some(sh) => sh.get_sh_info_unwrap().inner() as usize,
+            None => phnum.inner() as usize,
+        }
+    }
+
+    /// Index of section header string table. This follows extended numbering.
+    /// If there is no table, this returns None.
+    pub fn section_name_table_index(&self) -> Option<usize> {
+        let shstrndx = self.header.get_e_shstrndx_unwrap();
+        if shstrndx.is_SHN_UNDEF() {
+            return None;
+        }
+        if shstrndx.is_SHN_XINDEX() {
+            return self
+                .initial_section_header()
+                .map(|sh| sh.get_sh_link_unwrap().inner() as usize);
+        }
+        Some(shstrndx.inner() as usize)
+    }
+
+    pub fn seeker(&self) -> Seekable<'a> {
+        self.seeker
+    }
+
+    pub fn header(&self) -> &elf_header::Header {
+        &self.header
+    }
+
+    pub fn section_headers(&self) -> &[section_header::Header] {
+        &self.section_headers
+    }
+
+    pub fn program_headers(&self) -> &[program_header::Header] {
+        &self.program_headers
+    }
+
+    /// Raw contents of section. SHT_NOBITS sections are empty.
+    /// If the contents are out of file, this returns None.
+    pub fn section_data(&self, sh: &section_header::Header) -> Option<&'a [u8]> {
+        if sh.get_sh_type_unwrap().is_SHT_NOBITS() {
+            return Some(&[]);
+        }
+        self.seeker.bytes_abs_pos(
+            sh.get_sh_offset_unwrap().inner(),
+            sh.get_sh_size_unwrap().inner() as usize,
+        )
+    }
+
+    /// Raw contents of segment in file.
+    /// If the contents are out of file, this returns None.
+    pub fn segment_data(&self, ph: &program_header::Header) -> Option<&'a [u8]> {
+        self.seeker.bytes_abs_pos(
+            ph.get_p_offset_unwrap().inner(),
+            ph.get_p_filesz_unwrap().inner() as usize,
+        )
+    }
+
+    /// Notes in all PT_NOTE segments.
+    /// Notes are read until the end of segment or the first malformed one.
+    pub fn notes(&self) -> Vec<Note<'a>> {
+        let mut ret = vec![];
+
+        for ph in &self.program_headers {
+            if !ph.get_p_type_unwrap().is_PT_NOTE() {
+                continue;
+            }
+            let data = match self.segment_data(ph) {
+                Some(x) => x,
+                None => continue,
+            };
+            // GNU property notes are 8 byte aligned. Others are 4 byte aligned even in 64 bit files.
+            let align = if ph.get_p_align_unwrap().inner() == 8 {
+                8
+            } else {
+                4
+            };
+            let pad = |x: usize| x.checked_add(align - 1).map(|x| x & !(align - 1));
+
+            let mut at = 0;
+            while let Some(Ok((_, header))) = data.get(at..).map(note::Header::from_slice) {
+                let namesz = header.get_n_namesz_unwrap().inner() as usize;
+                let descsz = header.get_n_descsz_unwrap().inner() as usize;
+
+                let name_at = at + note::SIZE;
+                let desc_at = match pad(namesz).and_then(|x| x.checked_add(name_at)) {
+                    Some(x) => x,
+                    None => break,
+                };
+                let (name, desc) = match (
+                    data.get(name_at..name_at + namesz),
+                    desc_at
+                        .checked_add(descsz)
+                        .and_then(|end| data.get(desc_at..end)),
+                ) {
+                    (Some(name), Some(desc)) => (name, desc),
+                    _ => break,
+                };
+
+                ret.push(Note {
+                    name: String::from_utf8_lossy(name)
+                        .trim_end_matches('\0')
+                        .to_string(),
+                    typ: *header.get_n_type_unwrap(),
+                    desc,
+                });
+                at = match pad(desc_at + descsz) {
+                    Some(x) => x,
+                    None => break,
+                };
+            }
+        }
+
+        ret
+    }
+
+    /// Contents of memory from `addr` to the end of the PT_LOAD segment that contains it.
+    /// Only the part in file is available, so this returns None for bss and pages not dumped in core files.
+    pub fn memory_at(&self, addr: u64) -> Option<&'a [u8]> {
+        self.program_headers
+            .iter()
+            .filter(|ph| ph.get_p_type_unwrap().is_PT_LOAD())
+            .find_map(|ph| {
+                let start = ph.get_p_vaddr_unwrap().inner() as u64;
+                let offset = usize::try_from(addr.checked_sub(start)?).ok()?;
+                if offset >= ph.get_p_filesz_unwrap().inner() as usize {
+                    return None;
+                }
+                self.segment_data(ph)?.get(offset..)
+            })
+    }
+
+    /// Read `len` bytes of memory at `addr`. The range may span adjacent PT_LOAD segments.
+    /// If any byte is not available in file, this returns None.
+    pub fn read_memory(&self, addr: u64, len: usize) -> Option<Vec<u8>> {
+        // `len` may be larger than the file, so this grows with the bytes found.
+        let mut ret = vec![];
+        while ret.len() < len {
+            let data = self.memory_at(addr.checked_add(ret.len() as u64)?)?;
+            let n = data.len().min(len - ret.len());
+            ret.extend_from_slice(&data[..n]);
+        }
+        Some(ret)
+    }
+
+    /// Null terminated string at `offset` in string table section `strtab`.
+    pub fn string_at(&self, strtab: &section_header::Header, offset: usize) -> Option<String> {
+        let data = self.section_data(strtab)?;
+        let (_, s) = NullTermString::from_slice(data.get(offset..)?).ok()?;
+        Some(s)
+    }
+
+    /// Name of section.
+    pub fn section_name(&self, sh: &section_header::Header) -> Option<String> {
+        let strtab = self.section_headers.get(self.section_name_table_index()?)?;
+        self.string_at(strtab, sh.get_sh_name_unwrap().inner() as usize)
+    }
+
+    /// Find section header by name.
+    pub fn section_by_name(&self, name: &str) -> Option<&section_header::Header> {
+        self.section_headers
+            .iter()
+            .find(|sh| self.section_name(sh).as_deref() == Some(name))
+    }
+
+    /// Symbol table for inspection. .symtab is preferred, and .dynsym is used if the file is stripped.
+    pub fn symbol_table(&self) -> Option<&section_header::Header> {
+        let find = |t: section_header::Type| {
+            self.section_headers
+                .iter()
+                .find(|sh| *sh.get_sh_type_unwrap() == t)
+        };
+        find(section_header::Type::SHT_SYMTAB).or_else(|| find(section_header::Type::SHT_DYNSYM))
+    }
+
+    /// Symbols in symbol table section `symtab`. Names are looked up in the string table of sh_link.
+    /// Entries are read as much as possible. If the string table is missing, names are empty.
+    pub fn symbols(&self, symtab: &section_header::Header) -> Vec<Symbol> {
+        let entsize = symtab.get_sh_entsize_unwrap().inner() as usize;
+        let count = symtab.get_sh_size_unwrap().inner() as usize / entsize.max(symbol::SIZE);
+        let strtab = self
+            .section_headers
+            .get(symtab.get_sh_link_unwrap().inner() as usize);
+
+        self.read_table::<symbol::Header>(
+            symtab.get_sh_offset_unwrap().inner(),
+            entsize,
+            symbol::SIZE,
+            count,
+        )
+        .into_iter()
+        .map(|entry| Symbol {
+            name: strtab
+                .and_then(|x| self.string_at(x, entry.get_st_name_unwrap().inner() as usize))
+                .unwrap_or_default(),
+            entry,
+        })
+        .collect()
+    }
+
+    /// Symbol at `index` of symbol table section `symtab`. None if it is out of the table or file.
+    pub fn symbol(&self, symtab: &section_header::Header, index: usize) -> Option<Symbol> {
+        let entsize = (symtab.get_sh_entsize_unwrap().inner() as usize).max(symbol::SIZE);
+        if index >= symtab.get_sh_size_unwrap().inner() as usize / entsize {
+            return None;
+        }
+        let offset = symtab.get_sh_offset_unwrap().inner().checked_add(index * entsize)?;
+        let entry = self
+            .read_table::<symbol::Header>(offset, entsize, symbol::SIZE, 1)
+            .pop()?;
+        let strtab = self
+            .section_headers
+            .get(symtab.get_sh_link_unwrap().inner() as usize);
+
+        Some(Symbol {
+            name: strtab
+                .and_then(|x| self.string_at(x, entry.get_st_name_unwrap().inner() as usize))
+                .unwrap_or_default(),
+            entry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const OUT: &[u8] = include_bytes!("../../../example_bin/out");
+
+    #[test]
+    fn symbols() {
+        let s = Sequential::from_vec(OUT.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        let symtab = elf.symbol_table().unwrap();
+        assert_eq!(elf.section_name(symtab).as_deref(), Some(".symtab"));
+        let symbols = elf.symbols(symtab);
+        assert_eq!(symbols.len(), 37);
+        assert_eq!(symbols[0].name, "");
+
+        let main = symbols.iter().find(|x| x.name == "main").unwrap();
+        let info = main.entry.get_st_info_unwrap();
+        assert_eq!(info.typ(), symbol::Type::STT_FUNC);
+        assert_eq!(info.bind(), symbol::Bind::STB_GLOBAL);
+        assert_eq!(main.entry.get_st_other_unwrap().visibility(), symbol::Visibility::STV_DEFAULT);
+        assert_eq!(main.entry.get_st_shndx_unwrap().inner(), 14);
+        assert_eq!(main.entry.get_st_value_unwrap().inner(), 0x1139);
+        assert_eq!(main.entry.get_st_size_unwrap().inner(), 26);
+
+        let dynsym = elf.section_by_name(".dynsym").unwrap();
+        assert_eq!(elf.symbols(dynsym).len(), 7);
+
+        assert_eq!(elf.symbol(symtab, 0).as_ref(), symbols.first());
+        assert_eq!(elf.symbol(symtab, 36).as_ref(), symbols.last());
+        assert_eq!(elf.symbol(symtab, 37), None);
+    }
+}
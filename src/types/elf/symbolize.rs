@@ -0,0 +1,260 @@
+use super::{section_header, symbol, Elf};
+use crate::types::dwarf::{Dwarf, LineTable, SourceLocation};
+
+/// Function that encloses an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbolized {
+    /// Address as given, including the load bias
+    pub address: u64,
+    /// Name of the function symbol
+    pub name: String,
+    /// Offset from the start of the function
+    pub offset: u64,
+    /// Source position if line information is available
+    pub location: Option<SourceLocation>,
+}
+
+impl core::fmt::Display for Symbolized {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}+{:#x}", self.name, self.offset)?;
+        if let Some(location) = &self.location {
+            write!(f, " at {}", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// Function symbol with the range of addresses it covers.
+#[derive(Debug, Clone)]
+struct Function {
+    start: u64,
+    end: u64,
+    name: String,
+}
+
+/// Index of function symbols sorted by address.
+/// Build this once to symbolize many addresses of the same binary.
+#[derive(Debug, Clone)]
+pub struct Symbolizer {
+    bias: u64,
+    functions: Vec<Function>,
+    /// Largest end of functions up to each index. Lookup walks back only while this is beyond the address.
+    max_end: Vec<u64>,
+    /// Empty if there is no DWARF line information or it is broken.
+    lines: LineTable,
+}
+
+/// Preference among symbols at the same address. Smaller is preferred.
+fn bind_rank(bind: symbol::Bind) -> u8 {
+    match bind {
+        symbol::Bind::STB_GLOBAL => 0,
+        symbol::Bind::STB_WEAK => 1,
+        _ => 2,
+    }
+}
+
+impl Symbolizer {
+    /// Index function symbols of .symtab and .dynsym, and DWARF line table if present.
+    /// `bias` is added to addresses in file when the binary is loaded, like the base address of PIE binaries.
+    /// Use 0 for addresses in file.
+    pub fn new(elf: &Elf, bias: u64) -> Self {
+        let sections = elf.section_headers();
+        let mut functions = vec![];
+
+        for t in [
+            section_header::Type::SHT_SYMTAB,
+            section_header::Type::SHT_DYNSYM,
+        ] {
+            for symtab in sections.iter().filter(|x| *x.get_sh_type_unwrap() == t) {
+                for sym in elf.symbols(symtab) {
+                    let e = &sym.entry;
+                    let typ = e.get_st_info_unwrap().typ();
+                    let shndx = e.get_st_shndx_unwrap();
+                    let start = e.get_st_value_unwrap().inner() as u64;
+                    if !(typ.is_STT_FUNC() || typ.is_STT_GNU_IFUNC())
+                        || shndx.is_SHN_UNDEF()
+                        || start == 0
+                    {
+                        continue;
+                    }
+
+                    let size = e.get_st_size_unwrap().inner();
+                    // Symbols without size, like ones written in assembly, end at the end of their section at most.
+                    let end = if size != 0 {
+                        start.saturating_add(size)
+                    } else {
+                        sections
+                            .get(shndx.inner() as usize)
+                            .filter(|_| shndx.inner() < symbol::SectionIndex::SHN_LORESERVE.inner())
+                            .map(|sh| {
+                                (sh.get_sh_addr_unwrap().inner() as u64)
+                                    .saturating_add(sh.get_sh_size_unwrap().inner())
+                            })
+                            .unwrap_or(u64::MAX)
+                    };
+                    functions.push((
+                        size == 0,
+                        bind_rank(e.get_st_info_unwrap().bind()),
+                        Function {
+                            start,
+                            end,
+                            name: sym.name,
+                        },
+                    ));
+                }
+            }
+        }
+
+        // Sized symbols are preferred, then global ones. The first is taken if symtab and dynsym have the same one.
+        functions.sort_by_key(|(unsized_, rank, f)| (f.start, *unsized_, *rank));
+        functions.dedup_by_key(|(_, _, f)| f.start);
+
+        // Unsized symbols also end at the next function. Without any bound, they cover only their start.
+        let starts: Vec<_> = functions.iter().map(|x| x.2.start).collect();
+        let functions: Vec<_> = functions
+            .into_iter()
+            .enumerate()
+            .map(|(i, (unsized_, _, mut f))| {
+                if unsized_ {
+                    f.end = f.end.min(starts.get(i + 1).copied().unwrap_or(u64::MAX));
+                    if f.end == u64::MAX {
+                        f.end = f.start;
+                    }
+                }
+                f.end = f.end.max(f.start.saturating_add(1));
+                f
+            })
+            .collect();
+
+        let max_end = functions
+            .iter()
+            .scan(0, |max, f| {
+                *max = f.end.max(*max);
+                Some(*max)
+            })
+            .collect();
+
+        Self {
+            bias,
+            functions,
+            max_end,
+            lines: Dwarf::from_elf(elf).line_table().unwrap_or_default(),
+        }
+    }
+
+    pub fn bias(&self) -> u64 {
+        self.bias
+    }
+
+    /// Nearest function that encloses `address`. Nested functions are resolved to the innermost one.
+    pub fn symbolize(&self, address: u64) -> Option<Symbolized> {
+        let vaddr = address.checked_sub(self.bias)?;
+        let mut i = self.functions.partition_point(|f| f.start <= vaddr);
+
+        while i > 0 && self.max_end[i - 1] > vaddr {
+            i -= 1;
+            let f = &self.functions[i];
+            if vaddr < f.end {
+                return Some(Symbolized {
+                    address,
+                    name: f.name.clone(),
+                    offset: vaddr - f.start,
+                    location: self.lines.lookup(vaddr),
+                });
+            }
+        }
+        None
+    }
+
+    /// Symbolize each of `addresses`. The results are in the same order.
+    pub fn symbolize_all(&self, addresses: &[u64]) -> Vec<Option<Symbolized>> {
+        addresses.iter().map(|x| self.symbolize(*x)).collect()
+    }
+}
+
+impl<'a> Elf<'a> {
+    /// Index of function symbols for addresses loaded with `bias`.
+    pub fn symbolizer(&self, bias: u64) -> Symbolizer {
+        Symbolizer::new(self, bias)
+    }
+
+    /// Function that encloses `addr` in file.
+    /// Use `symbolizer` to look up many addresses or addresses of loaded PIE binaries.
+    pub fn symbolize(&self, addr: u64) -> Option<Symbolized> {
+        self.symbolizer(0).symbolize(addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const OUT: &[u8] = include_bytes!("../../../example_bin/out");
+    const DWARF5: &[u8] = include_bytes!("../../../example_bin/dwarf5");
+
+    fn names(found: &[Option<Symbolized>]) -> Vec<Option<(&str, u64)>> {
+        found
+            .iter()
+            .map(|x| x.as_ref().map(|x| (x.name.as_str(), x.offset)))
+            .collect()
+    }
+
+    #[test]
+    fn symbolize() {
+        let s = Sequential::from_vec(OUT.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        let found = elf.symbolize(0x1140).unwrap();
+        assert_eq!((found.name.as_str(), found.offset), ("main", 7));
+        assert_eq!(found.to_string(), "main+0x7");
+
+        let symbolizer = elf.symbolizer(0);
+        let found = symbolizer.symbolize_all(&[
+            0x1139, 0x1152, 0x1154, 0x1040, 0x1066, 0x1068, 0x10a5, 0x1010, 0x1020,
+        ]);
+        assert_eq!(
+            names(&found),
+            vec![
+                Some(("main", 0)),
+                Some(("main", 25)),
+                Some(("_fini", 0)),
+                Some(("_start", 0)),
+                // Padding after sized function.
+                None,
+                None,
+                // Unsized function ends at the next one.
+                Some(("register_tm_clones", 5)),
+                // Unsized function ends at the end of section.
+                Some(("_init", 0x10)),
+                None,
+            ]
+        );
+        assert_eq!(elf.symbolize(0), None);
+        assert_eq!(elf.symbolize(0x4030), None);
+    }
+
+    #[test]
+    fn load_bias() {
+        let s = Sequential::from_vec(OUT.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        let bias = 0x5555_5555_4000;
+        let symbolizer = elf.symbolizer(bias);
+        let found = symbolizer.symbolize(bias + 0x1140).unwrap();
+        assert_eq!(found.address, bias + 0x1140);
+        assert_eq!((found.name.as_str(), found.offset), ("main", 7));
+        assert_eq!(symbolizer.symbolize(0x1140), None);
+    }
+
+    #[test]
+    fn source_location() {
+        let s = Sequential::from_vec(DWARF5.to_vec());
+        let elf = Elf::parse(s.to_seeakble()).unwrap();
+
+        let found = elf.symbolize(0x1141).unwrap();
+        assert_eq!(found.to_string(), "add+0x9 at /tmp/dw/dwarf.c:12:13");
+        let found = elf.symbolizer(0x1000).symbolize(0x2173).unwrap();
+        assert_eq!(found.to_string(), "main+0x29 at /tmp/dw/dwarf.c:19:13");
+    }
+}
@@ -0,0 +1,208 @@
+use super::{Elf, PrStatus};
+use crate::types::dwarf::{
+    evaluate, CallFrame, CfaRule, Cie, EhFrameHdr, FrameKind, RegisterRule, UnwindRow,
+};
+
+/// Frame of unwound stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackFrame {
+    /// Address being executed in the innermost frame, and return address in callers.
+    pub pc: u64,
+    /// Stack pointer in the frame, if known.
+    pub sp: Option<u64>,
+}
+
+/// Call frame information of a loaded binary.
+#[derive(Debug, Clone)]
+struct Module<'a> {
+    bias: u64,
+    eh_frame: Option<CallFrame<'a>>,
+    eh_frame_hdr: Option<EhFrameHdr>,
+    debug_frame: Option<CallFrame<'a>>,
+}
+
+/// Stack unwinder over call frame information of binaries.
+/// Add the executable and shared objects with their load bias, then unwind threads of core files.
+#[derive(Debug, Clone, Default)]
+pub struct Unwinder<'a> {
+    modules: Vec<Module<'a>>,
+}
+
+/// Frames to unwind at most, which stops walking broken stacks.
+const MAX_FRAMES: usize = 256;
+
+impl<'a> Unwinder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add .eh_frame and .debug_frame of `elf` loaded at `bias`.
+    /// Broken .eh_frame_hdr is ignored and FDEs are searched linearly.
+    pub fn add(&mut self, elf: &'a Elf, bias: u64) {
+        self.modules.push(Module {
+            bias,
+            eh_frame: CallFrame::from_elf(elf, FrameKind::EhFrame),
+            eh_frame_hdr: EhFrameHdr::from_elf(elf).and_then(|x| x.ok()),
+            debug_frame: CallFrame::from_elf(elf, FrameKind::DebugFrame),
+        });
+    }
+
+    /// Row and CIE to unwind the frame executing `pc`. Addresses of the row are in file, without bias.
+    pub fn unwind_row(&self, pc: u64) -> Option<(UnwindRow<'a>, Cie<'a>)> {
+        self.modules.iter().find_map(|m| {
+            let address = pc.checked_sub(m.bias)?;
+            [
+                (m.eh_frame.as_ref(), m.eh_frame_hdr.as_ref()),
+                (m.debug_frame.as_ref(), None),
+            ]
+            .into_iter()
+            .find_map(|(frame, hdr)| {
+                let fde = frame?.find_fde(address, hdr).ok()??;
+                let row = fde.unwind_row(address).ok()??;
+                Some((row, fde.cie))
+            })
+        })
+    }
+
+    /// Walk the stack from `registers` indexed by DWARF register number.
+    /// `sp` is the number of the stack pointer register, and `memory` reads little endian value
+    /// of the size at the address. Unwinding stops at the frame without call frame information
+    /// or return address.
+    pub fn unwind(
+        &self,
+        registers: Vec<Option<u64>>,
+        pc: u64,
+        sp: u16,
+        memory: impl Fn(u64, u8) -> Option<u64>,
+    ) -> Vec<StackFrame> {
+        let mut frames = vec![];
+        let (mut registers, mut pc) = (registers, pc);
+        let mut after_call = false;
+
+        loop {
+            let get =
+                |registers: &[Option<u64>], r: u16| registers.get(r as usize).copied().flatten();
+            let current_sp = get(&registers, sp);
+            frames.push(StackFrame { pc, sp: current_sp });
+            if frames.len() >= MAX_FRAMES {
+                break;
+            }
+
+            // Return address is after the call, which may be out of the function for noreturn calls.
+            let lookup = if after_call { pc.wrapping_sub(1) } else { pc };
+            let Some((row, cie)) = self.unwind_row(lookup) else {
+                break;
+            };
+            let value = |r| get(&registers, r);
+            let cfa = match row.cfa {
+                CfaRule::RegisterOffset(r, offset) => {
+                    value(r).map(|x| x.wrapping_add(offset as u64))
+                }
+                CfaRule::Expression(expr) => evaluate(expr, cie.address_size, value, &memory, None),
+            };
+            let Some(cfa) = cfa else {
+                break;
+            };
+
+            // Registers without rules keep their values, which is right for callee-saved ones.
+            let mut caller = registers.clone();
+            for (r, rule) in &row.registers {
+                let at = |offset: i64| cfa.wrapping_add(offset as u64);
+                let recovered = match *rule {
+                    RegisterRule::Undefined => None,
+                    RegisterRule::SameValue => value(*r),
+                    RegisterRule::Offset(offset) => memory(at(offset), cie.address_size),
+                    RegisterRule::ValOffset(offset) => Some(at(offset)),
+                    RegisterRule::Register(source) => value(source),
+                    RegisterRule::Expression(expr) => {
+                        evaluate(expr, cie.address_size, value, &memory, Some(cfa))
+                            .and_then(|x| memory(x, cie.address_size))
+                    }
+                    RegisterRule::ValExpression(expr) => {
+                        evaluate(expr, cie.address_size, value, &memory, Some(cfa))
+                    }
+                };
+                if caller.len() <= *r as usize {
+                    caller.resize(*r as usize + 1, None);
+                }
+                caller[*r as usize] = recovered;
+            }
+            if caller.len() <= sp as usize {
+                caller.resize(sp as usize + 1, None);
+            }
+            caller[sp as usize] = Some(cfa);
+
+            let Some(return_address) =
+                get(&caller, cie.return_address_register).filter(|x| *x != 0)
+            else {
+                break;
+            };
+            // Stack grows down. A caller at the same or lower stack is a loop of broken information.
+            if current_sp.is_some_and(|x| cfa <= x) && !cie.signal_frame {
+                break;
+            }
+
+            registers = caller;
+            pc = return_address;
+            after_call = !cie.signal_frame;
+        }
+        frames
+    }
+}
+
+impl<'a> Elf<'a> {
+    /// Stack of `thread` of this core file, unwound with call frame information in `unwinder`.
+    /// Empty if registers of the architecture are not supported.
+    pub fn backtrace(&self, thread: &PrStatus, unwinder: &Unwinder) -> Vec<StackFrame> {
+        let registers = &thread.registers;
+        let (Some(pc), Some(sp)) = (registers.pc(), registers.dwarf_sp()) else {
+            return vec![];
+        };
+        unwinder.unwind(registers.dwarf(), pc, sp, |address, size| {
+            let bytes = self.read_memory(address, size as usize)?;
+            Some(bytes.iter().rev().fold(0, |acc, x| (acc << 8) | *x as u64))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const CORE: &[u8] = include_bytes!("../../../example_bin/core_x86_64");
+    const EXECUTABLE: &[u8] = include_bytes!("../../../example_bin/core_x86_64_exe");
+
+    #[test]
+    fn backtrace() {
+        let s = Sequential::from_vec(CORE.to_vec());
+        let core = Elf::parse(s.to_seeakble()).unwrap();
+        let s = Sequential::from_vec(EXECUTABLE.to_vec());
+        let executable = Elf::parse(s.to_seeakble()).unwrap();
+
+        let mut unwinder = Unwinder::new();
+        unwinder.add(&executable, 0);
+        let thread = &core.threads()[0];
+        let frames = core.backtrace(thread, &unwinder);
+
+        let symbolizer = executable.symbolizer(0);
+        let names: Vec<_> = frames
+            .iter()
+            .map(|x| symbolizer.symbolize(x.pc).unwrap().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "main+0x2a",
+                "__libc_start_call_main+0x64",
+                "__libc_start_main_impl+0x8a0",
+                // Return address after the call
+                "_start+0x21",
+            ]
+        );
+        assert_eq!(frames[0].sp, Some(0x7ffc0bc2a430));
+        // main saves rbp and return address.
+        assert_eq!(frames[1].sp, Some(0x7ffc0bc2a460));
+        assert!(frames.windows(2).all(|x| x[0].sp < x[1].sp));
+    }
+}
@@ -0,0 +1,880 @@
+use super::Array;
+use crate::{define_composed_type, define_model_type, define_tagged_type};
+
+mod fat;
+mod reader;
+pub use fat::FatBinary;
+pub use reader::{LoadCommandEntry, MachO, MachOError, Symbol};
+
+/// Size of mach_header in bytes.
+pub const HEADER_SIZE: usize = 28;
+
+/// Size of mach_header_64 in bytes. This has a reserved word after mach_header.
+pub const HEADER_64_SIZE: usize = 32;
+
+/// Size of load_command (cmd and cmdsize) in bytes.
+pub const LOAD_COMMAND_HEADER_SIZE: usize = 8;
+
+/// Field sizes of a structure as (size of field, number of fields).
+/// Mach-O files may be big endian. Their structures are converted to little endian with this before interpretation.
+pub trait Layout {
+    const LAYOUT: &'static [(usize, usize)];
+
+    /// Size of the structure in bytes.
+    fn size() -> usize {
+        Self::LAYOUT.iter().map(|(size, count)| size * count).sum()
+    }
+}
+
+/// Reverse byte order of each field of `layout` in `bytes`. Bytes after the layout are not changed.
+pub(crate) fn swap_fields(bytes: &mut [u8], layout: &[(usize, usize)]) {
+    let mut at = 0;
+    for &(size, count) in layout {
+        for _ in 0..count {
+            if let Some(field) = bytes.get_mut(at..at + size) {
+                field.reverse();
+            }
+            at += size;
+        }
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Magic(u32),
+    pub
+    [
+#define	MH_MAGIC	0xfeedface	/* the mach magic number */
+#define MH_CIGAM	0xcefaedfe	/* NXSwapInt(MH_MAGIC) */
+#define MH_MAGIC_64 0xfeedfacf /* the 64-bit mach magic number */
+#define MH_CIGAM_64 0xcffaedfe /* NXSwapInt(MH_MAGIC_64) */
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CpuType(i32),
+    pub
+    [
+#define CPU_TYPE_ANY		-1
+#define CPU_TYPE_VAX		1
+#define CPU_TYPE_MC680x0	6
+#define CPU_TYPE_X86		7
+#define CPU_TYPE_X86_64		0x01000007	/* CPU_TYPE_X86 | CPU_ARCH_ABI64 */
+#define CPU_TYPE_MC98000	10
+#define CPU_TYPE_HPPA		11
+#define CPU_TYPE_ARM		12
+#define CPU_TYPE_ARM64		0x0100000c	/* CPU_TYPE_ARM | CPU_ARCH_ABI64 */
+#define CPU_TYPE_ARM64_32	0x0200000c	/* CPU_TYPE_ARM | CPU_ARCH_ABI64_32 */
+#define CPU_TYPE_MC88000	13
+#define CPU_TYPE_SPARC		14
+#define CPU_TYPE_I860		15
+#define CPU_TYPE_POWERPC	18
+#define CPU_TYPE_POWERPC64	0x01000012	/* CPU_TYPE_POWERPC | CPU_ARCH_ABI64 */
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CpuSubtype(i32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FileType(u32),
+    pub
+    [
+#define	MH_OBJECT	0x1		/* relocatable object file */
+#define	MH_EXECUTE	0x2		/* demand paged executable file */
+#define	MH_FVMLIB	0x3		/* fixed VM shared library file */
+#define	MH_CORE		0x4		/* core file */
+#define	MH_PRELOAD	0x5		/* preloaded executable file */
+#define	MH_DYLIB	0x6		/* dynamically bound shared library */
+#define	MH_DYLINKER	0x7		/* dynamic link editor */
+#define	MH_BUNDLE	0x8		/* dynamically bound bundle file */
+#define	MH_DYLIB_STUB	0x9		/* shared library stub for static linking only, no section contents */
+#define	MH_DSYM		0xa		/* companion file with only debug sections */
+#define	MH_KEXT_BUNDLE	0xb		/* x86_64 kexts */
+#define	MH_FILESET	0xc		/* set of mach-o's */
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Count(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Size32(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Size64(u64),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Address32(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Address64(u64),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Offset32(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Offset64(u64),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Reserved32(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct HeaderFlags(u32),
+    pub
+    [
+#define	MH_NOUNDEFS	0x1		/* the object file has no undefined references */
+#define	MH_INCRLINK	0x2		/* the object file is the output of an incremental link */
+#define MH_DYLDLINK	0x4		/* the object file is input for the dynamic linker */
+#define MH_BINDATLOAD	0x8		/* the object file's undefined references are bound by the dynamic linker when loaded. */
+#define MH_PREBOUND	0x10		/* the file has its dynamic undefined references prebound. */
+#define MH_SPLIT_SEGS	0x20		/* the file has its read-only and read-write segments split */
+#define MH_LAZY_INIT	0x40		/* the shared library init routine is to be run lazily */
+#define MH_TWOLEVEL	0x80		/* the image is using two-level name space bindings */
+#define MH_FORCE_FLAT	0x100		/* the executable is forcing all images to use flat name space bindings */
+#define MH_NOMULTIDEFS	0x200		/* this umbrella guarantees no multiple definitions of symbols in its sub-images */
+#define MH_NOFIXPREBINDING 0x400	/* do not have dyld notify the prebinding agent about this executable */
+#define MH_PREBINDABLE  0x800           /* the binary is not prebound but can have its prebinding redone. */
+#define MH_ALLMODSBOUND 0x1000		/* indicates that this binary binds to all two-level namespace modules of its dependent libraries. */
+#define MH_SUBSECTIONS_VIA_SYMBOLS 0x2000 /* safe to divide up the sections into sub-sections via symbols for dead code stripping */
+#define MH_CANONICAL    0x4000		/* the binary has been canonicalized via the unprebind operation */
+#define MH_WEAK_DEFINES	0x8000		/* the final linked image contains external weak symbols */
+#define MH_BINDS_TO_WEAK 0x10000	/* the final linked image uses weak symbols */
+#define MH_ALLOW_STACK_EXECUTION 0x20000 /* all stacks in the task will be given stack execution privilege. */
+#define MH_ROOT_SAFE 0x40000           /* the binary declares it is safe for use in processes with uid zero */
+#define MH_SETUID_SAFE 0x80000         /* the binary declares it is safe for use in processes when issetugid() is true */
+#define MH_NO_REEXPORTED_DYLIBS 0x100000 /* the static linker does not need to examine dependent dylibs to see if any are re-exported */
+#define	MH_PIE 0x200000			/* the OS will load the main executable at a random address. */
+#define	MH_DEAD_STRIPPABLE_DYLIB 0x400000 /* the static linker will automatically not create a LC_LOAD_DYLIB load command to the dylib if no symbols are being referenced from the dylib. */
+#define MH_HAS_TLV_DESCRIPTORS 0x800000 /* contains a section of type S_THREAD_LOCAL_VARIABLES */
+#define MH_NO_HEAP_EXECUTION 0x1000000	/* the OS will run the main executable with a non-executable heap */
+#define MH_APP_EXTENSION_SAFE 0x02000000 /* the code was linked for use in an application extension. */
+#define	MH_NLIST_OUTOFSYNC_WITH_DYLDINFO 0x04000000 /* the external symbols listed in the nlist symbol table do not include all the symbols listed in the dyld info. */
+#define	MH_SIM_SUPPORT 0x08000000	/* allow LC_MIN_VERSION_MACOS and LC_BUILD_VERSION load commands with the platforms macOS, iOSMac, iOSSimulator, tvOSSimulator and watchOSSimulator. */
+#define MH_DYLIB_IN_CACHE 0x80000000u32	/* Only for use on dylibs. When this bit is set, the dylib is part of the dyld shared cache. */
+    ],
+    display = true, bitflags = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Header {
+        /// mach magic number identifier
+        magic: Option<Magic>,
+        /// cpu specifier
+        cputype: Option<CpuType>,
+        /// machine specifier
+        cpusubtype: Option<CpuSubtype>,
+        /// type of file
+        filetype: Option<FileType>,
+        /// number of load commands
+        ncmds: Option<Count>,
+        /// the size of all the load commands
+        sizeofcmds: Option<Size32>,
+        flags: Option<HeaderFlags>,
+    },
+    display = true,
+);
+impl Layout for Header {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 7)];
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Command(u32),
+    pub
+    [
+#define	LC_SEGMENT	0x1	/* segment of this file to be mapped */
+#define	LC_SYMTAB	0x2	/* link-edit stab symbol table info */
+#define	LC_SYMSEG	0x3	/* link-edit gdb symbol table info (obsolete) */
+#define	LC_THREAD	0x4	/* thread */
+#define	LC_UNIXTHREAD	0x5	/* unix thread (includes a stack) */
+#define	LC_LOADFVMLIB	0x6	/* load a specified fixed VM shared library */
+#define	LC_IDFVMLIB	0x7	/* fixed VM shared library identification */
+#define	LC_IDENT	0x8	/* object identification info (obsolete) */
+#define LC_FVMFILE	0x9	/* fixed VM file inclusion (internal use) */
+#define LC_PREPAGE      0xa     /* prepage command (internal use) */
+#define	LC_DYSYMTAB	0xb	/* dynamic link-edit symbol table info */
+#define	LC_LOAD_DYLIB	0xc	/* load a dynamically linked shared library */
+#define	LC_ID_DYLIB	0xd	/* dynamically linked shared lib ident */
+#define LC_LOAD_DYLINKER 0xe	/* load a dynamic linker */
+#define LC_ID_DYLINKER	0xf	/* dynamic linker identification */
+#define	LC_PREBOUND_DYLIB 0x10	/* modules prebound for a dynamically linked shared library */
+#define	LC_ROUTINES	0x11	/* image routines */
+#define	LC_SUB_FRAMEWORK 0x12	/* sub framework */
+#define	LC_SUB_UMBRELLA 0x13	/* sub umbrella */
+#define	LC_SUB_CLIENT	0x14	/* sub client */
+#define	LC_SUB_LIBRARY  0x15	/* sub library */
+#define	LC_TWOLEVEL_HINTS 0x16	/* two-level namespace lookup hints */
+#define	LC_PREBIND_CKSUM  0x17	/* prebind checksum */
+#define	LC_LOAD_WEAK_DYLIB 0x80000018u32	/* load a dynamically linked shared library that is allowed to be missing (all symbols are weak imported). */
+#define	LC_SEGMENT_64	0x19	/* 64-bit segment of this file to be mapped */
+#define	LC_ROUTINES_64	0x1a	/* 64-bit image routines */
+#define LC_UUID		0x1b	/* the uuid */
+#define LC_RPATH       0x8000001cu32    /* runpath additions */
+#define LC_CODE_SIGNATURE 0x1d	/* local of code signature */
+#define LC_SEGMENT_SPLIT_INFO 0x1e /* local of info to split segments */
+#define LC_REEXPORT_DYLIB 0x8000001fu32 /* load and re-export dylib */
+#define	LC_LAZY_LOAD_DYLIB 0x20	/* delay load of dylib until first use */
+#define	LC_ENCRYPTION_INFO 0x21	/* encrypted segment information */
+#define	LC_DYLD_INFO 	0x22	/* compressed dyld information */
+#define	LC_DYLD_INFO_ONLY 0x80000022u32	/* compressed dyld information only */
+#define	LC_LOAD_UPWARD_DYLIB 0x80000023u32 /* load upward dylib */
+#define LC_VERSION_MIN_MACOSX 0x24   /* build for MacOSX min OS version */
+#define LC_VERSION_MIN_IPHONEOS 0x25 /* build for iPhoneOS min OS version */
+#define LC_FUNCTION_STARTS 0x26 /* compressed table of function start addresses */
+#define LC_DYLD_ENVIRONMENT 0x27 /* string for dyld to treat like environment variable */
+#define LC_MAIN 0x80000028u32 /* replacement for LC_UNIXTHREAD */
+#define LC_DATA_IN_CODE 0x29 /* table of non-instructions in __text */
+#define LC_SOURCE_VERSION 0x2A /* source version used to build binary */
+#define LC_DYLIB_CODE_SIGN_DRS 0x2B /* Code signing DRs copied from linked dylibs */
+#define	LC_ENCRYPTION_INFO_64 0x2C /* 64-bit encrypted segment information */
+#define LC_LINKER_OPTION 0x2D /* linker options in MH_OBJECT files */
+#define LC_LINKER_OPTIMIZATION_HINT 0x2E /* optimization hints in MH_OBJECT files */
+#define LC_VERSION_MIN_TVOS 0x2F /* build for AppleTV min OS version */
+#define LC_VERSION_MIN_WATCHOS 0x30 /* build for Watch min OS version */
+#define LC_NOTE 0x31 /* arbitrary data included within a Mach-O file */
+#define LC_BUILD_VERSION 0x32 /* build for platform min OS version */
+#define LC_DYLD_EXPORTS_TRIE 0x80000033u32 /* used with linkedit_data_command, payload is trie */
+#define LC_DYLD_CHAINED_FIXUPS 0x80000034u32 /* used with linkedit_data_command */
+#define LC_FILESET_ENTRY 0x80000035u32 /* used with fileset_entry_command */
+    ],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct LoadCommandHeader {
+        /// type of load command
+        cmd: Option<Command>,
+        /// total size of command in bytes
+        cmdsize: Option<Size32>,
+    },
+    display = true,
+);
+impl Layout for LoadCommandHeader {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 2)];
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FixedName(Array<u8, 16>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for FixedName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.to_string_lossy())
+    }
+}
+impl FixedName {
+    /// Name without trailing NULs. All of 16 bytes may be used.
+    pub fn to_string_lossy(&self) -> String {
+        let bytes = self.0.inner();
+        let len = bytes.iter().position(|x| *x == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..len]).into_owned()
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct VmProt(i32),
+    pub
+    [
+#define	VM_PROT_READ	0x01	/* read permission */
+#define	VM_PROT_WRITE	0x02	/* write permission */
+#define	VM_PROT_EXECUTE	0x04	/* execute permission */
+    ],
+    display = true, bitflags = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SegmentFlags(u32),
+    pub
+    [
+#define	SG_HIGHVM	0x1	/* the file contents for this segment is for the high part of the VM space */
+#define	SG_FVMLIB	0x2	/* this segment is the VM that is allocated by a fixed VM library */
+#define	SG_NORELOC	0x4	/* this segment has nothing that was relocated in it and nothing relocated to it */
+#define SG_PROTECTED_VERSION_1	0x8 /* This segment is protected. */
+#define SG_READ_ONLY    0x10 /* This segment is made read-only after fixups */
+    ],
+    display = true, bitflags = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SegmentCommand {
+        segname: Option<FixedName>,
+        /// memory address of this segment
+        vmaddr: Option<Address32>,
+        /// memory size of this segment
+        vmsize: Option<Size32>,
+        /// file offset of this segment
+        fileoff: Option<Offset32>,
+        /// amount to map from the file
+        filesize: Option<Size32>,
+        /// maximum VM protection
+        maxprot: Option<VmProt>,
+        /// initial VM protection
+        initprot: Option<VmProt>,
+        /// number of sections in segment
+        nsects: Option<Count>,
+        flags: Option<SegmentFlags>,
+    },
+    display = true,
+);
+impl Layout for SegmentCommand {
+    const LAYOUT: &'static [(usize, usize)] = &[(1, 16), (4, 8)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SegmentCommand64 {
+        segname: Option<FixedName>,
+        /// memory address of this segment
+        vmaddr: Option<Address64>,
+        /// memory size of this segment
+        vmsize: Option<Size64>,
+        /// file offset of this segment
+        fileoff: Option<Offset64>,
+        /// amount to map from the file
+        filesize: Option<Size64>,
+        /// maximum VM protection
+        maxprot: Option<VmProt>,
+        /// initial VM protection
+        initprot: Option<VmProt>,
+        /// number of sections in segment
+        nsects: Option<Count>,
+        flags: Option<SegmentFlags>,
+    },
+    display = true,
+);
+impl Layout for SegmentCommand64 {
+    const LAYOUT: &'static [(usize, usize)] = &[(1, 16), (8, 4), (4, 4)];
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SectionType(u8),
+    pub
+    [
+#define	S_REGULAR		0x0	/* regular section */
+#define	S_ZEROFILL		0x1	/* zero fill on demand section */
+#define	S_CSTRING_LITERALS	0x2	/* section with only literal C strings*/
+#define	S_4BYTE_LITERALS	0x3	/* section with only 4 byte literals */
+#define	S_8BYTE_LITERALS	0x4	/* section with only 8 byte literals */
+#define	S_LITERAL_POINTERS	0x5	/* section with only pointers to literals */
+#define	S_NON_LAZY_SYMBOL_POINTERS	0x6	/* section with only non-lazy symbol pointers */
+#define	S_LAZY_SYMBOL_POINTERS		0x7	/* section with only lazy symbol pointers */
+#define	S_SYMBOL_STUBS			0x8	/* section with only symbol stubs, byte size of stub in the reserved2 field */
+#define	S_MOD_INIT_FUNC_POINTERS	0x9	/* section with only function pointers for initialization*/
+#define	S_MOD_TERM_FUNC_POINTERS	0xa	/* section with only function pointers for termination */
+#define	S_COALESCED			0xb	/* section contains symbols that are to be coalesced */
+#define	S_GB_ZEROFILL			0xc	/* zero fill on demand section (that can be larger than 4 gigabytes) */
+#define	S_INTERPOSING			0xd	/* section with only pairs of function pointers for interposing */
+#define	S_16BYTE_LITERALS		0xe	/* section with only 16 byte literals */
+#define	S_DTRACE_DOF			0xf	/* section contains DTrace Object Format */
+#define	S_LAZY_DYLIB_SYMBOL_POINTERS	0x10	/* section with only lazy symbol pointers to lazy loaded dylibs */
+#define S_THREAD_LOCAL_REGULAR                   0x11  /* template of initial values for TLVs */
+#define S_THREAD_LOCAL_ZEROFILL                  0x12  /* template of initial values for TLVs */
+#define S_THREAD_LOCAL_VARIABLES                 0x13  /* TLV descriptors */
+#define S_THREAD_LOCAL_VARIABLE_POINTERS         0x14  /* pointers to TLV descriptors */
+#define S_THREAD_LOCAL_INIT_FUNCTION_POINTERS    0x15  /* functions to call to initialize TLV values */
+#define S_INIT_FUNC_OFFSETS                      0x16  /* 32-bit offsets to initializers */
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SectionFlags(u32),
+    pub
+    [
+#define SECTION_TYPE		 0x000000ff	/* 256 section types */
+#define S_ATTR_PURE_INSTRUCTIONS 0x80000000u32	/* section contains only true machine instructions */
+#define S_ATTR_NO_TOC 		 0x40000000	/* section contains coalesced symbols that are not to be in a ranlib table of contents */
+#define S_ATTR_STRIP_STATIC_SYMS 0x20000000	/* ok to strip static symbols in this section in files with the MH_DYLDLINK flag */
+#define S_ATTR_NO_DEAD_STRIP	 0x10000000	/* no dead stripping */
+#define S_ATTR_LIVE_SUPPORT	 0x08000000	/* blocks are live if they reference live blocks */
+#define S_ATTR_SELF_MODIFYING_CODE 0x04000000	/* Used with i386 code stubs written on by dyld */
+#define	S_ATTR_DEBUG		 0x02000000	/* a debug section */
+#define S_ATTR_SOME_INSTRUCTIONS 0x00000400	/* section contains some machine instructions */
+#define S_ATTR_EXT_RELOC	 0x00000200	/* section has external relocation entries */
+#define S_ATTR_LOC_RELOC	 0x00000100	/* section has local relocation entries */
+    ],
+    display = true, bitflags = true,
+);
+impl SectionFlags {
+    /// Section type in the low byte.
+    pub fn section_type(&self) -> SectionType {
+        SectionType::new(self.0 as u8)
+    }
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Section {
+        /// name of this section
+        sectname: Option<FixedName>,
+        /// segment this section goes in
+        segname: Option<FixedName>,
+        /// memory address of this section
+        addr: Option<Address32>,
+        /// size in bytes of this section
+        size: Option<Size32>,
+        /// file offset of this section
+        offset: Option<Offset32>,
+        /// section alignment (power of 2)
+        align: Option<Count>,
+        /// file offset of relocation entries
+        reloff: Option<Offset32>,
+        /// number of relocation entries
+        nreloc: Option<Count>,
+        flags: Option<SectionFlags>,
+        reserved1: Option<Reserved32>,
+        reserved2: Option<Reserved32>,
+    },
+    display = true,
+);
+impl Layout for Section {
+    const LAYOUT: &'static [(usize, usize)] = &[(1, 32), (4, 9)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Section64 {
+        /// name of this section
+        sectname: Option<FixedName>,
+        /// segment this section goes in
+        segname: Option<FixedName>,
+        /// memory address of this section
+        addr: Option<Address64>,
+        /// size in bytes of this section
+        size: Option<Size64>,
+        /// file offset of this section
+        offset: Option<Offset32>,
+        /// section alignment (power of 2)
+        align: Option<Count>,
+        /// file offset of relocation entries
+        reloff: Option<Offset32>,
+        /// number of relocation entries
+        nreloc: Option<Count>,
+        flags: Option<SectionFlags>,
+        reserved1: Option<Reserved32>,
+        reserved2: Option<Reserved32>,
+        reserved3: Option<Reserved32>,
+    },
+    display = true,
+);
+impl Layout for Section64 {
+    const LAYOUT: &'static [(usize, usize)] = &[(1, 32), (8, 2), (4, 8)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SymtabCommand {
+        /// symbol table offset
+        symoff: Option<Offset32>,
+        /// number of symbol table entries
+        nsyms: Option<Count>,
+        /// string table offset
+        stroff: Option<Offset32>,
+        /// string table size in bytes
+        strsize: Option<Size32>,
+    },
+    display = true,
+);
+impl Layout for SymtabCommand {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 4)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DysymtabCommand {
+        /// index to local symbols
+        ilocalsym: Option<Count>,
+        /// number of local symbols
+        nlocalsym: Option<Count>,
+        /// index to externally defined symbols
+        iextdefsym: Option<Count>,
+        /// number of externally defined symbols
+        nextdefsym: Option<Count>,
+        /// index to undefined symbols
+        iundefsym: Option<Count>,
+        /// number of undefined symbols
+        nundefsym: Option<Count>,
+        /// file offset to table of contents
+        tocoff: Option<Offset32>,
+        /// number of entries in table of contents
+        ntoc: Option<Count>,
+        /// file offset to module table
+        modtaboff: Option<Offset32>,
+        /// number of module table entries
+        nmodtab: Option<Count>,
+        /// offset to referenced symbol table
+        extrefsymoff: Option<Offset32>,
+        /// number of referenced symbol table entries
+        nextrefsyms: Option<Count>,
+        /// file offset to the indirect symbol table
+        indirectsymoff: Option<Offset32>,
+        /// number of indirect symbol table entries
+        nindirectsyms: Option<Count>,
+        /// offset to external relocation entries
+        extreloff: Option<Offset32>,
+        /// number of external relocation entries
+        nextrel: Option<Count>,
+        /// offset to local relocation entries
+        locreloff: Option<Offset32>,
+        /// number of local relocation entries
+        nlocrel: Option<Count>,
+    },
+    display = true,
+);
+impl Layout for DysymtabCommand {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 18)];
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Version(u32),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for Version {
+    /// X.Y.Z is encoded in nibbles xxxx.yy.zz.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.0 >> 16, (self.0 >> 8) & 0xff, self.0 & 0xff)
+    }
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DylibCommand {
+        /// library's path name. Offset of the string from the start of the load command.
+        name: Option<Offset32>,
+        /// library's build time stamp
+        timestamp: Option<Reserved32>,
+        /// library's current version number
+        current_version: Option<Version>,
+        /// library's compatibility vers number
+        compatibility_version: Option<Version>,
+    },
+    display = true,
+);
+impl Layout for DylibCommand {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 4)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DylinkerCommand {
+        /// dynamic linker's path name. Offset of the string from the start of the load command.
+        name: Option<Offset32>,
+    },
+    display = true,
+);
+impl Layout for DylinkerCommand {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 1)];
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Uuid(Array<u8, 16>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for Uuid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (i, x) in self.0.inner().iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                write!(f, "-")?;
+            }
+            write!(f, "{:02X}", x)?;
+        }
+        Ok(())
+    }
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct UuidCommand {
+        /// the 128-bit uuid
+        uuid: Option<Uuid>,
+    },
+    display = true,
+);
+impl Layout for UuidCommand {
+    const LAYOUT: &'static [(usize, usize)] = &[(1, 16)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct EntryPointCommand {
+        /// file (__TEXT) offset of main()
+        entryoff: Option<Offset64>,
+        /// if not zero, initial stack size
+        stacksize: Option<Size64>,
+    },
+    display = true,
+);
+impl Layout for EntryPointCommand {
+    const LAYOUT: &'static [(usize, usize)] = &[(8, 2)];
+}
+
+// Body of load command after cmd and cmdsize. Strings and sections that follow the body are not included.
+define_tagged_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub enum LoadCommand(Command) {
+        (LC_SEGMENT: Segment(SegmentCommand)),
+        (LC_SEGMENT_64: Segment64(SegmentCommand64)),
+        (LC_SYMTAB: Symtab(SymtabCommand)),
+        (LC_DYSYMTAB: Dysymtab(DysymtabCommand)),
+        (LC_LOAD_DYLIB: LoadDylib(DylibCommand)),
+        (LC_ID_DYLIB: IdDylib(DylibCommand)),
+        (LC_LOAD_WEAK_DYLIB: LoadWeakDylib(DylibCommand)),
+        (LC_REEXPORT_DYLIB: ReexportDylib(DylibCommand)),
+        (LC_LAZY_LOAD_DYLIB: LazyLoadDylib(DylibCommand)),
+        (LC_LOAD_UPWARD_DYLIB: LoadUpwardDylib(DylibCommand)),
+        (LC_LOAD_DYLINKER: LoadDylinker(DylinkerCommand)),
+        (LC_ID_DYLINKER: IdDylinker(DylinkerCommand)),
+        (LC_UUID: Uuid(UuidCommand)),
+        (LC_MAIN: Main(EntryPointCommand)),
+    },
+    display = true,
+);
+
+impl LoadCommand {
+    /// Layout of body of `cmd`. Unknown commands have empty layout, so they are left as they are.
+    pub fn body_layout(cmd: Command) -> &'static [(usize, usize)] {
+        match cmd {
+            Command::LC_SEGMENT => SegmentCommand::LAYOUT,
+            Command::LC_SEGMENT_64 => SegmentCommand64::LAYOUT,
+            Command::LC_SYMTAB => SymtabCommand::LAYOUT,
+            Command::LC_DYSYMTAB => DysymtabCommand::LAYOUT,
+            Command::LC_LOAD_DYLIB
+            | Command::LC_ID_DYLIB
+            | Command::LC_LOAD_WEAK_DYLIB
+            | Command::LC_REEXPORT_DYLIB
+            | Command::LC_LAZY_LOAD_DYLIB
+            | Command::LC_LOAD_UPWARD_DYLIB => DylibCommand::LAYOUT,
+            Command::LC_LOAD_DYLINKER | Command::LC_ID_DYLINKER => DylinkerCommand::LAYOUT,
+            Command::LC_UUID => UuidCommand::LAYOUT,
+            Command::LC_MAIN => EntryPointCommand::LAYOUT,
+            _ => &[],
+        }
+    }
+
+    /// Dylib of LC_*_DYLIB commands.
+    pub fn dylib(&self) -> Option<&DylibCommand> {
+        match self {
+            LoadCommand::LoadDylib(x)
+            | LoadCommand::IdDylib(x)
+            | LoadCommand::LoadWeakDylib(x)
+            | LoadCommand::ReexportDylib(x)
+            | LoadCommand::LazyLoadDylib(x)
+            | LoadCommand::LoadUpwardDylib(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SymbolType(u8),
+    pub
+    [
+#define	N_STAB	0xe0  /* if any of these bits set, a symbolic debugging entry */
+#define	N_PEXT	0x10  /* private external symbol bit */
+#define	N_TYPE	0x0e  /* mask for the type bits */
+#define	N_EXT	0x01  /* external symbol bit, set for external symbols */
+    ],
+    display = true, bitflags = true,
+);
+impl SymbolType {
+    /// Type bits masked by N_TYPE.
+    pub fn kind(&self) -> SymbolKind {
+        SymbolKind::new(self.0 & Self::N_TYPE.0)
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SymbolKind(u8),
+    pub
+    [
+#define	N_UNDF	0x0		/* undefined, n_sect == NO_SECT */
+#define	N_ABS	0x2		/* absolute, n_sect == NO_SECT */
+#define	N_SECT	0xe		/* defined in section number n_sect */
+#define	N_PBUD	0xc		/* prebound undefined (defined in a dylib) */
+#define N_INDR	0xa		/* indirect */
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SectionIndex(u8),
+    pub
+    [
+#define	NO_SECT		0	/* symbol is not in any section */
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Desc(u16),
+    [],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Nlist {
+        /// index into the string table
+        n_strx: Option<Offset32>,
+        /// type flag
+        n_type: Option<SymbolType>,
+        /// section number or NO_SECT
+        n_sect: Option<SectionIndex>,
+        /// see <mach-o/stab.h>
+        n_desc: Option<Desc>,
+        /// value of this symbol (or stab offset)
+        n_value: Option<Address32>,
+    },
+    display = true,
+);
+impl Layout for Nlist {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 1), (1, 2), (2, 1), (4, 1)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Nlist64 {
+        /// index into the string table
+        n_strx: Option<Offset32>,
+        /// type flag
+        n_type: Option<SymbolType>,
+        /// section number or NO_SECT
+        n_sect: Option<SectionIndex>,
+        /// see <mach-o/stab.h>
+        n_desc: Option<Desc>,
+        /// value of this symbol (or stab offset)
+        n_value: Option<Address64>,
+    },
+    display = true,
+);
+impl Layout for Nlist64 {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 1), (1, 2), (2, 1), (8, 1)];
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FatMagic(u32),
+    pub
+    [
+#define FAT_MAGIC	0xcafebabe
+#define FAT_CIGAM	0xbebafeca	/* NXSwapLong(FAT_MAGIC) */
+#define FAT_MAGIC_64	0xcafebabf
+#define FAT_CIGAM_64	0xbfbafeca	/* NXSwapLong(FAT_MAGIC_64) */
+    ],
+    display = true, bitflags = false,
+);
+
+// Fat header and architectures are always big endian.
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FatHeader {
+        magic: Option<FatMagic>,
+        /// number of structs that follow
+        nfat_arch: Option<Count>,
+    },
+    display = true,
+);
+impl Layout for FatHeader {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 2)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FatArch {
+        /// cpu specifier (int)
+        cputype: Option<CpuType>,
+        /// machine specifier (int)
+        cpusubtype: Option<CpuSubtype>,
+        /// file offset to this object file
+        offset: Option<Offset32>,
+        /// size of this object file
+        size: Option<Size32>,
+        /// alignment as a power of 2
+        align: Option<Count>,
+    },
+    display = true,
+);
+impl Layout for FatArch {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 5)];
+}
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FatArch64 {
+        /// cpu specifier (int)
+        cputype: Option<CpuType>,
+        /// machine specifier (int)
+        cpusubtype: Option<CpuSubtype>,
+        /// file offset to this object file
+        offset: Option<Offset64>,
+        /// size of this object file
+        size: Option<Size64>,
+        /// alignment as a power of 2
+        align: Option<Count>,
+        reserved: Option<Reserved32>,
+    },
+    display = true,
+);
+impl Layout for FatArch64 {
+    const LAYOUT: &'static [(usize, usize)] = &[(4, 2), (8, 2), (4, 2)];
+}
@@ -0,0 +1,168 @@
+use super::reader::read_struct;
+use super::{CpuType, FatArch, FatArch64, FatHeader, FatMagic, Layout, MachO, MachOError};
+use crate::file::Seekable;
+
+/// Java class files share FAT_MAGIC. Their version in place of nfat_arch is 45 or later.
+const MAX_ARCHITECTURES: u32 = 32;
+
+/// Fat (universal) binary that contains Mach-O files for several architectures.
+#[derive(Debug, Clone)]
+pub struct FatBinary<'a> {
+    seeker: Seekable<'a>,
+    header: FatHeader,
+    archs: Vec<FatArch64>,
+}
+
+impl<'a> FatBinary<'a> {
+    /// Interpret fat binary that starts at offset 0 of `seeker`.
+    /// Architectures are normalized to fat_arch_64.
+    pub fn parse(seeker: Seekable<'a>) -> Result<Self, MachOError> {
+        let header = read_struct::<FatHeader>(&seeker, 0, true)?;
+        let is_64 = match *header.get_magic_unwrap() {
+            FatMagic::FAT_MAGIC => false,
+            FatMagic::FAT_MAGIC_64 => true,
+            _ => return Err(MachOError::BadMagic),
+        };
+        let count = header.get_nfat_arch_unwrap().inner();
+        if count > MAX_ARCHITECTURES {
+            return Err(MachOError::TooManyArchitectures(count));
+        }
+
+        let mut at = FatHeader::size();
+        let mut archs = vec![];
+        for _ in 0..count {
+            if is_64 {
+                archs.push(read_struct::<FatArch64>(&seeker, at, true)?);
+                at += FatArch64::size();
+            } else {
+                let x = read_struct::<FatArch>(&seeker, at, true)?;
+                archs.push(FatArch64 {
+                    cputype: x.cputype,
+                    cpusubtype: x.cpusubtype,
+                    offset: x.offset.map(|x| super::Offset64::new(x.inner() as u64)),
+                    size: x.size.map(|x| super::Size64::new(x.inner() as u64)),
+                    align: x.align,
+                    reserved: Some(super::Reserved32::new(0)),
+                });
+                at += FatArch::size();
+            }
+        }
+
+        Ok(Self {
+            seeker,
+            header,
+            archs,
+        })
+    }
+
+    pub fn header(&self) -> &FatHeader {
+        &self.header
+    }
+
+    pub fn archs(&self) -> &[FatArch64] {
+        &self.archs
+    }
+
+    /// Find architecture by CPU type.
+    pub fn find(&self, cputype: CpuType) -> Option<&FatArch64> {
+        self.archs.iter().find(|x| *x.get_cputype_unwrap() == cputype)
+    }
+
+    /// Seekable limited to the slice of `arch`.
+    /// If the slice is out of file, this returns None.
+    pub fn arch_seeker(&self, arch: &FatArch64) -> Option<Seekable<'a>> {
+        let offset = usize::try_from(arch.get_offset_unwrap().inner()).ok()?;
+        let size = usize::try_from(arch.get_size_unwrap().inner()).ok()?;
+        self.seeker.range(offset, size)
+    }
+
+    /// Interpret the slice of `arch` as Mach-O file.
+    pub fn macho(&self, arch: &FatArch64) -> Result<MachO<'a>, MachOError> {
+        let offset = arch.get_offset_unwrap().inner() as usize;
+        let seeker = self
+            .arch_seeker(arch)
+            .ok_or(MachOError::Truncated(offset))?;
+        MachO::parse(seeker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const FAT: &[u8] = include_bytes!("../../../example_bin/macho_fat");
+    const X86_64: &[u8] = include_bytes!("../../../example_bin/macho_x86_64");
+
+    #[test]
+    fn archs() {
+        let s = Sequential::from_vec(FAT.to_vec());
+        let fat = FatBinary::parse(s.to_seeakble()).unwrap();
+
+        let archs: Vec<_> = fat
+            .archs()
+            .iter()
+            .map(|x| {
+                (
+                    *x.get_cputype_unwrap(),
+                    x.get_offset_unwrap().inner(),
+                    x.get_size_unwrap().inner(),
+                    x.get_align_unwrap().inner(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            archs,
+            vec![
+                (CpuType::CPU_TYPE_X86_64, 4096, 4184, 12),
+                (CpuType::CPU_TYPE_POWERPC, 12288, 4172, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn slices() {
+        let s = Sequential::from_vec(FAT.to_vec());
+        let fat = FatBinary::parse(s.to_seeakble()).unwrap();
+
+        let arch = fat.find(CpuType::CPU_TYPE_X86_64).unwrap();
+        let seeker = fat.arch_seeker(arch).unwrap();
+        assert_eq!(seeker.base(), 4096);
+        assert_eq!(seeker.len(), X86_64.len());
+        assert_eq!(seeker.bytes_abs_pos(0, X86_64.len()), Some(X86_64));
+        assert_eq!(seeker.bytes_abs_pos(X86_64.len(), 1), None);
+
+        let m = fat.macho(arch).unwrap();
+        assert_eq!(m.symbols().unwrap()[1].name, "_main");
+        let cstring = m.section_by_name("__TEXT", "__cstring").unwrap();
+        assert_eq!(m.section_data(&cstring), Some(&b"Hello, Mach-O!\0"[..]));
+
+        let ppc = fat.macho(fat.find(CpuType::CPU_TYPE_POWERPC).unwrap()).unwrap();
+        assert!(ppc.is_big_endian());
+        assert_eq!(ppc.dylibs(), vec!["/usr/lib/libSystem.B.dylib".to_string()]);
+        assert!(fat.find(CpuType::CPU_TYPE_ARM64).is_none());
+    }
+
+    #[test]
+    fn parse_errors() {
+        // Java class file: minor version 0, major version 52.
+        let s = Sequential::from_vec(b"\xca\xfe\xba\xbe\x00\x00\x00\x34".to_vec());
+        assert_eq!(
+            FatBinary::parse(s.to_seeakble()).unwrap_err(),
+            MachOError::TooManyArchitectures(52)
+        );
+
+        let s = Sequential::from_vec(X86_64.to_vec());
+        assert_eq!(FatBinary::parse(s.to_seeakble()).unwrap_err(), MachOError::BadMagic);
+
+        // Slice runs out of file.
+        let s = Sequential::from_vec(FAT[..12288].to_vec());
+        let fat = FatBinary::parse(s.to_seeakble()).unwrap();
+        assert!(fat.arch_seeker(&fat.archs()[0]).is_some());
+        assert!(fat.arch_seeker(&fat.archs()[1]).is_none());
+        assert_eq!(
+            fat.macho(&fat.archs()[1]).unwrap_err(),
+            MachOError::Truncated(12288)
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use super::{
+    swap_fields, Header, Layout, LoadCommand, LoadCommandHeader, Magic, Nlist, Nlist64,
+    Section, Section64, SegmentCommand, SegmentCommand64, SymtabCommand, Uuid, HEADER_64_SIZE,
+    HEADER_SIZE, LOAD_COMMAND_HEADER_SIZE,
+};
+use crate::file::Seekable;
+use crate::types::{primitive::NullTermString, FromU8Array};
+
+/// Error of Mach-O parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachOError {
+    /// File does not start with Mach-O or fat magic.
+    BadMagic,
+
+    /// The structure at the offset is out of file.
+    Truncated(usize),
+
+    /// cmdsize of the load command at the offset is smaller than its header or runs out of sizeofcmds.
+    BadCommandSize(usize),
+
+    /// Fat header declares too many architectures. This is likely a Java class file.
+    TooManyArchitectures(u32),
+}
+
+impl core::fmt::Display for MachOError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MachOError::BadMagic => write!(fmt, "bad Mach-O magic"),
+            MachOError::Truncated(at) => write!(fmt, "truncated structure at {:#x}", at),
+            MachOError::BadCommandSize(at) => write!(fmt, "bad load command size at {:#x}", at),
+            MachOError::TooManyArchitectures(n) => write!(fmt, "too many architectures ({})", n),
+        }
+    }
+}
+
+impl std::error::Error for MachOError {}
+
+/// Interpret `T` at `at` of `seeker`. Big endian fields are swapped before interpretation.
+pub(super) fn read_struct<T>(seeker: &Seekable, at: usize, big_endian: bool) -> Result<T, MachOError>
+where
+    T: FromU8Array + Layout,
+{
+    let mut bytes = seeker
+        .bytes_abs_pos(at, T::size())
+        .ok_or(MachOError::Truncated(at))?
+        .to_vec();
+    if big_endian {
+        swap_fields(&mut bytes, T::LAYOUT);
+    }
+    T::from_slice(&bytes)
+        .map(|(_, x)| x)
+        .map_err(|_| MachOError::Truncated(at))
+}
+
+/// Load command with its place in file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadCommandEntry<'a> {
+    /// File offset of the load command.
+    pub offset: usize,
+    pub command: LoadCommand,
+    /// Whole load command including cmd and cmdsize, in file byte order.
+    pub data: &'a [u8],
+}
+
+impl LoadCommandEntry<'_> {
+    /// lc_str at `offset` from the start of the load command.
+    pub fn string_at(&self, offset: u32) -> Option<String> {
+        let slice = self.data.get(offset as usize..)?;
+        let (_, s) = NullTermString::from_slice(slice).ok()?;
+        Some(s.to_string())
+    }
+}
+
+/// Symbol table entry and its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// Entry widened to 64-bit.
+    pub entry: Nlist64,
+}
+
+/// Thin Mach-O file of one architecture.
+/// This holds the header and load commands. Symbols are interpreted on demand.
+#[derive(Debug, Clone)]
+pub struct MachO<'a> {
+    seeker: Seekable<'a>,
+    big_endian: bool,
+    is_64: bool,
+    header: Header,
+    load_commands: Vec<LoadCommandEntry<'a>>,
+}
+
+impl<'a> MachO<'a> {
+    /// Interpret Mach-O file that starts at offset 0 of `seeker`.
+    /// The header is normalized, so the magic is MH_MAGIC or MH_MAGIC_64 for both endiannesses.
+    pub fn parse(seeker: Seekable<'a>) -> Result<Self, MachOError> {
+        let (_, magic) = seeker
+            .interpret_abs_pos::<Magic>(0)
+            .to_tuple()
+            .1
+            .map_err(|_| MachOError::Truncated(0))?;
+        let (big_endian, is_64) = match magic {
+            Magic::MH_MAGIC => (false, false),
+            Magic::MH_CIGAM => (true, false),
+            Magic::MH_MAGIC_64 => (false, true),
+            Magic::MH_CIGAM_64 => (true, true),
+            _ => return Err(MachOError::BadMagic),
+        };
+        let header = read_struct::<Header>(&seeker, 0, big_endian)?;
+
+        let mut ret = Self {
+            seeker,
+            big_endian,
+            is_64,
+            header,
+            load_commands: vec![],
+        };
+        ret.read_load_commands()?;
+
+        Ok(ret)
+    }
+
+    /// Read load commands in sizeofcmds after the header.
+    fn read_load_commands(&mut self) -> Result<(), MachOError> {
+        let start = match self.is_64 {
+            true => HEADER_64_SIZE,
+            false => HEADER_SIZE,
+        };
+        let end = start + self.header.get_sizeofcmds_unwrap().inner() as usize;
+        let mut at = start;
+
+        for _ in 0..self.header.get_ncmds_unwrap().inner() {
+            let lc = read_struct::<LoadCommandHeader>(&self.seeker, at, self.big_endian)?;
+            let cmd = *lc.get_cmd_unwrap();
+            let size = lc.get_cmdsize_unwrap().inner() as usize;
+            if size < LOAD_COMMAND_HEADER_SIZE || at + size > end {
+                return Err(MachOError::BadCommandSize(at));
+            }
+            let data = self
+                .seeker
+                .bytes_abs_pos(at, size)
+                .ok_or(MachOError::Truncated(at))?;
+
+            let mut body = data[LOAD_COMMAND_HEADER_SIZE..].to_vec();
+            if self.big_endian {
+                swap_fields(&mut body, LoadCommand::body_layout(cmd));
+            }
+            let (_, command) =
+                LoadCommand::from_body_slice(&cmd, &body).map_err(|_| MachOError::Truncated(at))?;
+            self.load_commands.push(LoadCommandEntry {
+                offset: at,
+                command,
+                data,
+            });
+            at += size;
+        }
+
+        Ok(())
+    }
+
+    pub fn seeker(&self) -> Seekable<'a> {
+        self.seeker
+    }
+
+    /// Return true if the file is big endian.
+    pub fn is_big_endian(&self) -> bool {
+        self.big_endian
+    }
+
+    /// Return true if the file uses 64-bit structures.
+    pub fn is_64(&self) -> bool {
+        self.is_64
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn load_commands(&self) -> &[LoadCommandEntry<'a>] {
+        &self.load_commands
+    }
+
+    /// Segments of LC_SEGMENT and LC_SEGMENT_64. 32-bit segments are widened.
+    pub fn segments(&self) -> Vec<SegmentCommand64> {
+        self.load_commands
+            .iter()
+            .filter_map(|x| match &x.command {
+                LoadCommand::Segment(x) => Some(widen_segment(x)),
+                LoadCommand::Segment64(x) => Some(*x),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sections of all segments in order. Section numbers of symbols start from 1 in this list.
+    /// 32-bit sections are widened.
+    pub fn sections(&self) -> Result<Vec<Section64>, MachOError> {
+        let mut ret = vec![];
+        for entry in &self.load_commands {
+            let (count, at) = match &entry.command {
+                LoadCommand::Segment(x) => (x.get_nsects_unwrap(), SegmentCommand::size()),
+                LoadCommand::Segment64(x) => (x.get_nsects_unwrap(), SegmentCommand64::size()),
+                _ => continue,
+            };
+            let mut at = entry.offset + LOAD_COMMAND_HEADER_SIZE + at;
+            for _ in 0..count.inner() {
+                if at >= entry.offset + entry.data.len() {
+                    return Err(MachOError::BadCommandSize(entry.offset));
+                }
+                if self.is_64 {
+                    ret.push(read_struct::<Section64>(&self.seeker, at, self.big_endian)?);
+                    at += Section64::size();
+                } else {
+                    let s = read_struct::<Section>(&self.seeker, at, self.big_endian)?;
+                    ret.push(widen_section(&s));
+                    at += Section::size();
+                }
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Find section by segment and section name, such as ("__TEXT", "__text").
+    pub fn section_by_name(&self, segment: &str, section: &str) -> Option<Section64> {
+        self.sections().ok()?.into_iter().find(|x| {
+            x.get_segname_unwrap().to_string_lossy() == segment
+                && x.get_sectname_unwrap().to_string_lossy() == section
+        })
+    }
+
+    /// Raw contents of section in file.
+    /// If the section is zero fill or the contents are out of file, this returns None.
+    pub fn section_data(&self, section: &Section64) -> Option<&'a [u8]> {
+        let kind = section.get_flags_unwrap().section_type();
+        if kind.is_S_ZEROFILL() || kind.is_S_GB_ZEROFILL() || kind.is_S_THREAD_LOCAL_ZEROFILL() {
+            return None;
+        }
+        self.seeker.bytes_abs_pos(
+            section.get_offset_unwrap().inner() as usize,
+            section.get_size_unwrap().inner() as usize,
+        )
+    }
+
+    /// Paths of dylibs in LC_*_DYLIB commands.
+    pub fn dylibs(&self) -> Vec<String> {
+        self.load_commands
+            .iter()
+            .filter_map(|x| {
+                let dylib = x.command.dylib()?;
+                x.string_at(dylib.get_name_unwrap().inner())
+            })
+            .collect()
+    }
+
+    /// Path of dynamic linker in LC_LOAD_DYLINKER.
+    pub fn dylinker(&self) -> Option<String> {
+        self.load_commands.iter().find_map(|x| match &x.command {
+            LoadCommand::LoadDylinker(d) => x.string_at(d.get_name_unwrap().inner()),
+            _ => None,
+        })
+    }
+
+    pub fn uuid(&self) -> Option<Uuid> {
+        self.load_commands.iter().find_map(|x| match &x.command {
+            LoadCommand::Uuid(x) => Some(*x.get_uuid_unwrap()),
+            _ => None,
+        })
+    }
+
+    /// File offset of main() in LC_MAIN.
+    pub fn entry_offset(&self) -> Option<u64> {
+        self.load_commands.iter().find_map(|x| match &x.command {
+            LoadCommand::Main(x) => Some(x.get_entryoff_unwrap().inner()),
+            _ => None,
+        })
+    }
+
+    pub fn symtab(&self) -> Option<&SymtabCommand> {
+        self.load_commands.iter().find_map(|x| match &x.command {
+            LoadCommand::Symtab(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    /// Symbols in LC_SYMTAB. If there is no symbol table, this returns empty list.
+    /// 32-bit entries are widened.
+    pub fn symbols(&self) -> Result<Vec<Symbol>, MachOError> {
+        let symtab = match self.symtab() {
+            Some(x) => x,
+            None => return Ok(vec![]),
+        };
+        let stroff = symtab.get_stroff_unwrap().inner() as usize;
+        let strsize = symtab.get_strsize_unwrap().inner() as usize;
+        let strtab = self
+            .seeker
+            .bytes_abs_pos(stroff, strsize)
+            .ok_or(MachOError::Truncated(stroff))?;
+
+        let mut at = symtab.get_symoff_unwrap().inner() as usize;
+        let mut ret = vec![];
+        for _ in 0..symtab.get_nsyms_unwrap().inner() {
+            let entry = match self.is_64 {
+                true => read_struct::<Nlist64>(&self.seeker, at, self.big_endian)?,
+                false => widen_nlist(&read_struct::<Nlist>(&self.seeker, at, self.big_endian)?),
+            };
+            at += match self.is_64 {
+                true => Nlist64::size(),
+                false => Nlist::size(),
+            };
+
+            let name = strtab
+                .get(entry.get_n_strx_unwrap().inner() as usize..)
+                .and_then(|x| NullTermString::from_slice(x).ok())
+                .map(|(_, x)| x.to_string())
+                .unwrap_or_default();
+            ret.push(Symbol { name, entry });
+        }
+
+        Ok(ret)
+    }
+}
+
+fn widen_segment(x: &SegmentCommand) -> SegmentCommand64 {
+    SegmentCommand64 {
+        segname: x.segname,
+        vmaddr: x.vmaddr.map(|x| super::Address64::new(x.inner() as u64)),
+        vmsize: x.vmsize.map(|x| super::Size64::new(x.inner() as u64)),
+        fileoff: x.fileoff.map(|x| super::Offset64::new(x.inner() as u64)),
+        filesize: x.filesize.map(|x| super::Size64::new(x.inner() as u64)),
+        maxprot: x.maxprot,
+        initprot: x.initprot,
+        nsects: x.nsects,
+        flags: x.flags,
+    }
+}
+
+fn widen_section(x: &Section) -> Section64 {
+    Section64 {
+        sectname: x.sectname,
+        segname: x.segname,
+        addr: x.addr.map(|x| super::Address64::new(x.inner() as u64)),
+        size: x.size.map(|x| super::Size64::new(x.inner() as u64)),
+        offset: x.offset,
+        align: x.align,
+        reloff: x.reloff,
+        nreloc: x.nreloc,
+        flags: x.flags,
+        reserved1: x.reserved1,
+        reserved2: x.reserved2,
+        reserved3: Some(super::Reserved32::new(0)),
+    }
+}
+
+fn widen_nlist(x: &Nlist) -> Nlist64 {
+    Nlist64 {
+        n_strx: x.n_strx,
+        n_type: x.n_type,
+        n_sect: x.n_sect,
+        n_desc: x.n_desc,
+        n_value: x.n_value.map(|x| super::Address64::new(x.inner() as u64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::macho::{CpuType, FileType, SectionIndex, SymbolKind};
+
+    const X86_64: &[u8] = include_bytes!("../../../example_bin/macho_x86_64");
+    const PPC: &[u8] = include_bytes!("../../../example_bin/macho_ppc");
+
+    fn names(symbols: &[Symbol]) -> Vec<&str> {
+        symbols.iter().map(|x| x.name.as_str()).collect()
+    }
+
+    #[test]
+    fn x86_64_headers() {
+        let s = Sequential::from_vec(X86_64.to_vec());
+        let m = MachO::parse(s.to_seeakble()).unwrap();
+
+        assert!(m.is_64());
+        assert!(!m.is_big_endian());
+        let h = m.header();
+        assert_eq!(*h.get_magic_unwrap(), Magic::MH_MAGIC_64);
+        assert_eq!(*h.get_cputype_unwrap(), CpuType::CPU_TYPE_X86_64);
+        assert_eq!(*h.get_filetype_unwrap(), FileType::MH_EXECUTE);
+        assert!(h.get_flags_unwrap().get_MH_PIE());
+        assert_eq!(m.load_commands().len(), 9);
+
+        let segments: Vec<_> = m
+            .segments()
+            .iter()
+            .map(|x| (x.get_segname_unwrap().to_string_lossy(), x.get_vmaddr_unwrap().inner()))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                ("__PAGEZERO".to_string(), 0),
+                ("__TEXT".to_string(), 0x100000000),
+                ("__LINKEDIT".to_string(), 0x100001000),
+            ]
+        );
+
+        let sections = m.sections().unwrap();
+        assert_eq!(sections.len(), 2);
+        let text = m.section_by_name("__TEXT", "__text").unwrap();
+        assert_eq!(text.get_addr_unwrap().inner(), 0x100000400);
+        assert!(text.get_flags_unwrap().get_S_ATTR_PURE_INSTRUCTIONS());
+        let cstring = m.section_by_name("__TEXT", "__cstring").unwrap();
+        assert!(cstring.get_flags_unwrap().section_type().is_S_CSTRING_LITERALS());
+        assert_eq!(m.section_data(&cstring), Some(&b"Hello, Mach-O!\0"[..]));
+
+        assert_eq!(m.dylibs(), vec!["/usr/lib/libSystem.B.dylib".to_string()]);
+        assert_eq!(m.dylinker().as_deref(), Some("/usr/lib/dyld"));
+        let dylib = m.load_commands().iter().find_map(|x| x.command.dylib()).unwrap();
+        assert_eq!(dylib.get_current_version_unwrap().to_string(), "1292.0.0");
+        assert_eq!(dylib.get_compatibility_version_unwrap().to_string(), "1.0.0");
+        assert_eq!(
+            m.uuid().unwrap().to_string(),
+            "10111213-1415-1617-1819-1A1B1C1D1E1F"
+        );
+        assert_eq!(m.entry_offset(), Some(0x400));
+    }
+
+    #[test]
+    fn x86_64_symbols() {
+        let s = Sequential::from_vec(X86_64.to_vec());
+        let m = MachO::parse(s.to_seeakble()).unwrap();
+        let symbols = m.symbols().unwrap();
+
+        assert_eq!(names(&symbols), vec!["__mh_execute_header", "_main", "_puts"]);
+        let main = &symbols[1].entry;
+        assert_eq!(main.get_n_type_unwrap().kind(), SymbolKind::N_SECT);
+        assert!(main.get_n_type_unwrap().get_N_EXT());
+        assert_eq!(main.get_n_sect_unwrap().inner(), 1);
+        assert_eq!(main.get_n_value_unwrap().inner(), 0x100000400);
+        let puts = &symbols[2].entry;
+        assert_eq!(puts.get_n_type_unwrap().kind(), SymbolKind::N_UNDF);
+        assert_eq!(*puts.get_n_sect_unwrap(), SectionIndex::NO_SECT);
+
+        let dysymtab = m
+            .load_commands()
+            .iter()
+            .find_map(|x| match &x.command {
+                LoadCommand::Dysymtab(x) => Some(*x),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(dysymtab.get_nextdefsym_unwrap().inner(), 2);
+        assert_eq!(dysymtab.get_iundefsym_unwrap().inner(), 2);
+    }
+
+    #[test]
+    fn ppc_big_endian() {
+        let s = Sequential::from_vec(PPC.to_vec());
+        let m = MachO::parse(s.to_seeakble()).unwrap();
+
+        assert!(!m.is_64());
+        assert!(m.is_big_endian());
+        assert_eq!(*m.header().get_magic_unwrap(), Magic::MH_MAGIC);
+        assert_eq!(*m.header().get_cputype_unwrap(), CpuType::CPU_TYPE_POWERPC);
+        assert_eq!(m.header().get_sizeofcmds_unwrap().inner(), 464);
+        assert_eq!(m.load_commands().len(), 7);
+
+        let text = m.section_by_name("__TEXT", "__text").unwrap();
+        assert_eq!(text.get_addr_unwrap().inner(), 0x1400);
+        assert_eq!(text.get_size_unwrap().inner(), 0xc);
+        assert_eq!(m.sections().unwrap().len(), 2);
+        assert_eq!(m.segments()[0].get_vmaddr_unwrap().inner(), 0x1000);
+
+        assert_eq!(m.dylibs(), vec!["/usr/lib/libSystem.B.dylib".to_string()]);
+        assert!(m.uuid().is_some());
+        assert_eq!(m.entry_offset(), None);
+        let symbols = m.symbols().unwrap();
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[1].name, "_main");
+        assert_eq!(symbols[1].entry.get_n_value_unwrap().inner(), 0x1400);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let s = Sequential::from_vec(b"\x7fELF\x02\x01\x01\x00".to_vec());
+        assert_eq!(MachO::parse(s.to_seeakble()).unwrap_err(), MachOError::BadMagic);
+
+        let s = Sequential::from_vec(X86_64[..16].to_vec());
+        assert_eq!(MachO::parse(s.to_seeakble()).unwrap_err(), MachOError::Truncated(0));
+
+        // cmdsize of the first load command runs out of sizeofcmds.
+        let mut v = X86_64.to_vec();
+        v[HEADER_64_SIZE + 4..HEADER_64_SIZE + 8].copy_from_slice(&0x1000u32.to_le_bytes());
+        let s = Sequential::from_vec(v);
+        assert_eq!(
+            MachO::parse(s.to_seeakble()).unwrap_err(),
+            MachOError::BadCommandSize(HEADER_64_SIZE)
+        );
+    }
+}
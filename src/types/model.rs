@@ -1,4 +1,4 @@
-use super::{Array, FromU8Array};
+use super::{Array, FromU8Array, RawBytes};
 
 #[macro_use]
 pub mod macros;
@@ -6,11 +6,89 @@ pub mod macros;
 /// Display nest width.
 pub const NEST_DEPTH: usize = 4;
 
+/// Severity of Diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth to know, but not a problem.
+    Info,
+    /// Suspicious, but it may be interpreted.
+    Warning,
+    /// The value is insane.
+    Error,
+}
+
+impl core::fmt::Display for Severity {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Severity::Info => write!(fmt, "info"),
+            Severity::Warning => write!(fmt, "warning"),
+            Severity::Error => write!(fmt, "error"),
+        }
+    }
+}
+
+/// A finding of validation.
+/// `path` is field path from the validated object, e.g, `e_ident[3]` or `sh_flags`.
+/// It is empty if the finding is about the validated object itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: String,
+    pub value: String,
+    pub severity: Severity,
+    pub reason: String,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic about the validated object itself.
+    pub fn new(severity: Severity, value: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            path: String::new(),
+            value: value.into(),
+            severity,
+            reason: reason.into(),
+        }
+    }
+
+    /// Prepend field name to path. Indexes(`[N]`) are concatenated without dot.
+    pub fn nest(mut self, name: &str) -> Self {
+        if self.path.is_empty() || self.path.starts_with('[') {
+            self.path.insert_str(0, name);
+        } else {
+            self.path.insert(0, '.');
+            self.path.insert_str(0, name);
+        }
+        self
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.path.is_empty() {
+            write!(fmt, "{}: {}: {}", self.severity, self.value, self.reason)
+        } else {
+            write!(
+                fmt,
+                "{}: {} = {}: {}",
+                self.severity, self.path, self.value, self.reason
+            )
+        }
+    }
+}
+
 /// Modeled types.
 /// This has sanityness e.g, file offset value is too big.
 pub trait ModelFromU8Array: FromU8Array {
+    /// Return diagnostics that explain why the value is insane.
+    /// Empty if the value is valid.
+    fn validate(&self) -> Vec<Diagnostic>;
+
     /// Return true if value is valid.
-    fn is_sanity(&self) -> bool;
+    /// Diagnostics that are less than Severity::Error are ignored.
+    fn is_sanity(&self) -> bool {
+        self.validate()
+            .iter()
+            .all(|d| d.severity < Severity::Error)
+    }
 }
 
 /// Composed type members are Option<$mtype>.
@@ -28,8 +106,29 @@ impl<T, const N: usize> ModelFromU8Array for Array<T, N>
 where
     T: ModelFromU8Array,
 {
-    fn is_sanity(&self) -> bool {
-        self.0.iter().all(|x| x.is_sanity())
+    fn validate(&self) -> Vec<Diagnostic> {
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(i, x)| x.validate().into_iter().map(move |d| d.nest(&format!("[{}]", i))))
+            .collect()
+    }
+}
+impl ModelFromU8Array for RawBytes {
+    fn validate(&self) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+impl core::fmt::Display for RawBytes {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "[")?;
+        for (i, x) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(fmt, " ")?;
+            }
+            write!(fmt, "{:0>2x}", x)?;
+        }
+        write!(fmt, "]")
     }
 }
 impl<T, const N: usize> core::fmt::Display for Array<T, N>
@@ -182,6 +281,163 @@ mod tests {
         assert_eq!(d.is_sanity(), false);
     }
 
+    define_model_type!(
+        #[derive(Copy, Clone, PartialEq, Eq)]
+        struct Tag(u8),
+        pub
+        [
+            (SHORT: 1),
+            (WIDE: 2),
+            (NOBODY: 3),
+        ],
+        display = true,
+        bitflags = false,
+    );
+    define_model_type!(
+        #[derive(Copy, Clone, PartialEq, Eq)]
+        struct Len(u8),
+        [],
+        display = true,
+        bitflags = false,
+    );
+
+    define_tagged_type!(
+        #[derive(PartialEq, Eq)]
+        enum Tagged(Tag, Len) {
+            (SHORT: Short(MT1)),
+            (WIDE: Wide(MT2)),
+        },
+        display = true,
+    );
+    define_tagged_type!(
+        #[derive(PartialEq, Eq)]
+        enum TaggedNoLen(Tag) {
+            (SHORT: Short(MT1)),
+        },
+        display = true,
+    );
+
+    #[test]
+    fn tagged_type_select() {
+        let slice: &[u8] = &[1, 3, 0, 0, 0xff, 3, 1, 5];
+        let (read, t) = Tagged::from_slice(slice).unwrap();
+
+        // The length bounds the body even if the body is shorter.
+        assert_eq!(read, 5);
+        assert_eq!(t, Tagged::Short(MT1::VAL));
+        assert_eq!(t.tag(), Tag::SHORT);
+        assert_eq!(t.is_sanity(), true);
+        assert_eq!(t.to_string(), "Short(VAL(0))");
+
+        let (read, t) = Tagged::from_slice(&slice[5..]).unwrap();
+        assert_eq!(read, 3);
+        assert_eq!(t, Tagged::Raw(Tag::NOBODY, vec![5].into()));
+    }
+
+    #[test]
+    fn tagged_type_raw() {
+        let slice: &[u8] = &[3, 2, 0xab, 0xcd, 1];
+        let (read, t) = Tagged::from_slice(slice).unwrap();
+
+        assert_eq!(read, 4);
+        assert_eq!(t, Tagged::Raw(Tag::NOBODY, vec![0xab, 0xcd].into()));
+        assert_eq!(t.tag(), Tag::NOBODY);
+        assert_eq!(t.is_sanity(), false);
+        assert_eq!(t.to_string(), "Raw(NOBODY(3), [ab cd])");
+        assert_eq!(t.to_slice().as_ref(), &slice[..4]);
+
+        let (read, t) = TaggedNoLen::from_slice(slice).unwrap();
+        assert_eq!(read, 5);
+        assert_eq!(t, TaggedNoLen::Raw(Tag::NOBODY, vec![2, 0xab, 0xcd, 1].into()));
+    }
+
+    #[test]
+    fn tagged_type_to_slice() {
+        let t = Tagged::Short(MT1::new(0x0102));
+        assert_eq!(t.to_slice().as_ref(), &[1, 2, 0x02, 0x01]);
+
+        let t = TaggedNoLen::Short(MT1::new(0x0102));
+        assert_eq!(t.to_slice().as_ref(), &[1, 0x02, 0x01]);
+
+        // The length field is a byte, so the body must not be truncated to 0.
+        let t = Tagged::Raw(Tag::NOBODY, vec![0; 256].into());
+        assert_eq!(t.try_to_slice(), Err(LengthOverflow(256)));
+        let t = Tagged::Raw(Tag::NOBODY, vec![0; 255].into());
+        assert_eq!(t.try_to_slice().unwrap()[..2], [3, 255]);
+    }
+
+    #[test]
+    fn tagged_type_error() {
+        // Length runs past the slice.
+        let slice: &[u8] = &[1, 4, 0, 0];
+        assert_eq!(Tagged::from_slice(slice), Err(FromU8Error::NotEnoughSlice(None)));
+
+        // Body runs past its length.
+        let slice: &[u8] = &[1, 1, 0, 0];
+        assert_eq!(
+            Tagged::from_slice(slice),
+            Err(FromU8Error::InvalidValue((3, Some(Tagged::Raw(Tag::SHORT, vec![0].into())))))
+        );
+    }
+
+    #[test]
+    fn validate_paths() {
+        let mut d = MockType {
+            a: Some([MT1::VAL, MT1::VAL, MT1::VAL].into()),
+            v: None,
+            bf: Some(BF(0b11)),
+        };
+        assert_eq!(d.validate()[0].path, "v");
+        assert_eq!(d.validate()[0].reason, "missing");
+
+        d.v = Some(MT2::VAL);
+        d.get_a_unwrap_mut()[1] = 10.into();
+        *d.get_bf_unwrap_mut().inner_mut() |= 0b100;
+
+        let diags = d.validate();
+        assert_eq!(diags.len(), 2);
+        assert_eq!(diags[0].path, "a[1]");
+        assert_eq!(diags[0].value, "10");
+        assert_eq!(diags[0].severity, Severity::Error);
+        assert_eq!(diags[1].path, "bf");
+        assert_eq!(diags[1].reason, "unknown bits 4");
+    }
+
+    #[test]
+    fn validate_ranges_and_predicates() {
+        define_model_type!(
+            #[derive(PartialEq, Eq)]
+            struct Free(u32),
+            [],
+            display = true, bitflags = false,
+        );
+        define_model_type!(
+            #[derive(PartialEq, Eq)]
+            struct Ranged(u32),
+            pub
+            [
+                (SPECIAL: 0xff),
+            ],
+            display = true, bitflags = false,
+            valid = [0..=0x10, 0x20..0x30],
+            predicates = [
+                (Severity::Warning, "odd", |x: &u32| x.is_multiple_of(2)),
+            ],
+        );
+
+        assert_eq!(Free::new(0xdeadbeef).validate(), vec![]);
+
+        assert!(Ranged::new(0x10).validate().is_empty());
+        assert!(Ranged::new(0x2e).validate().is_empty());
+        assert_eq!(Ranged::new(0x30).validate()[0].severity, Severity::Error);
+
+        let diags = Ranged::SPECIAL.validate();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, Severity::Warning);
+        assert_eq!(diags[0].to_string(), "warning: 255: odd");
+        assert_eq!(Ranged::SPECIAL.is_sanity(), true);
+    }
+
     #[test]
     fn bitflags_to_string() {
         define_model_type!(
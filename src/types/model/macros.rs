@@ -154,13 +154,13 @@ macro_rules! define_model_type_normal {
     ) => {
         impl core::fmt::Debug for $strname {
             fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                $(
-                    if self == &Self::$name {
-                        return write!(fmt, "{} {{ {}({:?}) }}", core::any::type_name::<$strname>(), stringify!($name), self.0);
-                    }
-                )*
+                if let Some(name) = self.name() {
+                    return write!(fmt, "{} {{ {}({:?}) }}", core::any::type_name::<$strname>(), name, self.0);
+                }
 
-                return write!(fmt, "{} {{ {}({:?}) }}", core::any::type_name::<$strname>(), "Unknown", self.0);
+                write!(fmt, "{} {{ ", core::any::type_name::<$strname>())?;
+                self.fmt_unnamed(fmt)?;
+                write!(fmt, " }}")
             }
         }
 
@@ -184,13 +184,11 @@ macro_rules! define_model_type_normal {
 
         impl core::fmt::Display for $strname {
             fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                $(
-                    if self == &Self::$name {
-                        return write!(fmt, "{}({:?})", stringify!($name), self.0);
-                    }
-                )*
+                if let Some(name) = self.name() {
+                    return write!(fmt, "{}({:?})", name, self.0);
+                }
 
-                return write!(fmt, "{}({:?})", "Unknown", self.0);
+                self.fmt_unnamed(fmt)
             }
         }
     };
@@ -206,6 +204,7 @@ macro_rules! define_constants {
      ],
      display = $display: tt,
      bitflags = true,
+     $(predicates = [$(($psev: expr, $preason: expr, $pred: expr)),* $(,)?],)?
     ) => {
         $(#[$strmeta])*
         $str_vis struct $strname(
@@ -224,7 +223,10 @@ macro_rules! define_constants {
 
         impl crate::types::model::ModelFromU8Array for $strname {
             #[allow(unused_mut)]
-            fn is_sanity(&self) -> bool {
+            fn validate(&self) -> Vec<crate::types::model::Diagnostic> {
+                use crate::types::model::{Diagnostic, Severity};
+                let mut ret = Vec::new();
+
                 let mut copyed = Self::new(*self.inner_ref());
                 paste::paste! {
                     $(
@@ -232,7 +234,20 @@ macro_rules! define_constants {
                     )*
                 }
 
-                copyed.is_zero()
+                if !copyed.is_zero() {
+                    ret.push(Diagnostic::new(
+                        Severity::Error,
+                        format!("{:?}", self.0),
+                        format!("unknown bits {:?}", copyed.0),
+                    ));
+                }
+                $($(
+                    if !($pred)(self.inner_ref()) {
+                        ret.push(Diagnostic::new($psev, format!("{:?}", self.0), $preason));
+                    }
+                )*)?
+
+                ret
             }
         }
 
@@ -403,6 +418,10 @@ macro_rules! define_constants {
      ],
      display = $display: tt,
      bitflags = false,
+     $(ranges = [$(($label: literal: $lo: ident ..= $hi: ident)),* $(,)?],)?
+     $(aliases = [$($alias: ident),* $(,)?],)?
+     $(valid = [$($range: expr),* $(,)?],)?
+     $(predicates = [$(($psev: expr, $preason: expr, $pred: expr)),* $(,)?],)?
      ) => {
         $(#[$strmeta])*
         $str_vis struct $strname(
@@ -419,13 +438,38 @@ macro_rules! define_constants {
                                   );
 
         impl crate::types::model::ModelFromU8Array for $strname {
-            fn is_sanity(&self) -> bool {
+            #[allow(unused_mut, unused_assignments)]
+            fn validate(&self) -> Vec<crate::types::model::Diagnostic> {
+                use crate::types::model::{Diagnostic, Severity};
+                let mut ret = Vec::new();
+
+                // If neither constants nor ranges are listed, any value is acceptable.
+                let mut is_listed = false;
+                let mut is_matched = false;
                 $(
-                    if self == &Self::$name {
-                        return true;
-                    }
+                    is_listed = true;
+                    is_matched |= self == &Self::$name;
                 )*
-                return false;
+                $($(
+                    is_listed = true;
+                    is_matched |= ($range).contains(self.inner_ref());
+                )*)?
+                is_matched |= self.class().is_some();
+
+                if is_listed && !is_matched {
+                    ret.push(Diagnostic::new(
+                        Severity::Error,
+                        format!("{:?}", self.0),
+                        "not a listed constant or in a valid range",
+                    ));
+                }
+                $($(
+                    if !($pred)(self.inner_ref()) {
+                        ret.push(Diagnostic::new($psev, format!("{:?}", self.0), $preason));
+                    }
+                )*)?
+
+                ret
             }
         }
 
@@ -452,6 +496,64 @@ macro_rules! define_constants {
                 }
             }
         }
+
+        impl $strname {
+            /// Constant names that are never displayed.
+            /// Another constant that has the same value, or the range, is displayed instead.
+            #[allow(unused)]
+            pub const ALIASES: &'static [&'static str] = &[$($(stringify!($alias)),*)?];
+
+            /// Primary name of the value.
+            /// This is the first listed constant that is not an alias.
+            #[allow(unused)]
+            pub fn name(&self) -> Option<&'static str> {
+                $(
+                    if self == &Self::$name && !Self::ALIASES.contains(&stringify!($name)) {
+                        return Some(stringify!($name));
+                    }
+                )*
+                None
+            }
+
+            /// All of constant names of the value, including aliases.
+            #[allow(unused)]
+            pub fn names(&self) -> Vec<&'static str> {
+                let mut ret = Vec::new();
+                $(
+                    if self == &Self::$name {
+                        ret.push(stringify!($name));
+                    }
+                )*
+                ret
+            }
+
+            /// Label of the first range that the value belongs to.
+            #[allow(unused)]
+            pub fn class(&self) -> Option<&'static str> {
+                $($(
+                    if (Self::$lo.0..=Self::$hi.0).contains(&self.0) {
+                        return Some($label);
+                    }
+                )*)?
+                None
+            }
+
+            /// Write the value that has no primary name.
+            #[allow(unused)]
+            fn fmt_unnamed(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                $($(
+                    if (Self::$lo.0..=Self::$hi.0).contains(&self.0) {
+                        return write!(fmt, "{}({:#x})", $label, self.0);
+                    }
+                )*)?
+                $($(
+                    if ($range).contains(&self.0) {
+                        return write!(fmt, "{:?}", self.0);
+                    }
+                )*)?
+                write!(fmt, "{}({:?})", "Unknown", self.0)
+            }
+        }
      };
 }
 
@@ -476,6 +578,22 @@ macro_rules! define_constants {
 /// In any cases, it can take attributes(struct and member).
 /// If you provide "display_implementation = true" at last, this macro implements Display
 /// trait(pretty print)
+///
+/// Classifications and validation rules can follow them.
+/// ```ignore
+/// display = true, bitflags = false,
+/// ranges = [
+///     ("OS-specific": LOOS..=HIOS), // Displayed as `OS-specific(0xfe05)`. Values in ranges are valid.
+/// ],
+/// aliases = [LOOS, HIOS], // Never displayed. The first non-alias constant of the value wins.
+/// valid = [0..=0x10, 0x20..0x30], // Values in ranges are valid as well as constants.
+/// predicates = [
+///     (Severity::Warning, "reason", |x: &InnerType| check(x)), // Reported if it returns false.
+/// ],
+/// ```
+/// If neither constants nor ranges are listed, any value is valid.
+/// Bitflags types take only predicates.
+/// Order of them is fixed as above, and each of them is optional.
 #[macro_export]
 macro_rules! define_model_type {
     (
@@ -789,17 +907,22 @@ macro_rules! define_composed_type {
         }
 
         impl crate::types::model::ModelFromU8Array for $struct_name {
-            fn is_sanity(&self) -> bool {
+            fn validate(&self) -> Vec<crate::types::model::Diagnostic> {
+                use crate::types::model::{Diagnostic, Severity};
+                let mut ret = Vec::new();
+
                 $(
-                    if !match &self.$member {
-                        Some(x) => x.is_sanity(),
-                        None => false
-                    } {
-                        return false;
+                    match &self.$member {
+                        Some(x) => ret.extend(
+                            x.validate().into_iter().map(|d| d.nest(stringify!($member)))
+                        ),
+                        None => ret.push(
+                            Diagnostic::new(Severity::Error, "None", "missing").nest(stringify!($member))
+                        ),
                     }
                 )*
 
-                return true;
+                ret
             }
         }
 
@@ -827,3 +950,225 @@ macro_rules! define_composed_type {
         }
     };
 }
+
+/// Define tagged types (discriminated records).
+/// A discriminant modeled type selects which composed/modeled body follows it.
+/// Tags that have no variant fall back to `Raw`, which keeps the tag and undecoded bytes.
+/// ```ignore
+/// define_tagged_type!(
+///     pub enum EnumName(TagType, LengthType) { // LengthType is optional.
+///         (TAG_CONSTANT1: Variant1(Type1)), // TagType::TAG_CONSTANT1 selects Type1.
+///         (TAG_CONSTANT2: Variant2(Type2)),
+///     },
+///     display = true,
+/// );
+/// ```
+/// If LengthType is provided, it is read after the tag and bounds the body.
+/// Writing a body longer than LengthType can hold fails in `try_to_slice`, and panics in `to_slice`.
+/// Otherwise, the body is interpreted from the rest of slice, and `Raw` consumes all of it.
+/// If you provide "display = true" at last, this macro implements Display trait.
+#[macro_export]
+macro_rules! define_tagged_type {
+    (
+        $(#[$enum_meta: meta])*
+        $vis: vis enum $enum_name: ident ($tag: ty $(, $len: ty)?) {
+            $(
+                $(#[$variant_meta: meta])*
+                ($cname: ident: $variant: ident ($vtype: ty)),
+            )*
+        },
+        display = true,
+    ) => {
+        crate::define_tagged_type!(
+            $(#[$enum_meta])*
+            $vis enum $enum_name ($tag $(, $len)?) {
+                $(
+                    $(#[$variant_meta])*
+                    ($cname: $variant ($vtype)),
+                )*
+            },
+            display = false,
+        );
+        impl core::fmt::Display for $enum_name {
+            fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    $(
+                        Self::$variant(x) => {
+                            write!(fmt, "{}(", stringify!($variant))?;
+                            core::fmt::Display::fmt(x, fmt)?;
+                            write!(fmt, ")")
+                        }
+                    )*
+                    Self::Raw(tag, raw) => write!(fmt, "Raw({}, {})", tag, raw),
+                }
+            }
+        }
+    };
+    (
+        $(#[$enum_meta: meta])*
+        $vis: vis enum $enum_name: ident ($tag: ty $(, $len: ty)?) {
+            $(
+                $(#[$variant_meta: meta])*
+                ($cname: ident: $variant: ident ($vtype: ty)),
+            )*
+        },
+        display = false,
+    ) => {
+        $(#[$enum_meta])*
+        $vis enum $enum_name {
+            $(
+                $(#[$variant_meta])*
+                $variant($vtype),
+            )*
+            /// The tag has no variant. This holds the tag and undecoded body.
+            Raw($tag, crate::types::RawBytes),
+        }
+
+        impl $enum_name {
+            /// Return the discriminant of self.
+            #[allow(unused)]
+            pub fn tag(&self) -> $tag {
+                match self {
+                    $(
+                        Self::$variant(_) => <$tag>::$cname,
+                    )*
+                    Self::Raw(tag, _) => <$tag>::new(*tag.inner_ref()),
+                }
+            }
+
+            /// Interpret body that is selected by `tag`.
+            /// Use this if the tag is not placed just before the body.
+            #[allow(unused)]
+            pub fn from_body_slice(
+                tag: &$tag,
+                slice: &[u8],
+            ) -> Result<(usize, Self), crate::types::FromU8Error<Box<Self>>> {
+                use crate::types::{FromU8Array, FromU8Error};
+                $(
+                    if tag == &<$tag>::$cname {
+                        return match <$vtype>::from_slice(slice) {
+                            Ok((read, x)) => Ok((read, Self::$variant(x))),
+                            Err(FromU8Error::NotEnoughSlice(_)) => Err(FromU8Error::NotEnoughSlice(None)),
+                            Err(FromU8Error::InvalidValue((read, x))) => {
+                                Err(FromU8Error::InvalidValue((read, x.map(|x| Box::new(Self::$variant(x))))))
+                            }
+                        };
+                    }
+                )*
+
+                let raw = crate::types::RawBytes::from(slice.to_vec());
+                Ok((slice.len(), Self::Raw(<$tag>::new(*tag.inner_ref()), raw)))
+            }
+
+            /// Transform body (without tag and length) to slice on heap.
+            #[allow(unused)]
+            pub fn body_to_slice(&self) -> Box<[u8]> {
+                use crate::types::FromU8Array;
+                match self {
+                    $(
+                        Self::$variant(x) => x.to_slice(),
+                    )*
+                    Self::Raw(_, raw) => raw.to_slice(),
+                }
+            }
+
+            /// Transform to slice on heap. This fails if the body does not fit in the length field.
+            #[allow(unused)]
+            pub fn try_to_slice(&self) -> Result<Box<[u8]>, crate::types::LengthOverflow> {
+                use crate::types::FromU8Array;
+                let body = self.body_to_slice();
+                let mut ret = Vec::new();
+
+                ret.extend_from_slice(self.tag().to_slice().as_ref());
+                $(
+                    let len = body.len().try_into().map_err(|_| crate::types::LengthOverflow(body.len()))?;
+                    ret.extend_from_slice(<$len>::new(len).to_slice().as_ref());
+                )?
+                ret.extend_from_slice(body.as_ref());
+
+                Ok(ret.into_boxed_slice())
+            }
+        }
+
+        impl crate::types::FromU8Array for $enum_name {
+            #[allow(unused_mut)]
+            fn from_slice(slice: &[u8]) -> Result<(usize, Self), crate::types::FromU8Error<Self>> {
+                use crate::types::FromU8Error;
+
+                let (mut total, tag) = match <$tag>::from_slice(slice) {
+                    Ok(x) => x,
+                    Err(FromU8Error::NotEnoughSlice(_)) => return Err(FromU8Error::NotEnoughSlice(None)),
+                    Err(FromU8Error::InvalidValue((read, _))) => return Err(FromU8Error::InvalidValue((read, None))),
+                };
+                let slice = &slice[total..];
+                #[allow(unused)]
+                let bound: Option<usize> = None;
+
+                $(
+                    let (read, len) = match <$len>::from_slice(slice) {
+                        Ok(x) => x,
+                        Err(_) => return Err(FromU8Error::NotEnoughSlice(None)),
+                    };
+                    total += read;
+                    let len = *len.inner_ref() as usize;
+                    if slice.len() < read + len {
+                        return Err(FromU8Error::NotEnoughSlice(None));
+                    }
+                    let slice = &slice[read..read + len];
+                    let bound = Some(len);
+                )?
+
+                match Self::from_body_slice(&tag, slice) {
+                    Ok((read, x)) => Ok((total + bound.unwrap_or(read), x)),
+                    Err(FromU8Error::InvalidValue((read, x))) => {
+                        Err(FromU8Error::InvalidValue((total + bound.unwrap_or(read), x.map(|x| *x))))
+                    }
+                    Err(FromU8Error::NotEnoughSlice(_)) => match bound {
+                        // The body overruns its own length.
+                        Some(len) => Err(FromU8Error::InvalidValue((
+                            total + len,
+                            Some(Self::Raw(tag, crate::types::RawBytes::from(slice.to_vec()))),
+                        ))),
+                        None => Err(FromU8Error::NotEnoughSlice(None)),
+                    },
+                }
+            }
+
+            fn to_slice(&self) -> Box<[u8]> {
+                match self.try_to_slice() {
+                    Ok(x) => x,
+                    Err(e) => panic!("body of {} bytes does not fit in the length field", e.0),
+                }
+            }
+        }
+
+        impl crate::types::model::ModelFromU8Array for $enum_name {
+            fn validate(&self) -> Vec<crate::types::model::Diagnostic> {
+                use crate::types::model::{Diagnostic, Severity};
+                match self {
+                    $(
+                        Self::$variant(x) => x
+                            .validate()
+                            .into_iter()
+                            .map(|d| d.nest(stringify!($variant)))
+                            .collect(),
+                    )*
+                    Self::Raw(tag, _) => vec![
+                        Diagnostic::new(Severity::Error, format!("{:?}", tag.inner_ref()), "unknown tag")
+                    ],
+                }
+            }
+        }
+
+        impl core::fmt::Debug for $enum_name {
+            fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    $(
+                        Self::$variant(x) => write!(fmt, "{}::{}({:?})", core::any::type_name::<$enum_name>(), stringify!($variant), x),
+                    )*
+                    Self::Raw(tag, raw) => write!(fmt, "{}::Raw({:?}, {:?})", core::any::type_name::<$enum_name>(), tag, raw),
+                }
+            }
+        }
+    };
+}
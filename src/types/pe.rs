@@ -0,0 +1,694 @@
+use super::Array;
+use crate::{define_composed_type, define_model_type, define_tagged_type};
+
+mod directory;
+mod reader;
+pub use directory::{ExportTarget, ExportedSymbol, Exports, ImportedLibrary, ImportedSymbol};
+pub use reader::{Pe, PeError};
+
+/// Size of IMAGE_DOS_HEADER in bytes.
+pub const DOS_HEADER_SIZE: usize = 0x40;
+
+/// Size of IMAGE_FILE_HEADER in bytes.
+pub const FILE_HEADER_SIZE: usize = 20;
+
+/// Size of IMAGE_DATA_DIRECTORY in bytes.
+pub const DATA_DIRECTORY_SIZE: usize = 8;
+
+/// Size of IMAGE_SECTION_HEADER in bytes.
+pub const SECTION_HEADER_SIZE: usize = 40;
+
+/// Size of IMAGE_IMPORT_DESCRIPTOR in bytes.
+pub const IMPORT_DESCRIPTOR_SIZE: usize = 20;
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DosMagic(u16),
+    pub
+    [
+#define IMAGE_DOS_SIGNATURE                 0x5A4D      // MZ
+    ],
+    display = true, bitflags = false,
+    valid = [0x5a4d..=0x5a4d],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DosWord(u16),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DosReserved4(Array<u16, 4>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for DosReserved4 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.0.inner())
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DosReserved10(Array<u16, 10>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for DosReserved10 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.0.inner())
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct NewHeaderOffset(u32),
+    [],
+    display = true, bitflags = false,
+    valid = [DOS_HEADER_SIZE as u32..],
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DosHeader {
+        /// Magic number
+        e_magic: Option<DosMagic>,
+        /// Bytes on last page of file
+        e_cblp: Option<DosWord>,
+        /// Pages in file
+        e_cp: Option<DosWord>,
+        /// Relocations
+        e_crlc: Option<DosWord>,
+        /// Size of header in paragraphs
+        e_cparhdr: Option<DosWord>,
+        /// Minimum extra paragraphs needed
+        e_minalloc: Option<DosWord>,
+        /// Maximum extra paragraphs needed
+        e_maxalloc: Option<DosWord>,
+        /// Initial (relative) SS value
+        e_ss: Option<DosWord>,
+        /// Initial SP value
+        e_sp: Option<DosWord>,
+        /// Checksum
+        e_csum: Option<DosWord>,
+        /// Initial IP value
+        e_ip: Option<DosWord>,
+        /// Initial (relative) CS value
+        e_cs: Option<DosWord>,
+        /// File address of relocation table
+        e_lfarlc: Option<DosWord>,
+        /// Overlay number
+        e_ovno: Option<DosWord>,
+        /// Reserved words
+        e_res: Option<DosReserved4>,
+        /// OEM identifier (for e_oeminfo)
+        e_oemid: Option<DosWord>,
+        /// OEM information; e_oemid specific
+        e_oeminfo: Option<DosWord>,
+        /// Reserved words
+        e_res2: Option<DosReserved10>,
+        /// File address of new exe header
+        e_lfanew: Option<NewHeaderOffset>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct PeSignature(u32),
+    pub
+    [
+#define IMAGE_NT_SIGNATURE                  0x00004550  // PE00
+    ],
+    display = true, bitflags = false,
+    valid = [0x4550..=0x4550],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Machine(u16),
+    pub
+    [
+#define IMAGE_FILE_MACHINE_UNKNOWN           0
+#define IMAGE_FILE_MACHINE_TARGET_HOST       0x0001  // Useful for indicating we want to interact with the host and not a WoW guest.
+#define IMAGE_FILE_MACHINE_I386              0x014c  // Intel 386.
+#define IMAGE_FILE_MACHINE_R3000             0x0162  // MIPS little-endian, 0x160 big-endian
+#define IMAGE_FILE_MACHINE_R4000             0x0166  // MIPS little-endian
+#define IMAGE_FILE_MACHINE_R10000            0x0168  // MIPS little-endian
+#define IMAGE_FILE_MACHINE_WCEMIPSV2         0x0169  // MIPS little-endian WCE v2
+#define IMAGE_FILE_MACHINE_ALPHA             0x0184  // Alpha_AXP
+#define IMAGE_FILE_MACHINE_SH3               0x01a2  // SH3 little-endian
+#define IMAGE_FILE_MACHINE_SH3DSP            0x01a3
+#define IMAGE_FILE_MACHINE_SH3E              0x01a4  // SH3E little-endian
+#define IMAGE_FILE_MACHINE_SH4               0x01a6  // SH4 little-endian
+#define IMAGE_FILE_MACHINE_SH5               0x01a8  // SH5
+#define IMAGE_FILE_MACHINE_ARM               0x01c0  // ARM Little-Endian
+#define IMAGE_FILE_MACHINE_THUMB             0x01c2  // ARM Thumb/Thumb-2 Little-Endian
+#define IMAGE_FILE_MACHINE_ARMNT             0x01c4  // ARM Thumb-2 Little-Endian
+#define IMAGE_FILE_MACHINE_AM33              0x01d3
+#define IMAGE_FILE_MACHINE_POWERPC           0x01F0  // IBM PowerPC Little-Endian
+#define IMAGE_FILE_MACHINE_POWERPCFP         0x01f1
+#define IMAGE_FILE_MACHINE_IA64              0x0200  // Intel 64
+#define IMAGE_FILE_MACHINE_MIPS16            0x0266  // MIPS
+#define IMAGE_FILE_MACHINE_ALPHA64           0x0284  // ALPHA64
+#define IMAGE_FILE_MACHINE_MIPSFPU           0x0366  // MIPS
+#define IMAGE_FILE_MACHINE_MIPSFPU16         0x0466  // MIPS
+#define IMAGE_FILE_MACHINE_TRICORE           0x0520  // Infineon
+#define IMAGE_FILE_MACHINE_CEF               0x0CEF
+#define IMAGE_FILE_MACHINE_EBC               0x0EBC  // EFI Byte Code
+#define IMAGE_FILE_MACHINE_RISCV32           0x5032
+#define IMAGE_FILE_MACHINE_RISCV64           0x5064
+#define IMAGE_FILE_MACHINE_RISCV128          0x5128
+#define IMAGE_FILE_MACHINE_LOONGARCH32       0x6232
+#define IMAGE_FILE_MACHINE_LOONGARCH64       0x6264
+#define IMAGE_FILE_MACHINE_AMD64             0x8664  // AMD64 (K8)
+#define IMAGE_FILE_MACHINE_M32R              0x9041  // M32R little-endian
+#define IMAGE_FILE_MACHINE_ARM64             0xAA64  // ARM64 Little-Endian
+#define IMAGE_FILE_MACHINE_CEE               0xC0EE
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SectionCount(u16),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct TimeDateStamp(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SymbolCount(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct OptionalHeaderSize(u16),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Characteristics(u16),
+    pub
+    [
+#define IMAGE_FILE_RELOCS_STRIPPED           0x0001  // Relocation info stripped from file.
+#define IMAGE_FILE_EXECUTABLE_IMAGE          0x0002  // File is executable  (i.e. no unresolved external references).
+#define IMAGE_FILE_LINE_NUMS_STRIPPED        0x0004  // Line nunbers stripped from file.
+#define IMAGE_FILE_LOCAL_SYMS_STRIPPED       0x0008  // Local symbols stripped from file.
+#define IMAGE_FILE_AGGRESIVE_WS_TRIM         0x0010  // Aggressively trim working set
+#define IMAGE_FILE_LARGE_ADDRESS_AWARE       0x0020  // App can handle >2gb addresses
+#define IMAGE_FILE_BYTES_REVERSED_LO         0x0080  // Bytes of machine word are reversed.
+#define IMAGE_FILE_32BIT_MACHINE             0x0100  // 32 bit word machine.
+#define IMAGE_FILE_DEBUG_STRIPPED            0x0200  // Debugging info stripped from file in .DBG file
+#define IMAGE_FILE_REMOVABLE_RUN_FROM_SWAP   0x0400  // If Image is on removable media, copy and run from the swap file.
+#define IMAGE_FILE_NET_RUN_FROM_SWAP         0x0800  // If Image is on Net, copy and run from the swap file.
+#define IMAGE_FILE_SYSTEM                    0x1000  // System File.
+#define IMAGE_FILE_DLL                       0x2000  // File is a DLL.
+#define IMAGE_FILE_UP_SYSTEM_ONLY            0x4000  // File should only be run on a UP machine
+#define IMAGE_FILE_BYTES_REVERSED_HI         0x8000  // Bytes of machine word are reversed.
+    ],
+    display = true, bitflags = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct FileHeader {
+        machine: Option<Machine>,
+        number_of_sections: Option<SectionCount>,
+        time_date_stamp: Option<TimeDateStamp>,
+        /// File offset of COFF symbol table
+        pointer_to_symbol_table: Option<FileOffset>,
+        number_of_symbols: Option<SymbolCount>,
+        size_of_optional_header: Option<OptionalHeaderSize>,
+        characteristics: Option<Characteristics>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct OptionalMagic(u16),
+    pub
+    [
+#define IMAGE_NT_OPTIONAL_HDR32_MAGIC      0x10b
+#define IMAGE_NT_OPTIONAL_HDR64_MAGIC      0x20b
+#define IMAGE_ROM_OPTIONAL_HDR_MAGIC       0x107
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct LinkerVersion(u8),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct VersionNumber(u16),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Size(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Size64(u64),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
+    pub struct Rva(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
+    pub struct FileOffset(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ImageBase32(u32),
+    [],
+    display = true, bitflags = false,
+    predicates = [
+        (Severity::Warning, "not aligned to 64K", |x: &u32| x & 0xffff == 0),
+    ],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ImageBase64(u64),
+    [],
+    display = true, bitflags = false,
+    predicates = [
+        (Severity::Warning, "not aligned to 64K", |x: &u64| x & 0xffff == 0),
+    ],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Alignment(u32),
+    [],
+    display = true, bitflags = false,
+    predicates = [
+        (Severity::Error, "not a power of two", |x: &u32| x.is_power_of_two()),
+    ],
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Reserved32(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct CheckSum(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Subsystem(u16),
+    pub
+    [
+#define IMAGE_SUBSYSTEM_UNKNOWN              0   // Unknown subsystem.
+#define IMAGE_SUBSYSTEM_NATIVE               1   // Image doesn't require a subsystem.
+#define IMAGE_SUBSYSTEM_WINDOWS_GUI          2   // Image runs in the Windows GUI subsystem.
+#define IMAGE_SUBSYSTEM_WINDOWS_CUI          3   // Image runs in the Windows character subsystem.
+#define IMAGE_SUBSYSTEM_OS2_CUI              5   // image runs in the OS/2 character subsystem.
+#define IMAGE_SUBSYSTEM_POSIX_CUI            7   // image runs in the Posix character subsystem.
+#define IMAGE_SUBSYSTEM_NATIVE_WINDOWS       8   // image is a native Win9x driver.
+#define IMAGE_SUBSYSTEM_WINDOWS_CE_GUI       9   // Image runs in the Windows CE subsystem.
+#define IMAGE_SUBSYSTEM_EFI_APPLICATION      10  //
+#define IMAGE_SUBSYSTEM_EFI_BOOT_SERVICE_DRIVER  11   //
+#define IMAGE_SUBSYSTEM_EFI_RUNTIME_DRIVER   12  //
+#define IMAGE_SUBSYSTEM_EFI_ROM              13
+#define IMAGE_SUBSYSTEM_XBOX                 14
+#define IMAGE_SUBSYSTEM_WINDOWS_BOOT_APPLICATION 16
+#define IMAGE_SUBSYSTEM_XBOX_CODE_CATALOG    17
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DllCharacteristics(u16),
+    pub
+    [
+#define IMAGE_LIBRARY_PROCESS_INIT            0x0001     // Reserved.
+#define IMAGE_LIBRARY_PROCESS_TERM            0x0002     // Reserved.
+#define IMAGE_LIBRARY_THREAD_INIT             0x0004     // Reserved.
+#define IMAGE_LIBRARY_THREAD_TERM             0x0008     // Reserved.
+#define IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA    0x0020  // Image can handle a high entropy 64-bit virtual address space.
+#define IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE 0x0040     // DLL can move.
+#define IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY    0x0080     // Code Integrity Image
+#define IMAGE_DLLCHARACTERISTICS_NX_COMPAT    0x0100     // Image is NX compatible
+#define IMAGE_DLLCHARACTERISTICS_NO_ISOLATION 0x0200     // Image understands isolation and doesn't want it
+#define IMAGE_DLLCHARACTERISTICS_NO_SEH       0x0400     // Image does not use SEH.  No SE handler may reside in this image
+#define IMAGE_DLLCHARACTERISTICS_NO_BIND      0x0800     // Do not bind this image.
+#define IMAGE_DLLCHARACTERISTICS_APPCONTAINER 0x1000     // Image should execute in an AppContainer
+#define IMAGE_DLLCHARACTERISTICS_WDM_DRIVER   0x2000     // Driver uses WDM model
+#define IMAGE_DLLCHARACTERISTICS_GUARD_CF     0x4000     // Image supports Control Flow Guard.
+#define IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE     0x8000
+    ],
+    display = true, bitflags = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct RvaCount(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct OptionalHeader32 {
+        major_linker_version: Option<LinkerVersion>,
+        minor_linker_version: Option<LinkerVersion>,
+        size_of_code: Option<Size>,
+        size_of_initialized_data: Option<Size>,
+        size_of_uninitialized_data: Option<Size>,
+        address_of_entry_point: Option<Rva>,
+        base_of_code: Option<Rva>,
+        base_of_data: Option<Rva>,
+        image_base: Option<ImageBase32>,
+        section_alignment: Option<Alignment>,
+        file_alignment: Option<Alignment>,
+        major_operating_system_version: Option<VersionNumber>,
+        minor_operating_system_version: Option<VersionNumber>,
+        major_image_version: Option<VersionNumber>,
+        minor_image_version: Option<VersionNumber>,
+        major_subsystem_version: Option<VersionNumber>,
+        minor_subsystem_version: Option<VersionNumber>,
+        win32_version_value: Option<Reserved32>,
+        size_of_image: Option<Size>,
+        size_of_headers: Option<Size>,
+        check_sum: Option<CheckSum>,
+        subsystem: Option<Subsystem>,
+        dll_characteristics: Option<DllCharacteristics>,
+        size_of_stack_reserve: Option<Size>,
+        size_of_stack_commit: Option<Size>,
+        size_of_heap_reserve: Option<Size>,
+        size_of_heap_commit: Option<Size>,
+        loader_flags: Option<Reserved32>,
+        /// Number of data directories that follow
+        number_of_rva_and_sizes: Option<RvaCount>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct OptionalHeader64 {
+        major_linker_version: Option<LinkerVersion>,
+        minor_linker_version: Option<LinkerVersion>,
+        size_of_code: Option<Size>,
+        size_of_initialized_data: Option<Size>,
+        size_of_uninitialized_data: Option<Size>,
+        address_of_entry_point: Option<Rva>,
+        base_of_code: Option<Rva>,
+        image_base: Option<ImageBase64>,
+        section_alignment: Option<Alignment>,
+        file_alignment: Option<Alignment>,
+        major_operating_system_version: Option<VersionNumber>,
+        minor_operating_system_version: Option<VersionNumber>,
+        major_image_version: Option<VersionNumber>,
+        minor_image_version: Option<VersionNumber>,
+        major_subsystem_version: Option<VersionNumber>,
+        minor_subsystem_version: Option<VersionNumber>,
+        win32_version_value: Option<Reserved32>,
+        size_of_image: Option<Size>,
+        size_of_headers: Option<Size>,
+        check_sum: Option<CheckSum>,
+        subsystem: Option<Subsystem>,
+        dll_characteristics: Option<DllCharacteristics>,
+        size_of_stack_reserve: Option<Size64>,
+        size_of_stack_commit: Option<Size64>,
+        size_of_heap_reserve: Option<Size64>,
+        size_of_heap_commit: Option<Size64>,
+        loader_flags: Option<Reserved32>,
+        /// Number of data directories that follow
+        number_of_rva_and_sizes: Option<RvaCount>,
+    },
+    display = true,
+);
+
+// Optional header without data directories. They are variable length, so they are read separately.
+define_tagged_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub enum OptionalHeader(OptionalMagic) {
+        (IMAGE_NT_OPTIONAL_HDR32_MAGIC: Pe32(OptionalHeader32)),
+        (IMAGE_NT_OPTIONAL_HDR64_MAGIC: Pe32Plus(OptionalHeader64)),
+    },
+    display = true,
+);
+
+impl OptionalHeader {
+    pub fn address_of_entry_point(&self) -> Option<u32> {
+        match self {
+            OptionalHeader::Pe32(x) => Some(x.get_address_of_entry_point_unwrap().inner()),
+            OptionalHeader::Pe32Plus(x) => Some(x.get_address_of_entry_point_unwrap().inner()),
+            OptionalHeader::Raw(..) => None,
+        }
+    }
+
+    /// Preferred address of the image. PE32 image base is widened.
+    pub fn image_base(&self) -> Option<u64> {
+        match self {
+            OptionalHeader::Pe32(x) => Some(x.get_image_base_unwrap().inner() as u64),
+            OptionalHeader::Pe32Plus(x) => Some(x.get_image_base_unwrap().inner()),
+            OptionalHeader::Raw(..) => None,
+        }
+    }
+
+    pub fn size_of_headers(&self) -> Option<u32> {
+        match self {
+            OptionalHeader::Pe32(x) => Some(x.get_size_of_headers_unwrap().inner()),
+            OptionalHeader::Pe32Plus(x) => Some(x.get_size_of_headers_unwrap().inner()),
+            OptionalHeader::Raw(..) => None,
+        }
+    }
+
+    pub fn subsystem(&self) -> Option<Subsystem> {
+        match self {
+            OptionalHeader::Pe32(x) => Some(*x.get_subsystem_unwrap()),
+            OptionalHeader::Pe32Plus(x) => Some(*x.get_subsystem_unwrap()),
+            OptionalHeader::Raw(..) => None,
+        }
+    }
+
+    pub fn dll_characteristics(&self) -> Option<DllCharacteristics> {
+        match self {
+            OptionalHeader::Pe32(x) => Some(*x.get_dll_characteristics_unwrap()),
+            OptionalHeader::Pe32Plus(x) => Some(*x.get_dll_characteristics_unwrap()),
+            OptionalHeader::Raw(..) => None,
+        }
+    }
+
+    /// Number of data directories declared by the header.
+    pub fn number_of_rva_and_sizes(&self) -> Option<u32> {
+        match self {
+            OptionalHeader::Pe32(x) => Some(x.get_number_of_rva_and_sizes_unwrap().inner()),
+            OptionalHeader::Pe32Plus(x) => Some(x.get_number_of_rva_and_sizes_unwrap().inner()),
+            OptionalHeader::Raw(..) => None,
+        }
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DirectoryEntry(usize),
+    pub
+    [
+#define IMAGE_DIRECTORY_ENTRY_EXPORT          0   // Export Directory
+#define IMAGE_DIRECTORY_ENTRY_IMPORT          1   // Import Directory
+#define IMAGE_DIRECTORY_ENTRY_RESOURCE        2   // Resource Directory
+#define IMAGE_DIRECTORY_ENTRY_EXCEPTION       3   // Exception Directory
+#define IMAGE_DIRECTORY_ENTRY_SECURITY        4   // Security Directory
+#define IMAGE_DIRECTORY_ENTRY_BASERELOC       5   // Base Relocation Table
+#define IMAGE_DIRECTORY_ENTRY_DEBUG           6   // Debug Directory
+#define IMAGE_DIRECTORY_ENTRY_ARCHITECTURE    7   // Architecture Specific Data
+#define IMAGE_DIRECTORY_ENTRY_GLOBALPTR       8   // RVA of GP
+#define IMAGE_DIRECTORY_ENTRY_TLS             9   // TLS Directory
+#define IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG    10   // Load Configuration Directory
+#define IMAGE_DIRECTORY_ENTRY_BOUND_IMPORT   11   // Bound Import Directory in headers
+#define IMAGE_DIRECTORY_ENTRY_IAT            12   // Import Address Table
+#define IMAGE_DIRECTORY_ENTRY_DELAY_IMPORT   13   // Delay Load Import Descriptors
+#define IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR 14   // COM Runtime descriptor
+    ],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct DataDirectory {
+        /// RVA of the table. Security directory holds a file offset instead.
+        virtual_address: Option<Rva>,
+        size: Option<Size>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SectionName(Array<u8, 8>),
+    [],
+    display = false, bitflags = false,
+);
+impl core::fmt::Display for SectionName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}", self.to_string_lossy())
+    }
+}
+impl SectionName {
+    /// Name without trailing NULs. Names of object files may be "/n", an offset in the string table.
+    pub fn to_string_lossy(&self) -> String {
+        let bytes = self.0.inner();
+        let len = bytes.iter().position(|x| *x == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..len]).into_owned()
+    }
+}
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Count16(u16),
+    [],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SectionCharacteristics(u32),
+    pub
+    [
+#define IMAGE_SCN_TYPE_NO_PAD                0x00000008  // Reserved.
+#define IMAGE_SCN_CNT_CODE                   0x00000020  // Section contains code.
+#define IMAGE_SCN_CNT_INITIALIZED_DATA       0x00000040  // Section contains initialized data.
+#define IMAGE_SCN_CNT_UNINITIALIZED_DATA     0x00000080  // Section contains uninitialized data.
+#define IMAGE_SCN_LNK_OTHER                  0x00000100  // Reserved.
+#define IMAGE_SCN_LNK_INFO                   0x00000200  // Section contains comments or some other type of information.
+#define IMAGE_SCN_LNK_REMOVE                 0x00000800  // Section contents will not become part of image.
+#define IMAGE_SCN_LNK_COMDAT                 0x00001000  // Section contents comdat.
+#define IMAGE_SCN_NO_DEFER_SPEC_EXC          0x00004000  // Reset speculative exceptions handling bits in the TLB entries for this section.
+#define IMAGE_SCN_GPREL                      0x00008000  // Section content can be accessed relative to GP
+#define IMAGE_SCN_MEM_PURGEABLE              0x00020000
+#define IMAGE_SCN_MEM_LOCKED                 0x00040000
+#define IMAGE_SCN_MEM_PRELOAD                0x00080000
+#define IMAGE_SCN_ALIGN_MASK                 0x00F00000
+#define IMAGE_SCN_LNK_NRELOC_OVFL            0x01000000  // Section contains extended relocations.
+#define IMAGE_SCN_MEM_DISCARDABLE            0x02000000  // Section can be discarded.
+#define IMAGE_SCN_MEM_NOT_CACHED             0x04000000  // Section is not cachable.
+#define IMAGE_SCN_MEM_NOT_PAGED              0x08000000  // Section is not pageable.
+#define IMAGE_SCN_MEM_SHARED                 0x10000000  // Section is shareable.
+#define IMAGE_SCN_MEM_EXECUTE                0x20000000  // Section is executable.
+#define IMAGE_SCN_MEM_READ                   0x40000000  // Section is readable.
+#define IMAGE_SCN_MEM_WRITE                  0x80000000u32// Section is writeable.
+    ],
+    display = true, bitflags = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SectionHeader {
+        name: Option<SectionName>,
+        /// Size of section in memory
+        virtual_size: Option<Size>,
+        virtual_address: Option<Rva>,
+        /// Size of initialized data in file
+        size_of_raw_data: Option<Size>,
+        pointer_to_raw_data: Option<FileOffset>,
+        pointer_to_relocations: Option<FileOffset>,
+        pointer_to_linenumbers: Option<FileOffset>,
+        number_of_relocations: Option<Count16>,
+        number_of_linenumbers: Option<Count16>,
+        characteristics: Option<SectionCharacteristics>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ForwarderChain(u32),
+    [
+        (NONE: 0xffffffff),
+    ],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ImportDescriptor {
+        /// RVA of import lookup table
+        original_first_thunk: Option<Rva>,
+        /// 0 if not bound, -1 if bound to the time in bound import directory
+        time_date_stamp: Option<TimeDateStamp>,
+        forwarder_chain: Option<ForwarderChain>,
+        /// RVA of DLL name
+        name: Option<Rva>,
+        /// RVA of import address table
+        first_thunk: Option<Rva>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct Count32(u32),
+    [],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct ExportDirectory {
+        characteristics: Option<Reserved32>,
+        time_date_stamp: Option<TimeDateStamp>,
+        major_version: Option<VersionNumber>,
+        minor_version: Option<VersionNumber>,
+        /// RVA of DLL name
+        name: Option<Rva>,
+        /// Ordinal of the first entry of export address table
+        base: Option<Count32>,
+        number_of_functions: Option<Count32>,
+        number_of_names: Option<Count32>,
+        /// RVA of export address table
+        address_of_functions: Option<Rva>,
+        /// RVA of export name pointer table
+        address_of_names: Option<Rva>,
+        /// RVA of export ordinal table
+        address_of_name_ordinals: Option<Rva>,
+    },
+    display = true,
+);
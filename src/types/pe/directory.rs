@@ -0,0 +1,278 @@
+use super::{
+    DirectoryEntry, ExportDirectory, ImportDescriptor, Pe, PeError, IMPORT_DESCRIPTOR_SIZE,
+};
+
+/// Symbol imported from a DLL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportedSymbol {
+    Ordinal(u16),
+    Name {
+        /// Index into export name pointer table of the DLL. This is a hint for faster lookup.
+        hint: u16,
+        name: String,
+    },
+}
+
+impl core::fmt::Display for ImportedSymbol {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ImportedSymbol::Ordinal(x) => write!(fmt, "#{}", x),
+            ImportedSymbol::Name { name, .. } => write!(fmt, "{}", name),
+        }
+    }
+}
+
+/// DLL and symbols imported from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedLibrary {
+    pub name: String,
+    pub descriptor: ImportDescriptor,
+    pub symbols: Vec<ImportedSymbol>,
+}
+
+/// Where an exported symbol is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportTarget {
+    Rva(u32),
+
+    /// Symbol in another DLL, such as "KERNEL32.ExitProcess".
+    Forwarder(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedSymbol {
+    /// Biased ordinal. This is the number importers use.
+    pub ordinal: u32,
+    pub name: Option<String>,
+    pub target: ExportTarget,
+}
+
+/// Export directory and the symbols it exports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exports {
+    /// DLL name recorded by the linker.
+    pub name: String,
+    pub directory: ExportDirectory,
+    /// Symbols ordered by ordinal. Unused slots of export address table are skipped.
+    pub symbols: Vec<ExportedSymbol>,
+}
+
+impl Pe<'_> {
+    /// Read `count` little endian entries of `size` bytes at `rva`.
+    fn read_rva_table(&self, rva: u32, count: usize, size: usize) -> Result<Vec<u64>, PeError> {
+        let len = count.checked_mul(size).ok_or(PeError::BadRva(rva))?;
+        let bytes = self.bytes_at_rva(rva, len)?;
+        Ok(bytes
+            .chunks_exact(size)
+            .map(|x| {
+                let mut v = [0; 8];
+                v[..size].copy_from_slice(x);
+                u64::from_le_bytes(v)
+            })
+            .collect())
+    }
+
+    /// Read null terminated thunk array of import lookup table or import address table.
+    fn read_thunks(&self, mut rva: u32) -> Result<Vec<ImportedSymbol>, PeError> {
+        let (size, ordinal_flag) = match self.is_pe32_plus() {
+            true => (8, 1 << 63),
+            false => (4, 1 << 31),
+        };
+        let mut ret = vec![];
+
+        loop {
+            let thunk = self.read_rva_table(rva, 1, size)?[0];
+            if thunk == 0 {
+                break;
+            }
+            if thunk & ordinal_flag != 0 {
+                ret.push(ImportedSymbol::Ordinal(thunk as u16));
+            } else {
+                let at = thunk as u32;
+                let hint = self.interpret_at_rva::<u16>(at)?;
+                let name = self.string_at_rva(at + 2)?;
+                ret.push(ImportedSymbol::Name { hint, name });
+            }
+            rva = rva.checked_add(size as u32).ok_or(PeError::BadRva(rva))?;
+        }
+
+        Ok(ret)
+    }
+
+    /// DLLs and symbols in import directory.
+    /// If there is no import directory, this returns empty list.
+    pub fn imports(&self) -> Result<Vec<ImportedLibrary>, PeError> {
+        let directory = match self.data_directory(DirectoryEntry::IMAGE_DIRECTORY_ENTRY_IMPORT) {
+            Some(x) => x,
+            None => return Ok(vec![]),
+        };
+        let mut rva = directory.get_virtual_address_unwrap().inner();
+        let mut ret = vec![];
+
+        loop {
+            let descriptor = self.interpret_at_rva::<ImportDescriptor>(rva)?;
+            let name = descriptor.get_name_unwrap().inner();
+            let first_thunk = descriptor.get_first_thunk_unwrap().inner();
+            if name == 0 && first_thunk == 0 {
+                break;
+            }
+
+            // Import lookup table is optional. Import address table holds the same before binding.
+            let thunks = match descriptor.get_original_first_thunk_unwrap().inner() {
+                0 => first_thunk,
+                x => x,
+            };
+            ret.push(ImportedLibrary {
+                name: self.string_at_rva(name)?,
+                symbols: self.read_thunks(thunks)?,
+                descriptor,
+            });
+            rva = rva
+                .checked_add(IMPORT_DESCRIPTOR_SIZE as u32)
+                .ok_or(PeError::BadRva(rva))?;
+        }
+
+        Ok(ret)
+    }
+
+    /// Export directory and exported symbols.
+    /// If there is no export directory, this returns None.
+    pub fn exports(&self) -> Result<Option<Exports>, PeError> {
+        let directory = match self.data_directory(DirectoryEntry::IMAGE_DIRECTORY_ENTRY_EXPORT) {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let start = directory.get_virtual_address_unwrap().inner();
+        let range = start..start.saturating_add(directory.get_size_unwrap().inner());
+
+        let export = self.interpret_at_rva::<ExportDirectory>(start)?;
+        let base = export.get_base_unwrap().inner();
+        let functions = self.read_rva_table(
+            export.get_address_of_functions_unwrap().inner(),
+            export.get_number_of_functions_unwrap().inner() as usize,
+            4,
+        )?;
+        let count = export.get_number_of_names_unwrap().inner() as usize;
+        let names = self.read_rva_table(export.get_address_of_names_unwrap().inner(), count, 4)?;
+        let ordinals = self.read_rva_table(
+            export.get_address_of_name_ordinals_unwrap().inner(),
+            count,
+            2,
+        )?;
+
+        let mut symbol_names = vec![None; functions.len()];
+        for (name, index) in names.into_iter().zip(ordinals) {
+            if let Some(x) = symbol_names.get_mut(index as usize) {
+                *x = Some(self.string_at_rva(name as u32)?);
+            }
+        }
+
+        let mut symbols = vec![];
+        for (i, (rva, name)) in functions.into_iter().zip(symbol_names).enumerate() {
+            let rva = rva as u32;
+            if rva == 0 {
+                continue;
+            }
+            // RVA in export directory points to forwarder string.
+            let target = match range.contains(&rva) {
+                true => ExportTarget::Forwarder(self.string_at_rva(rva)?),
+                false => ExportTarget::Rva(rva),
+            };
+            symbols.push(ExportedSymbol {
+                ordinal: base.wrapping_add(i as u32),
+                name,
+                target,
+            });
+        }
+
+        Ok(Some(Exports {
+            name: self.string_at_rva(export.get_name_unwrap().inner())?,
+            directory: export,
+            symbols,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+
+    const DLL: &[u8] = include_bytes!("../../../example_bin/sample.dll");
+    const EXE: &[u8] = include_bytes!("../../../example_bin/sample32.exe");
+
+    fn named(hint: u16, name: &str) -> ImportedSymbol {
+        ImportedSymbol::Name {
+            hint,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn imports() {
+        for bin in [DLL, EXE] {
+            let s = Sequential::from_vec(bin.to_vec());
+            let pe = Pe::parse(s.to_seeakble()).unwrap();
+            let imports = pe.imports().unwrap();
+
+            assert_eq!(imports.len(), 2);
+            assert_eq!(imports[0].name, "KERNEL32.dll");
+            assert_eq!(
+                imports[0].symbols,
+                vec![named(0x167, "ExitProcess"), named(0x2d0, "GetStdHandle")]
+            );
+            assert_eq!(imports[1].name, "USER32.dll");
+            assert_eq!(
+                imports[1].symbols,
+                vec![ImportedSymbol::Ordinal(17), named(0x285, "MessageBoxA")]
+            );
+            assert_eq!(imports[1].symbols[0].to_string(), "#17");
+        }
+    }
+
+    #[test]
+    fn exports() {
+        let s = Sequential::from_vec(DLL.to_vec());
+        let pe = Pe::parse(s.to_seeakble()).unwrap();
+        let exports = pe.exports().unwrap().unwrap();
+
+        assert_eq!(exports.name, "sample.dll");
+        assert_eq!(exports.directory.get_major_version_unwrap().inner(), 1);
+        let symbols: Vec<_> = exports
+            .symbols
+            .iter()
+            .map(|x| (x.ordinal, x.name.as_deref(), x.target.clone()))
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                (1, Some("add"), ExportTarget::Rva(0x1000)),
+                (2, Some("sub"), ExportTarget::Rva(0x1004)),
+                (3, Some("exit"), ExportTarget::Forwarder("KERNEL32.ExitProcess".to_string())),
+                (4, None, ExportTarget::Rva(0x1009)),
+            ]
+        );
+
+        let s = Sequential::from_vec(EXE.to_vec());
+        assert_eq!(Pe::parse(s.to_seeakble()).unwrap().exports(), Ok(None));
+    }
+
+    #[test]
+    fn broken_tables() {
+        // Import descriptor points name out of file.
+        let mut v = DLL.to_vec();
+        let at = 0x400 + 12;
+        v[at..at + 4].copy_from_slice(&0x3000u32.to_le_bytes());
+        let s = Sequential::from_vec(v);
+        let pe = Pe::parse(s.to_seeakble()).unwrap();
+        assert_eq!(pe.imports(), Err(PeError::BadRva(0x3000)));
+
+        // Huge number of functions runs out of section.
+        let mut v = DLL.to_vec();
+        let at = 0x400 + 0xec + 20;
+        v[at..at + 4].copy_from_slice(&0x10000000u32.to_le_bytes());
+        let s = Sequential::from_vec(v);
+        let pe = Pe::parse(s.to_seeakble()).unwrap();
+        assert!(pe.exports().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use super::{
+    DataDirectory, DirectoryEntry, DosHeader, FileHeader, OptionalHeader, OptionalMagic,
+    PeSignature, SectionHeader, DATA_DIRECTORY_SIZE, FILE_HEADER_SIZE,
+};
+use crate::file::Seekable;
+use crate::types::{primitive::NullTermString, FromU8Array};
+
+/// Error of PE parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeError {
+    /// File does not start with "MZ".
+    BadDosSignature,
+
+    /// No "PE\0\0" at e_lfanew.
+    BadPeSignature,
+
+    /// The structure at the offset is out of file.
+    Truncated(usize),
+
+    /// Optional header of the magic is not supported.
+    UnsupportedOptionalHeader(OptionalMagic),
+
+    /// The RVA is not backed by the file.
+    BadRva(u32),
+}
+
+impl core::fmt::Display for PeError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PeError::BadDosSignature => write!(fmt, "bad DOS signature"),
+            PeError::BadPeSignature => write!(fmt, "bad PE signature"),
+            PeError::Truncated(at) => write!(fmt, "truncated structure at {:#x}", at),
+            PeError::UnsupportedOptionalHeader(m) => write!(fmt, "unsupported optional header {}", m),
+            PeError::BadRva(rva) => write!(fmt, "RVA {:#x} is not in file", rva),
+        }
+    }
+}
+
+impl std::error::Error for PeError {}
+
+/// PE image or COFF object with DOS stub.
+/// This holds the headers and section table. Directories are interpreted on demand.
+#[derive(Debug, Clone)]
+pub struct Pe<'a> {
+    seeker: Seekable<'a>,
+    dos_header: DosHeader,
+    file_header: FileHeader,
+    optional_header: Option<OptionalHeader>,
+    data_directories: Vec<DataDirectory>,
+    section_headers: Vec<SectionHeader>,
+}
+
+impl<'a> Pe<'a> {
+    /// Interpret PE file that starts at offset 0 of `seeker`.
+    /// Section headers are read as much as possible.
+    pub fn parse(seeker: Seekable<'a>) -> Result<Self, PeError> {
+        let (_, dos_header) = seeker
+            .interpret_abs_pos::<DosHeader>(0)
+            .to_tuple()
+            .1
+            .map_err(|_| PeError::Truncated(0))?;
+        if !dos_header.get_e_magic_unwrap().is_IMAGE_DOS_SIGNATURE() {
+            return Err(PeError::BadDosSignature);
+        }
+
+        let nt_offset = dos_header.get_e_lfanew_unwrap().inner() as usize;
+        let (read, signature) = seeker
+            .interpret_abs_pos::<PeSignature>(nt_offset)
+            .to_tuple()
+            .1
+            .map_err(|_| PeError::Truncated(nt_offset))?;
+        if !signature.is_IMAGE_NT_SIGNATURE() {
+            return Err(PeError::BadPeSignature);
+        }
+
+        let at = nt_offset + read;
+        let (_, file_header) = seeker
+            .interpret_abs_pos::<FileHeader>(at)
+            .to_tuple()
+            .1
+            .map_err(|_| PeError::Truncated(at))?;
+
+        let at = at + FILE_HEADER_SIZE;
+        let optional_size = file_header.get_size_of_optional_header_unwrap().inner() as usize;
+        let mut ret = Self {
+            seeker,
+            dos_header,
+            file_header,
+            optional_header: None,
+            data_directories: vec![],
+            section_headers: vec![],
+        };
+        if optional_size != 0 {
+            ret.read_optional_header(at, optional_size)?;
+        }
+
+        let count = ret.file_header.get_number_of_sections_unwrap().inner() as usize;
+        let mut at = at + optional_size;
+        for _ in 0..count {
+            match ret.seeker.interpret_abs_pos::<SectionHeader>(at).to_tuple().1 {
+                Ok((read, sh)) => {
+                    ret.section_headers.push(sh);
+                    at += read;
+                }
+                Err(_) => break,
+            }
+        }
+
+        Ok(ret)
+    }
+
+    /// Read optional header and the data directories that fit in `size`.
+    fn read_optional_header(&mut self, at: usize, size: usize) -> Result<(), PeError> {
+        let slice = self
+            .seeker
+            .bytes_abs_pos(at, size)
+            .ok_or(PeError::Truncated(at))?;
+        let (read, header) = OptionalHeader::from_slice(slice).map_err(|_| PeError::Truncated(at))?;
+        if let OptionalHeader::Raw(magic, _) = header {
+            return Err(PeError::UnsupportedOptionalHeader(magic));
+        }
+
+        let declared = header.number_of_rva_and_sizes().unwrap_or(0) as usize;
+        let mut slice = &slice[read..];
+        for _ in 0..declared.min(slice.len() / DATA_DIRECTORY_SIZE) {
+            self.data_directories.push(DataDirectory::from_slice_consume(&mut slice).unwrap());
+        }
+        self.optional_header = Some(header);
+
+        Ok(())
+    }
+
+    pub fn seeker(&self) -> Seekable<'a> {
+        self.seeker
+    }
+
+    pub fn dos_header(&self) -> &DosHeader {
+        &self.dos_header
+    }
+
+    pub fn file_header(&self) -> &FileHeader {
+        &self.file_header
+    }
+
+    pub fn optional_header(&self) -> Option<&OptionalHeader> {
+        self.optional_header.as_ref()
+    }
+
+    /// Return true if the image is PE32+ (64-bit).
+    pub fn is_pe32_plus(&self) -> bool {
+        matches!(self.optional_header, Some(OptionalHeader::Pe32Plus(_)))
+    }
+
+    pub fn data_directories(&self) -> &[DataDirectory] {
+        &self.data_directories
+    }
+
+    /// Data directory of `entry`. If it is missing or empty, this returns None.
+    pub fn data_directory(&self, entry: DirectoryEntry) -> Option<&DataDirectory> {
+        self.data_directories
+            .get(entry.inner())
+            .filter(|x| x.get_virtual_address_unwrap().inner() != 0)
+    }
+
+    pub fn section_headers(&self) -> &[SectionHeader] {
+        &self.section_headers
+    }
+
+    /// Find section header by name.
+    pub fn section_by_name(&self, name: &str) -> Option<&SectionHeader> {
+        self.section_headers
+            .iter()
+            .find(|sh| sh.get_name_unwrap().to_string_lossy() == name)
+    }
+
+    /// Section that contains `rva` in memory.
+    pub fn section_by_rva(&self, rva: u32) -> Option<&SectionHeader> {
+        self.section_headers.iter().find(|sh| {
+            let start = sh.get_virtual_address_unwrap().inner();
+            let size = sh
+                .get_virtual_size_unwrap()
+                .inner()
+                .max(sh.get_size_of_raw_data_unwrap().inner());
+            rva >= start && rva - start < size
+        })
+    }
+
+    /// Raw contents of section in file.
+    /// If the contents are out of file, this returns None.
+    pub fn section_data(&self, sh: &SectionHeader) -> Option<&'a [u8]> {
+        self.seeker.bytes_abs_pos(
+            sh.get_pointer_to_raw_data_unwrap().inner() as usize,
+            sh.get_size_of_raw_data_unwrap().inner() as usize,
+        )
+    }
+
+    /// File offset of `rva`. RVAs in headers map to the same offsets.
+    /// If `rva` is in uninitialized part of section or out of sections, this returns None.
+    pub fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        let headers = self.optional_header.as_ref().and_then(|x| x.size_of_headers());
+        if rva < headers.unwrap_or(0) {
+            return Some(rva as usize);
+        }
+
+        let sh = self.section_by_rva(rva)?;
+        let delta = rva - sh.get_virtual_address_unwrap().inner();
+        if delta >= sh.get_size_of_raw_data_unwrap().inner() {
+            return None;
+        }
+        Some(sh.get_pointer_to_raw_data_unwrap().inner() as usize + delta as usize)
+    }
+
+    /// Bytes at [`rva`, `rva` + `len`) in file.
+    pub fn bytes_at_rva(&self, rva: u32, len: usize) -> Result<&'a [u8], PeError> {
+        let offset = self.rva_to_offset(rva).ok_or(PeError::BadRva(rva))?;
+        self.seeker
+            .bytes_abs_pos(offset, len)
+            .ok_or(PeError::Truncated(offset))
+    }
+
+    /// Interpret object at `rva`.
+    pub fn interpret_at_rva<T>(&self, rva: u32) -> Result<T, PeError>
+    where
+        T: FromU8Array,
+    {
+        let offset = self.rva_to_offset(rva).ok_or(PeError::BadRva(rva))?;
+        match self.seeker.interpret_abs_pos::<T>(offset).to_tuple().1 {
+            Ok((_, x)) => Ok(x),
+            Err(_) => Err(PeError::Truncated(offset)),
+        }
+    }
+
+    /// Null terminated string at `rva`.
+    pub fn string_at_rva(&self, rva: u32) -> Result<String, PeError> {
+        self.interpret_at_rva::<NullTermString>(rva)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::pe::{Characteristics, Machine, SectionCharacteristics, Subsystem};
+
+    const DLL: &[u8] = include_bytes!("../../../example_bin/sample.dll");
+    const EXE: &[u8] = include_bytes!("../../../example_bin/sample32.exe");
+
+    #[test]
+    fn pe32_plus_headers() {
+        let s = Sequential::from_vec(DLL.to_vec());
+        let pe = Pe::parse(s.to_seeakble()).unwrap();
+        assert!(pe.is_pe32_plus());
+
+        let fh = pe.file_header();
+        assert_eq!(*fh.get_machine_unwrap(), Machine::IMAGE_FILE_MACHINE_AMD64);
+        assert!(fh.get_characteristics_unwrap().get(Characteristics::IMAGE_FILE_DLL));
+        assert_eq!(fh.get_time_date_stamp_unwrap().inner(), 0x62f4a5f2);
+
+        let oh = pe.optional_header().unwrap();
+        assert_eq!(oh.image_base(), Some(0x180000000));
+        assert_eq!(oh.address_of_entry_point(), Some(0));
+        assert_eq!(oh.subsystem(), Some(Subsystem::IMAGE_SUBSYSTEM_WINDOWS_CUI));
+        assert_eq!(pe.data_directories().len(), 16);
+        let export = pe.data_directory(DirectoryEntry::IMAGE_DIRECTORY_ENTRY_EXPORT).unwrap();
+        assert_eq!(export.get_virtual_address_unwrap().inner(), 0x20ec);
+        assert!(pe.data_directory(DirectoryEntry::IMAGE_DIRECTORY_ENTRY_TLS).is_none());
+        assert!(pe.data_directory(DirectoryEntry::new(16)).is_none());
+
+        let names: Vec<_> = pe
+            .section_headers()
+            .iter()
+            .map(|sh| sh.get_name_unwrap().to_string_lossy())
+            .collect();
+        assert_eq!(names, vec![".text", ".rdata", ".bss"]);
+        let text = pe.section_by_name(".text").unwrap();
+        assert!(text.get_characteristics_unwrap().get(SectionCharacteristics::IMAGE_SCN_MEM_EXECUTE));
+        assert_eq!(&pe.section_data(text).unwrap()[..4], &[0x8d, 0x04, 0x11, 0xc3]);
+    }
+
+    #[test]
+    fn pe32_headers() {
+        let s = Sequential::from_vec(EXE.to_vec());
+        let pe = Pe::parse(s.to_seeakble()).unwrap();
+        assert!(!pe.is_pe32_plus());
+        assert_eq!(*pe.file_header().get_machine_unwrap(), Machine::IMAGE_FILE_MACHINE_I386);
+
+        let oh = match pe.optional_header().unwrap() {
+            OptionalHeader::Pe32(x) => x,
+            x => panic!("{}", x),
+        };
+        assert_eq!(oh.get_image_base_unwrap().inner(), 0x400000);
+        assert_eq!(oh.get_base_of_data_unwrap().inner(), 0x2000);
+        assert_eq!(oh.get_address_of_entry_point_unwrap().inner(), 0x1000);
+        assert!(pe.data_directory(DirectoryEntry::IMAGE_DIRECTORY_ENTRY_EXPORT).is_none());
+    }
+
+    #[test]
+    fn rva_mapping() {
+        let s = Sequential::from_vec(DLL.to_vec());
+        let pe = Pe::parse(s.to_seeakble()).unwrap();
+
+        // Headers, .text and .rdata are mapped. .bss has no data in file.
+        assert_eq!(pe.rva_to_offset(0x80), Some(0x80));
+        assert_eq!(pe.rva_to_offset(0x1004), Some(0x204));
+        assert_eq!(pe.rva_to_offset(0x2010), Some(0x410));
+        assert_eq!(pe.rva_to_offset(0x3000), None);
+        assert_eq!(pe.rva_to_offset(0x5000), None);
+        assert_eq!(pe.section_by_rva(0x3080).unwrap().get_name_unwrap().to_string_lossy(), ".bss");
+
+        assert_eq!(pe.string_at_rva(0x80).unwrap(), "PE");
+        assert_eq!(pe.bytes_at_rva(0x3000, 1), Err(PeError::BadRva(0x3000)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let parse = |v: Vec<u8>| {
+            let s = Sequential::from_vec(v);
+            Pe::parse(s.to_seeakble()).map(|_| ())
+        };
+
+        assert_eq!(parse(b"MZ".to_vec()), Err(PeError::Truncated(0)));
+        assert_eq!(parse(b"\x7fELF".repeat(32)), Err(PeError::BadDosSignature));
+
+        let mut v = DLL.to_vec();
+        v[0x80] = b'N';
+        assert_eq!(parse(v), Err(PeError::BadPeSignature));
+
+        let mut v = DLL.to_vec();
+        v[0x3c] = 0xf0;
+        v[0x3d] = 0xff;
+        assert_eq!(parse(v), Err(PeError::Truncated(0xfff0)));
+
+        // ROM optional header is not supported.
+        let mut v = DLL.to_vec();
+        v[0x98] = 0x07;
+        v[0x99] = 0x01;
+        assert_eq!(
+            parse(v),
+            Err(PeError::UnsupportedOptionalHeader(OptionalMagic::IMAGE_ROM_OPTIONAL_HDR_MAGIC))
+        );
+
+        // Section table is cut off.
+        let s = Sequential::from_vec(DLL[..0x80 + 24 + 240 + 60].to_vec());
+        assert_eq!(Pe::parse(s.to_seeakble()).unwrap().section_headers().len(), 1);
+    }
+}
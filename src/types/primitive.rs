@@ -1,4 +1,4 @@
-use super::{Array, FromU8Array, FromU8Error};
+use super::{Array, FromU8Array, FromU8Error, RawBytes};
 
 impl<T, const N: usize> FromU8Array for Array<T, N>
 where
@@ -39,6 +39,15 @@ where
     }
 }
 
+impl FromU8Array for RawBytes {
+    fn from_slice(slice: &[u8]) -> Result<(usize, Self), FromU8Error<Self>> {
+        Ok((slice.len(), Self(slice.to_vec())))
+    }
+    fn to_slice(&self) -> Box<[u8]> {
+        self.0.clone().into_boxed_slice()
+    }
+}
+
 /// The macro implements FromU8Array.
 /// This uses primitive::from_le_bytes. so, we can apply this macro to only primitive types.
 #[macro_export]
@@ -104,6 +113,93 @@ impl FromU8Array for NullTermString {
     }
 }
 
+/// Unsigned LEB128 number of DWARF. Each byte holds 7 bits from the lowest ones, and the high bit tells if more follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uleb128(pub u64);
+
+/// Signed LEB128 number of DWARF. The value is sign extended from the last byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sleb128(pub i64);
+
+/// Length of LEB128 encoding that holds all 64 bits. Longer encodings are padded.
+const LEB128_MAX_LEN: usize = 10;
+
+impl FromU8Array for Uleb128 {
+    fn from_slice(slice: &[u8]) -> Result<(usize, Self), FromU8Error<Self>> {
+        let mut value = 0u64;
+        for (index, byte) in slice.iter().enumerate() {
+            let payload = byte & 0x7f;
+            if index < LEB128_MAX_LEN - 1 {
+                value |= (payload as u64) << (7 * index);
+            } else if payload > (index == LEB128_MAX_LEN - 1) as u8 {
+                // Bits beyond 64 may only be zero padding.
+                return Err(FromU8Error::InvalidValue((index + 1, None)));
+            } else if index == LEB128_MAX_LEN - 1 {
+                value |= (payload as u64) << 63;
+            }
+            if byte & 0x80 == 0 {
+                return Ok((index + 1, Self(value)));
+            }
+        }
+        Err(FromU8Error::NotEnoughSlice(None))
+    }
+
+    fn to_slice(&self) -> Box<[u8]> {
+        let mut ret = vec![];
+        let mut value = self.0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                ret.push(byte);
+                return ret.into_boxed_slice();
+            }
+            ret.push(byte | 0x80);
+        }
+    }
+}
+
+impl FromU8Array for Sleb128 {
+    fn from_slice(slice: &[u8]) -> Result<(usize, Self), FromU8Error<Self>> {
+        let mut value = 0i64;
+        for (index, byte) in slice.iter().enumerate() {
+            let payload = byte & 0x7f;
+            let shift = 7 * index as u32;
+            if index < LEB128_MAX_LEN - 1 {
+                value |= (payload as i64) << shift;
+            } else {
+                // Bits beyond 64 may only be the sign extension of bit 63.
+                let sign = if index == LEB128_MAX_LEN - 1 { payload & 1 } else { (value < 0) as u8 };
+                if payload != sign * 0x7f {
+                    return Err(FromU8Error::InvalidValue((index + 1, None)));
+                }
+                value |= (sign as i64) << 63;
+            }
+            if byte & 0x80 == 0 {
+                if shift + 7 < 64 && byte & 0x40 != 0 {
+                    value |= -1i64 << (shift + 7);
+                }
+                return Ok((index + 1, Self(value)));
+            }
+        }
+        Err(FromU8Error::NotEnoughSlice(None))
+    }
+
+    fn to_slice(&self) -> Box<[u8]> {
+        let mut ret = vec![];
+        let mut value = self.0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+                ret.push(byte);
+                return ret.into_boxed_slice();
+            }
+            ret.push(byte | 0x80);
+        }
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -213,4 +309,56 @@ pub mod tests {
             FromU8Error::InvalidValue((6, Some("abcdef".to_string())))
         );
     }
+
+    #[test]
+    fn uleb128() {
+        let mut slice: &[u8] = &[0x02, 0x7f, 0x80, 0x01, 0xe5, 0x8e, 0x26, 0xff];
+        let values: Vec<_> = (0..4)
+            .map(|_| Uleb128::from_slice_consume(&mut slice).unwrap().0)
+            .collect();
+        assert_eq!(values, vec![2, 127, 128, 624485]);
+        // Continuation bit without following byte.
+        assert_eq!(Uleb128::from_slice(slice), Err(FromU8Error::NotEnoughSlice(None)));
+
+        let max: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(Uleb128::from_slice(max).unwrap(), (10, Uleb128(u64::MAX)));
+        let overflow: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+        assert!(Uleb128::from_slice(overflow).is_err());
+        // Zero padding past 64 bits is still a valid encoding.
+        let padded: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x81, 0x80, 0x00];
+        assert_eq!(Uleb128::from_slice(padded).unwrap(), (12, Uleb128(u64::MAX)));
+        let padded: &[u8] = &[0x81, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+        assert_eq!(Uleb128::from_slice(padded).unwrap(), (11, Uleb128(1)));
+        let overflow: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+        assert!(Uleb128::from_slice(overflow).is_err());
+
+        assert_eq!(Uleb128(624485).to_slice().as_ref(), &[0xe5, 0x8e, 0x26]);
+        assert_eq!(Uleb128(u64::MAX).to_slice().as_ref(), max);
+    }
+
+    #[test]
+    fn sleb128() {
+        let mut slice: &[u8] = &[0x02, 0x7e, 0xff, 0x00, 0x81, 0x7f, 0x80, 0x7f, 0xc0, 0xbb, 0x78];
+        let values: Vec<_> = (0..5)
+            .map(|_| Sleb128::from_slice_consume(&mut slice).unwrap().0)
+            .collect();
+        assert_eq!(values, vec![2, -2, 127, -127, -128]);
+        assert_eq!(Sleb128::from_slice(slice).unwrap(), (3, Sleb128(-123456)));
+
+        for x in [0, -1, 63, -64, 64, -65, i64::MAX, i64::MIN] {
+            let bytes = Sleb128(x).to_slice();
+            assert_eq!(Sleb128::from_slice(&bytes).unwrap(), (bytes.len(), Sleb128(x)));
+        }
+        assert_eq!(Sleb128(-128).to_slice().as_ref(), &[0x80, 0x7f]);
+
+        // Sign extension past 64 bits is padding, anything else overflows.
+        let padded: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+        assert_eq!(Sleb128::from_slice(padded).unwrap(), (11, Sleb128(-1)));
+        let padded: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00];
+        assert_eq!(Sleb128::from_slice(padded).unwrap(), (11, Sleb128(0)));
+        let overflow: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+        assert!(Sleb128::from_slice(overflow).is_err());
+        let overflow: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+        assert!(Sleb128::from_slice(overflow).is_err());
+    }
 }
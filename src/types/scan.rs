@@ -0,0 +1,224 @@
+use super::detect::{Confidence, Format, FORMATS};
+use crate::file::{Seekable, Sequential};
+
+/// Format found by scanner.
+#[derive(Debug, Clone, Copy)]
+pub struct Payload {
+    /// Offset of the start in the binary.
+    pub offset: usize,
+    /// Number of bytes the payload spans.
+    pub len: usize,
+    pub format: &'static Format,
+    pub confidence: Confidence,
+    /// Index of the innermost payload that contains this. None for top level payloads.
+    pub parent: Option<usize>,
+    /// Number of payloads that contain this.
+    pub depth: usize,
+}
+
+impl Payload {
+    /// Offset just after the end.
+    pub fn end(&self) -> usize {
+        self.offset + self.len
+    }
+
+    /// Return true if `other` is in the range of self.
+    pub fn contains(&self, other: &Payload) -> bool {
+        self.offset <= other.offset && other.end() <= self.end()
+    }
+}
+
+/// Result of scanning a binary.
+#[derive(Debug, Clone)]
+pub struct Scan {
+    /// Payloads ordered by offset. Outer payload comes first if they start at the same offset.
+    pub payloads: Vec<Payload>,
+    /// Formats that accept the whole binary. More than one means the binary is a polyglot.
+    pub whole: Vec<&'static Format>,
+}
+
+impl Scan {
+    /// Return true if the binary is valid as several formats at once, like a zip appended to ELF with adjusted offsets.
+    pub fn is_polyglot(&self) -> bool {
+        self.whole.len() > 1
+    }
+}
+
+/// Formats that accept `seeker` as a whole, without magic filter.
+/// Formats anchored at the tail, like zip, are found here even if the binary starts with another format.
+fn whole_formats(seeker: Seekable) -> Vec<&'static Format> {
+    FORMATS
+        .iter()
+        .filter(|f| matches!((f.probe)(seeker), Some(c) if c >= Confidence::Medium))
+        .filter(|f| (f.extent)(seeker).is_some())
+        .collect()
+}
+
+/// Check `format` at `offset`. The payload must parse at least to its header and fit in the binary.
+fn payload_at(seeker: Seekable, offset: usize, format: &'static Format) -> Option<Payload> {
+    let rest = seeker.range(offset, seeker.len() - offset)?;
+    let confidence = (format.probe)(rest).filter(|c| *c >= Confidence::Medium)?;
+    let len = (format.extent)(rest).filter(|x| *x > 0 && *x <= rest.len())?;
+
+    Some(Payload {
+        offset,
+        len,
+        format,
+        confidence,
+        parent: None,
+        depth: 0,
+    })
+}
+
+/// Find every offset in `s` where a registered format parses and passes sanity checks.
+/// Formats are searched by their magics. Nesting is resolved by extents.
+/// A format is not searched again inside its own payload, since each probe may parse the rest of the binary.
+pub fn scan(s: &Sequential) -> Scan {
+    let seeker = s.to_seeakble();
+    let bytes = seeker.bytes_abs_pos(0, seeker.len()).unwrap_or(&[]);
+
+    // Skip offsets quickly by the first byte of magics.
+    let mut first = [false; 256];
+    for format in FORMATS {
+        for magic in format.magics {
+            first[magic[0] as usize] = true;
+        }
+    }
+
+    let mut payloads = vec![];
+    // End of the last payload of each format.
+    let mut covered = vec![0; FORMATS.len()];
+    for (offset, x) in bytes.iter().enumerate() {
+        if !first[*x as usize] {
+            continue;
+        }
+        for (format, covered) in FORMATS.iter().zip(covered.iter_mut()) {
+            if offset < *covered || !format.matches_magic(&bytes[offset..]) {
+                continue;
+            }
+            if let Some(payload) = payload_at(seeker, offset, format) {
+                *covered = payload.end();
+                payloads.push(payload);
+            }
+        }
+    }
+
+    payloads.sort_by(|a: &Payload, b: &Payload| a.offset.cmp(&b.offset).then(b.len.cmp(&a.len)));
+    let mut ret: Vec<Payload> = vec![];
+    for mut payload in payloads {
+        // Innermost container is the last preceding payload that contains this.
+        let parent = (0..ret.len()).rev().find(|&j| ret[j].contains(&payload));
+        payload.parent = parent;
+        payload.depth = parent.map_or(0, |j| ret[j].depth + 1);
+        ret.push(payload);
+    }
+
+    Scan {
+        payloads: ret,
+        whole: whole_formats(seeker),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::detect::Kind;
+    use crate::types::zip::ZipArchive;
+
+    const OUT: &[u8] = include_bytes!("../../example_bin/out");
+    const ZIP: &[u8] = include_bytes!("../../example_bin/archives.zip");
+    const AR: &[u8] = include_bytes!("../../example_bin/libsample.a");
+    const FAT: &[u8] = include_bytes!("../../example_bin/macho_fat");
+
+    fn summary(found: &Scan) -> Vec<(usize, usize, Kind, Option<usize>)> {
+        found.payloads
+            .iter()
+            .map(|x| (x.offset, x.len, x.format.kind, x.parent))
+            .collect()
+    }
+
+    /// Zip appended to `prefix` with offsets adjusted, so the whole binary is also a zip.
+    fn polyglot(prefix: &[u8]) -> Vec<u8> {
+        let mut v = [prefix, ZIP].concat();
+        let s = Sequential::from_vec(ZIP.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let shift = |v: &mut Vec<u8>, at: usize| {
+            let at = prefix.len() + at;
+            let x = u32::from_le_bytes(v[at..at + 4].try_into().unwrap()) + prefix.len() as u32;
+            v[at..at + 4].copy_from_slice(&x.to_le_bytes());
+        };
+        for entry in zip.entries() {
+            // Local header offset in central directory header.
+            shift(&mut v, entry.offset() + 42);
+        }
+        // Central directory offset in end of central directory record.
+        shift(&mut v, zip.eocd_offset() + 16);
+        v
+    }
+
+    #[test]
+    fn appended_zip() {
+        let s = Sequential::from_vec([OUT, ZIP].concat());
+        let found = scan(&s);
+
+        assert_eq!(
+            summary(&found),
+            vec![(0, OUT.len(), Kind::Elf, None), (OUT.len(), ZIP.len(), Kind::Zip, None)]
+        );
+        assert!(found.payloads.iter().all(|x| x.confidence == Confidence::High));
+        // Offsets in central directory are relative to the zip, so the whole binary is only ELF.
+        assert!(!found.is_polyglot());
+    }
+
+    #[test]
+    fn polyglot_elf_zip() {
+        let s = Sequential::from_vec(polyglot(OUT));
+        let found = scan(&s);
+
+        assert!(found.is_polyglot());
+        let kinds: Vec<_> = found.whole.iter().map(|x| x.kind).collect();
+        assert_eq!(kinds, vec![Kind::Elf, Kind::Zip]);
+    }
+
+    #[test]
+    fn nested() {
+        let s = Sequential::from_vec(AR.to_vec());
+        let found = scan(&s);
+        let kinds: Vec<_> = found.payloads.iter().map(|x| (x.format.kind, x.depth)).collect();
+        assert_eq!(kinds, vec![(Kind::Ar, 0), (Kind::Elf, 1), (Kind::Elf, 1)]);
+        assert_eq!(found.payloads[1].parent, Some(0));
+
+        // Fat binary in firmware-like padding.
+        let mut v = vec![0xff; 100];
+        v.extend_from_slice(FAT);
+        v.extend_from_slice(&[0xff; 100]);
+        let s = Sequential::from_vec(v);
+        let found = scan(&s);
+        assert_eq!(
+            summary(&found),
+            vec![
+                (100, FAT.len(), Kind::FatMachO, None),
+                (100 + 4096, 4184, Kind::MachO, Some(0)),
+                (100 + 12288, 4172, Kind::MachO, Some(0)),
+            ]
+        );
+        assert!(!found.is_polyglot());
+        assert!(found.whole.is_empty());
+    }
+
+    #[test]
+    fn repeated_magics() {
+        // Local file headers without data. Each of them starts a zip that spans to the end.
+        let mut header = b"PK\x03\x04".to_vec();
+        header.resize(30, 0);
+        let s = Sequential::from_vec(header.repeat(10000));
+        assert_eq!(summary(&scan(&s)), vec![(0, 300000, Kind::Zip, None)]);
+    }
+
+    #[test]
+    fn false_magics() {
+        // Magics without valid headers are not reported.
+        let s = Sequential::from_vec(b"MZ....\x7fELF....PK\x03\x04....BM....!<arch>\nxx".to_vec());
+        assert!(scan(&s).payloads.is_empty());
+    }
+}
@@ -1,4 +1,17 @@
-use crate::{define_composed_type, define_model_type};
+use crate::types::model::{Diagnostic, ModelFromU8Array};
+use crate::types::{FromU8Array, FromU8Error};
+use crate::{define_composed_type, define_model_type, define_tagged_type};
+
+mod crypto;
+mod extract;
+mod reader;
+mod time;
+mod writer;
+pub use crypto::{DecryptError, Encryption};
+pub use extract::{sanitize_path, ExtractOptions};
+pub use reader::{ZipArchive, ZipEntry, ZipError};
+pub use time::{DosDateTime, ExtendedTimestamp, NtfsTimestamps};
+pub use writer::{Compression, FileOptions, ZipWriter};
 
 define_model_type!(
     #[derive(PartialEq, Eq, Clone)]
@@ -24,26 +37,46 @@ define_model_type!(
     pub struct BitFlags(u16),
     pub
     [
+        (ENCRYPTED: 1 << 0),
+        (COMPRESSION_OPTION_1: 1 << 1),
+        (COMPRESSION_OPTION_2: 1 << 2),
+        (DATA_DESCRIPTOR: 1 << 3),
+        (ENHANCED_DEFLATION: 1 << 4),
+        (PATCHED: 1 << 5),
+        (STRONG_ENCRYPTION: 1 << 6),
+        (UTF8: 1 << 11),
+        (MASKED_HEADER: 1 << 13),
     ],
     display = true, bitflags = true,
 );
 
+// Display is implemented in time.rs.
 define_model_type!(
     #[derive(PartialEq, Eq, Clone)]
     pub struct Time(u16),
     pub
     [
     ],
-    display = true, bitflags = false,
+    display = false, bitflags = false,
+    predicates = [
+        (Severity::Warning, "hour out of range", |x: &u16| *x >> 11 < 24),
+        (Severity::Warning, "minute out of range", |x: &u16| (*x >> 5 & 0x3f) < 60),
+        (Severity::Warning, "second out of range", |x: &u16| (*x & 0x1f) < 30),
+    ],
 );
 
+// Display is implemented in time.rs.
 define_model_type!(
     #[derive(PartialEq, Eq, Clone)]
     pub struct Data(u16),
     pub
     [
     ],
-    display = true, bitflags = false,
+    display = false, bitflags = false,
+    predicates = [
+        (Severity::Warning, "month out of range", |x: &u16| (1..=12).contains(&(*x >> 5 & 0xf))),
+        (Severity::Warning, "day is zero", |x: &u16| *x & 0x1f != 0),
+    ],
 );
 
 define_model_type!(
@@ -55,6 +88,13 @@ define_model_type!(
     display = true, bitflags = false,
 );
 
+impl CRC32 {
+    /// CRC-32 of data.
+    pub fn compute(data: &[u8]) -> Self {
+        Self::new(crate::compress::crc32::crc32(data))
+    }
+}
+
 define_model_type!(
     #[derive(PartialEq, Eq, Clone)]
     pub struct Size(u32),
@@ -77,8 +117,9 @@ define_composed_type!(
     #[derive(PartialEq, Eq, Clone)]
     pub struct Header {
         magic: Option<Magic>,
-        version: Option<Version>,
+        version_needed: Option<Version>,
         general_purpose_bf: Option<BitFlags>,
+        compression_method: Option<Method>,
         last_modify_time: Option<Time>,
         last_modify_data: Option<Data>,
         crc32: Option<CRC32>,
@@ -89,3 +130,366 @@ define_composed_type!(
     },
     display = true,
 );
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct DataDescriptorMagic(u32),
+    pub
+    [
+        (VALID: 0x08074b50),
+    ],
+    display = true, bitflags = false,
+);
+
+// Data descriptor follows file data if general purpose bit 3 is set.
+// The signature is optional in APPNOTE, but writers commonly emit it.
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct DataDescriptor {
+        magic: Option<DataDescriptorMagic>,
+        crc32: Option<CRC32>,
+        compressed_size: Option<Size>,
+        uncompressed_size: Option<Size>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct CentralDirectoryMagic(u32),
+    pub
+    [
+        (VALID: 0x02014b50),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct EndOfCentralDirectoryMagic(u32),
+    pub
+    [
+        (VALID: 0x06054b50),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Method(u16),
+    pub
+    [
+        (STORED: 0),
+        (SHRUNK: 1),
+        (REDUCED_1: 2),
+        (REDUCED_2: 3),
+        (REDUCED_3: 4),
+        (REDUCED_4: 5),
+        (IMPLODED: 6),
+        (DEFLATE: 8),
+        (DEFLATE64: 9),
+        (PKWARE_IMPLODED: 10),
+        (BZIP2: 12),
+        (LZMA: 14),
+        (IBM_CMPSC: 16),
+        (IBM_TERSE: 18),
+        (IBM_LZ77: 19),
+        (ZSTD_DEPRECATED: 20),
+        (ZSTD: 93),
+        (MP3: 94),
+        (XZ: 95),
+        (JPEG: 96),
+        (WAVPACK: 97),
+        (PPMD: 98),
+        (AES: 99),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct DiskNumber(u16),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Count(u16),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct InternalAttributes(u16),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct ExternalAttributes(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Offset(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct CentralDirectoryHeader {
+        magic: Option<CentralDirectoryMagic>,
+        version_made_by: Option<Version>,
+        version_needed: Option<Version>,
+        general_purpose_bf: Option<BitFlags>,
+        compression_method: Option<Method>,
+        last_modify_time: Option<Time>,
+        last_modify_data: Option<Data>,
+        crc32: Option<CRC32>,
+        compressed_size: Option<Size>,
+        uncompressed_size: Option<Size>,
+        file_name_length: Option<Length>,
+        extra_field_length: Option<Length>,
+        file_comment_length: Option<Length>,
+        disk_number_start: Option<DiskNumber>,
+        internal_attributes: Option<InternalAttributes>,
+        external_attributes: Option<ExternalAttributes>,
+        local_header_offset: Option<Offset>,
+    },
+    display = true,
+);
+
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct EndOfCentralDirectory {
+        magic: Option<EndOfCentralDirectoryMagic>,
+        disk_number: Option<DiskNumber>,
+        central_directory_disk: Option<DiskNumber>,
+        disk_entries: Option<Count>,
+        total_entries: Option<Count>,
+        central_directory_size: Option<Size>,
+        central_directory_offset: Option<Offset>,
+        comment_length: Option<Length>,
+    },
+    display = true,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct HeaderId(u16),
+    pub
+    [
+        (ZIP64: 0x0001),
+        (AV_INFO: 0x0007),
+        (OS2: 0x0009),
+        (NTFS: 0x000a),
+        (OPENVMS: 0x000c),
+        (UNIX: 0x000d),
+        (PATCH_DESCRIPTOR: 0x000f),
+        (STRONG_ENCRYPTION: 0x0017),
+        (EXTENDED_TIMESTAMP: 0x5455),
+        (INFO_ZIP_UNIX_OLD: 0x5855),
+        (INFO_ZIP_UNICODE_COMMENT: 0x6375),
+        (INFO_ZIP_UNICODE_PATH: 0x7075),
+        (INFO_ZIP_UNIX: 0x7875),
+        (AES: 0x9901),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Zip64EndOfCentralDirectoryMagic(u32),
+    pub
+    [
+        (VALID: 0x06064b50),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Zip64LocatorMagic(u32),
+    pub
+    [
+        (VALID: 0x07064b50),
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct DiskNumber32(u32),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Count64(u64),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Size64(u64),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+define_model_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Offset64(u64),
+    pub
+    [
+    ],
+    display = true, bitflags = false,
+);
+
+// ZIP64 end of central directory record. This is followed by extensible data sector.
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Zip64EndOfCentralDirectory {
+        magic: Option<Zip64EndOfCentralDirectoryMagic>,
+        /// Size of the rest of record, excluding magic and this field
+        record_size: Option<Size64>,
+        version_made_by: Option<Version>,
+        version_needed: Option<Version>,
+        disk_number: Option<DiskNumber32>,
+        central_directory_disk: Option<DiskNumber32>,
+        disk_entries: Option<Count64>,
+        total_entries: Option<Count64>,
+        central_directory_size: Option<Size64>,
+        central_directory_offset: Option<Offset64>,
+    },
+    display = true,
+);
+
+// ZIP64 end of central directory locator. This is placed just before end of central directory record.
+define_composed_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub struct Zip64Locator {
+        magic: Option<Zip64LocatorMagic>,
+        eocd_disk: Option<DiskNumber32>,
+        eocd_offset: Option<Offset64>,
+        total_disks: Option<DiskNumber32>,
+    },
+    display = true,
+);
+
+/// Body of ZIP64 extended information extra field.
+/// This holds only values whose counterparts in the header are saturated,
+/// in order of uncompressed size, compressed size, local header offset and disk number start.
+/// Since the layout depends on the header, values are kept in order and resolved by the reader.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Zip64ExtendedInformation {
+    pub values: Vec<u64>,
+    pub disk_number_start: Option<u32>,
+}
+
+impl FromU8Array for Zip64ExtendedInformation {
+    fn from_slice(slice: &[u8]) -> Result<(usize, Self), FromU8Error<Self>> {
+        let mut chunks = slice.chunks_exact(8);
+        let values = chunks
+            .by_ref()
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        let disk_number_start = match chunks.remainder() {
+            [] => None,
+            r @ [_, _, _, _] => Some(u32::from_le_bytes(r.try_into().unwrap())),
+            _ => return Err(FromU8Error::InvalidValue((slice.len(), None))),
+        };
+
+        Ok((
+            slice.len(),
+            Self {
+                values,
+                disk_number_start,
+            },
+        ))
+    }
+    fn to_slice(&self) -> Box<[u8]> {
+        let mut ret: Vec<u8> = self.values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        if let Some(disk) = self.disk_number_start {
+            ret.extend_from_slice(&disk.to_le_bytes());
+        }
+        ret.into_boxed_slice()
+    }
+}
+
+impl ModelFromU8Array for Zip64ExtendedInformation {
+    fn validate(&self) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+
+impl core::fmt::Display for Zip64ExtendedInformation {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{:x?}", self.values)?;
+        if let Some(disk) = self.disk_number_start {
+            write!(fmt, ", disk {}", disk)?;
+        }
+        Ok(())
+    }
+}
+
+define_tagged_type!(
+    #[derive(PartialEq, Eq, Clone)]
+    pub enum ExtraField(HeaderId, Length) {
+        (ZIP64: Zip64(Zip64ExtendedInformation)),
+        (NTFS: Ntfs(NtfsTimestamps)),
+        (EXTENDED_TIMESTAMP: ExtendedTimestamp(ExtendedTimestamp)),
+    },
+    display = true,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::model::Severity;
+
+    #[test]
+    fn method_names() {
+        assert_eq!(Method::DEFLATE.to_string(), "DEFLATE(8)");
+        assert_eq!(Method::new(93).name(), Some("ZSTD"));
+        assert_eq!(Method::new(7).name(), None);
+    }
+
+    #[test]
+    fn general_purpose_flags() {
+        let bf = BitFlags::new(0x0809);
+        assert!(bf.get(BitFlags::ENCRYPTED));
+        assert!(bf.get(BitFlags::DATA_DESCRIPTOR));
+        assert!(bf.get(BitFlags::UTF8));
+        assert!(!bf.get(BitFlags::STRONG_ENCRYPTION));
+        assert!(bf.validate().is_empty());
+
+        // Bit 7 is reserved.
+        let diags = BitFlags::new(0x0080).validate();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, Severity::Error);
+    }
+}
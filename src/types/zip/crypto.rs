@@ -0,0 +1,154 @@
+//! Traditional PKWARE encryption (ZipCrypto).
+//! This is weak and only decryption is provided to read legacy archives.
+
+use crate::compress::crc32::update_raw;
+
+/// Size of encryption header that precedes encrypted data.
+pub const ENCRYPTION_HEADER_SIZE: usize = 12;
+
+/// Encryption of entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encryption {
+    None,
+    /// Traditional PKWARE encryption.
+    ZipCrypto,
+    /// WinZip AES encryption (method 99 and extra field 0x9901).
+    Aes,
+    /// PKWARE strong encryption (general purpose bit 6).
+    Strong,
+}
+
+impl core::fmt::Display for Encryption {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let s = match self {
+            Encryption::None => "none",
+            Encryption::ZipCrypto => "ZipCrypto",
+            Encryption::Aes => "AES",
+            Encryption::Strong => "strong encryption",
+        };
+        write!(fmt, "{}", s)
+    }
+}
+
+/// Error of decryption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecryptError {
+    /// Entry is encrypted, but no password is given.
+    PasswordRequired,
+
+    /// Check byte of encryption header does not match.
+    WrongPassword,
+
+    /// Encryption header is truncated.
+    Truncated,
+
+    /// The encryption is not supported.
+    Unsupported(Encryption),
+}
+
+impl core::fmt::Display for DecryptError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecryptError::PasswordRequired => write!(fmt, "password required"),
+            DecryptError::WrongPassword => write!(fmt, "wrong password"),
+            DecryptError::Truncated => write!(fmt, "encryption header is truncated"),
+            DecryptError::Unsupported(e) => write!(fmt, "unsupported encryption: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+/// Keys of ZipCrypto stream cipher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZipCryptoKeys {
+    key0: u32,
+    key1: u32,
+    key2: u32,
+}
+
+impl ZipCryptoKeys {
+    /// Initialize keys with password.
+    pub fn new(password: &[u8]) -> Self {
+        let mut ret = Self {
+            key0: 0x12345678,
+            key1: 0x23456789,
+            key2: 0x34567890,
+        };
+        for &b in password {
+            ret.update(b);
+        }
+        ret
+    }
+
+    /// Update keys with a plain byte.
+    fn update(&mut self, plain: u8) {
+        self.key0 = update_raw(self.key0, plain);
+        self.key1 = self
+            .key1
+            .wrapping_add(self.key0 & 0xff)
+            .wrapping_mul(134775813)
+            .wrapping_add(1);
+        self.key2 = update_raw(self.key2, (self.key1 >> 24) as u8);
+    }
+
+    /// Next byte of key stream.
+    fn stream_byte(&self) -> u8 {
+        let temp = (self.key2 | 2) as u16;
+        (temp.wrapping_mul(temp ^ 1) >> 8) as u8
+    }
+
+    pub fn decrypt_byte(&mut self, cipher: u8) -> u8 {
+        let plain = cipher ^ self.stream_byte();
+        self.update(plain);
+        plain
+    }
+}
+
+/// Decrypt data that starts with 12 bytes encryption header.
+/// The last byte of the header must be `check`: high byte of CRC-32, or high byte of
+/// last modification time if data descriptor is used.
+/// One in 256 wrong passwords passes the check, so the caller must verify CRC-32 as well.
+pub fn decrypt(data: &[u8], password: &[u8], check: u8) -> Result<Vec<u8>, DecryptError> {
+    if data.len() < ENCRYPTION_HEADER_SIZE {
+        return Err(DecryptError::Truncated);
+    }
+    let (header, body) = data.split_at(ENCRYPTION_HEADER_SIZE);
+
+    let mut keys = ZipCryptoKeys::new(password);
+    let header: Vec<u8> = header.iter().map(|&c| keys.decrypt_byte(c)).collect();
+    if header[ENCRYPTION_HEADER_SIZE - 1] != check {
+        return Err(DecryptError::WrongPassword);
+    }
+
+    Ok(body.iter().map(|&c| keys.decrypt_byte(c)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encrypt(plain: &[u8], password: &[u8], check: u8) -> Vec<u8> {
+        let mut keys = ZipCryptoKeys::new(password);
+        let mut header = [0x5a; ENCRYPTION_HEADER_SIZE];
+        header[ENCRYPTION_HEADER_SIZE - 1] = check;
+        header
+            .iter()
+            .chain(plain)
+            .map(|&b| {
+                let c = b ^ keys.stream_byte();
+                keys.update(b);
+                c
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let cipher = encrypt(b"hello", b"secret", 0xd0);
+        assert_eq!(cipher.len(), 17);
+        assert_eq!(decrypt(&cipher, b"secret", 0xd0).unwrap(), b"hello");
+        assert_eq!(decrypt(&cipher, b"Secret", 0xd0), Err(DecryptError::WrongPassword));
+        assert_eq!(decrypt(&cipher[..11], b"secret", 0xd0), Err(DecryptError::Truncated));
+    }
+}
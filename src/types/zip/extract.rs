@@ -0,0 +1,458 @@
+//! Extraction of zip archives into a directory.
+//! Archives are untrusted, so paths are sanitized and sizes are capped before anything is written.
+
+use super::{ZipArchive, ZipEntry, ZipError};
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Max number of links followed to resolve a link, as ELOOP of Linux.
+const MAX_LINK_DEPTH: usize = 40;
+
+/// Limits of extraction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractOptions {
+    /// Max uncompressed size of each entry.
+    pub max_entry_size: u64,
+
+    /// Max sum of uncompressed sizes of entries.
+    pub max_total_size: u64,
+
+    /// Password of ZipCrypto encrypted entries.
+    pub password: Option<Vec<u8>>,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            max_entry_size: 1 << 30,
+            max_total_size: 4 << 30,
+            password: None,
+        }
+    }
+}
+
+/// Convert entry name into relative path.
+/// Both '/' and '\\' are separators. Absolute paths, drive letters and ".." are rejected,
+/// so the result never leaves the directory it is joined to.
+pub fn sanitize_path(name: &str) -> Option<PathBuf> {
+    if name.starts_with(['/', '\\']) || name.contains('\0') {
+        return None;
+    }
+    // Drive letter such as "C:" or "C:\\".
+    if let [drive, b':', ..] = name.as_bytes() {
+        if drive.is_ascii_alphabetic() {
+            return None;
+        }
+    }
+
+    let mut ret = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            _ => ret.push(part),
+        }
+    }
+    // Components such as prefixes of the host system are not a plain file name.
+    if !ret.components().all(|c| matches!(c, Component::Normal(_))) {
+        return None;
+    }
+
+    match ret.as_os_str().is_empty() {
+        true => None,
+        false => Some(ret),
+    }
+}
+
+/// Resolve symbolic link target relatively to the link, without touching file system.
+/// `links` are all links of the archive by their paths. They exist after extraction regardless of
+/// entry order, so components naming them are followed.
+/// This returns None if the target escapes extraction root, or links nest too deep.
+fn resolve_link(links: &HashMap<PathBuf, String>, link: &Path, target: &str, depth: usize) -> Option<PathBuf> {
+    if depth > MAX_LINK_DEPTH {
+        return None;
+    }
+    if target.starts_with(['/', '\\']) || target.contains('\0') {
+        return None;
+    }
+    if let [drive, b':', ..] = target.as_bytes() {
+        if drive.is_ascii_alphabetic() {
+            return None;
+        }
+    }
+
+    let mut ret = link.parent()?.to_path_buf();
+    for part in target.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => {
+                if !ret.pop() {
+                    return None;
+                }
+            }
+            _ => {
+                ret.push(part);
+                if let Some(next) = links.get(&ret) {
+                    ret = resolve_link(links, &ret, next, depth + 1)?;
+                }
+            }
+        }
+    }
+    Some(ret)
+}
+
+fn io_error(path: &Path) -> impl Fn(std::io::Error) -> ZipError + '_ {
+    move |e| ZipError::Io {
+        path: path.to_path_buf(),
+        kind: e.kind(),
+    }
+}
+
+/// Create directories from `root` along `relative`.
+/// Existing symbolic links are not followed, since they may point out of `root`.
+fn create_dirs(root: &Path, relative: &Path) -> Result<(), ZipError> {
+    let mut path = root.to_path_buf();
+    for part in relative.components() {
+        path.push(part);
+        match std::fs::symlink_metadata(&path) {
+            Ok(m) if m.file_type().is_symlink() => {
+                return Err(ZipError::UnsafePath(relative.display().to_string()))
+            }
+            Ok(m) if m.is_dir() => {}
+            Ok(_) => {
+                return Err(ZipError::Io {
+                    path,
+                    kind: std::io::ErrorKind::AlreadyExists,
+                })
+            }
+            Err(_) => std::fs::create_dir(&path).map_err(io_error(&path))?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<(), ZipError> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).map_err(io_error(path))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> Result<(), ZipError> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &str, path: &Path) -> Result<(), ZipError> {
+    std::os::unix::fs::symlink(target, path).map_err(io_error(path))
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &str, path: &Path) -> Result<(), ZipError> {
+    Err(ZipError::Io {
+        path: path.to_path_buf(),
+        kind: std::io::ErrorKind::Unsupported,
+    })
+}
+
+fn read(entry: &ZipEntry, options: &ExtractOptions) -> Result<Vec<u8>, ZipError> {
+    match &options.password {
+        Some(password) => entry.read_to_vec_with_password(password),
+        None => entry.read_to_vec(),
+    }
+}
+
+/// Entry that passed checks.
+struct Planned<'e, 'a> {
+    entry: &'e ZipEntry<'a>,
+    path: PathBuf,
+}
+
+impl<'a> ZipArchive<'a> {
+    /// Extract all entries into `dir` with default limits.
+    pub fn extract_to(&self, dir: impl AsRef<Path>) -> Result<(), ZipError> {
+        self.extract_to_with(dir, &ExtractOptions::default())
+    }
+
+    /// Extract all entries into `dir`.
+    /// All names and sizes are checked before anything is written. Symbolic links are created
+    /// only if their targets stay in `dir`, and no entry is written through a symbolic link.
+    /// Permission bits are restored from Unix mode, except setuid, setgid and sticky bits.
+    pub fn extract_to_with(&self, dir: impl AsRef<Path>, options: &ExtractOptions) -> Result<(), ZipError> {
+        let root = dir.as_ref();
+
+        let mut plan = Vec::with_capacity(self.entries().len());
+        let mut links = HashMap::new();
+        let mut total: u64 = 0;
+        for entry in self.entries() {
+            let path = sanitize_path(entry.name())
+                .ok_or_else(|| ZipError::UnsafePath(entry.name().to_string()))?;
+
+            let size = entry.uncompressed_size();
+            if size > options.max_entry_size {
+                return Err(ZipError::EntryTooLarge(entry.name().to_string()));
+            }
+            total = total.saturating_add(size);
+            if total > options.max_total_size {
+                return Err(ZipError::ArchiveTooLarge);
+            }
+
+            if entry.is_symlink() {
+                let target = read(entry, options)?;
+                let target = String::from_utf8(target)
+                    .map_err(|_| ZipError::UnsafePath(entry.name().to_string()))?;
+                links.insert(path.clone(), target);
+            }
+
+            plan.push(Planned { entry, path });
+        }
+        // Links are resolved after all of them are known, since a target may go through
+        // another link of the archive.
+        for Planned { entry, path } in plan.iter().filter(|p| p.entry.is_symlink()) {
+            resolve_link(&links, path, &links[path], 0)
+                .ok_or_else(|| ZipError::UnsafePath(entry.name().to_string()))?;
+        }
+
+        std::fs::create_dir_all(root).map_err(io_error(root))?;
+        let mut dirs = vec![];
+        for Planned { entry, path } in &plan {
+            let dest = root.join(path);
+
+            if entry.is_dir() {
+                create_dirs(root, path)?;
+                dirs.push((dest, entry.unix_mode()));
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                create_dirs(root, parent)?;
+            }
+            // Do not write through existing link.
+            if let Ok(m) = std::fs::symlink_metadata(&dest) {
+                if m.file_type().is_symlink() || m.is_dir() {
+                    return Err(ZipError::UnsafePath(entry.name().to_string()));
+                }
+            }
+
+            let data = read(entry, options)?;
+            if entry.is_symlink() {
+                // Checked in the plan.
+                create_symlink(std::str::from_utf8(&data).unwrap(), &dest)?;
+                continue;
+            }
+            std::fs::write(&dest, &data).map_err(io_error(&dest))?;
+            if let Some(mode) = entry.unix_mode() {
+                set_mode(&dest, mode & 0o777)?;
+            }
+        }
+
+        // Directories may be read-only, so their permissions are restored at last, deepest first.
+        dirs.sort_by_key(|(dest, _): &(PathBuf, _)| std::cmp::Reverse(dest.components().count()));
+        for (dest, mode) in &dirs {
+            if let Some(mode) = mode {
+                set_mode(dest, mode & 0o777)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::zip::{Compression, FileOptions, ZipWriter};
+
+    const ARCHIVES: &[u8] = include_bytes!("../../../example_bin/archives.zip");
+
+    /// Empty directory under temporary directory. It is removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("elfeat-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn archive(entries: &[(&str, &[u8], Option<u32>)]) -> Vec<u8> {
+        let mut w = ZipWriter::new();
+        for &(name, data, unix_mode) in entries {
+            let options = FileOptions {
+                unix_mode,
+                ..Default::default()
+            };
+            w.add_file(name, data, &options).unwrap();
+        }
+        w.finish().unwrap()
+    }
+
+    #[test]
+    fn sanitize() {
+        assert_eq!(sanitize_path("a/b.txt"), Some(PathBuf::from("a/b.txt")));
+        assert_eq!(sanitize_path("./a//b/"), Some(PathBuf::from("a/b")));
+        assert_eq!(sanitize_path("a\\b"), Some(PathBuf::from("a/b")));
+        assert_eq!(sanitize_path("../a"), None);
+        assert_eq!(sanitize_path("a/../../b"), None);
+        assert_eq!(sanitize_path("a\\..\\b"), None);
+        assert_eq!(sanitize_path("/etc/passwd"), None);
+        assert_eq!(sanitize_path("\\\\server\\share"), None);
+        assert_eq!(sanitize_path("C:/Windows"), None);
+        assert_eq!(sanitize_path("c:evil"), None);
+        assert_eq!(sanitize_path("./"), None);
+    }
+
+    #[test]
+    fn link_resolution() {
+        let resolve = |link: &str, target: &str| resolve_link(&HashMap::new(), Path::new(link), target, 0);
+        assert_eq!(resolve("a/link", "b"), Some(PathBuf::from("a/b")));
+        assert_eq!(resolve("a/link", "../b"), Some(PathBuf::from("b")));
+        assert_eq!(resolve("a/link", "../../b"), None);
+        assert_eq!(resolve("link", ".."), None);
+        assert_eq!(resolve("link", "/etc"), None);
+
+        // Other links are followed, and cycles are rejected.
+        let links: HashMap<_, _> = [("t", "."), ("a/up", ".."), ("x", "y"), ("y", "x")]
+            .into_iter()
+            .map(|(link, target)| (PathBuf::from(link), target.to_string()))
+            .collect();
+        let resolve = |link: &str, target: &str| resolve_link(&links, Path::new(link), target, 0);
+        assert_eq!(resolve("s", "t/a"), Some(PathBuf::from("a")));
+        assert_eq!(resolve("s", "t/.."), None);
+        assert_eq!(resolve("s", "a/up/b"), Some(PathBuf::from("b")));
+        assert_eq!(resolve("s", "a/up/.."), None);
+        assert_eq!(resolve("s", "x"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn extract_fixture() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("extract-fixture");
+        let s = Sequential::from_vec(ARCHIVES.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        zip.extract_to(&dir.0).unwrap();
+
+        let file = dir.0.join("archives/text_file.txt");
+        assert_eq!(std::fs::read(&file).unwrap(), b"Hello from zip file\n");
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(&file), 0o644);
+        assert_eq!(mode(&dir.0.join("archives")), 0o755);
+    }
+
+    #[test]
+    fn reject_slip() {
+        let dir = TempDir::new("extract-slip");
+        for name in ["../evil.txt", "/tmp/evil.txt", "C:\\evil.txt", "a/..\\..\\evil.txt"] {
+            let v = archive(&[("ok.txt", b"ok", None), (name, b"evil", None)]);
+            let s = Sequential::from_vec(v);
+            let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+            assert_eq!(zip.extract_to(&dir.0), Err(ZipError::UnsafePath(name.to_string())));
+        }
+        // Nothing is written if any entry is rejected.
+        assert!(!dir.0.join("ok.txt").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn reject_symlink_escape() {
+        let dir = TempDir::new("extract-link");
+        let link = Some(0o120777);
+
+        for (target, name) in [("../outside", "link"), ("/etc", "link"), ("../..", "a/link")] {
+            let v = archive(&[(name, target.as_bytes(), link)]);
+            let s = Sequential::from_vec(v);
+            let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+            assert_eq!(zip.extract_to(&dir.0), Err(ZipError::UnsafePath(name.to_string())));
+        }
+
+        // Each link stays inside alone, but the chain escapes. Order of entries does not matter.
+        for entries in [[("t", "."), ("s", "t/..")], [("s", "t/.."), ("t", ".")]] {
+            let entries = entries.map(|(name, target)| (name, target.as_bytes(), link));
+            let s = Sequential::from_vec(archive(&entries));
+            let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+            assert_eq!(zip.extract_to(&dir.0), Err(ZipError::UnsafePath("s".to_string())));
+            assert!(std::fs::symlink_metadata(dir.0.join("t")).is_err());
+        }
+
+        // The link itself stays inside, but writing through it is rejected.
+        let v = archive(&[("sub/", b"", Some(0o40755)), ("link", b"sub", link), ("link/file", b"x", None)]);
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(zip.extract_to(&dir.0), Err(ZipError::UnsafePath("link".to_string())));
+        assert!(std::fs::symlink_metadata(dir.0.join("link")).unwrap().file_type().is_symlink());
+        assert!(!dir.0.join("sub/file").exists());
+    }
+
+    #[test]
+    fn size_limits() {
+        let dir = TempDir::new("extract-limits");
+        let v = archive(&[("a", &[0; 1000], None), ("b", &[0; 1000], None)]);
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+
+        let options = ExtractOptions {
+            max_entry_size: 999,
+            max_total_size: 10000,
+            ..Default::default()
+        };
+        assert_eq!(zip.extract_to_with(&dir.0, &options), Err(ZipError::EntryTooLarge("a".to_string())));
+
+        let options = ExtractOptions {
+            max_entry_size: 1000,
+            max_total_size: 1999,
+            ..Default::default()
+        };
+        assert_eq!(zip.extract_to_with(&dir.0, &options), Err(ZipError::ArchiveTooLarge));
+    }
+
+    #[test]
+    fn lying_size() {
+        // Uncompressed size in central directory is smaller than the real data.
+        let mut w = ZipWriter::new();
+        let options = FileOptions {
+            compression: Compression::Deflate,
+            ..Default::default()
+        };
+        w.add_file("bomb", &[0; 100000], &options).unwrap();
+        let mut v = w.finish().unwrap();
+
+        let s = Sequential::from_vec(v.clone());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let at = zip.entries()[0].offset() + 24;
+        v[at..at + 4].copy_from_slice(&100u32.to_le_bytes());
+
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(
+            zip.entries()[0].read_to_vec(),
+            Err(ZipError::Inflate(crate::compress::inflate::InflateError::OutputLimit))
+        );
+    }
+
+    #[test]
+    fn encrypted() {
+        const ENCRYPTED: &[u8] = include_bytes!("../../../example_bin/encrypted.zip");
+
+        let dir = TempDir::new("extract-encrypted");
+        let s = Sequential::from_vec(ENCRYPTED.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let options = ExtractOptions {
+            password: Some(b"secret".to_vec()),
+            ..Default::default()
+        };
+        zip.extract_to_with(&dir.0, &options).unwrap();
+        assert_eq!(std::fs::read(dir.0.join("tiny.txt")).unwrap(), b"tiny\n");
+    }
+}
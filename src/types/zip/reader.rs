@@ -0,0 +1,856 @@
+use super::crypto::{decrypt, DecryptError, Encryption};
+use super::{
+    BitFlags, CentralDirectoryHeader, DosDateTime, EndOfCentralDirectory, ExtraField, Header,
+    HeaderId, Method, Zip64EndOfCentralDirectory, Zip64Locator, CRC32,
+};
+use crate::compress::inflate::{inflate_limited, InflateError};
+use crate::file::Seekable;
+use crate::types::FromU8Array;
+
+use std::time::SystemTime;
+
+/// Size of end of central directory record without comment.
+const EOCD_SIZE: usize = 22;
+
+/// Size of ZIP64 end of central directory locator.
+const ZIP64_LOCATOR_SIZE: usize = 20;
+
+/// Size of central directory file header without variable length fields.
+const CENTRAL_HEADER_SIZE: usize = 46;
+
+/// Size of local file header without variable length fields.
+const LOCAL_HEADER_SIZE: usize = 30;
+
+/// Host systems in upper byte of version made by, which store Unix mode in external attributes.
+const HOST_UNIX: u16 = 3;
+const HOST_OSX: u16 = 19;
+
+/// File types of Unix mode.
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
+
+/// Error of zip archive reading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZipError {
+    /// End of central directory record is not found.
+    NoEndOfCentralDirectory,
+
+    /// The structure at the offset is out of file.
+    Truncated(usize),
+
+    /// The structure at the offset has wrong signature.
+    BadSignature(usize),
+
+    /// Compression method is not supported.
+    UnsupportedMethod(u16),
+
+    /// Compressed data is broken.
+    Inflate(InflateError),
+
+    /// Encrypted data cannot be decrypted.
+    Decrypt(DecryptError),
+
+    /// Extracted data does not match CRC-32 in central directory.
+    CrcMismatch { expected: u32, actual: u32 },
+
+    /// Extracted data does not match uncompressed size in central directory.
+    SizeMismatch { expected: u64, actual: u64 },
+
+    /// The value does not fit in the field without ZIP64.
+    Overflow(&'static str),
+
+    /// Entry path or link target escapes the destination directory.
+    UnsafePath(String),
+
+    /// Entry exceeds the size limit of extraction.
+    EntryTooLarge(String),
+
+    /// Total size of entries exceeds the size limit of extraction.
+    ArchiveTooLarge,
+
+    /// File system operation failed at the path.
+    Io {
+        path: std::path::PathBuf,
+        kind: std::io::ErrorKind,
+    },
+}
+
+impl From<InflateError> for ZipError {
+    fn from(e: InflateError) -> Self {
+        ZipError::Inflate(e)
+    }
+}
+
+impl From<DecryptError> for ZipError {
+    fn from(e: DecryptError) -> Self {
+        ZipError::Decrypt(e)
+    }
+}
+
+impl core::fmt::Display for ZipError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ZipError::NoEndOfCentralDirectory => {
+                write!(fmt, "end of central directory record is not found")
+            }
+            ZipError::Truncated(at) => write!(fmt, "truncated structure at {:#x}", at),
+            ZipError::BadSignature(at) => write!(fmt, "bad signature at {:#x}", at),
+            ZipError::UnsupportedMethod(m) => write!(fmt, "unsupported compression method {}", m),
+            ZipError::Inflate(e) => write!(fmt, "{}", e),
+            ZipError::Decrypt(e) => write!(fmt, "{}", e),
+            ZipError::CrcMismatch { expected, actual } => {
+                write!(fmt, "CRC-32 mismatch: expected {:#010x}, got {:#010x}", expected, actual)
+            }
+            ZipError::SizeMismatch { expected, actual } => {
+                write!(fmt, "size mismatch: expected {}, got {}", expected, actual)
+            }
+            ZipError::Overflow(field) => write!(fmt, "{} does not fit without ZIP64", field),
+            ZipError::UnsafePath(name) => write!(fmt, "unsafe path: {}", name),
+            ZipError::EntryTooLarge(name) => write!(fmt, "entry too large: {}", name),
+            ZipError::ArchiveTooLarge => write!(fmt, "archive too large"),
+            ZipError::Io { path, kind } => write!(fmt, "{}: {}", path.display(), kind),
+        }
+    }
+}
+
+impl std::error::Error for ZipError {}
+
+/// Decode text in zip headers.
+/// If `is_utf8` (general purpose bit 11) is not set, the text is encoded in IBM Code Page 437.
+pub fn decode_text(bytes: &[u8], is_utf8: bool) -> String {
+    const CP437_HIGH: &str = "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒáíóúñÑªº¿⌐¬½¼¡«»\
+                              ░▒▓│┤╡╢╖╕╣║╗╝╜╛┐└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀\
+                              αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{a0}";
+
+    if is_utf8 {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x00..=0x7f => b as char,
+            _ => CP437_HIGH.chars().nth((b - 0x80) as usize).unwrap_or('?'),
+        })
+        .collect()
+}
+
+/// An entry of central directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry<'a> {
+    seeker: Seekable<'a>,
+    offset: usize,
+    header: CentralDirectoryHeader,
+    name: String,
+    extra_fields: Vec<ExtraField>,
+    comment: String,
+    uncompressed_size: u64,
+    compressed_size: u64,
+    local_header_offset: u64,
+}
+
+impl<'a> ZipEntry<'a> {
+    /// Offset of central directory file header of this entry.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn header(&self) -> &CentralDirectoryHeader {
+        &self.header
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn extra_fields(&self) -> &[ExtraField] {
+        &self.extra_fields
+    }
+
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    pub fn crc32(&self) -> u32 {
+        *self.header.get_crc32_unwrap().inner_ref()
+    }
+
+    /// Compressed size. This is widened by ZIP64 extra field.
+    pub fn compressed_size(&self) -> u64 {
+        self.compressed_size
+    }
+
+    /// Uncompressed size. This is widened by ZIP64 extra field.
+    pub fn uncompressed_size(&self) -> u64 {
+        self.uncompressed_size
+    }
+
+    /// Offset of local file header of this entry. This is widened by ZIP64 extra field.
+    pub fn local_header_offset(&self) -> u64 {
+        self.local_header_offset
+    }
+
+    /// Return true if this entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/') || self.unix_mode().is_some_and(|m| m & S_IFMT == S_IFDIR)
+    }
+
+    /// Return true if this entry is a symbolic link. Its data is the link target.
+    pub fn is_symlink(&self) -> bool {
+        self.unix_mode().is_some_and(|m| m & S_IFMT == S_IFLNK)
+    }
+
+    /// Unix file type and permission bits in upper half of external attributes.
+    /// This is None if the archive is not made on Unix-like system.
+    pub fn unix_mode(&self) -> Option<u32> {
+        let host = *self.header.get_version_made_by_unwrap().inner_ref() >> 8;
+        let mode = *self.header.get_external_attributes_unwrap().inner_ref() >> 16;
+        match host {
+            HOST_UNIX | HOST_OSX if mode != 0 => Some(mode),
+            _ => None,
+        }
+    }
+
+    /// Last modification time in MS-DOS format.
+    pub fn last_modified(&self) -> DosDateTime {
+        DosDateTime::new(
+            self.header.get_last_modify_data_unwrap().clone(),
+            self.header.get_last_modify_time_unwrap().clone(),
+        )
+    }
+
+    /// Last modification time.
+    /// This prefers NTFS and extended timestamp extra fields, which are UTC and more precise than MS-DOS time.
+    pub fn modified(&self) -> Option<SystemTime> {
+        let ntfs = self.extra_fields.iter().find_map(|f| match f {
+            ExtraField::Ntfs(x) => Some(x.modified()),
+            _ => None,
+        });
+        let extended = self.extra_fields.iter().find_map(|f| match f {
+            ExtraField::ExtendedTimestamp(x) => x.modified(),
+            _ => None,
+        });
+        ntfs.or(extended)
+            .or_else(|| self.last_modified().to_system_time())
+    }
+
+    pub fn compression_method(&self) -> Method {
+        self.header.get_compression_method_unwrap().clone()
+    }
+
+    /// Local file header of this entry.
+    pub fn local_header(&self) -> Result<Header, ZipError> {
+        let at = self.local_header_offset() as usize;
+        let (_, header) = self
+            .seeker
+            .interpret_abs_pos::<Header>(at)
+            .to_tuple()
+            .1
+            .map_err(|_| ZipError::Truncated(at))?;
+        if !header.get_magic_unwrap().is_VALID() {
+            return Err(ZipError::BadSignature(at));
+        }
+        Ok(header)
+    }
+
+    /// Offset of file data. This follows local file header, whose variable length fields
+    /// may differ from the ones in central directory.
+    pub fn data_offset(&self) -> Result<usize, ZipError> {
+        let header = self.local_header()?;
+        let name_length = *header.get_file_name_length_unwrap().inner_ref() as usize;
+        let extra_length = *header.get_extra_field_length_unwrap().inner_ref() as usize;
+
+        Ok(self.local_header_offset() as usize + LOCAL_HEADER_SIZE + name_length + extra_length)
+    }
+
+    /// Raw file data as stored in archive.
+    pub fn raw_data(&self) -> Result<&'a [u8], ZipError> {
+        let at = self.data_offset()?;
+        self.seeker
+            .bytes_abs_pos(at, self.compressed_size() as usize)
+            .ok_or(ZipError::Truncated(at))
+    }
+
+    /// Encryption of this entry.
+    pub fn encryption(&self) -> Encryption {
+        let bf = self.header.get_general_purpose_bf_unwrap();
+        if !bf.get(BitFlags::ENCRYPTED) {
+            return Encryption::None;
+        }
+        if bf.get(BitFlags::STRONG_ENCRYPTION) {
+            return Encryption::Strong;
+        }
+        let aes_extra = self.extra_fields.iter().any(|f| f.tag() == HeaderId::AES);
+        if self.compression_method() == Method::AES || aes_extra {
+            return Encryption::Aes;
+        }
+        Encryption::ZipCrypto
+    }
+
+    /// Extract file data.
+    /// The result is verified with uncompressed size and CRC-32 in central directory.
+    /// Decompression stops at the uncompressed size, so the data cannot expand over it.
+    pub fn read_to_vec(&self) -> Result<Vec<u8>, ZipError> {
+        self.read(None)
+    }
+
+    /// Extract file data that may be encrypted with ZipCrypto.
+    /// The password is checked with encryption header. Since one in 256 wrong passwords
+    /// passes the check, deflate stream that is broken from its first block header is also
+    /// reported as wrong password. Other errors are reported as they are.
+    pub fn read_to_vec_with_password(&self, password: &[u8]) -> Result<Vec<u8>, ZipError> {
+        self.read(Some(password))
+    }
+
+    fn read(&self, password: Option<&[u8]>) -> Result<Vec<u8>, ZipError> {
+        let raw = self.raw_data()?;
+        let decrypted;
+        let raw = match self.encryption() {
+            Encryption::None => raw,
+            Encryption::ZipCrypto => {
+                let password = password.ok_or(DecryptError::PasswordRequired)?;
+                decrypted = decrypt(raw, password, self.check_byte())?;
+                decrypted.as_slice()
+            }
+            e => return Err(DecryptError::Unsupported(e).into()),
+        };
+
+        match self.decompress(raw) {
+            Err(ZipError::Inflate(InflateError::InvalidBlockType | InflateError::StoredLengthMismatch))
+                if self.encryption() == Encryption::ZipCrypto =>
+            {
+                Err(DecryptError::WrongPassword.into())
+            }
+            x => x,
+        }
+    }
+
+    /// Expected last byte of ZipCrypto encryption header.
+    /// With data descriptor, CRC-32 is unknown when the header is written, so time is used instead.
+    fn check_byte(&self) -> u8 {
+        if self.header.get_general_purpose_bf_unwrap().get(BitFlags::DATA_DESCRIPTOR) {
+            (*self.header.get_last_modify_time_unwrap().inner_ref() >> 8) as u8
+        } else {
+            (self.crc32() >> 24) as u8
+        }
+    }
+
+    fn decompress(&self, raw: &[u8]) -> Result<Vec<u8>, ZipError> {
+        let limit = usize::try_from(self.uncompressed_size()).unwrap_or(usize::MAX);
+        let data = match self.compression_method() {
+            Method::STORED => raw.to_vec(),
+            Method::DEFLATE => inflate_limited(raw, limit)?,
+            m => return Err(ZipError::UnsupportedMethod(m.inner())),
+        };
+
+        if data.len() as u64 != self.uncompressed_size() {
+            return Err(ZipError::SizeMismatch {
+                expected: self.uncompressed_size(),
+                actual: data.len() as u64,
+            });
+        }
+        let crc = CRC32::compute(&data);
+        if &crc != self.header.get_crc32_unwrap() {
+            return Err(ZipError::CrcMismatch {
+                expected: self.crc32(),
+                actual: *crc.inner_ref(),
+            });
+        }
+
+        Ok(data)
+    }
+}
+
+/// Zip archive. This reads entries from central directory.
+#[derive(Debug, Clone)]
+pub struct ZipArchive<'a> {
+    seeker: Seekable<'a>,
+    eocd_offset: usize,
+    eocd: EndOfCentralDirectory,
+    zip64_eocd: Option<Zip64EndOfCentralDirectory>,
+    comment: String,
+    entries: Vec<ZipEntry<'a>>,
+}
+
+impl<'a> ZipArchive<'a> {
+    /// Read zip archive.
+    /// This starts from end of central directory record at the tail of `seeker`.
+    pub fn parse(seeker: Seekable<'a>) -> Result<Self, ZipError> {
+        let eocd_offset = Self::find_eocd(seeker).ok_or(ZipError::NoEndOfCentralDirectory)?;
+        let (_, eocd) = seeker
+            .interpret_abs_pos::<EndOfCentralDirectory>(eocd_offset)
+            .to_tuple()
+            .1
+            .map_err(|_| ZipError::Truncated(eocd_offset))?;
+
+        let comment_length = *eocd.get_comment_length_unwrap().inner_ref() as usize;
+        let comment = seeker
+            .bytes_abs_pos(eocd_offset + EOCD_SIZE, comment_length)
+            .ok_or(ZipError::Truncated(eocd_offset))?;
+
+        let mut ret = Self {
+            seeker,
+            eocd_offset,
+            comment: decode_text(comment, false),
+            eocd,
+            zip64_eocd: Self::read_zip64_eocd(seeker, eocd_offset)?,
+            entries: vec![],
+        };
+        ret.entries = ret.read_central_directory()?;
+
+        Ok(ret)
+    }
+
+    /// Search end of central directory record backward.
+    /// The record is placed at the tail, but it may be followed by a comment up to 0xffff bytes.
+    fn find_eocd(seeker: Seekable) -> Option<usize> {
+        let last = seeker.len().checked_sub(EOCD_SIZE)?;
+        let first = last.saturating_sub(0xffff);
+
+        (first..=last).rev().find(|&at| {
+            let eocd = match seeker
+                .interpret_abs_pos::<EndOfCentralDirectory>(at)
+                .to_tuple()
+                .1
+            {
+                Ok((_, eocd)) => eocd,
+                Err(_) => return false,
+            };
+            let comment_length = *eocd.get_comment_length_unwrap().inner_ref() as usize;
+            eocd.get_magic_unwrap().is_VALID() && at + EOCD_SIZE + comment_length <= seeker.len()
+        })
+    }
+
+    /// Read ZIP64 end of central directory record if its locator precedes end of central directory record.
+    fn read_zip64_eocd(
+        seeker: Seekable,
+        eocd_offset: usize,
+    ) -> Result<Option<Zip64EndOfCentralDirectory>, ZipError> {
+        let locator = match eocd_offset
+            .checked_sub(ZIP64_LOCATOR_SIZE)
+            .and_then(|at| seeker.interpret_abs_pos::<Zip64Locator>(at).to_tuple().1.ok())
+        {
+            Some((_, locator)) if locator.get_magic_unwrap().is_VALID() => locator,
+            _ => return Ok(None),
+        };
+
+        let at = *locator.get_eocd_offset_unwrap().inner_ref() as usize;
+        let (_, eocd) = seeker
+            .interpret_abs_pos::<Zip64EndOfCentralDirectory>(at)
+            .to_tuple()
+            .1
+            .map_err(|_| ZipError::Truncated(at))?;
+        if !eocd.get_magic_unwrap().is_VALID() {
+            return Err(ZipError::BadSignature(at));
+        }
+
+        Ok(Some(eocd))
+    }
+
+    fn read_central_directory(&self) -> Result<Vec<ZipEntry<'a>>, ZipError> {
+        let count = self.entry_count();
+        let mut at = self.central_directory_offset() as usize;
+        let mut ret = Vec::with_capacity(count.min(0x10000) as usize);
+
+        for _ in 0..count {
+            let entry = self.read_entry(at)?;
+            at = entry.offset + CENTRAL_HEADER_SIZE + self.variable_length(&entry.header);
+            ret.push(entry);
+        }
+
+        Ok(ret)
+    }
+
+    fn variable_length(&self, header: &CentralDirectoryHeader) -> usize {
+        *header.get_file_name_length_unwrap().inner_ref() as usize
+            + *header.get_extra_field_length_unwrap().inner_ref() as usize
+            + *header.get_file_comment_length_unwrap().inner_ref() as usize
+    }
+
+    fn read_entry(&self, at: usize) -> Result<ZipEntry<'a>, ZipError> {
+        let (_, header) = self
+            .seeker
+            .interpret_abs_pos::<CentralDirectoryHeader>(at)
+            .to_tuple()
+            .1
+            .map_err(|_| ZipError::Truncated(at))?;
+        if !header.get_magic_unwrap().is_VALID() {
+            return Err(ZipError::BadSignature(at));
+        }
+
+        let name_length = *header.get_file_name_length_unwrap().inner_ref() as usize;
+        let extra_length = *header.get_extra_field_length_unwrap().inner_ref() as usize;
+        let comment_length = *header.get_file_comment_length_unwrap().inner_ref() as usize;
+
+        let variable = self
+            .seeker
+            .bytes_abs_pos(at + CENTRAL_HEADER_SIZE, self.variable_length(&header))
+            .ok_or(ZipError::Truncated(at))?;
+        let (name, variable) = variable.split_at(name_length);
+        let (mut extra, comment) = variable.split_at(extra_length);
+        debug_assert_eq!(comment.len(), comment_length);
+
+        let is_utf8 = header.get_general_purpose_bf_unwrap().get(BitFlags::UTF8);
+
+        // Broken extra field is ignored.
+        let mut extra_fields = vec![];
+        while let Ok(field) = ExtraField::from_slice_consume(&mut extra) {
+            extra_fields.push(field);
+        }
+
+        // ZIP64 extra field holds values of saturated fields in order.
+        let mut zip64 = extra_fields
+            .iter()
+            .find_map(|f| match f {
+                ExtraField::Zip64(x) => Some(x.values.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+            .iter();
+        let mut widen = |v: u32| match v {
+            u32::MAX => zip64.next().copied().unwrap_or(v as u64),
+            _ => v as u64,
+        };
+        let uncompressed_size = widen(*header.get_uncompressed_size_unwrap().inner_ref());
+        let compressed_size = widen(*header.get_compressed_size_unwrap().inner_ref());
+        let local_header_offset = widen(*header.get_local_header_offset_unwrap().inner_ref());
+
+        Ok(ZipEntry {
+            seeker: self.seeker,
+            offset: at,
+            name: decode_text(name, is_utf8),
+            comment: decode_text(comment, is_utf8),
+            header,
+            extra_fields,
+            uncompressed_size,
+            compressed_size,
+            local_header_offset,
+        })
+    }
+
+    /// Offset of end of central directory record.
+    pub fn eocd_offset(&self) -> usize {
+        self.eocd_offset
+    }
+
+    pub fn eocd(&self) -> &EndOfCentralDirectory {
+        &self.eocd
+    }
+
+    /// Offset just after end of central directory record and archive comment. This is the end of the archive.
+    pub fn end_offset(&self) -> usize {
+        self.eocd_offset + EOCD_SIZE + *self.eocd.get_comment_length_unwrap().inner_ref() as usize
+    }
+
+    /// Archive comment.
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// ZIP64 end of central directory record, if the archive has it.
+    pub fn zip64_eocd(&self) -> Option<&Zip64EndOfCentralDirectory> {
+        self.zip64_eocd.as_ref()
+    }
+
+    /// Number of entries in central directory. This prefers ZIP64 record.
+    pub fn entry_count(&self) -> u64 {
+        match &self.zip64_eocd {
+            Some(z) => *z.get_total_entries_unwrap().inner_ref(),
+            None => *self.eocd.get_total_entries_unwrap().inner_ref() as u64,
+        }
+    }
+
+    /// Offset of central directory. This prefers ZIP64 record.
+    pub fn central_directory_offset(&self) -> u64 {
+        match &self.zip64_eocd {
+            Some(z) => *z.get_central_directory_offset_unwrap().inner_ref(),
+            None => *self.eocd.get_central_directory_offset_unwrap().inner_ref() as u64,
+        }
+    }
+
+    /// Size of central directory. This prefers ZIP64 record.
+    pub fn central_directory_size(&self) -> u64 {
+        match &self.zip64_eocd {
+            Some(z) => *z.get_central_directory_size_unwrap().inner_ref(),
+            None => *self.eocd.get_central_directory_size_unwrap().inner_ref() as u64,
+        }
+    }
+
+    pub fn entries(&self) -> &[ZipEntry<'a>] {
+        &self.entries
+    }
+
+    /// Find entry by name.
+    pub fn by_name(&self, name: &str) -> Option<&ZipEntry<'a>> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    pub fn seeker(&self) -> Seekable<'a> {
+        self.seeker
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::zip::HeaderId;
+
+    const ARCHIVES: &[u8] = include_bytes!("../../../example_bin/archives.zip");
+    const DEFLATE: &[u8] = include_bytes!("../../../example_bin/deflate.zip");
+    const ZIP64: &[u8] = include_bytes!("../../../example_bin/zip64.zip");
+    const ENCRYPTED: &[u8] = include_bytes!("../../../example_bin/encrypted.zip");
+
+    #[test]
+    fn list_entries() {
+        let s = Sequential::from_vec(ARCHIVES.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+
+        assert_eq!(zip.eocd_offset(), 0x152);
+        assert_eq!(zip.entry_count(), 2);
+        assert_eq!(zip.comment(), "");
+
+        let entries = zip.entries();
+        assert_eq!(entries[0].name(), "archives/");
+        assert!(entries[0].is_dir());
+        assert_eq!(entries[0].local_header_offset(), 0);
+
+        let file = zip.by_name("archives/text_file.txt").unwrap();
+        assert!(!file.is_dir());
+        assert_eq!(file.crc32(), 0x581edf55);
+        assert_eq!(file.compressed_size(), 20);
+        assert_eq!(file.uncompressed_size(), 20);
+        assert_eq!(file.local_header_offset(), 67);
+        assert_eq!(*file.header().get_external_attributes_unwrap().inner_ref() >> 16, 0o100644);
+        assert_eq!(file.last_modified().to_string(), "2022-08-11 15:47:14");
+        // Extended timestamp is UTC, while MS-DOS time is local time of the archiver.
+        assert_eq!(
+            file.modified().unwrap(),
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(0x62f4a5f2)
+        );
+
+        let tags: Vec<_> = file.extra_fields().iter().map(|f| f.tag()).collect();
+        assert_eq!(tags, vec![HeaderId::EXTENDED_TIMESTAMP, HeaderId::INFO_ZIP_UNIX]);
+    }
+
+    #[test]
+    fn eocd_with_comment() {
+        let mut v = ARCHIVES.to_vec();
+        let len = v.len();
+        v[len - 2..].copy_from_slice(&5u16.to_le_bytes());
+        v.extend_from_slice(b"hello");
+
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(zip.comment(), "hello");
+        assert_eq!(zip.entries().len(), 2);
+    }
+
+    #[test]
+    fn not_zip() {
+        let s = Sequential::from_vec(vec![0; 0x100]);
+        assert_eq!(
+            ZipArchive::parse(s.to_seeakble()).unwrap_err(),
+            ZipError::NoEndOfCentralDirectory
+        );
+    }
+
+    #[test]
+    fn cp437_text() {
+        assert_eq!(decode_text(&[b'a', 0x80, 0xe1, 0xff], false), "aÇß\u{a0}");
+        assert_eq!(decode_text("ü".as_bytes(), true), "ü");
+    }
+
+    #[test]
+    fn read_stored() {
+        let s = Sequential::from_vec(ARCHIVES.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+
+        let file = zip.by_name("archives/text_file.txt").unwrap();
+        let local = file.local_header().unwrap();
+        assert_eq!(*local.get_version_needed_unwrap().inner_ref(), 10);
+        assert_eq!(local.get_compression_method_unwrap(), &Method::STORED);
+        assert_eq!(local.get_last_modify_time_unwrap().to_string(), "15:47:14");
+        assert_eq!(*local.get_crc32_unwrap().inner_ref(), 0x581edf55);
+        assert_eq!(*local.get_file_name_length_unwrap().inner_ref(), 22);
+        assert_eq!(*local.get_extra_field_length_unwrap().inner_ref(), 28);
+        assert_eq!(file.data_offset().unwrap(), 67 + 30 + 22 + 28);
+        assert_eq!(file.read_to_vec().unwrap(), b"Hello from zip file\n");
+        assert_eq!(zip.by_name("archives/").unwrap().read_to_vec().unwrap(), b"");
+    }
+
+    #[test]
+    fn read_deflate() {
+        let s = Sequential::from_vec(DEFLATE.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+
+        let file = zip.by_name("lines.txt").unwrap();
+        assert_eq!(file.compression_method(), Method::DEFLATE);
+        let data = file.read_to_vec().unwrap();
+        assert_eq!(data.len(), 1050);
+        assert!(data.starts_with(b"line 0: the quick brown fox"));
+        assert!(data.ends_with(b"line 19: the quick brown fox jumps over the lazy dog\n"));
+
+        assert_eq!(zip.by_name("empty.txt").unwrap().read_to_vec().unwrap(), b"");
+    }
+
+    #[test]
+    fn read_errors() {
+        // Corrupt "lines.txt" data.
+        let mut v = DEFLATE.to_vec();
+        let s = Sequential::from_vec(DEFLATE.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let at = zip.by_name("lines.txt").unwrap().data_offset().unwrap() + 40;
+        v[at] ^= 0x80;
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert!(zip.by_name("lines.txt").unwrap().read_to_vec().is_err());
+
+        // Flip CRC-32 in central directory of stored entry.
+        let mut v = ARCHIVES.to_vec();
+        let s = Sequential::from_vec(ARCHIVES.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let at = zip.by_name("archives/text_file.txt").unwrap().offset() + 16;
+        v[at] ^= 1;
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(
+            zip.by_name("archives/text_file.txt").unwrap().read_to_vec(),
+            Err(ZipError::CrcMismatch {
+                expected: 0x581edf54,
+                actual: 0x581edf55
+            })
+        );
+    }
+
+    #[test]
+    fn zip64() {
+        let mut v = ZIP64.to_vec();
+        // Saturate counts, size and offset in end of central directory record.
+        let eocd = v.len() - EOCD_SIZE;
+        v[eocd + 8..eocd + 20].fill(0xff);
+
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert!(zip.zip64_eocd().is_some());
+        assert_eq!(zip.entry_count(), 2);
+        assert_eq!(zip.central_directory_offset(), 0xd8);
+        assert_eq!(zip.central_directory_size(), 0x8a);
+
+        let small = zip.by_name("small.txt").unwrap();
+        assert_eq!(small.read_to_vec().unwrap(), b"tiny\n");
+
+        let file = zip.by_name("lines.txt").unwrap();
+        assert_eq!(*file.header().get_compressed_size_unwrap().inner_ref(), u32::MAX);
+        assert_eq!(file.uncompressed_size(), 1050);
+        assert_eq!(file.compressed_size(), 113);
+        assert_eq!(file.local_header_offset(), 0x2c);
+        assert_eq!(file.read_to_vec().unwrap().len(), 1050);
+    }
+
+    #[test]
+    fn zip64_broken_record() {
+        let mut v = ZIP64.to_vec();
+        v[0x162] = 0;
+
+        let s = Sequential::from_vec(v);
+        assert_eq!(
+            ZipArchive::parse(s.to_seeakble()).unwrap_err(),
+            ZipError::BadSignature(0x162)
+        );
+    }
+
+    #[test]
+    fn zipcrypto() {
+        let s = Sequential::from_vec(ENCRYPTED.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+
+        for entry in zip.entries() {
+            assert_eq!(entry.encryption(), Encryption::ZipCrypto);
+            assert_eq!(
+                entry.read_to_vec(),
+                Err(ZipError::Decrypt(DecryptError::PasswordRequired))
+            );
+        }
+
+        let lines = zip.by_name("lines.txt").unwrap();
+        assert_eq!(lines.read_to_vec_with_password(b"secret").unwrap().len(), 1050);
+        // Check byte does not match.
+        assert_eq!(
+            lines.read_to_vec_with_password(b"wrong"),
+            Err(ZipError::Decrypt(DecryptError::WrongPassword))
+        );
+        // Check byte matches, but the first block has reserved type.
+        assert_eq!(
+            lines.read_to_vec_with_password(b"pw582"),
+            Err(ZipError::Decrypt(DecryptError::WrongPassword))
+        );
+        // Check byte and block type match. The stream may be just corrupted.
+        assert_eq!(
+            lines.read_to_vec_with_password(b"pw838"),
+            Err(ZipError::Inflate(InflateError::InvalidDistance))
+        );
+
+        let tiny = zip.by_name("tiny.txt").unwrap();
+        assert_eq!(tiny.read_to_vec_with_password(b"secret").unwrap(), b"tiny\n");
+        let piped = zip.by_name("-").unwrap();
+        assert_eq!(piped.read_to_vec_with_password(b"secret").unwrap(), b"piped data\n");
+    }
+
+    #[test]
+    fn zipcrypto_errors_after_password() {
+        let s = Sequential::from_vec(ENCRYPTED.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let at = zip.by_name("lines.txt").unwrap().offset();
+
+        // Uncompressed size in central directory is smaller than the real data.
+        let mut v = ENCRYPTED.to_vec();
+        v[at + 24..at + 28].copy_from_slice(&100u32.to_le_bytes());
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(
+            zip.by_name("lines.txt").unwrap().read_to_vec_with_password(b"secret"),
+            Err(ZipError::Inflate(InflateError::OutputLimit))
+        );
+
+        // Flip CRC-32 in central directory. Check byte comes from time with data descriptor.
+        let mut v = ENCRYPTED.to_vec();
+        v[at + 16] ^= 1;
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(
+            zip.by_name("lines.txt").unwrap().read_to_vec_with_password(b"secret"),
+            Err(ZipError::CrcMismatch {
+                expected: 0x1105a795,
+                actual: 0x1105a794
+            })
+        );
+    }
+
+    #[test]
+    fn unsupported_encryption() {
+        let mut v = ARCHIVES.to_vec();
+        let s = Sequential::from_vec(ARCHIVES.to_vec());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let at = zip.by_name("archives/text_file.txt").unwrap().offset();
+
+        // Set encrypted flag and AES method in central directory.
+        v[at + 8] |= 1;
+        v[at + 10..at + 12].copy_from_slice(&99u16.to_le_bytes());
+        let s = Sequential::from_vec(v.clone());
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let entry = zip.by_name("archives/text_file.txt").unwrap();
+        assert_eq!(entry.encryption(), Encryption::Aes);
+        assert_eq!(
+            entry.read_to_vec_with_password(b"secret"),
+            Err(ZipError::Decrypt(DecryptError::Unsupported(Encryption::Aes)))
+        );
+
+        // Strong encryption flag.
+        v[at + 8] |= 1 << 6;
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        let entry = zip.by_name("archives/text_file.txt").unwrap();
+        assert_eq!(entry.encryption(), Encryption::Strong);
+    }
+}
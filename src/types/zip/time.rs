@@ -0,0 +1,397 @@
+//! MS-DOS date and time, and extra fields that carry timestamps of higher precision.
+
+use super::{Data, Time};
+use crate::types::model::{Diagnostic, ModelFromU8Array};
+use crate::types::{FromU8Array, FromU8Error};
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds from 1601-01-01 (NTFS epoch) to 1970-01-01 (Unix epoch).
+const NTFS_TO_UNIX_SECS: u64 = 11644473600;
+
+/// Days from 1970-01-01 to the civil date in proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Civil date of days from 1970-01-01. Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Seconds from Unix epoch to SystemTime. Negative values are before the epoch.
+fn system_time_from_secs(secs: i64) -> SystemTime {
+    match secs {
+        0.. => UNIX_EPOCH + Duration::from_secs(secs as u64),
+        _ => UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()),
+    }
+}
+
+impl Time {
+    /// Make time. Seconds are rounded down to even, since the format has 2 seconds resolution.
+    /// If some fields are out of range, this returns None.
+    pub fn from_hms(hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        Some(Self::new((hour as u16) << 11 | (minute as u16) << 5 | (second as u16) >> 1))
+    }
+
+    pub fn hour(&self) -> u8 {
+        (*self.inner_ref() >> 11) as u8
+    }
+
+    pub fn minute(&self) -> u8 {
+        (*self.inner_ref() >> 5 & 0x3f) as u8
+    }
+
+    pub fn second(&self) -> u8 {
+        (*self.inner_ref() & 0x1f) as u8 * 2
+    }
+}
+
+impl core::fmt::Display for Time {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{:02}:{:02}:{:02}", self.hour(), self.minute(), self.second())
+    }
+}
+
+impl Data {
+    /// Make date. If it is out of 1980 to 2107 or does not exist, this returns None.
+    pub fn from_ymd(year: u16, month: u8, day: u8) -> Option<Self> {
+        if !(1980..=2107).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        // Days past the end of month move to the next month.
+        let days = days_from_civil(year as i64, month as u32, day as u32);
+        if civil_from_days(days) != (year as i64, month as u32, day as u32) {
+            return None;
+        }
+        Some(Self::new((year - 1980) << 9 | (month as u16) << 5 | day as u16))
+    }
+
+    pub fn year(&self) -> u16 {
+        (*self.inner_ref() >> 9) + 1980
+    }
+
+    pub fn month(&self) -> u8 {
+        (*self.inner_ref() >> 5 & 0xf) as u8
+    }
+
+    pub fn day(&self) -> u8 {
+        (*self.inner_ref() & 0x1f) as u8
+    }
+}
+
+impl core::fmt::Display for Data {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{:04}-{:02}-{:02}", self.year(), self.month(), self.day())
+    }
+}
+
+/// MS-DOS date and time pair of zip headers.
+/// They have no time zone. Archivers usually write local time, but this converts them as UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DosDateTime {
+    pub date: Data,
+    pub time: Time,
+}
+
+impl Default for DosDateTime {
+    /// 1980-01-01 00:00:00, the earliest representable time.
+    fn default() -> Self {
+        Self {
+            date: Data::from_ymd(1980, 1, 1).unwrap(),
+            time: Time::new(0),
+        }
+    }
+}
+
+impl DosDateTime {
+    pub fn new(date: Data, time: Time) -> Self {
+        Self { date, time }
+    }
+
+    /// Convert to SystemTime. If some fields are out of range, this returns None.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let (year, month, day) = (self.date.year(), self.date.month(), self.date.day());
+        let (hour, minute, second) = (self.time.hour(), self.time.minute(), self.time.second());
+        // Check fields in the same way as making them, so dates such as Feb 31 are not normalized.
+        Data::from_ymd(year, month, day)?;
+        Time::from_hms(hour, minute, second)?;
+
+        let days = days_from_civil(year as i64, month as u32, day as u32);
+        let secs = days * 86400 + hour as i64 * 3600 + minute as i64 * 60 + second as i64;
+        Some(system_time_from_secs(secs))
+    }
+
+    /// Convert from SystemTime. If it is out of 1980 to 2107, this returns None.
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(_) => return None,
+        };
+        let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+        if !(1980..=2107).contains(&year) {
+            return None;
+        }
+
+        let secs = secs.rem_euclid(86400);
+        Some(Self {
+            date: Data::from_ymd(year as u16, month as u8, day as u8)?,
+            time: Time::from_hms((secs / 3600) as u8, (secs / 60 % 60) as u8, (secs % 60) as u8)?,
+        })
+    }
+}
+
+impl core::fmt::Display for DosDateTime {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(fmt, "{} {}", self.date, self.time)
+    }
+}
+
+/// Body of extended timestamp extra field (0x5455).
+/// Timestamps are seconds from Unix epoch. Flags tell which of them are present in local header,
+/// but central directory holds only modification time.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExtendedTimestamp {
+    pub flags: u8,
+    pub modify: Option<i32>,
+    pub access: Option<i32>,
+    pub create: Option<i32>,
+}
+
+impl ExtendedTimestamp {
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modify.map(|t| system_time_from_secs(t as i64))
+    }
+}
+
+impl FromU8Array for ExtendedTimestamp {
+    fn from_slice(slice: &[u8]) -> Result<(usize, Self), FromU8Error<Self>> {
+        let (&flags, mut rest) = slice.split_first().ok_or(FromU8Error::NotEnoughSlice(None))?;
+
+        let mut times = [None; 3];
+        for (bit, time) in times.iter_mut().enumerate() {
+            if flags & 1 << bit == 0 || rest.len() < 4 {
+                continue;
+            }
+            let (t, r) = rest.split_at(4);
+            *time = Some(i32::from_le_bytes(t.try_into().unwrap()));
+            rest = r;
+        }
+
+        Ok((
+            slice.len(),
+            Self {
+                flags,
+                modify: times[0],
+                access: times[1],
+                create: times[2],
+            },
+        ))
+    }
+    fn to_slice(&self) -> Box<[u8]> {
+        let mut ret = vec![self.flags];
+        for t in [self.modify, self.access, self.create].into_iter().flatten() {
+            ret.extend_from_slice(&t.to_le_bytes());
+        }
+        ret.into_boxed_slice()
+    }
+}
+
+impl ModelFromU8Array for ExtendedTimestamp {
+    fn validate(&self) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+
+impl core::fmt::Display for ExtendedTimestamp {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            fmt,
+            "flags {:#x}, modify {:?}, access {:?}, create {:?}",
+            self.flags, self.modify, self.access, self.create
+        )
+    }
+}
+
+/// Body of NTFS extra field (0x000a).
+/// Timestamps are 100 ns ticks from 1601-01-01 UTC, in attribute tag 1.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NtfsTimestamps {
+    pub modify: u64,
+    pub access: u64,
+    pub create: u64,
+}
+
+impl NtfsTimestamps {
+    /// Attribute tag of timestamps.
+    const TAG_TIMES: u16 = 1;
+
+    pub fn modified(&self) -> SystemTime {
+        let secs = (self.modify / 10_000_000) as i64 - NTFS_TO_UNIX_SECS as i64;
+        let nanos = (self.modify % 10_000_000) as u32 * 100;
+        system_time_from_secs(secs) + Duration::from_nanos(nanos as u64)
+    }
+}
+
+impl FromU8Array for NtfsTimestamps {
+    fn from_slice(slice: &[u8]) -> Result<(usize, Self), FromU8Error<Self>> {
+        // 4 reserved bytes are followed by attributes of tag, size and body.
+        let mut rest = slice.get(4..).ok_or(FromU8Error::NotEnoughSlice(None))?;
+        while rest.len() >= 4 {
+            let tag = u16::from_le_bytes([rest[0], rest[1]]);
+            let size = u16::from_le_bytes([rest[2], rest[3]]) as usize;
+            let body = rest
+                .get(4..4 + size)
+                .ok_or(FromU8Error::NotEnoughSlice(None))?;
+
+            if tag == Self::TAG_TIMES && size == 24 {
+                let time = |i: usize| u64::from_le_bytes(body[i * 8..i * 8 + 8].try_into().unwrap());
+                return Ok((
+                    slice.len(),
+                    Self {
+                        modify: time(0),
+                        access: time(1),
+                        create: time(2),
+                    },
+                ));
+            }
+            rest = &rest[4 + size..];
+        }
+
+        Err(FromU8Error::InvalidValue((slice.len(), None)))
+    }
+    fn to_slice(&self) -> Box<[u8]> {
+        let mut ret = vec![0; 4];
+        ret.extend_from_slice(&Self::TAG_TIMES.to_le_bytes());
+        ret.extend_from_slice(&24u16.to_le_bytes());
+        for t in [self.modify, self.access, self.create] {
+            ret.extend_from_slice(&t.to_le_bytes());
+        }
+        ret.into_boxed_slice()
+    }
+}
+
+impl ModelFromU8Array for NtfsTimestamps {
+    fn validate(&self) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+
+impl core::fmt::Display for NtfsTimestamps {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            fmt,
+            "modify {:#x}, access {:#x}, create {:#x}",
+            self.modify, self.access, self.create
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dos_fields() {
+        let time = Time::new(0x7de7);
+        assert_eq!((time.hour(), time.minute(), time.second()), (15, 47, 14));
+        let date = Data::new(0x550b);
+        assert_eq!((date.year(), date.month(), date.day()), (2022, 8, 11));
+
+        let dt = DosDateTime::new(date, time);
+        assert_eq!(dt.to_string(), "2022-08-11 15:47:14");
+        assert_eq!(Time::from_hms(15, 47, 15), Some(Time::new(0x7de7)));
+        assert_eq!(Data::from_ymd(2022, 8, 11), Some(Data::new(0x550b)));
+    }
+
+    #[test]
+    fn dos_fields_out_of_range() {
+        assert_eq!(Data::from_ymd(1979, 12, 31), None);
+        assert_eq!(Data::from_ymd(2108, 1, 1), None);
+        assert_eq!(Data::from_ymd(2107, 12, 31).unwrap().year(), 2107);
+        assert_eq!(Data::from_ymd(2022, 0, 1), None);
+        assert_eq!(Data::from_ymd(2022, 13, 1), None);
+        assert_eq!(Data::from_ymd(2022, 8, 0), None);
+        assert_eq!(Data::from_ymd(2023, 2, 29), None);
+        assert!(Data::from_ymd(2024, 2, 29).is_some());
+
+        assert_eq!(Time::from_hms(24, 0, 0), None);
+        assert_eq!(Time::from_hms(0, 60, 0), None);
+        assert_eq!(Time::from_hms(0, 0, 60), None);
+        assert!(Time::from_hms(23, 59, 59).is_some());
+    }
+
+    #[test]
+    fn system_time_round_trip() {
+        let dt = DosDateTime::new(Data::from_ymd(2022, 8, 11).unwrap(), Time::from_hms(14, 3, 22).unwrap());
+        let t = dt.to_system_time().unwrap();
+        assert_eq!(t.duration_since(UNIX_EPOCH).unwrap().as_secs(), 1660226602);
+        assert_eq!(DosDateTime::from_system_time(t).unwrap(), dt);
+
+        let leap = DosDateTime::new(Data::from_ymd(2024, 2, 29).unwrap(), Time::from_hms(23, 59, 58).unwrap());
+        assert_eq!(DosDateTime::from_system_time(leap.to_system_time().unwrap()).unwrap(), leap);
+
+        assert_eq!(DosDateTime::default().to_system_time().unwrap(), UNIX_EPOCH + Duration::from_secs(315532800));
+        assert_eq!(DosDateTime::from_system_time(UNIX_EPOCH), None);
+        assert_eq!(DosDateTime::new(Data::new(0), Time::new(0)).to_system_time(), None);
+        // 2022-02-31 and 2022-00-11.
+        assert_eq!(DosDateTime::new(Data::new(0x545f), Time::new(0)).to_system_time(), None);
+        assert_eq!(DosDateTime::new(Data::new(0x540b), Time::new(0)).to_system_time(), None);
+    }
+
+    #[test]
+    fn extended_timestamp() {
+        // Local header has modification and access time, central directory has only modification time.
+        let (_, local) = ExtendedTimestamp::from_slice(&[3, 0xf2, 0xa5, 0xf4, 0x62, 1, 0, 0, 0]).unwrap();
+        assert_eq!(local.modify, Some(0x62f4a5f2));
+        assert_eq!(local.access, Some(1));
+        assert_eq!(local.create, None);
+
+        let (_, central) = ExtendedTimestamp::from_slice(&[3, 0xf2, 0xa5, 0xf4, 0x62]).unwrap();
+        assert_eq!(central.access, None);
+        assert_eq!(
+            central.modified().unwrap(),
+            UNIX_EPOCH + Duration::from_secs(0x62f4a5f2)
+        );
+        assert_eq!(&*central.to_slice(), &[3, 0xf2, 0xa5, 0xf4, 0x62]);
+    }
+
+    #[test]
+    fn ntfs_timestamps() {
+        // 2022-08-11 06:47:14.5 UTC
+        let ticks = (0x62f4a5f2 + NTFS_TO_UNIX_SECS) * 10_000_000 + 5_000_000;
+        let ntfs = NtfsTimestamps {
+            modify: ticks,
+            access: ticks,
+            create: 0,
+        };
+        let bytes = ntfs.to_slice();
+        assert_eq!(bytes.len(), 32);
+
+        let (read, parsed) = NtfsTimestamps::from_slice(&bytes).unwrap();
+        assert_eq!(read, 32);
+        assert_eq!(parsed, ntfs);
+        assert_eq!(
+            parsed.modified(),
+            UNIX_EPOCH + Duration::from_millis(0x62f4a5f2 * 1000 + 500)
+        );
+    }
+}
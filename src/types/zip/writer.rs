@@ -0,0 +1,350 @@
+use super::{
+    BitFlags, CentralDirectoryHeader, CentralDirectoryMagic, Count, DataDescriptor,
+    DataDescriptorMagic, DiskNumber, DosDateTime, EndOfCentralDirectory,
+    EndOfCentralDirectoryMagic, ExternalAttributes, Header, InternalAttributes, Length, Magic,
+    Method, Offset, Size, Version, ZipError, CRC32,
+};
+use crate::compress::deflate::deflate;
+use crate::types::FromU8Array;
+
+/// Version needed to extract deflated data and directories (2.0).
+const VERSION_NEEDED: u16 = 20;
+
+/// Version made by: upper byte is host system (3 = Unix), lower byte is the version.
+const VERSION_MADE_BY_UNIX: u16 = 3 << 8 | VERSION_NEEDED;
+
+/// Compression of entry data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Stored,
+    Deflate,
+}
+
+impl Compression {
+    fn method(self) -> Method {
+        match self {
+            Compression::Stored => Method::STORED,
+            Compression::Deflate => Method::DEFLATE,
+        }
+    }
+}
+
+/// Options of an entry added to `ZipWriter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOptions {
+    pub compression: Compression,
+
+    /// Write CRC-32 and sizes in data descriptor after the data, instead of local file header.
+    pub data_descriptor: bool,
+
+    /// Last modification time. Use `DosDateTime::from_system_time` to convert from SystemTime.
+    pub last_modified: DosDateTime,
+
+    /// Unix file type and permission bits. They are stored in upper half of external attributes.
+    pub unix_mode: Option<u32>,
+}
+
+impl Default for FileOptions {
+    fn default() -> Self {
+        Self {
+            compression: Compression::Deflate,
+            data_descriptor: false,
+            last_modified: DosDateTime::default(),
+            unix_mode: None,
+        }
+    }
+}
+
+/// Zip archive writer. The archive is built in memory.
+/// ZIP64 is not written, so entries and the archive must stay below 4 GiB and 65535 entries.
+#[derive(Debug, Clone, Default)]
+pub struct ZipWriter {
+    out: Vec<u8>,
+    entries: Vec<(CentralDirectoryHeader, Vec<u8>)>,
+    comment: Vec<u8>,
+}
+
+// In counts, sizes and offsets, all ones is the ZIP64 marker, so it is not a valid value either.
+fn to_u16(value: usize, field: &'static str) -> Result<u16, ZipError> {
+    if value < u16::MAX as usize {
+        Ok(value as u16)
+    } else {
+        Err(ZipError::Overflow(field))
+    }
+}
+
+/// Lengths of variable fields have no ZIP64 marker, so all ones is valid.
+fn to_length(value: usize, field: &'static str) -> Result<Length, ZipError> {
+    u16::try_from(value).map(Length::new).map_err(|_| ZipError::Overflow(field))
+}
+
+fn to_u32(value: usize, field: &'static str) -> Result<u32, ZipError> {
+    if value < u32::MAX as usize {
+        Ok(value as u32)
+    } else {
+        Err(ZipError::Overflow(field))
+    }
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set archive comment. Bytes are written as they are, but readers decode them in
+    /// IBM Code Page 437, so only ASCII comment reads back the same.
+    pub fn set_comment(&mut self, comment: &str) {
+        self.comment = comment.as_bytes().to_vec();
+    }
+
+    /// Add a file.
+    pub fn add_file(&mut self, name: &str, data: &[u8], options: &FileOptions) -> Result<(), ZipError> {
+        let compressed = match options.compression {
+            Compression::Stored => data.to_vec(),
+            Compression::Deflate => deflate(data),
+        };
+        self.add_entry(name, data, &compressed, options)
+    }
+
+    /// Add a directory. A trailing '/' is appended to `name` if it is missing.
+    pub fn add_directory(&mut self, name: &str, options: &FileOptions) -> Result<(), ZipError> {
+        let mut name = name.to_string();
+        if !name.ends_with('/') {
+            name.push('/');
+        }
+        let options = FileOptions {
+            compression: Compression::Stored,
+            ..options.clone()
+        };
+        self.add_entry(&name, &[], &[], &options)
+    }
+
+    fn add_entry(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        compressed: &[u8],
+        options: &FileOptions,
+    ) -> Result<(), ZipError> {
+        let offset = to_u32(self.out.len(), "local header offset")?;
+        let crc32 = CRC32::compute(data);
+        let compressed_size = Size::new(to_u32(compressed.len(), "compressed size")?);
+        let uncompressed_size = Size::new(to_u32(data.len(), "uncompressed size")?);
+        let file_name_length = to_length(name.len(), "file name length")?;
+
+        let mut general_purpose_bf = BitFlags::new(0);
+        general_purpose_bf.set(BitFlags::UTF8, !name.is_ascii());
+        general_purpose_bf.set(BitFlags::DATA_DESCRIPTOR, options.data_descriptor);
+
+        let central = CentralDirectoryHeader {
+            magic: Some(CentralDirectoryMagic::VALID),
+            version_made_by: Some(Version::new(match options.unix_mode {
+                Some(_) => VERSION_MADE_BY_UNIX,
+                None => VERSION_NEEDED,
+            })),
+            version_needed: Some(Version::new(VERSION_NEEDED)),
+            general_purpose_bf: Some(general_purpose_bf.clone()),
+            compression_method: Some(options.compression.method()),
+            last_modify_time: Some(options.last_modified.time.clone()),
+            last_modify_data: Some(options.last_modified.date.clone()),
+            crc32: Some(crc32.clone()),
+            compressed_size: Some(compressed_size.clone()),
+            uncompressed_size: Some(uncompressed_size.clone()),
+            file_name_length: Some(file_name_length.clone()),
+            extra_field_length: Some(Length::new(0)),
+            file_comment_length: Some(Length::new(0)),
+            disk_number_start: Some(DiskNumber::new(0)),
+            internal_attributes: Some(InternalAttributes::new(0)),
+            external_attributes: Some(ExternalAttributes::new(options.unix_mode.unwrap_or(0) << 16)),
+            local_header_offset: Some(Offset::new(offset)),
+        };
+
+        // With data descriptor, CRC-32 and sizes in local file header are zero.
+        let (crc32, compressed_size, uncompressed_size) = match options.data_descriptor {
+            true => (CRC32::new(0), Size::new(0), Size::new(0)),
+            false => (crc32, compressed_size, uncompressed_size),
+        };
+        let local = Header {
+            magic: Some(Magic::VALID),
+            version_needed: Some(Version::new(VERSION_NEEDED)),
+            general_purpose_bf: Some(general_purpose_bf),
+            compression_method: Some(options.compression.method()),
+            last_modify_time: Some(options.last_modified.time.clone()),
+            last_modify_data: Some(options.last_modified.date.clone()),
+            crc32: Some(crc32),
+            compressed_size: Some(compressed_size),
+            uncompressed_size: Some(uncompressed_size),
+            file_name_length: Some(file_name_length),
+            extra_field_length: Some(Length::new(0)),
+        };
+
+        self.out.extend_from_slice(&local.to_slice());
+        self.out.extend_from_slice(name.as_bytes());
+        self.out.extend_from_slice(compressed);
+
+        if options.data_descriptor {
+            let descriptor = DataDescriptor {
+                magic: Some(DataDescriptorMagic::VALID),
+                crc32: central.crc32.clone(),
+                compressed_size: central.compressed_size.clone(),
+                uncompressed_size: central.uncompressed_size.clone(),
+            };
+            self.out.extend_from_slice(&descriptor.to_slice());
+        }
+
+        self.entries.push((central, name.as_bytes().to_vec()));
+        Ok(())
+    }
+
+    /// Write central directory and end of central directory record, and return the archive.
+    pub fn finish(mut self) -> Result<Vec<u8>, ZipError> {
+        let offset = to_u32(self.out.len(), "central directory offset")?;
+        for (header, name) in &self.entries {
+            self.out.extend_from_slice(&header.to_slice());
+            self.out.extend_from_slice(name);
+        }
+        let size = to_u32(self.out.len() - offset as usize, "central directory size")?;
+        let count = Count::new(to_u16(self.entries.len(), "entry count")?);
+
+        let eocd = EndOfCentralDirectory {
+            magic: Some(EndOfCentralDirectoryMagic::VALID),
+            disk_number: Some(DiskNumber::new(0)),
+            central_directory_disk: Some(DiskNumber::new(0)),
+            disk_entries: Some(count.clone()),
+            total_entries: Some(count),
+            central_directory_size: Some(Size::new(size)),
+            central_directory_offset: Some(Offset::new(offset)),
+            comment_length: Some(to_length(self.comment.len(), "comment length")?),
+        };
+        self.out.extend_from_slice(&eocd.to_slice());
+        self.out.extend_from_slice(&self.comment);
+
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::Sequential;
+    use crate::types::zip::{Data, Time, ZipArchive};
+
+    fn lines() -> Vec<u8> {
+        (0..100)
+            .flat_map(|i| format!("line {}: the quick brown fox jumps over the lazy dog\n", i).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut w = ZipWriter::new();
+        let stored = FileOptions {
+            compression: Compression::Stored,
+            unix_mode: Some(0o100644),
+            last_modified: DosDateTime::new(
+                Data::from_ymd(2022, 8, 11).unwrap(),
+                Time::from_hms(14, 3, 22).unwrap(),
+            ),
+            ..Default::default()
+        };
+        let described = FileOptions {
+            data_descriptor: true,
+            ..Default::default()
+        };
+        w.add_directory("dir", &FileOptions::default()).unwrap();
+        w.add_file("dir/stored.txt", b"stored data\n", &stored).unwrap();
+        w.add_file("dir/deflated.txt", &lines(), &FileOptions::default()).unwrap();
+        w.add_file("dir/descriptor.txt", &lines(), &described).unwrap();
+        w.add_file("dir/ünïcode.txt", b"", &FileOptions::default()).unwrap();
+        w.set_comment("written by elfeat");
+        let v = w.finish().unwrap();
+
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(zip.comment(), "written by elfeat");
+        assert_eq!(zip.entry_count(), 5);
+
+        let names: Vec<_> = zip.entries().iter().map(|e| e.name()).collect();
+        assert_eq!(
+            names,
+            vec!["dir/", "dir/stored.txt", "dir/deflated.txt", "dir/descriptor.txt", "dir/ünïcode.txt"]
+        );
+        assert!(zip.entries()[0].is_dir());
+        assert_eq!(zip.entries()[0].read_to_vec().unwrap(), b"");
+
+        let entry = zip.by_name("dir/stored.txt").unwrap();
+        assert_eq!(entry.compression_method(), Method::STORED);
+        assert_eq!(*entry.header().get_external_attributes_unwrap().inner_ref() >> 16, 0o100644);
+        assert_eq!(entry.read_to_vec().unwrap(), b"stored data\n");
+        assert_eq!(entry.last_modified().to_string(), "2022-08-11 14:03:22");
+
+        let entry = zip.by_name("dir/deflated.txt").unwrap();
+        assert_eq!(entry.compression_method(), Method::DEFLATE);
+        assert!(entry.compressed_size() < entry.uncompressed_size());
+        assert_eq!(entry.read_to_vec().unwrap(), lines());
+
+        let entry = zip.by_name("dir/descriptor.txt").unwrap();
+        assert!(entry.header().get_general_purpose_bf_unwrap().get(BitFlags::DATA_DESCRIPTOR));
+        assert_eq!(entry.read_to_vec().unwrap(), lines());
+
+        assert_eq!(zip.by_name("dir/ünïcode.txt").unwrap().read_to_vec().unwrap(), b"");
+    }
+
+    #[test]
+    fn data_descriptor_layout() {
+        let mut w = ZipWriter::new();
+        let options = FileOptions {
+            compression: Compression::Stored,
+            data_descriptor: true,
+            ..Default::default()
+        };
+        w.add_file("a", b"abc", &options).unwrap();
+        let v = w.finish().unwrap();
+
+        // Local header has zero CRC-32 and sizes.
+        let (size, local) = Header::from_slice(&v).unwrap();
+        assert_eq!(size, 30);
+        assert_eq!(local.get_compression_method_unwrap(), &Method::STORED);
+        assert_eq!(*local.get_crc32_unwrap().inner_ref(), 0);
+        assert_eq!(*local.get_compressed_size_unwrap().inner_ref(), 0);
+
+        // Descriptor follows header, name and data.
+        let (_, descriptor) = DataDescriptor::from_slice(&v[size + 1 + 3..]).unwrap();
+        assert!(descriptor.get_magic_unwrap().is_VALID());
+        assert_eq!(descriptor.get_crc32_unwrap(), &CRC32::compute(b"abc"));
+        assert_eq!(*descriptor.get_uncompressed_size_unwrap().inner_ref(), 3);
+    }
+
+    #[test]
+    fn zip64_markers_overflow() {
+        assert_eq!(to_u16(0xFFFE, "entry count").unwrap(), 0xFFFE);
+        assert!(matches!(to_u16(0xFFFF, "entry count"), Err(ZipError::Overflow("entry count"))));
+        assert_eq!(to_u32(0xFFFF_FFFE, "size").unwrap(), 0xFFFF_FFFE);
+        assert!(matches!(to_u32(0xFFFF_FFFF, "size"), Err(ZipError::Overflow("size"))));
+
+        // Lengths may be all ones.
+        let mut w = ZipWriter::new();
+        w.add_file(&"n".repeat(0xFFFF), b"", &FileOptions::default()).unwrap();
+        w.set_comment(&"c".repeat(0xFFFF));
+        let v = w.finish().unwrap();
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(zip.entries()[0].name().len(), 0xFFFF);
+        assert_eq!(zip.comment().len(), 0xFFFF);
+
+        let mut w = ZipWriter::new();
+        let name = "n".repeat(0x10000);
+        let options = FileOptions::default();
+        assert!(matches!(w.add_file(&name, b"", &options), Err(ZipError::Overflow("file name length"))));
+    }
+
+    #[test]
+    fn empty_archive() {
+        let v = ZipWriter::new().finish().unwrap();
+        assert_eq!(v.len(), 22);
+
+        let s = Sequential::from_vec(v);
+        let zip = ZipArchive::parse(s.to_seeakble()).unwrap();
+        assert_eq!(zip.entry_count(), 0);
+    }
+}